@@ -0,0 +1,66 @@
+//! Pattern matching throughput on the PowerPC definitions: the compiled decode tree behind
+//! `match_pattern` against the linear highest-specificity scan it replaced.
+
+use std::hint::black_box;
+use std::path::PathBuf;
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use nanemu::loader::isa::IsaLoader;
+use nanemu::soc::isa::machine::{InstructionPattern, MachineDescription};
+
+const WORDS: usize = 4096;
+
+fn powerpc() -> MachineDescription {
+    let isa = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("defs/powerpc/ppc_eref2.isa");
+    IsaLoader::new().load_machine(isa).expect("load ppc_eref2")
+}
+
+/// `insn` words cycling through every pattern with pseudo-random operand bits.
+fn program(patterns: &[InstructionPattern]) -> Vec<u64> {
+    let insn: Vec<&InstructionPattern> = patterns.iter().filter(|p| p.space == "insn").collect();
+    let mut seed = 0x9E37_79B9_7F4A_7C15u64;
+    (0..WORDS)
+        .map(|idx| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            let pattern = insn[idx % insn.len()];
+            (pattern.value | (seed & !pattern.mask)) & 0xFFFF_FFFF
+        })
+        .collect()
+}
+
+fn linear_match(patterns: &[InstructionPattern], bits: u64) -> Option<&InstructionPattern> {
+    patterns
+        .iter()
+        .filter(|pattern| pattern.space == "insn" && bits & pattern.mask == pattern.value)
+        .max_by_key(|pattern| pattern.specificity)
+}
+
+fn decode(c: &mut Criterion) {
+    let machine = powerpc();
+    let words = program(machine.patterns());
+
+    let mut group = c.benchmark_group("powerpc_match");
+    group.throughput(Throughput::Elements(WORDS as u64));
+    group.bench_function("linear_scan", |b| {
+        b.iter(|| {
+            black_box(&words)
+                .iter()
+                .filter_map(|word| linear_match(machine.patterns(), *word))
+                .count()
+        })
+    });
+    group.bench_function("decode_tree", |b| {
+        b.iter(|| {
+            black_box(&words)
+                .iter()
+                .filter_map(|word| machine.match_pattern("insn", *word))
+                .count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, decode);
+criterion_main!(benches);
@@ -0,0 +1,31 @@
+//! Loads an `.isa`/`.coredef` tree and reports ambiguous instruction masks and undecoded
+//! encodings. Exits with status 1 when any pair of patterns conflicts.
+
+use std::process::ExitCode;
+
+use nanemu::loader::isa::IsaLoader;
+use nanemu::soc::isa::validator::analyze_decode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [entry] = args.as_slice() else {
+        eprintln!("usage: nanemu-isa-check <entry.isa|entry.coredef>");
+        return ExitCode::from(2);
+    };
+    let machine = match IsaLoader::new().load_machine(entry) {
+        Ok(machine) => machine,
+        Err(err) => {
+            eprintln!("nanemu-isa-check: {err}");
+            return ExitCode::from(1);
+        }
+    };
+    let report = analyze_decode(&machine);
+    for diagnostic in report.diagnostics() {
+        println!("{}", diagnostic.format_human());
+    }
+    if report.conflicts.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(1)
+    }
+}
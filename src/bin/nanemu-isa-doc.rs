@@ -0,0 +1,51 @@
+//! Loads an `.isa`/`.coredef` tree and writes a Markdown (or, with `--html`, HTML) reference
+//! manual for it.
+
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+use nanemu::loader::isa::IsaLoader;
+use nanemu::soc::isa::machine::ReferenceFormat;
+
+fn main() -> ExitCode {
+    let mut format = ReferenceFormat::Markdown;
+    let mut paths = Vec::new();
+    for arg in std::env::args().skip(1) {
+        if arg == "--html" {
+            format = ReferenceFormat::Html;
+        } else {
+            paths.push(arg);
+        }
+    }
+    let (entry, output) = match paths.as_slice() {
+        [entry] => (entry, None),
+        [entry, output] => (entry, Some(output)),
+        _ => {
+            eprintln!("usage: nanemu-isa-doc [--html] <entry.isa|entry.coredef> [output]");
+            return ExitCode::from(2);
+        }
+    };
+    let machine = match IsaLoader::new().load_machine(entry) {
+        Ok(machine) => machine,
+        Err(err) => {
+            eprintln!("nanemu-isa-doc: {err}");
+            return ExitCode::from(1);
+        }
+    };
+    let title = Path::new(entry)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| entry.clone());
+    let manual = machine.reference_manual(&title, format);
+    match output {
+        Some(path) => {
+            if let Err(err) = fs::write(path, manual) {
+                eprintln!("nanemu-isa-doc: cannot write '{path}': {err}");
+                return ExitCode::from(1);
+            }
+        }
+        None => print!("{manual}"),
+    }
+    ExitCode::SUCCESS
+}
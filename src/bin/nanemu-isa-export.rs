@@ -0,0 +1,36 @@
+//! Loads an `.isa`/`.coredef` tree and writes its machine description as JSON.
+
+use std::fs;
+use std::process::ExitCode;
+
+use nanemu::loader::isa::IsaLoader;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (entry, output) = match args.as_slice() {
+        [entry] => (entry, None),
+        [entry, output] => (entry, Some(output)),
+        _ => {
+            eprintln!("usage: nanemu-isa-export <entry.isa|entry.coredef> [output.json]");
+            return ExitCode::from(2);
+        }
+    };
+    let machine = match IsaLoader::new().load_machine(entry) {
+        Ok(machine) => machine,
+        Err(err) => {
+            eprintln!("nanemu-isa-export: {err}");
+            return ExitCode::from(1);
+        }
+    };
+    let json = serde_json::to_string_pretty(&machine.to_json()).expect("JSON values serialize");
+    match output {
+        Some(path) => {
+            if let Err(err) = fs::write(path, json + "\n") {
+                eprintln!("nanemu-isa-export: cannot write '{path}': {err}");
+                return ExitCode::from(1);
+            }
+        }
+        None => println!("{json}"),
+    }
+    ExitCode::SUCCESS
+}
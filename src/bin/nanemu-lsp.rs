@@ -0,0 +1,19 @@
+//! Language server for ISA machine descriptions, speaking JSON-RPC over stdio.
+
+use std::io;
+use std::process::ExitCode;
+
+use nanemu::lsp::Server;
+
+fn main() -> ExitCode {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    match Server::new().run(&mut stdin.lock(), &mut stdout.lock()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(err) => {
+            eprintln!("nanemu-lsp: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! On-disk cache of validated machine descriptions. Each entry file maps to one blob holding
+//! the SHA-256 of every source the load read plus a [`MachineDescription`] snapshot; the blob is
+//! only used while that digest still matches the files on disk and none of the optional
+//! includes the load found missing has appeared since.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::soc::isa::error::IsaError;
+use crate::soc::isa::machine::MachineDescription;
+
+const CACHE_MAGIC: &[u8; 8] = b"NANEMUMC";
+const CACHE_EXTENSION: &str = "machine";
+
+#[derive(Debug, Clone)]
+pub struct MachineCache {
+    dir: PathBuf,
+}
+
+impl MachineCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the cached machine for `entry`, or `None` when there is no blob, the blob cannot
+    /// be decoded, any recorded source changed since it was written, or a recorded missing
+    /// optional include now exists.
+    pub fn load(&self, entry: &Path) -> Option<MachineDescription> {
+        let blob = fs::read(self.blob_path(entry)).ok()?;
+        let (digest, sources, rest) = split_blob(&blob)?;
+        let (absent, snapshot) = split_paths(rest)?;
+        if absent.iter().any(|path| path.exists()) {
+            return None;
+        }
+        if fileset_digest(&sources).ok()? != digest {
+            return None;
+        }
+        MachineDescription::from_snapshot(snapshot).ok()
+    }
+
+    /// Writes the blob for `entry`, recording `sources` and the `absent` optional includes the
+    /// load probed for, so later loads can detect stale entries.
+    pub fn store(
+        &self,
+        entry: &Path,
+        sources: &[PathBuf],
+        absent: &[PathBuf],
+        machine: &MachineDescription,
+    ) -> Result<(), IsaError> {
+        let sources = sources
+            .iter()
+            .map(fs::canonicalize)
+            .collect::<io::Result<Vec<_>>>()?;
+        let absent: Vec<PathBuf> = absent.iter().map(|path| absolute_path(path)).collect();
+        let digest = fileset_digest(&sources)?;
+        let mut blob = Vec::new();
+        blob.extend_from_slice(CACHE_MAGIC);
+        blob.extend_from_slice(&digest);
+        write_paths(&mut blob, &sources);
+        write_paths(&mut blob, &absent);
+        blob.extend_from_slice(&machine.to_snapshot());
+
+        fs::create_dir_all(&self.dir)?;
+        // Write then rename so a concurrent reader never sees a partial blob.
+        let path = self.blob_path(entry);
+        let staging = path.with_extension(format!("{CACHE_EXTENSION}.{}", std::process::id()));
+        fs::write(&staging, blob)?;
+        fs::rename(&staging, &path)?;
+        Ok(())
+    }
+
+    fn blob_path(&self, entry: &Path) -> PathBuf {
+        let entry = fs::canonicalize(entry).unwrap_or_else(|_| entry.to_path_buf());
+        let key = Sha256::digest(entry.to_string_lossy().as_bytes());
+        let name: String = key.iter().map(|byte| format!("{byte:02x}")).collect();
+        self.dir.join(name).with_extension(CACHE_EXTENSION)
+    }
+}
+
+/// SHA-256 over the path and contents of each source, in load order.
+pub fn fileset_digest(sources: &[PathBuf]) -> io::Result<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    for source in sources {
+        let path = source.to_string_lossy();
+        let contents = fs::read(source)?;
+        hasher.update((path.len() as u64).to_le_bytes());
+        hasher.update(path.as_bytes());
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(&contents);
+    }
+    Ok(hasher.finalize().into())
+}
+
+/// Anchors a path that may not exist at its canonical parent directory, so the probe
+/// stays valid regardless of the working directory of later loads.
+fn absolute_path(path: &Path) -> PathBuf {
+    match (path.parent().map(fs::canonicalize), path.file_name()) {
+        (Some(Ok(dir)), Some(name)) => dir.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn write_paths(blob: &mut Vec<u8>, paths: &[PathBuf]) {
+    blob.extend_from_slice(&(paths.len() as u32).to_le_bytes());
+    for path in paths {
+        let path = path.to_string_lossy();
+        blob.extend_from_slice(&(path.len() as u32).to_le_bytes());
+        blob.extend_from_slice(path.as_bytes());
+    }
+}
+
+fn split_blob(blob: &[u8]) -> Option<([u8; 32], Vec<PathBuf>, &[u8])> {
+    let rest = blob.strip_prefix(CACHE_MAGIC.as_slice())?;
+    let (digest, rest) = rest.split_first_chunk::<32>()?;
+    let (sources, rest) = split_paths(rest)?;
+    Some((*digest, sources, rest))
+}
+
+fn split_paths(blob: &[u8]) -> Option<(Vec<PathBuf>, &[u8])> {
+    let (count, mut rest) = blob.split_first_chunk::<4>()?;
+    let mut paths = Vec::new();
+    for _ in 0..u32::from_le_bytes(*count) {
+        let (len, tail) = rest.split_first_chunk::<4>()?;
+        let len = u32::from_le_bytes(*len) as usize;
+        if tail.len() < len {
+            return None;
+        }
+        let (path, tail) = tail.split_at(len);
+        paths.push(PathBuf::from(std::str::from_utf8(path).ok()?));
+        rest = tail;
+    }
+    Some((paths, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::isa::IsaLoader;
+    use tempfile::tempdir;
+
+    const BASE: &str = ":space reg addr=32 word=32 type=register\n:reg PC size=32\n:space insn addr=32 word=32 type=logic endian=big\n:insn BASE subfields={\n    OPCD @(0..5) op=func\n}\n:insn::BASE nop mask={OPCD=1}\n";
+
+    #[test]
+    fn reuses_blob_until_a_source_changes() {
+        let dir = tempdir().expect("tempdir");
+        let base = dir.path().join("base.isa");
+        fs::write(&base, BASE).expect("write base");
+        let coredef = dir.path().join("core.coredef");
+        fs::write(&coredef, ":include \"base.isa\"\n").expect("write coredef");
+        let cache = MachineCache::new(dir.path().join("cache"));
+
+        assert!(cache.load(&coredef).is_none(), "empty cache misses");
+        let mut loader = IsaLoader::new().with_cache(cache.clone());
+        let machine = loader.load_machine(&coredef).expect("full load");
+        assert_eq!(loader.sources().len(), 2, "coredef plus base were read");
+
+        let cached = cache.load(&coredef).expect("blob written by the full load");
+        assert_eq!(cached.instructions.len(), machine.instructions.len());
+        assert_eq!(cached.disassemble(&[0x04, 0, 0, 0])[0].mnemonic, "nop");
+
+        fs::write(&base, BASE.replace("nop", "halt")).expect("edit base");
+        assert!(cache.load(&coredef).is_none(), "edited include is stale");
+        let machine = loader.load_machine(&coredef).expect("reload");
+        assert_eq!(machine.disassemble(&[0x04, 0, 0, 0])[0].mnemonic, "halt");
+        let cached = cache.load(&coredef).expect("blob refreshed");
+        assert_eq!(cached.disassemble(&[0x04, 0, 0, 0])[0].mnemonic, "halt");
+    }
+
+    #[test]
+    fn corrupt_blob_falls_back_to_full_load() {
+        let dir = tempdir().expect("tempdir");
+        let base = dir.path().join("base.isa");
+        fs::write(&base, BASE).expect("write base");
+        let cache = MachineCache::new(dir.path().join("cache"));
+        IsaLoader::new()
+            .with_cache(cache.clone())
+            .load_machine(&base)
+            .expect("full load");
+
+        let blob = cache.blob_path(&base);
+        let bytes = fs::read(&blob).expect("blob exists");
+        fs::write(&blob, &bytes[..bytes.len() - 4]).expect("truncate blob");
+        assert!(
+            cache.load(&base).is_none(),
+            "truncated snapshot is rejected"
+        );
+        let machine = IsaLoader::new()
+            .with_cache(cache.clone())
+            .load_machine(&base)
+            .expect("falls back to a full load");
+        assert_eq!(machine.instructions.len(), 1);
+        assert!(cache.load(&base).is_some(), "fallback rewrites the blob");
+    }
+
+    #[test]
+    fn appearing_optional_include_invalidates_blob() {
+        let dir = tempdir().expect("tempdir");
+        let base = dir.path().join("base.isa");
+        fs::write(&base, BASE).expect("write base");
+        let machine = IsaLoader::new().load_machine(&base).expect("load");
+        let cache = MachineCache::new(dir.path().join("cache"));
+        let overlay = dir.path().join("overlay.isaext");
+        cache
+            .store(
+                &base,
+                std::slice::from_ref(&base),
+                std::slice::from_ref(&overlay),
+                &machine,
+            )
+            .expect("store");
+
+        assert!(
+            cache.load(&base).is_some(),
+            "missing include is still missing"
+        );
+        fs::write(&overlay, "").expect("create include");
+        assert!(
+            cache.load(&base).is_none(),
+            "an optional include that appeared makes the blob stale"
+        );
+    }
+
+    #[test]
+    fn failed_store_is_recorded_not_printed() {
+        let dir = tempdir().expect("tempdir");
+        let base = dir.path().join("base.isa");
+        fs::write(&base, BASE).expect("write base");
+        let blocker = dir.path().join("cache");
+        fs::write(&blocker, "not a directory").expect("write blocker");
+
+        let mut loader = IsaLoader::new().with_cache(MachineCache::new(&blocker));
+        let machine = loader
+            .load_machine(&base)
+            .expect("load succeeds without the cache");
+        assert_eq!(machine.instructions.len(), 1);
+        assert!(loader.cache_error().is_some(), "the store failure is kept");
+    }
+}
@@ -677,6 +677,27 @@ impl<'src> Lexer<'src> {
         }
     }
 
+    /// Reads a file path operand: a string literal, or a bare run of non-whitespace
+    /// characters such as `./cores/e200.coredef` that the regular token rules would split.
+    pub(super) fn consume_path(&mut self) -> Result<Token, IsaError> {
+        self.skip_inline_whitespace();
+        if self.peek_char() == Some('"') {
+            return self.consume_string();
+        }
+        let start = self.offset;
+        let (line, column) = self.position();
+        while let Some(ch) = self.peek_char() {
+            if ch.is_whitespace() || (ch == '/' && self.peek_next_char() == Some('/')) {
+                break;
+            }
+            self.advance_char();
+        }
+        if self.offset == start {
+            return Err(self.lexer_error_here("lexer.path.missing", "expected a file path"));
+        }
+        Ok(self.make_token_from_span(TokenKind::String, start, self.offset, line, column))
+    }
+
     pub(super) fn capture_braced_block(&mut self) -> Result<CapturedBlock, IsaError> {
         let mut depth = 1usize;
         let mut body = String::new();
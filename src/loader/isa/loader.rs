@@ -4,10 +4,11 @@ use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::loader::isa::parse_str_with_spaces;
+use crate::loader::isa::{MachineCache, ParameterScope, parse_str_recovering};
 use crate::soc::isa::ast::{
     FieldDecl, IncludeDecl, IsaItem, IsaSpecification, SpaceDecl, SpaceKind, SpaceMember,
 };
+use crate::soc::isa::diagnostic::{IsaDiagnostic, first_phase};
 use crate::soc::isa::error::IsaError;
 use crate::soc::isa::machine::MachineDescription;
 use crate::soc::isa::validator::Validator;
@@ -17,6 +18,13 @@ pub struct IsaLoader {
     visited: BTreeSet<PathBuf>,
     stack: Vec<PathBuf>,
     known_spaces: HashMap<String, SpaceKind>,
+    overlay: Option<(PathBuf, String)>,
+    diagnostics: Vec<IsaDiagnostic>,
+    parameters: ParameterScope,
+    cache: Option<MachineCache>,
+    cache_error: Option<IsaError>,
+    sources: Vec<PathBuf>,
+    absent: Vec<PathBuf>,
 }
 
 impl IsaLoader {
@@ -24,19 +32,81 @@ impl IsaLoader {
         Self::default()
     }
 
+    /// Runs the full loading pipeline with `src` standing in for the contents of `entry`, so
+    /// editors can check unsaved buffers while includes still resolve from disk.
+    pub fn load_machine_from_source<P: AsRef<Path>>(
+        &mut self,
+        entry: P,
+        src: &str,
+    ) -> Result<MachineDescription, IsaError> {
+        self.overlay = Some((entry.as_ref().to_path_buf(), src.to_string()));
+        let result = self.load_machine(entry);
+        self.overlay = None;
+        result
+    }
+
+    /// Serves later loads from `cache` while the sources they read are unchanged.
+    pub fn with_cache(mut self, cache: MachineCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Files read by the most recent load that was not served from the cache, in load order.
+    pub fn sources(&self) -> &[PathBuf] {
+        &self.sources
+    }
+
+    /// Why the most recent load could not update the cache. The load itself still succeeded;
+    /// the next one simply cannot be served from the cache.
+    pub fn cache_error(&self) -> Option<&IsaError> {
+        self.cache_error.as_ref()
+    }
+
     pub fn load_machine<P: AsRef<Path>>(
         &mut self,
         entry: P,
     ) -> Result<MachineDescription, IsaError> {
+        let entry = entry.as_ref();
+        self.cache_error = None;
+        let Some(cache) = self.cache.clone().filter(|_| self.overlay.is_none()) else {
+            return self.load_sources(entry);
+        };
+        if let Some(machine) = cache.load(entry) {
+            return Ok(machine);
+        }
+        let machine = self.load_sources(entry)?;
+        self.cache_error = cache
+            .store(entry, &self.sources, &self.absent, &machine)
+            .err();
+        Ok(machine)
+    }
+
+    fn load_sources(&mut self, entry: &Path) -> Result<MachineDescription, IsaError> {
         self.visited.clear();
+        self.sources.clear();
+        self.absent.clear();
         self.stack.clear();
         self.known_spaces.clear();
-        let docs = self.collect_documents(entry.as_ref())?;
-        if Self::is_coredef(entry.as_ref()) {
-            Self::verify_coredef_compatibility(entry.as_ref(), &docs)?;
+        self.diagnostics.clear();
+        self.parameters.clear();
+        let docs = self.collect_documents(entry)?;
+        if Self::is_coredef(entry) {
+            Self::verify_coredef_compatibility(entry, &docs)?;
         }
+        // Whatever parsed is still validated so one pass reports every problem in the tree.
         let mut validator = Validator::new();
-        validator.validate(&docs)?;
+        match validator.validate(&docs) {
+            Ok(()) => {}
+            Err(IsaError::Diagnostics { diagnostics, .. }) => self.diagnostics.extend(diagnostics),
+            Err(other) => return Err(other),
+        }
+        if !self.diagnostics.is_empty() {
+            let diagnostics = std::mem::take(&mut self.diagnostics);
+            return Err(IsaError::Diagnostics {
+                phase: first_phase(&diagnostics),
+                diagnostics,
+            });
+        }
         validator.finalize_machine(docs)
     }
 
@@ -54,9 +124,20 @@ impl IsaLoader {
             return Err(IsaError::IncludeLoop { chain });
         }
         self.stack.push(path.to_path_buf());
-        let src = fs::read_to_string(path)?;
-        let doc = parse_str_with_spaces(path.to_path_buf(), &src, &self.known_spaces)?;
+        let src = match &self.overlay {
+            Some((overlay_path, src)) if overlay_path == path => src.clone(),
+            _ => fs::read_to_string(path)?,
+        };
+        self.sources.push(path.to_path_buf());
+        let (doc, diagnostics) = parse_str_recovering(
+            path.to_path_buf(),
+            &src,
+            &self.known_spaces,
+            &self.parameters,
+        )?;
+        self.diagnostics.extend(diagnostics);
         self.record_spaces(&doc);
+        self.record_parameters(path, &doc);
         let mut docs = Vec::new();
         if Self::is_coredef(path) {
             self.collect_coredef(path, &doc, &mut docs)?;
@@ -68,6 +149,7 @@ impl IsaLoader {
                     match self.resolve_include(path, &include) {
                         Ok(mut nested) => include_docs.append(&mut nested),
                         Err(err) if include.optional => {
+                            self.record_absent(&Self::resolve_include_path(path, &include));
                             eprintln!("optional include skipped: {err}");
                         }
                         Err(err) => return Err(err),
@@ -81,6 +163,14 @@ impl IsaLoader {
         Ok(docs)
     }
 
+    /// Remembers a skipped optional include that does not exist, so a cached machine is
+    /// invalidated once the file appears.
+    fn record_absent(&mut self, path: &Path) {
+        if !path.exists() {
+            self.absent.push(path.to_path_buf());
+        }
+    }
+
     fn resolve_include(
         &mut self,
         parent: &Path,
@@ -108,12 +198,16 @@ impl IsaLoader {
         acc: &mut Vec<IsaSpecification>,
     ) -> Result<(), IsaError> {
         let mut includes = Vec::new();
+        let mut memory_map = Vec::new();
         for item in &doc.items {
             match item {
                 IsaItem::Include(include) => includes.push(include.clone()),
+                IsaItem::Space(_) | IsaItem::Bus(_) | IsaItem::Parameter(_) => {
+                    memory_map.push(item.clone())
+                }
                 _ => {
                     return Err(IsaError::Machine(format!(
-                        "coredef '{}' may only contain :include directives",
+                        "coredef '{}' may only contain :include, :param, :space and :bus directives",
                         parent.display()
                     )));
                 }
@@ -163,6 +257,7 @@ impl IsaLoader {
             match self.collect_documents(&include_path) {
                 Ok(mut nested) => acc.append(&mut nested),
                 Err(err) if include.optional => {
+                    self.record_absent(&include_path);
                     eprintln!("optional include skipped: {err}");
                 }
                 Err(err) => return Err(err),
@@ -174,6 +269,9 @@ impl IsaLoader {
                 parent.display()
             )));
         }
+        if !memory_map.is_empty() {
+            acc.push(IsaSpecification::new(parent.to_path_buf(), memory_map));
+        }
         Ok(())
     }
 
@@ -192,6 +290,22 @@ impl IsaLoader {
         }
     }
 
+    /// Feeds active `:param` values to the files parsed next. A `.coredef`'s parameters are
+    /// overrides that the defaults declared by its includes cannot replace.
+    fn record_parameters(&mut self, path: &Path, doc: &IsaSpecification) {
+        let is_coredef = Self::is_coredef(path);
+        for item in &doc.items {
+            if let IsaItem::Parameter(param) = item {
+                if is_coredef {
+                    self.parameters
+                        .override_value(&param.name, param.value.clone());
+                } else {
+                    self.parameters.define(&param.name, param.value.clone());
+                }
+            }
+        }
+    }
+
     fn verify_coredef_compatibility(
         coredef: &Path,
         docs: &[IsaSpecification],
@@ -249,7 +363,11 @@ impl CoreCompatibilityState {
                 IsaItem::Instruction(instr) => {
                     self.ensure_space_known(coredef, doc, &instr.space)?;
                 }
-                IsaItem::Space(_) | IsaItem::Parameter(_) | IsaItem::Include(_) => {}
+                IsaItem::Space(_)
+                | IsaItem::Parameter(_)
+                | IsaItem::Include(_)
+                | IsaItem::Bus(_)
+                | IsaItem::Attach(_) => {}
                 IsaItem::Macro(_) => {}
             }
         }
@@ -366,6 +484,8 @@ fn is_append_only(field: &FieldDecl) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::soc::isa::ast::ParameterValue;
+    use crate::soc::isa::diagnostic::DiagnosticPhase;
     use std::fs;
     use tempfile::tempdir;
 
@@ -486,6 +606,169 @@ mod tests {
             .expect("compatibility allows new spaces");
     }
 
+    #[test]
+    fn coredef_memory_map_builds_device_bus() {
+        let dir = tempdir().expect("tempdir");
+        write_file(
+            dir.path(),
+            "base.isa",
+            ":space reg addr=32 word=32 type=register\n:reg PC size=32\n",
+        );
+        let coredef = write_file(
+            dir.path(),
+            "core.coredef",
+            ":include \"base.isa\"\n:space flash addr=32 word=32 type=ro endian=big\n:space ram addr=32 word=32 type=rw endian=big\n:bus sysbus addr=32 ranges={\n    [0x0 .. 0xFFFF] -> flash\n    [0x40000000 +4kB] -> ram\n}\n",
+        );
+        let mut loader = IsaLoader::new();
+        let machine = loader.load_machine(&coredef).expect("load coredef");
+        assert!(
+            machine.buses.contains_key("sysbus"),
+            "coredef :bus should reach the machine description"
+        );
+        let bus = machine.build_bus("sysbus").expect("build bus");
+        assert_eq!(
+            bus.resolve(0xFFFF).expect("flash end").device.name(),
+            "flash"
+        );
+        assert_eq!(
+            bus.resolve(0x4000_0FFF).expect("ram end").device.name(),
+            "ram"
+        );
+        assert!(bus.resolve(0x4000_1000).is_err(), "ram window is 4kB wide");
+    }
+
+    #[test]
+    fn coredef_rejects_other_directives() {
+        let dir = tempdir().expect("tempdir");
+        write_file(dir.path(), "base.isa", "");
+        let coredef = write_file(
+            dir.path(),
+            "core.coredef",
+            ":include \"base.isa\"\n:macro helper() { a = 1 }",
+        );
+        let mut loader = IsaLoader::new();
+        let err = loader.collect_documents(coredef.as_path()).unwrap_err();
+        assert!(matches!(
+            err,
+            IsaError::Machine(msg) if msg.contains("may only contain")
+        ));
+    }
+
+    #[test]
+    fn source_overlay_replaces_entry_contents() {
+        let dir = tempdir().expect("tempdir");
+        write_file(
+            dir.path(),
+            "base.isa",
+            ":space reg addr=32 word=32 type=register\n:reg PC size=32",
+        );
+        write_file(dir.path(), "ext.isaext", ":reg SP size=32");
+        let coredef = write_file(dir.path(), "core.coredef", ":include \"base.isa\"");
+        let mut loader = IsaLoader::new();
+        let machine = loader
+            .load_machine_from_source(&coredef, ":include \"base.isa\"\n:include \"ext.isaext\"")
+            .expect("overlay loads");
+        let registers = &machine.spaces["reg"].registers;
+        assert!(
+            registers.contains_key("SP"),
+            "unsaved buffer contents are used for the entry file"
+        );
+        assert!(
+            registers.contains_key("PC"),
+            "includes still come from disk"
+        );
+
+        let base = dir.path().join("base.isa");
+        let err = loader
+            .load_machine_from_source(
+                &base,
+                ":space reg addr=32 word=32 type=register\n:reg PC size=32\n:reg PC size=32",
+            )
+            .unwrap_err();
+        assert!(
+            matches!(err, IsaError::Diagnostics { .. }),
+            "overlay contents are validated: {err:?}"
+        );
+    }
+
+    #[test]
+    fn coredef_parameters_select_conditional_variants() {
+        let dir = tempdir().expect("tempdir");
+        write_file(
+            dir.path(),
+            "base.isa",
+            ":param SIZE_MODE=32\n:space reg addr=32 word=64 type=register\n:if SIZE_MODE == 64\n:reg MSR size=64\n:else\n:reg MSR size=32\n:endif",
+        );
+        write_file(
+            dir.path(),
+            "spe.isaext",
+            ":if SPE\n:reg SPEFSCR size=32\n:endif",
+        );
+        let narrow = write_file(
+            dir.path(),
+            "narrow.coredef",
+            ":include \"base.isa\"\n:include \"spe.isaext\"",
+        );
+        let wide = write_file(
+            dir.path(),
+            "wide.coredef",
+            ":param SIZE_MODE=64\n:param SPE=1\n:include \"base.isa\"\n:include \"spe.isaext\"",
+        );
+
+        let mut loader = IsaLoader::new();
+        let machine = loader.load_machine(&narrow).expect("narrow core");
+        let registers = &machine.spaces["reg"].registers;
+        assert_eq!(registers["MSR"].size_bits, Some(32));
+        assert!(!registers.contains_key("SPEFSCR"), "SPE is off by default");
+
+        let machine = loader.load_machine(&wide).expect("wide core");
+        let registers = &machine.spaces["reg"].registers;
+        assert_eq!(
+            registers["MSR"].size_bits,
+            Some(64),
+            "coredef override wins"
+        );
+        assert!(
+            registers.contains_key("SPEFSCR"),
+            "optional unit toggled on"
+        );
+        assert!(matches!(
+            machine.parameters.get("SIZE_MODE"),
+            Some(ParameterValue::Number(64))
+        ));
+    }
+
+    #[test]
+    fn reports_parse_and_validation_errors_together() {
+        let dir = tempdir().expect("tempdir");
+        write_file(
+            dir.path(),
+            "base.isa",
+            ":space reg addr=32 word=32 type=register\n:reg PC size=32\n:reg LR size=32 bogus\n:reg PC size=32",
+        );
+        write_file(dir.path(), "ext.isaext", ":reg SP size=32 ~");
+        let coredef = write_file(
+            dir.path(),
+            "core.coredef",
+            ":include \"base.isa\"\n:include \"ext.isaext\"",
+        );
+        let err = IsaLoader::new().load_machine(&coredef).unwrap_err();
+        let IsaError::Diagnostics { phase, diagnostics } = err else {
+            panic!("expected diagnostics, got {err:?}");
+        };
+        assert_eq!(phase, DiagnosticPhase::Parser, "earliest phase wins");
+        let phases: Vec<_> = diagnostics.iter().map(|diag| diag.phase).collect();
+        assert_eq!(
+            phases,
+            vec![
+                DiagnosticPhase::Parser,
+                DiagnosticPhase::Parser,
+                DiagnosticPhase::Validation
+            ],
+            "errors from every file and phase are reported: {diagnostics:?}"
+        );
+    }
+
     fn write_file(dir: &Path, name: &str, contents: &str) -> PathBuf {
         let path = dir.join(name);
         fs::write(&path, contents).expect("write file");
@@ -1,9 +1,13 @@
 //! ISA file loading helpers (lexer, parser, include resolver).
 
+pub mod cache;
 pub mod lexer;
 pub mod loader;
 pub mod parser;
+pub mod system;
 
+pub use cache::MachineCache;
 pub use lexer::{Lexer, Token, TokenKind};
 pub use loader::IsaLoader;
-pub use parser::{Parser, parse_str, parse_str_with_spaces};
+pub use parser::{ParameterScope, Parser, parse_str, parse_str_recovering, parse_str_with_spaces};
+pub use system::SystemLoader;
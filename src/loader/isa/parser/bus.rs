@@ -0,0 +1,326 @@
+//! Parsing for `:bus` directives that map bus address windows onto memory spaces.
+
+use crate::soc::isa::ast::{BusDecl, BusRangeDecl, IsaItem};
+use crate::soc::isa::error::IsaError;
+use crate::soc::prog::types::{parse_u32_literal, parse_u64_literal};
+
+use super::spans::span_from_tokens;
+use super::{Parser, Token, TokenKind};
+
+pub(super) fn parse_bus_directive(parser: &mut Parser) -> Result<IsaItem, IsaError> {
+    let name_token = parser.expect_identifier_token("bus name")?;
+    let mut address_bits = None;
+    let mut ranges = None;
+
+    while !parser.check(TokenKind::EOF)? && !parser.check(TokenKind::Colon)? {
+        let attr_name = parser.expect_identifier("bus attribute name")?;
+        parser.expect(TokenKind::Equals, "'=' after bus attribute name")?;
+        match attr_name.to_ascii_lowercase().as_str() {
+            "addr" => {
+                let value = parser.expect(TokenKind::Number, "numeric value for addr")?;
+                let bits = parse_u32_literal(&value.lexeme).map_err(|err| {
+                    IsaError::Parser(format!(
+                        "invalid numeric literal '{}' for addr: {err}",
+                        value.lexeme
+                    ))
+                })?;
+                address_bits = Some(bits);
+            }
+            "ranges" => {
+                if ranges.is_some() {
+                    return Err(IsaError::Parser(
+                        "ranges attribute can only be specified once per :bus".into(),
+                    ));
+                }
+                ranges = Some(parse_ranges_block(parser)?);
+            }
+            other => {
+                return Err(IsaError::Parser(format!(
+                    "unknown :bus attribute '{other}'"
+                )));
+            }
+        }
+    }
+
+    let address_bits =
+        address_bits.ok_or_else(|| IsaError::Parser(":bus requires an addr attribute".into()))?;
+    let ranges =
+        ranges.ok_or_else(|| IsaError::Parser(":bus requires a ranges attribute".into()))?;
+    let end_token = parser
+        .last_consumed_token()
+        .cloned()
+        .unwrap_or_else(|| name_token.clone());
+    let span = span_from_tokens(parser.file_path(), &name_token, &end_token);
+    Ok(IsaItem::Bus(BusDecl {
+        name: name_token.lexeme,
+        address_bits,
+        ranges,
+        span,
+    }))
+}
+
+fn parse_ranges_block(parser: &mut Parser) -> Result<Vec<BusRangeDecl>, IsaError> {
+    parser.expect(TokenKind::LBrace, "'{' to start ranges block")?;
+    let mut ranges = Vec::new();
+    loop {
+        if parser.check(TokenKind::EOF)? {
+            return Err(IsaError::Parser(
+                "unterminated ranges block; missing closing '}'".into(),
+            ));
+        }
+        if parser.check(TokenKind::RBrace)? {
+            parser.consume()?;
+            break;
+        }
+        ranges.push(parse_range_entry(parser)?);
+    }
+    Ok(ranges)
+}
+
+fn parse_range_entry(parser: &mut Parser) -> Result<BusRangeDecl, IsaError> {
+    let range_token = parser.expect(TokenKind::Range, "bus range like [start +size]")?;
+    let (start, size) = parse_bus_range(&range_token)?;
+    parser.expect(TokenKind::DirectTo, "'->' after bus range")?;
+    let space = parser.expect_identifier("target space name")?;
+    let mut priority = 0;
+    let mut space_offset = 0;
+    while parser.check(TokenKind::Identifier)? {
+        let attr_name = parser.expect_identifier("bus range attribute name")?;
+        parser.expect(TokenKind::Equals, "'=' after bus range attribute name")?;
+        let value = parser.expect(TokenKind::Number, "numeric value for bus range attribute")?;
+        match attr_name.to_ascii_lowercase().as_str() {
+            "prio" => {
+                priority = parse_u32_literal(&value.lexeme).map_err(|err| {
+                    IsaError::Parser(format!(
+                        "invalid numeric literal '{}' for prio: {err}",
+                        value.lexeme
+                    ))
+                })?;
+            }
+            // `offset=` is accepted as shorthand because the spec examples use it.
+            "space_off" | "offset" => {
+                space_offset = parse_u64_literal(&value.lexeme).map_err(|err| {
+                    IsaError::Parser(format!(
+                        "invalid numeric literal '{}' for {attr_name}: {err}",
+                        value.lexeme
+                    ))
+                })?;
+            }
+            other => {
+                return Err(IsaError::Parser(format!(
+                    "unknown bus range attribute '{other}'"
+                )));
+            }
+        }
+    }
+    let end_token = parser
+        .last_consumed_token()
+        .cloned()
+        .unwrap_or_else(|| range_token.clone());
+    let span = span_from_tokens(parser.file_path(), &range_token, &end_token);
+    Ok(BusRangeDecl {
+        start,
+        size,
+        space,
+        priority,
+        space_offset,
+        span,
+    })
+}
+
+/// Converts a `[start +size<unit>]` or `[start..end]` token into a start/size pair.
+fn parse_bus_range(token: &Token) -> Result<(u64, u64), IsaError> {
+    let text = token.lexeme.trim();
+    let inner = text
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+        .ok_or_else(|| {
+            IsaError::Parser(format!(
+                "invalid bus range '{text}': expected [start +size] or [start..end]"
+            ))
+        })?;
+    let normalized: String = inner.chars().filter(|ch| !ch.is_whitespace()).collect();
+
+    if let Some((start_text, end_text)) = normalized.split_once("..") {
+        let start = parse_range_literal(start_text, "start address")?;
+        let end = parse_range_literal(end_text, "end address")?;
+        if end < start {
+            return Err(IsaError::Parser(format!(
+                "bus range end must be >= start ({start:#x}..{end:#x})"
+            )));
+        }
+        let size = (end - start).checked_add(1).ok_or_else(|| {
+            IsaError::Parser(format!(
+                "bus range {start:#x}..{end:#x} exceeds the 64-bit address space"
+            ))
+        })?;
+        return Ok((start, size));
+    }
+
+    let (start_text, size_text) = normalized.rsplit_once('+').ok_or_else(|| {
+        IsaError::Parser(format!("invalid bus range '{text}': missing '+' or '..'"))
+    })?;
+    let start = parse_range_literal(start_text, "start address")?;
+    let (digits, unit) = split_size_unit(size_text);
+    let count = parse_range_literal(digits, "size")?;
+    let multiplier = size_unit_multiplier(unit)?;
+    let size = count.checked_mul(multiplier).ok_or_else(|| {
+        IsaError::Parser(format!(
+            "bus range size '{size_text}' exceeds the 64-bit address space"
+        ))
+    })?;
+    if size == 0 {
+        return Err(IsaError::Parser(format!(
+            "bus range '{text}' must have a non-zero size"
+        )));
+    }
+    Ok((start, size))
+}
+
+fn parse_range_literal(text: &str, context: &str) -> Result<u64, IsaError> {
+    parse_u64_literal(text.trim_start_matches('+'))
+        .map_err(|err| IsaError::Parser(format!("invalid bus range {context} '{text}': {err}")))
+}
+
+/// Splits a size literal from its trailing unit using the same digit rules as the lexer,
+/// so `0x1B` stays a hex literal while `0x1kB` carries a `kB` unit.
+fn split_size_unit(text: &str) -> (&str, &str) {
+    let lower = text.to_ascii_lowercase();
+    let (prefix_len, accepts): (usize, fn(char) -> bool) = if lower.starts_with("0x") {
+        (2, |ch| ch.is_ascii_hexdigit())
+    } else if lower.starts_with("0b") {
+        (2, |ch| matches!(ch, '0' | '1'))
+    } else if lower.starts_with("0o") {
+        (2, |ch| matches!(ch, '0'..='7'))
+    } else {
+        (0, |ch| ch.is_ascii_digit())
+    };
+    let digits_end = text[prefix_len..]
+        .char_indices()
+        .find(|(_, ch)| *ch != '_' && !accepts(*ch))
+        .map(|(idx, _)| prefix_len + idx)
+        .unwrap_or(text.len());
+    text.split_at(digits_end)
+}
+
+fn size_unit_multiplier(unit: &str) -> Result<u64, IsaError> {
+    match unit.to_ascii_lowercase().as_str() {
+        "" => Ok(1),
+        "kb" => Ok(1 << 10),
+        "mb" => Ok(1 << 20),
+        "gb" => Ok(1 << 30),
+        "tb" => Ok(1 << 40),
+        "pb" => Ok(1 << 50),
+        other => Err(IsaError::Parser(format!(
+            "unknown bus range size unit '{other}': expected kB/MB/GB/TB/PB"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::soc::isa::ast::{BusDecl, IsaItem};
+    use crate::soc::isa::diagnostic::DiagnosticPhase;
+    use crate::soc::isa::error::IsaError;
+
+    use super::super::parse_str;
+
+    fn parse_bus(source: &str) -> BusDecl {
+        let doc = parse_str(PathBuf::from("test.coredef"), source).expect("parse");
+        match doc.items.into_iter().next() {
+            Some(IsaItem::Bus(bus)) => bus,
+            other => panic!("expected bus item, got {other:?}"),
+        }
+    }
+
+    fn expect_parser_diag(err: IsaError, needle: &str) {
+        match err {
+            IsaError::Diagnostics {
+                phase: DiagnosticPhase::Parser,
+                diagnostics,
+            } => {
+                assert!(
+                    diagnostics.iter().any(|diag| diag.message.contains(needle)),
+                    "diagnostics missing '{needle}': {diagnostics:?}"
+                );
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_ranges_with_size_units_and_inclusive_ends() {
+        let bus = parse_bus(
+            ":bus sysbus addr=32 ranges={\n    [0x0 .. 0x3FFFF] -> small_flash\n    [0x800000 +8MB] -> large_flash\n    [0x40000000 +512kB] -> ram\n    [0xC3F80000 +0x10000] -> etpu\n}",
+        );
+        assert_eq!(bus.name, "sysbus");
+        assert_eq!(bus.address_bits, 32);
+        let windows: Vec<_> = bus
+            .ranges
+            .iter()
+            .map(|range| (range.start, range.size, range.space.as_str()))
+            .collect();
+        assert_eq!(
+            windows,
+            vec![
+                (0x0, 0x40000, "small_flash"),
+                (0x800000, 8 << 20, "large_flash"),
+                (0x4000_0000, 512 << 10, "ram"),
+                (0xC3F8_0000, 0x10000, "etpu"),
+            ],
+            "inclusive ends and size units should normalize to byte sizes"
+        );
+    }
+
+    #[test]
+    fn parses_priority_and_space_offset() {
+        let bus = parse_bus(
+            ":bus sysbus addr=32 ranges={\n    [0x40000400 +1kB] -> ram space_off=0x1080 prio=1\n    [0x0 +4] -> rom offset=8\n}",
+        );
+        let overlay = &bus.ranges[0];
+        assert_eq!(overlay.priority, 1, "prio should be captured");
+        assert_eq!(overlay.space_offset, 0x1080, "space_off should be captured");
+        assert_eq!(
+            bus.ranges[1].space_offset, 8,
+            "offset= is accepted as an alias for space_off"
+        );
+        assert_eq!(bus.ranges[1].priority, 0, "priority defaults to zero");
+    }
+
+    #[test]
+    fn hex_sizes_keep_trailing_hex_digits() {
+        let bus = parse_bus(":bus b addr=16 ranges={ [0x0 +0x1B] -> ram }");
+        assert_eq!(
+            bus.ranges[0].size, 0x1B,
+            "a trailing 'B' is a hex digit rather than a size unit"
+        );
+    }
+
+    #[test]
+    fn rejects_bus_without_ranges() {
+        let err = parse_str(PathBuf::from("test.isa"), ":bus sysbus addr=32").unwrap_err();
+        expect_parser_diag(err, "requires a ranges attribute");
+    }
+
+    #[test]
+    fn rejects_reversed_inclusive_range() {
+        let err = parse_str(
+            PathBuf::from("test.isa"),
+            ":bus sysbus addr=32 ranges={ [0x10..0x4] -> ram }",
+        )
+        .unwrap_err();
+        expect_parser_diag(err, "end must be >= start");
+    }
+
+    #[test]
+    fn rejects_unknown_range_attribute() {
+        let err = parse_str(
+            PathBuf::from("test.isa"),
+            ":bus sysbus addr=32 ranges={ [0x0 +4] -> ram weight=2 }",
+        )
+        .unwrap_err();
+        expect_parser_diag(err, "unknown bus range attribute 'weight'");
+    }
+}
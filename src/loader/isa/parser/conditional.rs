@@ -0,0 +1,447 @@
+//! `:if` / `:elif` / `:else` / `:endif` directives keyed on `:param` values.
+//!
+//! Conditions are evaluated while parsing, so inactive branches never reach the AST: they are
+//! skipped token by token (braced blocks wholesale) without being parsed or validated.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::soc::isa::ast::ParameterValue;
+use crate::soc::isa::diagnostic::SourceSpan;
+use crate::soc::isa::error::IsaError;
+use crate::soc::isa::suggest::closest_name;
+
+use super::spans::span_from_token;
+use super::{Parser, Token, TokenKind};
+
+/// Parameter values visible to `:if` conditions. Values set through [`Self::override_value`]
+/// (the `:param` lines of a `.coredef`) win over the defaults declared by included files.
+#[derive(Debug, Clone, Default)]
+pub struct ParameterScope {
+    values: HashMap<String, ParameterValue>,
+    overridden: HashSet<String>,
+}
+
+impl ParameterScope {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a `:param` default unless the name has been overridden.
+    pub fn define(&mut self, name: &str, value: ParameterValue) {
+        if !self.overridden.contains(name) {
+            self.values.insert(name.to_string(), value);
+        }
+    }
+
+    pub fn override_value(&mut self, name: &str, value: ParameterValue) {
+        self.overridden.insert(name.to_string());
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ParameterValue> {
+        self.values.get(name)
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.overridden.clear();
+    }
+
+    fn names(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(String::as_str)
+    }
+}
+
+/// One open `:if` whose `:endif` has not been seen yet.
+#[derive(Debug, Clone)]
+pub(super) struct ConditionalFrame {
+    span: Option<SourceSpan>,
+    /// Whether one of the branches has already been taken.
+    taken: bool,
+    seen_else: bool,
+}
+
+enum BranchEnd {
+    Elif,
+    Else,
+    Endif,
+    Eof,
+}
+
+#[derive(Clone, Copy)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl<'src> Parser<'src> {
+    /// Handles the conditional directive `name`, whose keyword has just been consumed.
+    pub(super) fn parse_conditional_directive(&mut self, name: &str) -> Result<(), IsaError> {
+        match name {
+            "if" => {
+                let span = self
+                    .last_consumed_token()
+                    .map(|token| span_from_token(self.file_path(), token));
+                let active = self.parse_condition_or_false()?;
+                self.conditionals_mut().push(ConditionalFrame {
+                    span,
+                    taken: active,
+                    seen_else: false,
+                });
+                if active {
+                    Ok(())
+                } else {
+                    self.skip_inactive_branches()
+                }
+            }
+            "elif" | "else" => {
+                let Some(frame) = self.conditionals_mut().last_mut() else {
+                    return Err(IsaError::Parser(format!(":{name} without matching :if")));
+                };
+                if frame.seen_else {
+                    return Err(IsaError::Parser(format!(":{name} after :else")));
+                }
+                frame.seen_else = name == "else";
+                // Reached from an active branch, so everything up to :endif is inactive.
+                self.skip_inactive_branches()
+            }
+            _ => match self.conditionals_mut().pop() {
+                Some(_) => Ok(()),
+                None => Err(IsaError::Parser(":endif without matching :if".into())),
+            },
+        }
+    }
+
+    /// Reports every `:if` still open at the end of the document.
+    pub(super) fn close_conditionals(&mut self) {
+        let open = std::mem::take(self.conditionals_mut());
+        for frame in open {
+            self.push_diagnostic_at("unterminated :if; missing :endif".to_string(), frame.span);
+        }
+    }
+
+    /// Skips branches of the innermost frame until one becomes active or `:endif` closes it.
+    fn skip_inactive_branches(&mut self) -> Result<(), IsaError> {
+        loop {
+            match self.skip_inactive_branch() {
+                BranchEnd::Eof => return Ok(()),
+                BranchEnd::Endif => {
+                    self.conditionals_mut().pop();
+                    return Ok(());
+                }
+                BranchEnd::Else => {
+                    if self.current_frame().seen_else {
+                        self.push_diagnostic_at(":else after :else".to_string(), None);
+                    }
+                    let frame = self.current_frame();
+                    frame.seen_else = true;
+                    if !frame.taken {
+                        frame.taken = true;
+                        return Ok(());
+                    }
+                }
+                BranchEnd::Elif => {
+                    if self.current_frame().taken {
+                        continue;
+                    }
+                    if self.parse_condition_or_false()? {
+                        self.current_frame().taken = true;
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+
+    fn current_frame(&mut self) -> &mut ConditionalFrame {
+        self.conditionals_mut()
+            .last_mut()
+            .expect("skipping requires an open :if")
+    }
+
+    /// Consumes tokens up to the `:elif`, `:else` or `:endif` keyword closing the current
+    /// branch. Nested conditionals are skipped whole and lexer errors are ignored since the
+    /// text is inactive.
+    fn skip_inactive_branch(&mut self) -> BranchEnd {
+        let mut depth = 0usize;
+        loop {
+            let Ok(token) = self.consume() else {
+                continue;
+            };
+            match token.kind {
+                TokenKind::EOF => return BranchEnd::Eof,
+                TokenKind::LBrace => self.skip_braced_block(),
+                TokenKind::Colon => {
+                    let keyword = match self.peek() {
+                        Ok(next) if next.kind == TokenKind::Identifier => next.lexeme.clone(),
+                        _ => continue,
+                    };
+                    let end = match keyword.as_str() {
+                        "if" => {
+                            depth += 1;
+                            None
+                        }
+                        "endif" if depth > 0 => {
+                            depth -= 1;
+                            None
+                        }
+                        "endif" => Some(BranchEnd::Endif),
+                        "else" if depth == 0 => Some(BranchEnd::Else),
+                        "elif" if depth == 0 => Some(BranchEnd::Elif),
+                        _ => None,
+                    };
+                    if let Some(end) = end {
+                        let _ = self.consume();
+                        return end;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Evaluates a condition; a malformed one is reported and treated as false so the
+    /// matching `:else`/`:endif` still pair up.
+    fn parse_condition_or_false(&mut self) -> Result<bool, IsaError> {
+        match self.parse_or() {
+            Ok(value) => Ok(value),
+            Err(err) => {
+                self.record_recoverable(err)?;
+                Ok(false)
+            }
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<bool, IsaError> {
+        let mut value = self.parse_and()?;
+        while self.check(TokenKind::DoublePipe)? {
+            self.consume()?;
+            let rhs = self.parse_and()?;
+            value = value || rhs;
+        }
+        Ok(value)
+    }
+
+    fn parse_and(&mut self) -> Result<bool, IsaError> {
+        let mut value = self.parse_unary()?;
+        while self.check(TokenKind::DoubleAmpersand)? {
+            self.consume()?;
+            let rhs = self.parse_unary()?;
+            value = value && rhs;
+        }
+        Ok(value)
+    }
+
+    fn parse_unary(&mut self) -> Result<bool, IsaError> {
+        if self.check(TokenKind::Bang)? {
+            self.consume()?;
+            return Ok(!self.parse_unary()?);
+        }
+        if self.check(TokenKind::LParen)? {
+            self.consume()?;
+            let value = self.parse_or()?;
+            self.expect(TokenKind::RParen, "')' to close condition")?;
+            return Ok(value);
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<bool, IsaError> {
+        let name_token = self.expect_identifier_token("parameter name in condition")?;
+        let name = name_token.lexeme.trim_start_matches('#').to_string();
+        let Some(comparison) = self.parse_comparison_operator()? else {
+            // A bare name tests that the parameter is set to a truthy value.
+            return Ok(self.parameters().get(&name).is_some_and(is_truthy));
+        };
+        let operand = self.consume()?;
+        let Some(value) = self.parameters().get(&name).cloned() else {
+            let suggestion = closest_name(&name, self.parameters().names()).map(str::to_string);
+            return Err(self.error_with_suggestion(
+                format!("condition references undefined parameter '{name}'"),
+                suggestion.as_deref(),
+            ));
+        };
+        compare(&name, &value, comparison, &operand)
+    }
+
+    fn parse_comparison_operator(&mut self) -> Result<Option<Comparison>, IsaError> {
+        let comparison = match self.peek()?.kind {
+            TokenKind::DoubleEquals => Comparison::Eq,
+            TokenKind::BangEquals => Comparison::Ne,
+            TokenKind::LessThan => Comparison::Lt,
+            TokenKind::GreaterThan => Comparison::Gt,
+            _ => return Ok(None),
+        };
+        self.consume()?;
+        if self.check(TokenKind::Equals)? {
+            self.consume()?;
+            return Ok(Some(match comparison {
+                Comparison::Lt => Comparison::Le,
+                Comparison::Gt => Comparison::Ge,
+                _ => return Err(IsaError::Parser("unexpected '=' in condition".into())),
+            }));
+        }
+        Ok(Some(comparison))
+    }
+}
+
+fn is_truthy(value: &ParameterValue) -> bool {
+    match value {
+        ParameterValue::Number(value) => *value != 0,
+        ParameterValue::Word(word) => !matches!(
+            word.to_ascii_lowercase().as_str(),
+            "" | "0" | "false" | "no" | "off"
+        ),
+    }
+}
+
+fn compare(
+    name: &str,
+    value: &ParameterValue,
+    comparison: Comparison,
+    operand: &Token,
+) -> Result<bool, IsaError> {
+    let ordering = match (value, &operand.kind) {
+        (ParameterValue::Number(lhs), TokenKind::Number) => {
+            let rhs =
+                crate::soc::prog::types::parse_u64_literal(&operand.lexeme).map_err(|err| {
+                    IsaError::Parser(format!(
+                        "invalid numeric literal '{}': {err}",
+                        operand.lexeme
+                    ))
+                })?;
+            lhs.cmp(&rhs)
+        }
+        (ParameterValue::Word(lhs), TokenKind::Identifier | TokenKind::String) => {
+            let equal = lhs.eq_ignore_ascii_case(&operand.lexeme);
+            return match comparison {
+                Comparison::Eq => Ok(equal),
+                Comparison::Ne => Ok(!equal),
+                _ => Err(IsaError::Parser(format!(
+                    "parameter '{name}' holds a word and only supports == and !="
+                ))),
+            };
+        }
+        (ParameterValue::Number(_), TokenKind::Identifier | TokenKind::String)
+        | (ParameterValue::Word(_), TokenKind::Number) => {
+            return match comparison {
+                Comparison::Eq => Ok(false),
+                Comparison::Ne => Ok(true),
+                _ => Err(IsaError::Parser(format!(
+                    "cannot order parameter '{name}' against '{}'",
+                    operand.lexeme
+                ))),
+            };
+        }
+        _ => {
+            return Err(IsaError::Parser(format!(
+                "expected a number or word to compare parameter '{name}' against"
+            )));
+        }
+    };
+    Ok(match comparison {
+        Comparison::Eq => ordering.is_eq(),
+        Comparison::Ne => ordering.is_ne(),
+        Comparison::Lt => ordering.is_lt(),
+        Comparison::Le => ordering.is_le(),
+        Comparison::Gt => ordering.is_gt(),
+        Comparison::Ge => ordering.is_ge(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::loader::isa::parse_str_recovering;
+    use crate::soc::isa::ast::{IsaItem, IsaSpecification, SpaceMember};
+
+    fn parse_with(source: &str, scope: &ParameterScope) -> (IsaSpecification, Vec<String>) {
+        let (doc, diagnostics) =
+            parse_str_recovering(PathBuf::from("test.isa"), source, &HashMap::new(), scope)
+                .expect("parse");
+        let messages = diagnostics
+            .into_iter()
+            .map(|diag| diag.message_with_suggestion())
+            .collect();
+        (doc, messages)
+    }
+
+    fn register_names(doc: &IsaSpecification) -> Vec<String> {
+        doc.items
+            .iter()
+            .filter_map(|item| match item {
+                IsaItem::SpaceMember(member) => match &member.member {
+                    SpaceMember::Field(field) => Some(field.name.clone()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    const VARIANTS: &str = r#":param SIZE_MODE=32
+:space reg addr=32 word=64 type=register
+:if SIZE_MODE == 64
+:reg MSR64 size=64
+:elif SIZE_MODE == 32 && !SPE
+:reg MSR32 size=32
+:else
+:reg MSR size=32
+:endif
+:if SPE
+:reg SPEFSCR size=32 subfields={
+    // inactive text is skipped without being parsed
+    SOVH @(0) op=bogus
+}
+:if SIZE_MODE >= 32
+:reg NESTED size=32
+:endif
+:endif
+:reg PC size=32"#;
+
+    #[test]
+    fn selects_branches_from_file_parameters() {
+        let (doc, errors) = parse_with(VARIANTS, &ParameterScope::new());
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(register_names(&doc), vec!["MSR32", "PC"]);
+    }
+
+    #[test]
+    fn coredef_overrides_win_over_file_defaults() {
+        let mut scope = ParameterScope::new();
+        scope.override_value("SIZE_MODE", ParameterValue::Number(64));
+        scope.override_value("SPE", ParameterValue::Word("on".into()));
+        let (doc, errors) = parse_with(VARIANTS, &scope);
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(
+            register_names(&doc),
+            vec!["MSR64", "SPEFSCR", "NESTED", "PC"],
+            "the file's own :param SIZE_MODE=32 does not replace the override"
+        );
+    }
+
+    #[test]
+    fn reports_unbalanced_and_undefined_conditions() {
+        let (_, errors) = parse_with(
+            ":param SIZE_MODE=32\n:endif\n:if SIZE_MDOE == 64\n:param A=1\n:else\n:param B=1\n:else\n:param C=1",
+            &ParameterScope::new(),
+        );
+        assert_eq!(
+            errors,
+            vec![
+                ":endif without matching :if".to_string(),
+                "condition references undefined parameter 'SIZE_MDOE'; did you mean 'SIZE_MODE'?"
+                    .to_string(),
+                ":else after :else".to_string(),
+                "unterminated :if; missing :endif".to_string(),
+            ]
+        );
+    }
+}
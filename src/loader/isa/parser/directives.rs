@@ -2,41 +2,62 @@ use std::path::PathBuf;
 
 use super::spans::span_from_tokens;
 use super::{
-    Parser, TokenKind, parameters::parse_parameter_decl, space::parse_space_directive,
-    space_context::parse_space_context_directive,
+    Parser, TokenKind, bus::parse_bus_directive, parameters::parse_parameter_decl,
+    space::parse_space_directive, space_context::parse_space_context_directive,
 };
-use crate::soc::isa::ast::{IncludeDecl, IsaItem, MacroDecl};
+use crate::soc::isa::ast::{AttachDecl, IncludeDecl, IsaItem, MacroDecl};
 use crate::soc::isa::error::IsaError;
+use crate::soc::isa::suggest::closest_name;
+
+const DIRECTIVES: &[&str] = &[
+    "fileset", "param", "space", "include", "attach", "macro", "bus", "if", "elif", "else", "endif",
+];
 
 impl<'src> Parser<'src> {
-    pub(super) fn parse_directive(&mut self) -> Result<IsaItem, IsaError> {
+    /// Parses one directive. Conditional directives steer which directives are parsed and
+    /// yield no item of their own.
+    pub(super) fn parse_directive(&mut self) -> Result<Option<IsaItem>, IsaError> {
         self.expect(TokenKind::Colon, "directive introducer ':'")?;
         let name = self.expect_identifier("directive name")?;
+        if matches!(name.as_str(), "if" | "elif" | "else" | "endif") {
+            self.parse_conditional_directive(&name)?;
+            self.ensure_directive_boundary(&name)?;
+            return Ok(None);
+        }
         let item = match name.as_str() {
             "fileset" => self.parse_fileset_directive(),
             "param" => self.parse_param_directive(),
             "space" => parse_space_directive(self),
             "include" => self.parse_include_directive(),
+            "attach" => self.parse_attach_directive(),
             "macro" => self.parse_macro_directive(),
+            "bus" => parse_bus_directive(self),
             _ => {
                 if self.is_known_space(&name) {
                     self.parse_space_context(&name)
                 } else {
-                    Err(IsaError::Parser(format!("unsupported directive :{name}")))
+                    let candidates = DIRECTIVES.iter().copied().chain(self.known_space_names());
+                    let suggestion = closest_name(&name, candidates).map(|name| format!(":{name}"));
+                    Err(self.error_with_suggestion(
+                        format!("unsupported directive :{name}"),
+                        suggestion.as_deref(),
+                    ))
                 }
             }
         }?;
         self.ensure_directive_boundary(&name)?;
-        Ok(item)
+        Ok(Some(item))
     }
 
     fn parse_fileset_directive(&mut self) -> Result<IsaItem, IsaError> {
         let decl = parse_parameter_decl(self, "fileset parameter name")?;
+        self.parameters_mut().define(&decl.name, decl.value.clone());
         Ok(IsaItem::Parameter(decl))
     }
 
     fn parse_param_directive(&mut self) -> Result<IsaItem, IsaError> {
         let decl = parse_parameter_decl(self, "parameter name")?;
+        self.parameters_mut().define(&decl.name, decl.value.clone());
         Ok(IsaItem::Parameter(decl))
     }
 
@@ -53,6 +74,28 @@ impl<'src> Parser<'src> {
         }))
     }
 
+    fn parse_attach_directive(&mut self) -> Result<IsaItem, IsaError> {
+        if !self.allows_attach() {
+            return Err(IsaError::Parser(
+                ":attach directive is only allowed inside .sysdef files".into(),
+            ));
+        }
+        let context_token = self.expect_identifier_token("attach context tag")?;
+        let path_token = self.consume_path()?;
+        if !matches!(path_token.kind, TokenKind::String | TokenKind::Identifier) {
+            return Err(IsaError::Parser(format!(
+                "expected core definition path after :attach {}, found {:?}",
+                context_token.lexeme, path_token.kind
+            )));
+        }
+        let span = span_from_tokens(self.file_path(), &context_token, &path_token);
+        Ok(IsaItem::Attach(AttachDecl {
+            context: context_token.lexeme,
+            path: PathBuf::from(path_token.lexeme),
+            span,
+        }))
+    }
+
     fn parse_macro_directive(&mut self) -> Result<IsaItem, IsaError> {
         let name_token = self.expect_identifier_token("macro name")?;
         let params = self.parse_macro_parameters()?;
@@ -293,6 +336,56 @@ mod tests {
         expect_parser_diag(err, "only allowed inside .coredef");
     }
 
+    #[test]
+    fn parses_attach_in_sysdef() {
+        let doc = parse_str(
+            PathBuf::from("test.sysdef"),
+            ":attach CORE0 \"core0.coredef\"\n:attach ETPUA etpu.coredef\n:attach ETPUB ../cores/etpu-2.coredef // second engine",
+        )
+        .expect("parse");
+        let attached: Vec<_> = doc
+            .items
+            .iter()
+            .map(|item| match item {
+                IsaItem::Attach(attach) => (attach.context.as_str(), attach.path.clone()),
+                other => panic!("unexpected item: {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            attached,
+            vec![
+                ("CORE0", PathBuf::from("core0.coredef")),
+                ("ETPUA", PathBuf::from("etpu.coredef")),
+                ("ETPUB", PathBuf::from("../cores/etpu-2.coredef")),
+            ],
+            "quoted and bare paths should both be accepted"
+        );
+    }
+
+    #[test]
+    fn parses_the_example_sysdef() {
+        let path =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("docs/spec/examples/valid-sys.sysdef");
+        let source = std::fs::read_to_string(&path).expect("read example");
+        let doc = parse_str(path, &source).expect("example sysdef parses");
+        let attached = doc
+            .items
+            .iter()
+            .filter(|item| matches!(item, IsaItem::Attach(_)))
+            .count();
+        assert_eq!(attached, 3, "every :attach entry is parsed");
+    }
+
+    #[test]
+    fn rejects_attach_outside_sysdef() {
+        let err = parse_str(
+            PathBuf::from("test.coredef"),
+            ":attach CORE0 \"core0.coredef\"",
+        )
+        .unwrap_err();
+        expect_parser_diag(err, "only allowed inside .sysdef");
+    }
+
     #[test]
     fn parses_macro_directive() {
         let doc = parse(":macro upd_cr0(res) {$reg::CR0 = #res}");
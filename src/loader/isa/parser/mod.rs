@@ -1,5 +1,7 @@
 //! Recursive descent parser that turns lexer tokens into [`IsaDocument`](crate::soc::isa::ast::IsaDocument).
 
+mod bus;
+mod conditional;
 mod directives;
 mod parameters;
 mod semantics;
@@ -8,7 +10,8 @@ mod space_context;
 mod spans;
 mod specification;
 
-pub use specification::{Parser, parse_str, parse_str_with_spaces};
+pub use conditional::ParameterScope;
+pub use specification::{Parser, parse_str, parse_str_recovering, parse_str_with_spaces};
 
 pub(super) use super::lexer::{Lexer, Token, TokenKind};
 pub(super) use semantics::parse_semantic_expr_block;
@@ -1,12 +1,13 @@
 use crate::soc::isa::ast::{
-    ContextReference, FieldDecl, FieldIndexRange, FormDecl, InstructionDecl, IsaItem, MaskField,
-    MaskSelector, MaskSpec, SpaceKind, SpaceMember, SpaceMemberDecl, SubFieldDecl, SubFieldOp,
+    AliasConstraint, AliasSpec, AliasValue, ContextReference, FieldDecl, FieldIndexRange, FormDecl,
+    InstructionDecl, IsaItem, MaskField, MaskSelector, MaskSpec, SpaceKind, SpaceMember,
+    SpaceMemberDecl, SubFieldDecl, SubFieldOp,
 };
 use crate::soc::isa::error::IsaError;
-use crate::soc::isa::semantics::SemanticBlock;
-use crate::soc::prog::types::parse_u64_literal;
+use crate::soc::isa::semantics::{SemanticBlock, SemanticExpr};
+use crate::soc::prog::types::{parse_u64_literal, parse_u128_literal};
 
-use super::{Parser, Token, TokenKind, spans::span_from_tokens};
+use super::{Parser, Token, TokenKind, parse_semantic_expr_block, spans::span_from_tokens};
 
 pub(super) fn parse_space_context_directive(
     parser: &mut Parser,
@@ -40,6 +41,10 @@ fn parse_logic_context_directive(parser: &mut Parser, space: &str) -> Result<Isa
     let mut semantics: Option<SemanticBlock> = None;
     let mut display: Option<String> = None;
     let mut operator: Option<String> = None;
+    let mut extend: Option<String> = None;
+    let mut alias: Option<String> = None;
+    let mut constraints: Option<Vec<AliasConstraint>> = None;
+    let mut valid: Option<SemanticExpr> = None;
 
     while !parser.check(TokenKind::EOF)? && !parser.check(TokenKind::Colon)? {
         let attr_token = parser.expect_identifier_token("logic attribute name")?;
@@ -98,6 +103,38 @@ fn parse_logic_context_directive(parser: &mut Parser, space: &str) -> Result<Isa
                 let value = parser.expect(TokenKind::String, "string literal for op")?;
                 operator = Some(value.lexeme);
             }
+            "extend" => {
+                if extend.is_some() {
+                    return Err(IsaError::Parser(format!(
+                        "duplicate extend attribute for '{name}'"
+                    )));
+                }
+                extend = Some(parser.expect_identifier("logic space name for extend")?);
+            }
+            "alias" => {
+                if alias.is_some() {
+                    return Err(IsaError::Parser(format!(
+                        "duplicate alias attribute for '{name}'"
+                    )));
+                }
+                alias = Some(parser.expect_identifier("base instruction name for alias")?);
+            }
+            "when" => {
+                if constraints.is_some() {
+                    return Err(IsaError::Parser(format!(
+                        "duplicate when block for '{name}'"
+                    )));
+                }
+                constraints = Some(parse_when_block(parser)?);
+            }
+            "valid" => {
+                if valid.is_some() {
+                    return Err(IsaError::Parser(format!(
+                        "duplicate valid attribute for '{name}'"
+                    )));
+                }
+                valid = Some(parse_semantic_expr_block(parser, "valid expression")?);
+            }
             other => {
                 return Err(IsaError::Parser(format!(
                     "unknown logic attribute '{other}'"
@@ -112,7 +149,11 @@ fn parse_logic_context_directive(parser: &mut Parser, space: &str) -> Result<Isa
         .unwrap_or_else(|| name_token.clone());
     let span = span_from_tokens(parser.file_path(), &name_token, &end_token);
 
-    let has_instruction_attrs = mask.is_some() || semantics.is_some() || !operands.is_empty();
+    let has_instruction_attrs = mask.is_some()
+        || semantics.is_some()
+        || extend.is_some()
+        || alias.is_some()
+        || !operands.is_empty();
     if qualifier.is_none() && subfields.is_none() && !has_instruction_attrs {
         return Err(IsaError::Parser(format!(
             "form '{name}' must declare a subfields block"
@@ -140,6 +181,21 @@ fn parse_logic_context_directive(parser: &mut Parser, space: &str) -> Result<Isa
                 "forms cannot declare an op attribute ('{name}')"
             )));
         }
+        if extend.is_some() {
+            return Err(IsaError::Parser(format!(
+                "forms cannot declare an extend attribute ('{name}')"
+            )));
+        }
+        if alias.is_some() || constraints.is_some() {
+            return Err(IsaError::Parser(format!(
+                "forms cannot declare alias or when attributes ('{name}')"
+            )));
+        }
+        if valid.is_some() {
+            return Err(IsaError::Parser(format!(
+                "forms cannot declare a valid attribute ('{name}')"
+            )));
+        }
         let form = FormDecl {
             space: space.to_string(),
             name,
@@ -161,6 +217,31 @@ fn parse_logic_context_directive(parser: &mut Parser, space: &str) -> Result<Isa
         )));
     }
 
+    let alias = match (alias, constraints) {
+        (Some(base), constraints) => {
+            if mask.is_some() || semantics.is_some() || extend.is_some() {
+                return Err(IsaError::Parser(format!(
+                    "alias '{name}' takes its mask and semantics from '{base}' and cannot declare mask, semantics or extend"
+                )));
+            }
+            if valid.is_some() {
+                return Err(IsaError::Parser(format!(
+                    "alias '{name}' is checked by the valid attribute of '{base}' and cannot declare its own"
+                )));
+            }
+            Some(AliasSpec {
+                base,
+                constraints: constraints.unwrap_or_default(),
+            })
+        }
+        (None, Some(_)) => {
+            return Err(IsaError::Parser(format!(
+                "when block on '{name}' requires an alias attribute"
+            )));
+        }
+        (None, None) => None,
+    };
+
     let instruction = InstructionDecl {
         space: space.to_string(),
         form: qualifier,
@@ -172,6 +253,9 @@ fn parse_logic_context_directive(parser: &mut Parser, space: &str) -> Result<Isa
         semantics,
         display,
         operator,
+        extend,
+        alias,
+        valid,
         span,
     };
     Ok(IsaItem::SpaceMember(SpaceMemberDecl {
@@ -234,6 +318,46 @@ fn parse_mask_block(parser: &mut Parser) -> Result<MaskSpec, IsaError> {
     Ok(MaskSpec { fields })
 }
 
+/// `when={FIELD=<number>, FIELD=#OTHER}` constraints of an alias.
+fn parse_when_block(parser: &mut Parser) -> Result<Vec<AliasConstraint>, IsaError> {
+    parser.expect(TokenKind::LBrace, "'{' to start when block")?;
+    let mut constraints = Vec::new();
+    loop {
+        if parser.check(TokenKind::RBrace)? {
+            parser.consume()?;
+            break;
+        }
+        if parser.check(TokenKind::Comma)? {
+            parser.consume()?;
+            continue;
+        }
+        let field = parser.expect_identifier("field name in when block")?;
+        parser.expect(TokenKind::Equals, "'=' after when field name")?;
+        let value = if parser.check(TokenKind::Number)? {
+            let token = parser.consume()?;
+            AliasValue::Number(parse_u64_literal(&token.lexeme).map_err(|err| {
+                IsaError::Parser(format!("invalid when literal '{}': {err}", token.lexeme))
+            })?)
+        } else {
+            let token = parser.expect_identifier_token("number or '#field' in when block")?;
+            let Some(other) = token.lexeme.strip_prefix('#') else {
+                return Err(IsaError::Parser(format!(
+                    "when value for '{field}' must be a number or '#field', found '{}'",
+                    token.lexeme
+                )));
+            };
+            AliasValue::Field(other.to_string())
+        };
+        constraints.push(AliasConstraint { field, value });
+    }
+    if constraints.is_empty() {
+        return Err(IsaError::Parser(
+            "when block must contain at least one field".into(),
+        ));
+    }
+    Ok(constraints)
+}
+
 fn parse_mask_selector(parser: &mut Parser) -> Result<MaskSelector, IsaError> {
     if parser.check(TokenKind::BitExpr)? {
         let token = parser.consume()?;
@@ -286,7 +410,7 @@ fn parse_register_form(parser: &mut Parser, space: &str) -> Result<IsaItem, IsaE
             "reset" => {
                 ensure_redirect_compatible("reset", redirect.is_some())?;
                 ensure_unique(attr_name.as_str(), &reset)?;
-                reset = Some(parse_number(parser, "reset")?);
+                reset = Some(parse_wide_number(parser, "reset")?);
             }
             "descr" => {
                 ensure_unique(attr_name.as_str(), &description)?;
@@ -389,6 +513,16 @@ fn parse_number(parser: &mut Parser, context: &str) -> Result<u64, IsaError> {
     })
 }
 
+fn parse_wide_number(parser: &mut Parser, context: &str) -> Result<u128, IsaError> {
+    let token = parser.expect(TokenKind::Number, &format!("numeric literal for {context}"))?;
+    parse_u128_literal(&token.lexeme).map_err(|err| {
+        IsaError::Parser(format!(
+            "invalid numeric literal '{}' for {context}: {err}",
+            token.lexeme
+        ))
+    })
+}
+
 fn parse_index_range(token: &Token) -> Result<FieldIndexRange, IsaError> {
     let text = token.lexeme.trim();
     if !text.starts_with('[') || !text.ends_with(']') {
@@ -454,52 +588,75 @@ fn parse_subfields_block(parser: &mut Parser) -> Result<Vec<SubFieldDecl>, IsaEr
             parser.consume()?;
             break;
         }
-        let name = parser.expect_identifier("subfield name")?;
-        let bit_spec = parser.expect(TokenKind::BitExpr, "bit specification '@(...)'")?;
-        let mut operations = Vec::new();
-        let mut description = None;
-
-        loop {
-            if parser.check(TokenKind::Identifier)? {
-                let peek = parser.peek()?;
-                match peek.lexeme.as_str() {
-                    "op" => {
-                        parser.consume()?;
-                        parser.expect(TokenKind::Equals, "'=' after op attribute")?;
-                        if !operations.is_empty() {
-                            return Err(IsaError::Parser(format!(
-                                "subfield {name} op attribute specified multiple times"
-                            )));
-                        }
-                        operations = parse_subfield_ops(parser)?;
+        // A malformed entry drops the rest of the block but keeps the directive alive.
+        match parse_subfield_entry(parser) {
+            Ok(entry) => entries.push(entry),
+            Err(err) => {
+                parser.recover_in_block(err)?;
+                break;
+            }
+        }
+    }
+    Ok(entries)
+}
+
+fn parse_subfield_entry(parser: &mut Parser) -> Result<SubFieldDecl, IsaError> {
+    let name = parser.expect_identifier("subfield name")?;
+    let bit_spec = parser.expect(TokenKind::BitExpr, "bit specification '@(...)'")?;
+    let mut operations = Vec::new();
+    let mut description = None;
+    let mut reset = None;
+
+    loop {
+        if parser.check(TokenKind::Identifier)? {
+            let peek = parser.peek()?;
+            match peek.lexeme.as_str() {
+                "op" => {
+                    parser.consume()?;
+                    parser.expect(TokenKind::Equals, "'=' after op attribute")?;
+                    if !operations.is_empty() {
+                        return Err(IsaError::Parser(format!(
+                            "subfield {name} op attribute specified multiple times"
+                        )));
                     }
-                    "descr" => {
-                        parser.consume()?;
-                        parser.expect(TokenKind::Equals, "'=' after descr attribute")?;
-                        if description.is_some() {
-                            return Err(IsaError::Parser(format!(
-                                "subfield {name} descr attribute specified multiple times"
-                            )));
-                        }
-                        let value = parser
-                            .expect(TokenKind::String, "string literal for descr attribute")?;
-                        description = Some(value.lexeme);
+                    operations = parse_subfield_ops(parser)?;
+                }
+                "descr" => {
+                    parser.consume()?;
+                    parser.expect(TokenKind::Equals, "'=' after descr attribute")?;
+                    if description.is_some() {
+                        return Err(IsaError::Parser(format!(
+                            "subfield {name} descr attribute specified multiple times"
+                        )));
                     }
-                    _ => break,
+                    let value =
+                        parser.expect(TokenKind::String, "string literal for descr attribute")?;
+                    description = Some(value.lexeme);
                 }
-            } else {
-                break;
+                "reset" => {
+                    parser.consume()?;
+                    parser.expect(TokenKind::Equals, "'=' after reset attribute")?;
+                    if reset.is_some() {
+                        return Err(IsaError::Parser(format!(
+                            "subfield {name} reset attribute specified multiple times"
+                        )));
+                    }
+                    reset = Some(parse_wide_number(parser, "subfield reset")?);
+                }
+                _ => break,
             }
+        } else {
+            break;
         }
-
-        entries.push(SubFieldDecl {
-            name,
-            bit_spec: bit_spec.lexeme,
-            operations,
-            description,
-        });
     }
-    Ok(entries)
+
+    Ok(SubFieldDecl {
+        name,
+        bit_spec: bit_spec.lexeme,
+        operations,
+        description,
+        reset,
+    })
 }
 
 fn parse_subfield_ops(parser: &mut Parser) -> Result<Vec<SubFieldOp>, IsaError> {
@@ -530,7 +687,7 @@ fn parse_subfield_ops(parser: &mut Parser) -> Result<Vec<SubFieldOp>, IsaError>
 mod tests {
     use std::path::PathBuf;
 
-    use crate::soc::isa::ast::{IsaItem, MaskSelector, SpaceMember};
+    use crate::soc::isa::ast::{AliasValue, IsaItem, MaskSelector, SpaceMember};
     use crate::soc::isa::diagnostic::DiagnosticPhase;
     use crate::soc::isa::error::IsaError;
 
@@ -613,6 +770,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parses_alias_with_when_constraints() {
+        let doc = parse(
+            ":space logic addr=32 word=32 type=logic\n:logic X subfields={\n    OPCD @(0..5) op=func\n    RT @(6..10) op=target\n    RA @(11..15) op=source\n    RB @(16..20) op=source\n}\n:logic::X mr (RA, RT) alias=or when={RB=#RT, OPCD=0x1F} descr=\"Move register\"\n",
+        );
+        let instr = match &doc.items[2] {
+            IsaItem::SpaceMember(member) => match &member.member {
+                SpaceMember::Instruction(instr) => instr,
+                other => panic!("unexpected member: {other:?}"),
+            },
+            other => panic!("unexpected item: {other:?}"),
+        };
+        assert_eq!(instr.operands, vec!["RA", "RT"]);
+        let alias = instr.alias.as_ref().expect("alias parsed");
+        assert_eq!(alias.base, "or");
+        assert_eq!(alias.constraints.len(), 2);
+        assert_eq!(alias.constraints[0].field, "RB");
+        assert_eq!(alias.constraints[0].value, AliasValue::Field("RT".into()));
+        assert_eq!(alias.constraints[1].value, AliasValue::Number(31));
+
+        let err = parse_str(
+            PathBuf::from("test.isa"),
+            ":space logic addr=32 word=32 type=logic\n:logic X subfields={\n    OPCD @(0..5) op=func\n}\n:logic::X nop alias=ori mask={OPCD=24}\n",
+        )
+        .unwrap_err();
+        expect_parser_diag(err, "cannot declare mask, semantics or extend");
+
+        let err = parse_str(
+            PathBuf::from("test.isa"),
+            ":space logic addr=32 word=32 type=logic\n:logic X subfields={\n    OPCD @(0..5) op=func\n}\n:logic::X nop when={OPCD=24}\n",
+        )
+        .unwrap_err();
+        expect_parser_diag(err, "requires an alias attribute");
+    }
+
     #[test]
     fn parses_logic_form_inheritance_and_instruction_operands() {
         let doc = parse(
@@ -721,6 +913,28 @@ mod tests {
         assert!(field.reset.is_none());
     }
 
+    #[test]
+    fn parses_field_and_subfield_resets() {
+        let doc = parse(
+            ":space reg addr=32 word=32 type=register\n:reg MSR size=32 reset=0x1000 subfields={\n    EE @(16) reset=1 descr=\"External enable\"\n    PR @(17)\n}",
+        );
+        let field = match &doc.items[1] {
+            IsaItem::SpaceMember(member) => match &member.member {
+                SpaceMember::Field(field) => field,
+                other => panic!("unexpected member: {other:?}"),
+            },
+            other => panic!("unexpected item: {other:?}"),
+        };
+        assert_eq!(field.reset, Some(0x1000));
+        assert_eq!(field.subfields[0].reset, Some(1), "subfield reset parsed");
+        assert_eq!(
+            field.subfields[0].description.as_deref(),
+            Some("External enable"),
+            "reset mixes with other subfield attributes"
+        );
+        assert_eq!(field.subfields[1].reset, None);
+    }
+
     #[test]
     fn rejects_redirect_with_offset() {
         let err = parse_str(
@@ -3,11 +3,12 @@ use std::path::{Path, PathBuf};
 
 use crate::soc::isa::ast::{IsaSpecification, SpaceKind};
 use crate::soc::isa::diagnostic::{
-    DiagnosticLevel, DiagnosticPhase, IsaDiagnostic, SourcePosition, SourceSpan,
+    DiagnosticLevel, DiagnosticPhase, IsaDiagnostic, SourcePosition, SourceSpan, first_phase,
 };
 use crate::soc::isa::error::IsaError;
 use crate::soc::isa::semantics::SemanticBlock;
 
+use super::conditional::{ConditionalFrame, ParameterScope};
 use super::spans::span_from_token;
 use super::{Lexer, Token, TokenKind};
 
@@ -19,15 +20,20 @@ pub struct Parser<'src> {
     path: PathBuf,
     diagnostics: Vec<IsaDiagnostic>,
     allow_include: bool,
+    allow_attach: bool,
+    parameters: ParameterScope,
+    conditionals: Vec<ConditionalFrame>,
 }
 
 impl<'src> Parser<'src> {
     pub fn new(source: &'src str, path: PathBuf) -> Self {
-        let allow_include = path
+        let extension = path
             .extension()
             .and_then(|ext| ext.to_str())
-            .map(|ext| ext.eq_ignore_ascii_case("coredef"))
-            .unwrap_or(false);
+            .map(|ext| ext.to_ascii_lowercase())
+            .unwrap_or_default();
+        let allow_include = extension == "coredef";
+        let allow_attach = extension == "sysdef";
         Self {
             lexer: Lexer::new(source, path.clone()),
             peeked: None,
@@ -36,6 +42,9 @@ impl<'src> Parser<'src> {
             path,
             diagnostics: Vec::new(),
             allow_include,
+            allow_attach,
+            parameters: ParameterScope::new(),
+            conditionals: Vec::new(),
         }
     }
 
@@ -43,23 +52,48 @@ impl<'src> Parser<'src> {
         self.known_spaces = spaces.clone();
     }
 
+    /// Makes parameters from the including `.coredef` and earlier files visible to `:if`.
+    pub(super) fn seed_parameters(&mut self, parameters: &ParameterScope) {
+        self.parameters = parameters.clone();
+    }
+
     pub fn parse_document(&mut self) -> Result<IsaSpecification, IsaError> {
+        let (spec, diagnostics) = self.parse_document_recovering()?;
+        if diagnostics.is_empty() {
+            Ok(spec)
+        } else {
+            Err(IsaError::Diagnostics {
+                phase: first_phase(&diagnostics),
+                diagnostics,
+            })
+        }
+    }
+
+    /// Parses the whole document, resynchronizing after every lexer or parser error. Returns
+    /// the directives that parsed alongside every diagnostic raised on the way, so callers can
+    /// keep validating the rest of the file.
+    pub fn parse_document_recovering(
+        &mut self,
+    ) -> Result<(IsaSpecification, Vec<IsaDiagnostic>), IsaError> {
         let mut items = Vec::new();
-        while !self.check(TokenKind::EOF)? {
+        loop {
+            match self.check(TokenKind::EOF) {
+                Ok(true) => break,
+                Ok(false) => {}
+                Err(err) => {
+                    self.handle_parse_error(err)?;
+                    continue;
+                }
+            }
             match self.parse_directive() {
-                Ok(item) => items.push(item),
+                Ok(Some(item)) => items.push(item),
+                Ok(None) => {}
                 Err(err) => self.handle_parse_error(err)?,
             }
         }
-
-        if self.diagnostics.is_empty() {
-            Ok(IsaSpecification::new(self.path.clone(), items))
-        } else {
-            Err(IsaError::Diagnostics {
-                phase: DiagnosticPhase::Parser,
-                diagnostics: std::mem::take(&mut self.diagnostics),
-            })
-        }
+        self.close_conditionals();
+        let diagnostics = std::mem::take(&mut self.diagnostics);
+        Ok((IsaSpecification::new(self.path.clone(), items), diagnostics))
     }
 
     pub(super) fn expect_identifier_token(&mut self, context: &str) -> Result<Token, IsaError> {
@@ -67,6 +101,7 @@ impl<'src> Parser<'src> {
         if token.kind == TokenKind::Identifier {
             Ok(token)
         } else {
+            self.keep_directive_start(token);
             Err(IsaError::Parser(format!(
                 "expected identifier for {context}"
             )))
@@ -82,10 +117,19 @@ impl<'src> Parser<'src> {
         if token.kind == kind {
             Ok(token)
         } else {
+            self.keep_directive_start(token);
             Err(IsaError::Parser(format!("expected {context}")))
         }
     }
 
+    /// Pushes a mismatched `:` back so recovery resumes at the directive it introduces rather
+    /// than swallowing it along with the broken one.
+    fn keep_directive_start(&mut self, token: Token) {
+        if token.kind == TokenKind::Colon {
+            self.peeked = Some(token);
+        }
+    }
+
     pub(super) fn check(&mut self, kind: TokenKind) -> Result<bool, IsaError> {
         Ok(self.peek()?.kind == kind)
     }
@@ -109,6 +153,16 @@ impl<'src> Parser<'src> {
 }
 
 impl<'src> Parser<'src> {
+    /// Consumes a quoted or bare file path operand.
+    pub(super) fn consume_path(&mut self) -> Result<Token, IsaError> {
+        let token = match self.peeked.take() {
+            Some(token) => token,
+            None => self.lexer.consume_path()?,
+        };
+        self.last_token = Some(token.clone());
+        Ok(token)
+    }
+
     pub(super) fn parse_semantic_block(
         &mut self,
         context: &str,
@@ -140,14 +194,39 @@ impl<'src> Parser<'src> {
         self.known_spaces.contains_key(name)
     }
 
+    pub(super) fn known_space_names(&self) -> impl Iterator<Item = &str> {
+        self.known_spaces.keys().map(String::as_str)
+    }
+
     pub(super) fn space_kind(&self, name: &str) -> Option<SpaceKind> {
         self.known_spaces.get(name).cloned()
     }
 
+    pub(super) fn parameters(&self) -> &ParameterScope {
+        &self.parameters
+    }
+
+    pub(super) fn parameters_mut(&mut self) -> &mut ParameterScope {
+        &mut self.parameters
+    }
+
+    pub(super) fn conditionals_mut(&mut self) -> &mut Vec<ConditionalFrame> {
+        &mut self.conditionals
+    }
+
+    /// Discards a braced block whose `{` was just consumed, without tokenizing its contents.
+    pub(super) fn skip_braced_block(&mut self) {
+        let _ = self.lexer.capture_braced_block();
+    }
+
     pub(super) fn allows_include(&self) -> bool {
         self.allow_include
     }
 
+    pub(super) fn allows_attach(&self) -> bool {
+        self.allow_attach
+    }
+
     pub(super) fn file_path(&self) -> &Path {
         &self.path
     }
@@ -157,26 +236,92 @@ impl<'src> Parser<'src> {
     }
 
     fn handle_parse_error(&mut self, err: IsaError) -> Result<(), IsaError> {
-        match err {
-            IsaError::Parser(msg) => {
-                self.push_parser_diagnostic(msg);
-                self.synchronize_directive();
-                Ok(())
+        self.record_recoverable(err)?;
+        self.synchronize_directive();
+        Ok(())
+    }
+
+    /// Records an error raised inside a braced block and skips to the block's closing `}` so
+    /// the remaining attributes of the directive still parse. An unclosed block stops at the
+    /// next directive instead.
+    pub(super) fn recover_in_block(&mut self, err: IsaError) -> Result<(), IsaError> {
+        self.record_recoverable(err)?;
+        if self
+            .last_token
+            .as_ref()
+            .is_some_and(|token| token.kind == TokenKind::RBrace)
+        {
+            return Ok(());
+        }
+        let mut depth = 0usize;
+        loop {
+            match self.peek() {
+                Ok(token) => match token.kind {
+                    TokenKind::EOF | TokenKind::Colon => return Ok(()),
+                    TokenKind::RBrace if depth == 0 => {
+                        self.consume()?;
+                        return Ok(());
+                    }
+                    TokenKind::RBrace => depth -= 1,
+                    TokenKind::LBrace => depth += 1,
+                    _ => {}
+                },
+                Err(err) => {
+                    self.record_recoverable(err)?;
+                    continue;
+                }
             }
+            self.consume()?;
+        }
+    }
+
+    /// Builds a parser diagnostic for the last consumed token carrying a "did you mean" hint.
+    pub(super) fn error_with_suggestion(
+        &self,
+        message: String,
+        suggestion: Option<&str>,
+    ) -> IsaError {
+        let span = self
+            .last_token
+            .as_ref()
+            .map(|token| span_from_token(self.file_path(), token));
+        IsaError::Diagnostics {
+            phase: DiagnosticPhase::Parser,
+            diagnostics: vec![
+                IsaDiagnostic::new(
+                    DiagnosticPhase::Parser,
+                    DiagnosticLevel::Error,
+                    "parser.syntax",
+                    message,
+                    span,
+                )
+                .with_suggestion(suggestion),
+            ],
+        }
+    }
+
+    /// Lexer and parser failures become diagnostics; anything else (I/O, include loops) is
+    /// returned to abort the parse.
+    pub(super) fn record_recoverable(&mut self, err: IsaError) -> Result<(), IsaError> {
+        match err {
+            IsaError::Parser(msg) => self.push_parser_diagnostic(msg),
             IsaError::Diagnostics {
-                phase: DiagnosticPhase::Parser,
+                phase: DiagnosticPhase::Lexer | DiagnosticPhase::Parser,
                 diagnostics,
-            } => {
-                self.diagnostics.extend(diagnostics);
-                self.synchronize_directive();
-                Ok(())
-            }
-            other => Err(other),
+            } => self.diagnostics.extend(diagnostics),
+            other => return Err(other),
         }
+        Ok(())
     }
 
     fn push_parser_diagnostic(&mut self, message: String) {
         let span = self.current_error_span();
+        self.push_diagnostic_at(message, span);
+    }
+
+    /// Records a parser diagnostic at `span`, falling back to the current token.
+    pub(super) fn push_diagnostic_at(&mut self, message: String, span: Option<SourceSpan>) {
+        let span = span.or_else(|| self.current_error_span());
         self.diagnostics.push(IsaDiagnostic::new(
             DiagnosticPhase::Parser,
             DiagnosticLevel::Error,
@@ -199,6 +344,8 @@ impl<'src> Parser<'src> {
             .map(|token| span_from_token(path.as_path(), token))
     }
 
+    /// Skips to the next `:` directive introducer. Lexer errors met on the way are recorded;
+    /// the lexer always advances past the offending input, so this terminates.
     fn synchronize_directive(&mut self) {
         loop {
             match self.peek() {
@@ -210,7 +357,11 @@ impl<'src> Parser<'src> {
                         break;
                     }
                 }
-                Err(_) => break,
+                Err(err) => {
+                    if self.record_recoverable(err).is_err() {
+                        break;
+                    }
+                }
             }
         }
     }
@@ -223,6 +374,20 @@ pub fn parse_str(path: PathBuf, src: &str) -> Result<IsaSpecification, IsaError>
     parser.parse_document()
 }
 
+/// Recovering variant of [`parse_str_with_spaces`] that hands back the partial document and
+/// its diagnostics instead of failing on the first problem.
+pub fn parse_str_recovering(
+    path: PathBuf,
+    src: &str,
+    spaces: &HashMap<String, SpaceKind>,
+    parameters: &ParameterScope,
+) -> Result<(IsaSpecification, Vec<IsaDiagnostic>), IsaError> {
+    let mut parser = Parser::new(src, path);
+    parser.seed_known_spaces(spaces);
+    parser.seed_parameters(parameters);
+    parser.parse_document_recovering()
+}
+
 pub fn parse_str_with_spaces(
     path: PathBuf,
     src: &str,
@@ -232,3 +397,87 @@ pub fn parse_str_with_spaces(
     parser.seed_known_spaces(spaces);
     parser.parse_document()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn diagnostics(source: &str) -> Vec<IsaDiagnostic> {
+        let (_, diagnostics) = parse_str_recovering(
+            PathBuf::from("test.isa"),
+            source,
+            &HashMap::new(),
+            &ParameterScope::new(),
+        )
+        .expect("recoverable errors only");
+        diagnostics
+    }
+
+    #[test]
+    fn collects_lexer_and_parser_errors_in_one_pass() {
+        let source = ":space reg addr=32 word=32 type=register\n:reg A size=32 ~ ¤\n:reg B size=\n:bogus\n:reg C size=32";
+        let (doc, diagnostics) = parse_str_recovering(
+            PathBuf::from("test.isa"),
+            source,
+            &HashMap::new(),
+            &ParameterScope::new(),
+        )
+        .expect("recoverable errors only");
+        let phases: Vec<_> = diagnostics.iter().map(|diag| diag.phase).collect();
+        assert!(
+            phases.contains(&DiagnosticPhase::Lexer),
+            "lexer errors are collected: {diagnostics:?}"
+        );
+        assert!(
+            diagnostics
+                .iter()
+                .filter(|diag| diag.phase == DiagnosticPhase::Parser)
+                .count()
+                >= 2,
+            "every broken directive is reported: {diagnostics:?}"
+        );
+        assert_eq!(
+            first_phase(&diagnostics),
+            DiagnosticPhase::Lexer,
+            "combined errors report the earliest phase"
+        );
+        assert!(
+            doc.items.len() >= 2,
+            "directives around the errors still parse: {:?}",
+            doc.items
+        );
+    }
+
+    #[test]
+    fn resynchronizes_at_closing_brace_inside_subfields() {
+        let source = ":space reg addr=32 word=32 type=register\n:reg CR size=32 subfields={\n    SO @(0) op=\n    EQ @(1)\n} reset=0x0 descr=\"x\"\n:reg LR size=32 bogus";
+        let diagnostics = diagnostics(source);
+        assert_eq!(
+            diagnostics.len(),
+            2,
+            "the bad entry and the later directive are both reported: {diagnostics:?}"
+        );
+        assert_eq!(
+            diagnostics[1].span.as_ref().map(|span| span.start.line),
+            Some(6),
+            "parsing resumed after the subfields block"
+        );
+    }
+
+    #[test]
+    fn suggests_known_spaces_for_unknown_directives() {
+        let diagnostics = diagnostics(":space reg addr=32 word=32 type=register\n:rge PC size=32");
+        assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+        assert_eq!(diagnostics[0].suggestion.as_deref(), Some(":reg"));
+        assert!(
+            diagnostics[0]
+                .format_human()
+                .contains("did you mean ':reg'?"),
+            "suggestion is rendered: {}",
+            diagnostics[0].format_human()
+        );
+    }
+}
@@ -0,0 +1,199 @@
+//! Loader for `.sysdef` files that instantiates each `:attach`ed core definition under its
+//! context tag and builds the shared system spaces and buses.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::loader::isa::{IsaLoader, parse_str};
+use crate::soc::core::specification::CoreSpec;
+use crate::soc::isa::ast::{AttachDecl, IsaItem, IsaSpecification};
+use crate::soc::isa::error::IsaError;
+use crate::soc::isa::machine::MachineDescription;
+use crate::soc::isa::validator::Validator;
+use crate::soc::system::{SystemCore, SystemDescription};
+
+#[derive(Default)]
+pub struct SystemLoader {
+    machines: BTreeMap<PathBuf, Arc<MachineDescription>>,
+}
+
+impl SystemLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load_system<P: AsRef<Path>>(&mut self, entry: P) -> Result<SystemDescription, IsaError> {
+        let entry = entry.as_ref();
+        self.machines.clear();
+        let src = fs::read_to_string(entry)?;
+        let doc = parse_str(entry.to_path_buf(), &src)?;
+
+        let mut attachments = Vec::new();
+        let mut shared_items = Vec::new();
+        for item in doc.items {
+            match item {
+                IsaItem::Attach(attach) => attachments.push(attach),
+                IsaItem::Space(_) | IsaItem::Bus(_) | IsaItem::Parameter(_) => {
+                    shared_items.push(item)
+                }
+                _ => {
+                    return Err(IsaError::Machine(format!(
+                        "sysdef '{}' may only contain :attach, :param, :space and :bus directives",
+                        entry.display()
+                    )));
+                }
+            }
+        }
+
+        let mut contexts = BTreeSet::new();
+        let mut cores = Vec::with_capacity(attachments.len());
+        for attach in attachments {
+            if !contexts.insert(attach.context.clone()) {
+                return Err(IsaError::Machine(format!(
+                    "sysdef '{}' attaches context '{}' more than once",
+                    entry.display(),
+                    attach.context
+                )));
+            }
+            cores.push(self.attach_core(entry, attach)?);
+        }
+
+        let shared_docs = vec![IsaSpecification::new(entry.to_path_buf(), shared_items)];
+        let mut validator = Validator::new();
+        validator.validate(&shared_docs)?;
+        let shared = validator.finalize_machine(shared_docs)?;
+
+        Ok(SystemDescription {
+            path: entry.to_path_buf(),
+            cores,
+            shared,
+        })
+    }
+
+    fn attach_core(&mut self, sysdef: &Path, attach: AttachDecl) -> Result<SystemCore, IsaError> {
+        let source = if attach.path.is_relative() {
+            sysdef
+                .parent()
+                .unwrap_or_else(|| Path::new("."))
+                .join(&attach.path)
+        } else {
+            attach.path.clone()
+        };
+        let machine = match self.machines.get(&source) {
+            Some(machine) => machine.clone(),
+            None => {
+                let mut loader = IsaLoader::new();
+                let machine = Arc::new(loader.load_machine(&source).map_err(|err| {
+                    IsaError::Machine(format!(
+                        "failed to attach '{}' from '{}': {err}",
+                        attach.context,
+                        source.display()
+                    ))
+                })?);
+                self.machines.insert(source.clone(), machine.clone());
+                machine
+            }
+        };
+        let spec =
+            CoreSpec::from_machine(attach.context.clone(), &machine, None).map_err(|err| {
+                IsaError::Machine(format!(
+                    "failed to build core spec for '{}': {err}",
+                    attach.context
+                ))
+            })?;
+        Ok(SystemCore {
+            context: attach.context,
+            source,
+            machine,
+            spec: Arc::new(spec),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const BASE_ISA: &str =
+        ":space reg addr=32 word=32 type=register\n:reg PC size=32\n:reg GPR[0..3] size=32\n";
+
+    fn write_file(dir: &Path, name: &str, contents: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, contents).expect("write file");
+        path
+    }
+
+    #[test]
+    fn loads_namespaced_cores_with_shared_bus() {
+        let dir = tempdir().expect("tempdir");
+        write_file(dir.path(), "base.isa", BASE_ISA);
+        write_file(
+            dir.path(),
+            "etpu.isa",
+            ":space reg addr=16 word=24 type=register\n:reg ACC size=24\n",
+        );
+        write_file(dir.path(), "main.coredef", ":include \"base.isa\"\n");
+        write_file(dir.path(), "etpu.coredef", ":include \"etpu.isa\"\n");
+        let sysdef = write_file(
+            dir.path(),
+            "chip.sysdef",
+            ":attach CORE0 \"main.coredef\"\n:attach ETPUA etpu.coredef\n:attach ETPUB etpu.coredef\n:space ram addr=32 word=32 type=rw\n:bus sysbus addr=32 ranges={\n    [0x40000000 +4kB] -> ram\n}\n",
+        );
+
+        let mut loader = SystemLoader::new();
+        let system = loader.load_system(&sysdef).expect("load system");
+        assert_eq!(
+            system.contexts().collect::<Vec<_>>(),
+            vec!["CORE0", "ETPUA", "ETPUB"],
+            "cores keep their attach order"
+        );
+
+        let core0 = system.core("CORE0").expect("core0");
+        assert_eq!(
+            core0.spec.name(),
+            "CORE0",
+            "core spec is named after its context"
+        );
+        assert!(core0.spec.register("reg::GPR3").is_some());
+
+        let etpua = system.core("ETPUA").expect("etpua");
+        let etpub = system.core("ETPUB").expect("etpub");
+        assert!(
+            Arc::ptr_eq(&etpua.machine, &etpub.machine),
+            "repeated attachments share one machine description"
+        );
+        assert_eq!(etpub.spec.name(), "ETPUB");
+        assert!(
+            etpub.spec.register("reg::GPR0").is_none(),
+            "cores stay isolated"
+        );
+
+        let bus = system.build_bus("sysbus").expect("system bus");
+        assert_eq!(bus.resolve(0x4000_0010).expect("ram").device.name(), "ram");
+    }
+
+    #[test]
+    fn rejects_duplicate_contexts() {
+        let dir = tempdir().expect("tempdir");
+        write_file(dir.path(), "base.isa", BASE_ISA);
+        write_file(dir.path(), "main.coredef", ":include \"base.isa\"\n");
+        let sysdef = write_file(
+            dir.path(),
+            "chip.sysdef",
+            ":attach CORE0 main.coredef\n:attach CORE0 main.coredef\n",
+        );
+        let err = SystemLoader::new().load_system(&sysdef).unwrap_err();
+        assert!(matches!(err, IsaError::Machine(msg) if msg.contains("more than once")));
+    }
+
+    #[test]
+    fn reports_missing_core_definition_with_context() {
+        let dir = tempdir().expect("tempdir");
+        let sysdef = write_file(dir.path(), "chip.sysdef", ":attach CORE0 missing.coredef\n");
+        let err = SystemLoader::new().load_system(&sysdef).unwrap_err();
+        assert!(matches!(err, IsaError::Machine(msg) if msg.contains("attach 'CORE0'")));
+    }
+}
@@ -0,0 +1,193 @@
+//! Runs the loader over an editor buffer and converts the resulting [`IsaError`] into LSP
+//! `Diagnostic` objects anchored in that buffer.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde_json::{Value, json};
+
+use crate::loader::isa::{IsaLoader, parse_str_with_spaces};
+use crate::soc::isa::ast::SpaceKind;
+use crate::soc::isa::diagnostic::{DiagnosticLevel, IsaDiagnostic};
+use crate::soc::isa::error::IsaError;
+
+const SOURCE: &str = "nanemu";
+
+/// Checks `text` as the contents of `path`. Root documents (`.isa`, `.coredef`) run the full
+/// pipeline including validation; `.isaext` and `.sysdef` files are parsed against the
+/// spaces in `known_spaces` since they only make sense as part of a larger machine.
+pub fn check_document(
+    path: &Path,
+    text: &str,
+    known_spaces: &HashMap<String, SpaceKind>,
+) -> Vec<Value> {
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    let result = match extension {
+        "isaext" | "sysdef" => {
+            parse_str_with_spaces(path.to_path_buf(), text, known_spaces).map(|_| ())
+        }
+        _ => IsaLoader::new()
+            .load_machine_from_source(path, text)
+            .map(|_| ()),
+    };
+    match result {
+        Ok(()) => Vec::new(),
+        Err(err) => to_lsp_diagnostics(path, err),
+    }
+}
+
+/// Diagnostics located in other files (e.g. a broken include) are pinned to the top of the
+/// document with the offending file named, so the failure is still visible.
+pub fn to_lsp_diagnostics(path: &Path, err: IsaError) -> Vec<Value> {
+    match err {
+        IsaError::Diagnostics { diagnostics, .. } => {
+            diagnostics.iter().map(|diag| convert(path, diag)).collect()
+        }
+        other => vec![diagnostic(0, 0, 0, 1, 1, None, other.to_string())],
+    }
+}
+
+fn convert(path: &Path, diag: &IsaDiagnostic) -> Value {
+    let severity = match diag.level {
+        DiagnosticLevel::Error => 1,
+        DiagnosticLevel::Warning => 2,
+    };
+    match &diag.span {
+        Some(span) if span.path == path => {
+            let start_line = span.start.line.saturating_sub(1);
+            let start_char = span.start.column.saturating_sub(1);
+            let end_line = span.end.line.saturating_sub(1).max(start_line);
+            let mut end_char = span.end.column.saturating_sub(1);
+            if end_line == start_line && end_char <= start_char {
+                end_char = start_char + 1;
+            }
+            diagnostic(
+                start_line,
+                start_char,
+                end_line,
+                end_char,
+                severity,
+                Some(diag.code),
+                diag.message_with_suggestion(),
+            )
+        }
+        Some(span) => diagnostic(
+            0,
+            0,
+            0,
+            1,
+            severity,
+            Some(diag.code),
+            format!(
+                "{}:{}:{}: {}",
+                span.path.display(),
+                span.start.line,
+                span.start.column,
+                diag.message_with_suggestion()
+            ),
+        ),
+        None => diagnostic(
+            0,
+            0,
+            0,
+            1,
+            severity,
+            Some(diag.code),
+            diag.message_with_suggestion(),
+        ),
+    }
+}
+
+fn diagnostic(
+    start_line: usize,
+    start_char: usize,
+    end_line: usize,
+    end_char: usize,
+    severity: u8,
+    code: Option<&str>,
+    message: String,
+) -> Value {
+    let mut value = json!({
+        "range": {
+            "start": { "line": start_line, "character": start_char },
+            "end": { "line": end_line, "character": end_char },
+        },
+        "severity": severity,
+        "source": SOURCE,
+        "message": message,
+    });
+    if let Some(code) = code {
+        value["code"] = json!(code);
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn reports_parser_errors_at_their_span() {
+        let path = Path::new("/virtual/broken.isa");
+        let diagnostics = check_document(
+            path,
+            ":space reg addr=32 word=32 type=register\n:bus sysbus addr=32",
+            &HashMap::new(),
+        );
+        assert_eq!(diagnostics.len(), 1, "one parser error: {diagnostics:?}");
+        let diag = &diagnostics[0];
+        assert_eq!(diag["severity"], 1, "parser errors are errors");
+        assert_eq!(diag["source"], SOURCE);
+        assert_eq!(
+            diag["range"]["start"]["line"], 1,
+            "positions are zero-based lines of the buffer"
+        );
+        assert!(
+            diag["message"]
+                .as_str()
+                .unwrap()
+                .contains("requires a ranges attribute"),
+            "message is carried over: {diag}"
+        );
+    }
+
+    #[test]
+    fn validates_unsaved_buffers_against_disk_includes() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let base = dir.path().join("base.isa");
+        fs::write(
+            &base,
+            ":space reg addr=32 word=32 type=register\n:reg PC size=32",
+        )
+        .expect("write base");
+        let entry = dir.path().join("core.coredef");
+        fs::write(&entry, "").expect("write entry");
+        let clean = check_document(&entry, ":include \"base.isa\"", &HashMap::new());
+        assert!(clean.is_empty(), "valid buffers are clean: {clean:?}");
+
+        let missing = check_document(&entry, ":include \"absent.isa\"", &HashMap::new());
+        assert_eq!(
+            missing.len(),
+            1,
+            "unreadable includes surface as one diagnostic"
+        );
+        assert_eq!(missing[0]["range"]["start"]["line"], 0);
+    }
+
+    #[test]
+    fn extensions_parse_against_known_spaces() {
+        let path = Path::new("/virtual/ext.isaext");
+        let mut spaces = HashMap::new();
+        spaces.insert("reg".to_string(), SpaceKind::Register);
+        assert!(
+            check_document(path, ":reg EXTRA size=32", &spaces).is_empty(),
+            "extension members of a known space parse cleanly"
+        );
+        assert!(
+            !check_document(path, ":reg EXTRA size=32", &HashMap::new()).is_empty(),
+            "without the base space the directive is rejected"
+        );
+    }
+}
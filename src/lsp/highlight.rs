@@ -0,0 +1,295 @@
+//! Semantic-token classification for ISA documents. Every space tag gets its own modifier so
+//! editors can color `$reg::`, `$insn::` and friends consistently across a workspace.
+
+use serde_json::{Value, json};
+
+use super::index::{DocumentIndex, Reference, SymbolKind, TextRange, resolve};
+use crate::loader::isa::TokenKind;
+
+pub const TOKEN_TYPES: &[&str] = &[
+    "keyword",
+    "namespace",
+    "type",
+    "property",
+    "function",
+    "macro",
+    "parameter",
+    "number",
+    "string",
+    "comment",
+    "operator",
+];
+
+/// Distinct per-space modifiers; further spaces reuse them round-robin.
+const SPACE_MODIFIERS: usize = 8;
+
+const DECLARATION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenType {
+    Keyword,
+    Namespace,
+    Type,
+    Property,
+    Function,
+    Macro,
+    Parameter,
+    Number,
+    String,
+    Comment,
+    Operator,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SemanticToken {
+    range: TextRange,
+    kind: TokenType,
+    modifiers: u32,
+}
+
+/// The `semanticTokensProvider.legend` advertised during `initialize`.
+pub fn legend() -> Value {
+    let mut modifiers = vec!["declaration".to_string()];
+    modifiers.extend((0..SPACE_MODIFIERS).map(|idx| format!("space{idx}")));
+    json!({ "tokenTypes": TOKEN_TYPES, "tokenModifiers": modifiers })
+}
+
+/// Produces the LSP relative encoding for `document`. `workspace` supplies the other indexed
+/// documents used to resolve references and to fix the order of space modifiers.
+pub fn semantic_tokens(document: &DocumentIndex, workspace: &[&DocumentIndex]) -> Vec<u32> {
+    let mut documents = vec![document];
+    documents.extend(
+        workspace
+            .iter()
+            .copied()
+            .filter(|doc| doc.path != document.path),
+    );
+    let spaces = space_order(workspace, document);
+    let space_modifier = |space: &str| {
+        spaces
+            .iter()
+            .position(|name| name == space)
+            .map(|idx| 1 << (1 + idx % SPACE_MODIFIERS))
+            .unwrap_or(0)
+    };
+
+    let mut tokens: Vec<SemanticToken> = document
+        .comments
+        .iter()
+        .map(|range| SemanticToken {
+            range: *range,
+            kind: TokenType::Comment,
+            modifiers: 0,
+        })
+        .collect();
+    for (idx, token) in document.tokens.iter().enumerate() {
+        let after_colon = idx > 0 && document.tokens[idx - 1].kind == TokenKind::Colon;
+        let classified = match token.kind {
+            TokenKind::Number | TokenKind::BitExpr | TokenKind::Range => {
+                Some((TokenType::Number, 0))
+            }
+            TokenKind::String => Some((TokenType::String, 0)),
+            TokenKind::Equals | TokenKind::DirectTo => Some((TokenType::Operator, 0)),
+            TokenKind::Identifier => {
+                if let Some(symbol) = document
+                    .symbols
+                    .iter()
+                    .find(|symbol| symbol.range == token.range)
+                {
+                    let kind = symbol_type(symbol.kind);
+                    let space = symbol.space.as_deref().unwrap_or(match symbol.kind {
+                        SymbolKind::Space => symbol.name.as_str(),
+                        _ => "",
+                    });
+                    Some((kind, DECLARATION | space_modifier(space)))
+                } else if after_colon && is_directive(&token.lexeme) {
+                    Some((TokenType::Keyword, 0))
+                } else {
+                    document.reference_for_token(idx).map(|reference| {
+                        classify_reference(&documents, &reference, &space_modifier)
+                    })
+                }
+            }
+            _ => None,
+        };
+        if let Some((kind, modifiers)) = classified {
+            tokens.push(SemanticToken {
+                range: token.range,
+                kind,
+                modifiers,
+            });
+        }
+    }
+    tokens.sort_by_key(|token| token.range);
+    encode(&tokens)
+}
+
+fn classify_reference(
+    documents: &[&DocumentIndex],
+    reference: &Reference,
+    space_modifier: &dyn Fn(&str) -> u32,
+) -> (TokenType, u32) {
+    match reference {
+        Reference::Space(space) => (TokenType::Namespace, space_modifier(space)),
+        Reference::Parameter(_) => (TokenType::Parameter, 0),
+        Reference::Macro(_) => (TokenType::Macro, 0),
+        Reference::Member { space, .. } => {
+            let kind = resolve(documents, reference)
+                .map(|symbol| symbol_type(symbol.kind))
+                .unwrap_or(TokenType::Property);
+            (kind, space_modifier(space))
+        }
+    }
+}
+
+fn symbol_type(kind: SymbolKind) -> TokenType {
+    match kind {
+        SymbolKind::Space => TokenType::Namespace,
+        SymbolKind::Parameter | SymbolKind::Subfield => TokenType::Parameter,
+        SymbolKind::Macro => TokenType::Macro,
+        SymbolKind::Bus | SymbolKind::Form => TokenType::Type,
+        SymbolKind::Field => TokenType::Property,
+        SymbolKind::Instruction => TokenType::Function,
+    }
+}
+
+fn is_directive(name: &str) -> bool {
+    matches!(
+        name,
+        "space"
+            | "param"
+            | "macro"
+            | "bus"
+            | "include"
+            | "attach"
+            | "if"
+            | "elif"
+            | "else"
+            | "endif"
+    )
+}
+
+/// Spaces in first-declaration order across the workspace, then any the document adds.
+fn space_order(workspace: &[&DocumentIndex], document: &DocumentIndex) -> Vec<String> {
+    let mut order: Vec<String> = Vec::new();
+    for doc in workspace.iter().copied().chain([document]) {
+        for symbol in &doc.symbols {
+            if symbol.kind == SymbolKind::Space && !order.contains(&symbol.name) {
+                order.push(symbol.name.clone());
+            }
+        }
+    }
+    order
+}
+
+fn encode(tokens: &[SemanticToken]) -> Vec<u32> {
+    let mut data = Vec::with_capacity(tokens.len() * 5);
+    let (mut line, mut start) = (0, 0);
+    for token in tokens {
+        let delta_line = token.range.line - line;
+        let delta_start = if delta_line == 0 {
+            token.range.start - start
+        } else {
+            token.range.start
+        };
+        data.extend([
+            delta_line,
+            delta_start,
+            token.range.end - token.range.start,
+            token.kind as u32,
+            token.modifiers,
+        ]);
+        line = token.range.line;
+        start = token.range.start;
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    use super::*;
+
+    /// Decodes the relative encoding back into `(line, start, len, type, modifiers)`.
+    fn decode(data: &[u32]) -> Vec<(u32, u32, u32, &'static str, u32)> {
+        let (mut line, mut start) = (0, 0);
+        data.chunks(5)
+            .map(|chunk| {
+                if chunk[0] > 0 {
+                    start = 0;
+                }
+                line += chunk[0];
+                start += chunk[1];
+                (
+                    line,
+                    start,
+                    chunk[2],
+                    TOKEN_TYPES[chunk[3] as usize],
+                    chunk[4],
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn classifies_spaces_members_and_comments() {
+        let source = ":space reg addr=32 word=32 type=register\n:reg PC size=32 // program counter\n:space insn addr=32 word=32 type=logic\n:insn nop mask={OPCD=0} semantics={ $reg::PC = #X }";
+        let index = DocumentIndex::build(Path::new("a.isa"), source, &HashMap::new());
+        let tokens = decode(&semantic_tokens(&index, &[&index]));
+        let at = |line: u32, start: u32| {
+            tokens
+                .iter()
+                .find(|token| token.0 == line && token.1 == start)
+                .map(|token| (token.3, token.4))
+        };
+        let reg = 1 << 1;
+        let insn = 1 << 2;
+        assert_eq!(
+            at(0, 1),
+            Some(("keyword", 0)),
+            "directive names are keywords"
+        );
+        assert_eq!(
+            at(0, 7),
+            Some(("namespace", DECLARATION | reg)),
+            "space declarations carry their own modifier"
+        );
+        assert_eq!(at(1, 1), Some(("namespace", reg)), "space tags reuse it");
+        assert_eq!(at(1, 5), Some(("property", DECLARATION | reg)));
+        assert_eq!(at(1, 16), Some(("comment", 0)), "comments are reported");
+        assert_eq!(
+            at(3, 6),
+            Some(("function", DECLARATION | insn)),
+            "logic-space members are instructions"
+        );
+        let pc_use = source.lines().nth(3).unwrap().find("::PC").unwrap() as u32 + 2;
+        assert_eq!(
+            at(3, pc_use),
+            Some(("property", reg)),
+            "context references resolve to the declaration kind"
+        );
+        assert_eq!(at(3, pc_use + 5), Some(("parameter", 0)));
+    }
+
+    #[test]
+    fn encodes_relative_positions() {
+        let index = DocumentIndex::build(
+            Path::new("b.isa"),
+            ":param A=1\n  :param B=2",
+            &HashMap::new(),
+        );
+        let data = semantic_tokens(&index, &[]);
+        assert_eq!(
+            &data[..10],
+            &[0, 1, 5, 0, 0, 0, 6, 1, 6, DECLARATION],
+            "same-line tokens are offset from the previous start"
+        );
+        assert_eq!(
+            &data[20..25],
+            &[1, 3, 5, 0, 0],
+            "a new line restarts columns at zero"
+        );
+    }
+}
@@ -0,0 +1,686 @@
+//! Token-driven symbol index for a single ISA document. It reuses the loader's lexer so
+//! definitions, references and highlighting survive documents that do not parse yet.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::loader::isa::{Lexer, TokenKind};
+use crate::soc::isa::ast::SpaceKind;
+
+/// Lexer errors are skipped so the rest of the file stays navigable; a long run of them
+/// means the lexer is no longer making progress.
+const MAX_CONSECUTIVE_LEX_ERRORS: usize = 64;
+
+/// Zero-based, single-line `[start, end)` character range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TextRange {
+    pub line: u32,
+    pub start: u32,
+    pub end: u32,
+}
+
+impl TextRange {
+    pub fn contains(&self, line: u32, character: u32) -> bool {
+        self.line == line && self.start <= character && character < self.end.max(self.start + 1)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexedToken {
+    pub kind: TokenKind,
+    pub lexeme: String,
+    pub range: TextRange,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Space,
+    Parameter,
+    Macro,
+    Bus,
+    Form,
+    Field,
+    Subfield,
+    Instruction,
+}
+
+impl SymbolKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            SymbolKind::Space => "space",
+            SymbolKind::Parameter => "parameter",
+            SymbolKind::Macro => "macro",
+            SymbolKind::Bus => "bus",
+            SymbolKind::Form => "form",
+            SymbolKind::Field => "field",
+            SymbolKind::Subfield => "subfield",
+            SymbolKind::Instruction => "instruction",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub kind: SymbolKind,
+    pub name: String,
+    /// Owning space for forms, fields, subfields and instructions.
+    pub space: Option<String>,
+    /// Field or form owning a subfield, or the parent of an inherited form.
+    pub parent: Option<String>,
+    pub description: Option<String>,
+    pub path: PathBuf,
+    pub range: TextRange,
+}
+
+/// What the identifier under the cursor points at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reference {
+    Space(String),
+    /// `#NAME`: a `:param` or an operand of the surrounding instruction.
+    Parameter(String),
+    Macro(String),
+    Member {
+        space: String,
+        path: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct DocumentIndex {
+    pub path: PathBuf,
+    pub tokens: Vec<IndexedToken>,
+    pub comments: Vec<TextRange>,
+    pub symbols: Vec<Symbol>,
+    /// Spaces declared in this document.
+    pub spaces: HashMap<String, SpaceKind>,
+    /// Resolved `:include` and `:attach` targets.
+    pub includes: Vec<PathBuf>,
+}
+
+impl DocumentIndex {
+    /// Indexes `text`, treating `external_spaces` as already declared so `.isaext` members
+    /// of a base ISA space are still recognized.
+    pub fn build(path: &Path, text: &str, external_spaces: &HashMap<String, SpaceKind>) -> Self {
+        let mut index = Self {
+            path: path.to_path_buf(),
+            tokens: lex(path, text),
+            comments: scan_comments(text),
+            symbols: Vec::new(),
+            spaces: HashMap::new(),
+            includes: Vec::new(),
+        };
+        let statements = split_statements(&index.tokens);
+        for range in statements {
+            index.index_statement(range.0, range.1, external_spaces);
+        }
+        index
+    }
+
+    pub fn space_kind(&self, name: &str) -> Option<SpaceKind> {
+        self.spaces.get(name).cloned()
+    }
+
+    /// Classifies the identifier at the zero-based `line`/`character` position.
+    pub fn reference_at(&self, line: u32, character: u32) -> Option<Reference> {
+        let idx = self
+            .tokens
+            .iter()
+            .position(|token| token.range.contains(line, character))?;
+        self.reference_for_token(idx)
+    }
+
+    pub(crate) fn reference_for_token(&self, idx: usize) -> Option<Reference> {
+        let token = &self.tokens[idx];
+        if token.kind != TokenKind::Identifier {
+            return None;
+        }
+        if let Some(name) = token.lexeme.strip_prefix('#') {
+            return Some(Reference::Parameter(name.to_string()));
+        }
+        let mut start = idx;
+        while start >= 2
+            && self.tokens[start - 1].kind == TokenKind::DoubleColon
+            && self.tokens[start - 2].kind == TokenKind::Identifier
+        {
+            start -= 2;
+        }
+        let segments: Vec<String> = self.tokens[start..=idx]
+            .iter()
+            .step_by(2)
+            .map(|token| token.lexeme.clone())
+            .collect();
+        let (head, rest) = segments.split_first()?;
+        if let Some(space) = head.strip_prefix('$') {
+            return Some(match rest {
+                [] => Reference::Space(space.to_string()),
+                [name, ..] if space == "macro" => Reference::Macro(name.clone()),
+                _ => Reference::Member {
+                    space: space.to_string(),
+                    path: rest.to_vec(),
+                },
+            });
+        }
+        if start > 0 && self.tokens[start - 1].kind == TokenKind::Colon {
+            return Some(if rest.is_empty() {
+                Reference::Space(head.clone())
+            } else {
+                Reference::Member {
+                    space: head.clone(),
+                    path: rest.to_vec(),
+                }
+            });
+        }
+        let is_redirect = start >= 2
+            && self.tokens[start - 1].kind == TokenKind::Equals
+            && self.tokens[start - 2]
+                .lexeme
+                .eq_ignore_ascii_case("redirect");
+        if is_redirect {
+            let space = self.statement_space(start)?;
+            return Some(Reference::Member {
+                space,
+                path: segments,
+            });
+        }
+        None
+    }
+
+    /// Space tag of the `:<space>` directive enclosing token `idx`.
+    fn statement_space(&self, idx: usize) -> Option<String> {
+        self.tokens[..idx]
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, token)| token.kind == TokenKind::Colon)
+            .and_then(|(colon, _)| self.tokens.get(colon + 1))
+            .map(|token| token.lexeme.clone())
+    }
+
+    fn index_statement(
+        &mut self,
+        start: usize,
+        end: usize,
+        external_spaces: &HashMap<String, SpaceKind>,
+    ) {
+        let Some(head) = self.tokens.get(start + 1) else {
+            return;
+        };
+        if head.kind != TokenKind::Identifier {
+            return;
+        }
+        let head = head.lexeme.clone();
+        let name_at = |offset: usize| {
+            self.tokens[start..end]
+                .get(offset)
+                .filter(|token| token.kind == TokenKind::Identifier)
+                .cloned()
+        };
+        match head.as_str() {
+            "space" => {
+                let Some(name) = name_at(2) else {
+                    return;
+                };
+                let kind = self
+                    .attribute_value(start, end, "type")
+                    .and_then(|value| parse_space_kind(&value));
+                if let Some(kind) = kind {
+                    self.spaces.insert(name.lexeme.clone(), kind);
+                }
+                self.push_symbol(SymbolKind::Space, &name, None, None, None);
+            }
+            "param" | "macro" | "bus" => {
+                let Some(name) = name_at(2) else {
+                    return;
+                };
+                let kind = match head.as_str() {
+                    "param" => SymbolKind::Parameter,
+                    "macro" => SymbolKind::Macro,
+                    _ => SymbolKind::Bus,
+                };
+                let description = self.attribute_value(start, end, "descr");
+                self.push_symbol(kind, &name, None, None, description);
+            }
+            "include" | "attach" => {
+                let target = self.tokens[start + 2..end]
+                    .iter()
+                    .rfind(|token| matches!(token.kind, TokenKind::String | TokenKind::Identifier));
+                if let Some(target) = target {
+                    let target = PathBuf::from(&target.lexeme);
+                    let resolved = if target.is_relative() {
+                        self.path
+                            .parent()
+                            .unwrap_or_else(|| Path::new("."))
+                            .join(target)
+                    } else {
+                        target
+                    };
+                    self.includes.push(resolved);
+                }
+            }
+            space => {
+                let kind = self
+                    .spaces
+                    .get(space)
+                    .or_else(|| external_spaces.get(space))
+                    .cloned();
+                let Some(space_kind) = kind else {
+                    return;
+                };
+                let (parent, name) = if self.tokens[start + 2..end]
+                    .first()
+                    .is_some_and(|token| token.kind == TokenKind::DoubleColon)
+                {
+                    (name_at(3).map(|token| token.lexeme), name_at(4))
+                } else {
+                    (None, name_at(2))
+                };
+                let Some(name) = name else {
+                    return;
+                };
+                let subfields = self.attribute_index(start, end, "subfields");
+                let kind = match (space_kind, subfields.is_some()) {
+                    (SpaceKind::Logic, true) => SymbolKind::Form,
+                    (SpaceKind::Logic, false) => SymbolKind::Instruction,
+                    _ => SymbolKind::Field,
+                };
+                let description = self.attribute_value(start, end, "descr");
+                self.push_symbol(kind, &name, Some(space), parent, description);
+                if let Some(block) = subfields {
+                    self.index_subfields(block, end, space, &name.lexeme);
+                }
+            }
+        }
+    }
+
+    /// Records `NAME @(...)` entries of the `subfields={...}` block whose `{` follows `idx`.
+    fn index_subfields(&mut self, idx: usize, end: usize, space: &str, owner: &str) {
+        let mut depth = 0usize;
+        let mut entries: Vec<(IndexedToken, Option<String>)> = Vec::new();
+        let mut cursor = idx;
+        while cursor < end {
+            let token = &self.tokens[cursor];
+            match token.kind {
+                TokenKind::LBrace => depth += 1,
+                TokenKind::RBrace => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                TokenKind::Identifier if depth == 1 => {
+                    let next = self.tokens.get(cursor + 1).map(|token| token.kind.clone());
+                    if next == Some(TokenKind::BitExpr) {
+                        entries.push((token.clone(), None));
+                    } else if token.lexeme == "descr"
+                        && next == Some(TokenKind::Equals)
+                        && let Some(value) = self
+                            .tokens
+                            .get(cursor + 2)
+                            .filter(|token| token.kind == TokenKind::String)
+                        && let Some(entry) = entries.last_mut()
+                    {
+                        entry.1 = Some(value.lexeme.clone());
+                    }
+                }
+                _ => {}
+            }
+            cursor += 1;
+        }
+        for (name, description) in entries {
+            self.push_symbol(
+                SymbolKind::Subfield,
+                &name,
+                Some(space),
+                Some(owner.to_string()),
+                description,
+            );
+        }
+    }
+
+    /// Index of a top-level `name=` attribute inside the statement.
+    fn attribute_index(&self, start: usize, end: usize, name: &str) -> Option<usize> {
+        let mut depth = 0usize;
+        for idx in start..end {
+            let token = &self.tokens[idx];
+            match token.kind {
+                TokenKind::LBrace | TokenKind::LParen => depth += 1,
+                TokenKind::RBrace | TokenKind::RParen => depth = depth.saturating_sub(1),
+                TokenKind::Identifier
+                    if depth == 0
+                        && token.lexeme.eq_ignore_ascii_case(name)
+                        && self
+                            .tokens
+                            .get(idx + 1)
+                            .is_some_and(|next| next.kind == TokenKind::Equals) =>
+                {
+                    return Some(idx + 2);
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    fn attribute_value(&self, start: usize, end: usize, name: &str) -> Option<String> {
+        let idx = self.attribute_index(start, end, name)?;
+        self.tokens
+            .get(idx)
+            .filter(|token| matches!(token.kind, TokenKind::String | TokenKind::Identifier))
+            .map(|token| token.lexeme.clone())
+    }
+
+    fn push_symbol(
+        &mut self,
+        kind: SymbolKind,
+        name: &IndexedToken,
+        space: Option<&str>,
+        parent: Option<String>,
+        description: Option<String>,
+    ) {
+        self.symbols.push(Symbol {
+            kind,
+            name: name.lexeme.clone(),
+            space: space.map(str::to_string),
+            parent,
+            description,
+            path: self.path.clone(),
+            range: name.range,
+        });
+    }
+}
+
+/// Looks `reference` up across `documents`, earlier documents taking precedence.
+pub fn resolve<'a>(documents: &[&'a DocumentIndex], reference: &Reference) -> Option<&'a Symbol> {
+    let symbols = || documents.iter().flat_map(|doc| doc.symbols.iter());
+    let find = |kind: SymbolKind, name: &str| {
+        symbols().find(|symbol| symbol.kind == kind && symbol.name == name)
+    };
+    match reference {
+        Reference::Space(name) => find(SymbolKind::Space, name),
+        Reference::Parameter(name) => {
+            find(SymbolKind::Parameter, name).or_else(|| find(SymbolKind::Subfield, name))
+        }
+        Reference::Macro(name) => find(SymbolKind::Macro, name),
+        Reference::Member { space, path } => {
+            let (first, rest) = path.split_first()?;
+            let member = symbols()
+                .filter(|symbol| {
+                    symbol.space.as_deref() == Some(space.as_str())
+                        && matches!(
+                            symbol.kind,
+                            SymbolKind::Field | SymbolKind::Form | SymbolKind::Instruction
+                        )
+                })
+                .find(|symbol| names_member(&symbol.name, first))?;
+            let Some(subfield) = rest.first() else {
+                return Some(member);
+            };
+            symbols()
+                .find(|symbol| {
+                    symbol.kind == SymbolKind::Subfield
+                        && symbol.space.as_deref() == Some(space.as_str())
+                        && symbol.parent.as_deref() == Some(member.name.as_str())
+                        && symbol.name == *subfield
+                })
+                .or(Some(member))
+        }
+    }
+}
+
+/// Register arrays are referenced per element, so `GPR3` names the `GPR` declaration.
+fn names_member(declared: &str, used: &str) -> bool {
+    declared == used
+        || used
+            .strip_prefix(declared)
+            .is_some_and(|index| !index.is_empty() && index.chars().all(|ch| ch.is_ascii_digit()))
+}
+
+fn parse_space_kind(raw: &str) -> Option<SpaceKind> {
+    match raw.to_ascii_lowercase().as_str() {
+        "rw" => Some(SpaceKind::ReadWrite),
+        "ro" => Some(SpaceKind::ReadOnly),
+        "memio" => Some(SpaceKind::MemoryMappedIo),
+        "register" => Some(SpaceKind::Register),
+        "logic" => Some(SpaceKind::Logic),
+        _ => None,
+    }
+}
+
+fn lex(path: &Path, text: &str) -> Vec<IndexedToken> {
+    let lines: Vec<&str> = text.split('\n').collect();
+    let mut lexer = Lexer::new(text, path.to_path_buf());
+    let mut tokens = Vec::new();
+    let mut failures = 0;
+    loop {
+        match lexer.next_token() {
+            Ok(token) if token.kind == TokenKind::EOF => break,
+            Ok(token) => {
+                failures = 0;
+                let line = token.line.saturating_sub(1);
+                let start = token.column.saturating_sub(1);
+                let width = if token.kind == TokenKind::String {
+                    lines
+                        .get(line)
+                        .map(|text| quoted_width(text, start))
+                        .unwrap_or(2)
+                } else {
+                    token.lexeme.chars().count()
+                };
+                tokens.push(IndexedToken {
+                    kind: token.kind,
+                    lexeme: token.lexeme,
+                    range: TextRange {
+                        line: line as u32,
+                        start: start as u32,
+                        end: (start + width) as u32,
+                    },
+                });
+            }
+            Err(_) => {
+                failures += 1;
+                if failures > MAX_CONSECUTIVE_LEX_ERRORS {
+                    break;
+                }
+            }
+        }
+    }
+    tokens
+}
+
+/// Width of the quoted string starting at character `start`, quotes included.
+fn quoted_width(line: &str, start: usize) -> usize {
+    let mut chars = line.chars().skip(start + 1);
+    let mut width = 1;
+    while let Some(ch) = chars.next() {
+        width += 1;
+        match ch {
+            '\\' => width += chars.next().map_or(0, |_| 1),
+            '"' => break,
+            _ => {}
+        }
+    }
+    width
+}
+
+/// The lexer drops `//` comments, so they are located with a string-aware line scan.
+fn scan_comments(text: &str) -> Vec<TextRange> {
+    let mut comments = Vec::new();
+    for (line_no, line) in text.split('\n').enumerate() {
+        let chars: Vec<char> = line.trim_end_matches('\r').chars().collect();
+        let mut in_string = false;
+        let mut idx = 0;
+        while idx < chars.len() {
+            match chars[idx] {
+                '\\' if in_string => idx += 1,
+                '"' => in_string = !in_string,
+                '/' if !in_string && chars.get(idx + 1) == Some(&'/') => {
+                    comments.push(TextRange {
+                        line: line_no as u32,
+                        start: idx as u32,
+                        end: chars.len() as u32,
+                    });
+                    break;
+                }
+                _ => {}
+            }
+            idx += 1;
+        }
+    }
+    comments
+}
+
+/// Splits the token stream into `[start, end)` statements, each beginning at a top-level `:`.
+fn split_statements(tokens: &[IndexedToken]) -> Vec<(usize, usize)> {
+    let mut statements = Vec::new();
+    let mut depth = 0usize;
+    let mut current = None;
+    for (idx, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::LBrace | TokenKind::LParen => depth += 1,
+            TokenKind::RBrace | TokenKind::RParen => depth = depth.saturating_sub(1),
+            TokenKind::Colon if depth == 0 => {
+                if let Some(start) = current.replace(idx) {
+                    statements.push((start, idx));
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = current {
+        statements.push((start, tokens.len()));
+    }
+    statements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#":param SIZE_MODE=32
+:space reg addr=32 word=32 type=register
+:reg GPR[0..31] size=32 descr="General purpose registers"
+:reg CR size=32 subfields={
+    SO @(3) descr="Summary overflow" // sticky
+    EQ @(2)
+}
+:reg SP redirect=GPR1
+:space insn addr=32 word=32 type=logic
+:insn X_Form subfields={
+    RT @(6..10) op=target|reg.GPR descr="Target register"
+}
+:insn::X_Form add mask={OPCD=31} descr="Add" semantics={
+    $reg::GPR(#RT) = $reg::CR::SO + #SIZE_MODE
+}
+"#;
+
+    fn index() -> DocumentIndex {
+        DocumentIndex::build(Path::new("demo.isa"), SOURCE, &HashMap::new())
+    }
+
+    fn position_of(needle: &str, nth: usize) -> (u32, u32) {
+        SOURCE
+            .lines()
+            .enumerate()
+            .flat_map(|(line, text)| {
+                text.match_indices(needle)
+                    .map(move |(column, _)| (line as u32, column as u32))
+            })
+            .nth(nth)
+            .unwrap_or_else(|| panic!("'{needle}' occurrence {nth} not found"))
+    }
+
+    #[test]
+    fn indexes_declarations_with_descriptions() {
+        let index = index();
+        let summary: Vec<_> = index
+            .symbols
+            .iter()
+            .map(|symbol| (symbol.kind, symbol.name.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (SymbolKind::Parameter, "SIZE_MODE"),
+                (SymbolKind::Space, "reg"),
+                (SymbolKind::Field, "GPR"),
+                (SymbolKind::Field, "CR"),
+                (SymbolKind::Subfield, "SO"),
+                (SymbolKind::Subfield, "EQ"),
+                (SymbolKind::Field, "SP"),
+                (SymbolKind::Space, "insn"),
+                (SymbolKind::Form, "X_Form"),
+                (SymbolKind::Subfield, "RT"),
+                (SymbolKind::Instruction, "add"),
+            ],
+            "every directive and subfield entry becomes a symbol"
+        );
+        let gpr = &index.symbols[2];
+        assert_eq!(
+            gpr.description.as_deref(),
+            Some("General purpose registers")
+        );
+        assert_eq!(
+            index.symbols[4].description.as_deref(),
+            Some("Summary overflow"),
+            "subfield descr attaches to its own entry"
+        );
+        assert_eq!(index.symbols[10].parent.as_deref(), Some("X_Form"));
+        assert_eq!(index.space_kind("insn"), Some(SpaceKind::Logic));
+        assert_eq!(index.comments.len(), 1, "the trailing comment is tracked");
+    }
+
+    #[test]
+    fn resolves_context_references_and_parameters() {
+        let index = index();
+        let docs = [&index];
+        let lookup = |needle: &str, nth: usize| {
+            let (line, character) = position_of(needle, nth);
+            let reference = index
+                .reference_at(line, character + 1)
+                .unwrap_or_else(|| panic!("no reference at '{needle}'"));
+            resolve(&docs, &reference).map(|symbol| (symbol.kind, symbol.name.clone()))
+        };
+        assert_eq!(
+            lookup("GPR(#RT)", 0),
+            Some((SymbolKind::Field, "GPR".into())),
+            "$reg::GPR resolves to the register declaration"
+        );
+        assert_eq!(
+            lookup("SO +", 0),
+            Some((SymbolKind::Subfield, "SO".into())),
+            "field::subfield chains resolve to the subfield"
+        );
+        assert_eq!(
+            lookup("#SIZE_MODE", 0),
+            Some((SymbolKind::Parameter, "SIZE_MODE".into()))
+        );
+        assert_eq!(
+            lookup("#RT", 0),
+            Some((SymbolKind::Subfield, "RT".into())),
+            "operands fall back to form subfields"
+        );
+        assert_eq!(
+            lookup("GPR1", 0),
+            Some((SymbolKind::Field, "GPR".into())),
+            "redirect targets name array elements"
+        );
+        assert_eq!(
+            lookup("X_Form add", 0),
+            Some((SymbolKind::Form, "X_Form".into())),
+            "typed instructions link to their form"
+        );
+    }
+
+    #[test]
+    fn survives_lexer_errors() {
+        let index = DocumentIndex::build(
+            Path::new("broken.isa"),
+            ":space reg addr=32 word=32 type=register\n:reg A ~ \" unterminated\n:reg B size=32",
+            &HashMap::new(),
+        );
+        assert!(
+            index.symbols.iter().any(|symbol| symbol.name == "B"),
+            "directives after a lexer error are still indexed"
+        );
+    }
+}
@@ -0,0 +1,9 @@
+//! Language server for `.isa`, `.isaext`, `.coredef` and `.sysdef` files.
+
+pub mod diagnostics;
+pub mod highlight;
+pub mod index;
+pub mod server;
+pub mod transport;
+
+pub use server::Server;
@@ -0,0 +1,513 @@
+//! JSON-RPC dispatch for the language server. Open buffers are re-indexed on every change;
+//! files they include are indexed from disk so references resolve across the machine.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use serde_json::{Value, json};
+
+use super::diagnostics::check_document;
+use super::highlight::{legend, semantic_tokens};
+use super::index::{DocumentIndex, Symbol, resolve};
+use super::transport::{read_frame, write_message};
+use crate::soc::isa::ast::SpaceKind;
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Text synchronization mode `Full`: every change carries the whole buffer.
+const SYNC_FULL: u8 = 1;
+
+struct OpenDocument {
+    path: PathBuf,
+    text: String,
+}
+
+#[derive(Default)]
+pub struct Server {
+    documents: HashMap<String, OpenDocument>,
+    shutdown_requested: bool,
+}
+
+impl Server {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serves messages from `reader` until `exit` or end of stream. Returns whether the client
+    /// asked for a shutdown first, which decides the process exit code. A body that is not
+    /// valid JSON is answered with a parse error and does not stop the loop.
+    pub fn run<R: BufRead, W: Write>(
+        &mut self,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> io::Result<bool> {
+        while let Some(body) = read_frame(reader)? {
+            let message: Value = match serde_json::from_slice(&body) {
+                Ok(message) => message,
+                Err(err) => {
+                    let reply = json!({
+                        "jsonrpc": "2.0",
+                        "id": null,
+                        "error": { "code": PARSE_ERROR, "message": format!("invalid JSON: {err}") },
+                    });
+                    write_message(writer, &reply)?;
+                    continue;
+                }
+            };
+            if message["method"] == "exit" {
+                break;
+            }
+            for reply in self.handle(&message) {
+                write_message(writer, &reply)?;
+            }
+        }
+        Ok(self.shutdown_requested)
+    }
+
+    /// Handles one incoming message and returns the responses and notifications it produces.
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let method = message["method"].as_str().unwrap_or("");
+        let params = &message["params"];
+        let Some(id) = message.get("id").cloned() else {
+            return self.notification(method, params);
+        };
+        let result = match method {
+            "initialize" => Ok(capabilities()),
+            "shutdown" => {
+                self.shutdown_requested = true;
+                Ok(Value::Null)
+            }
+            "textDocument/semanticTokens/full" => self
+                .document_param(params)
+                .map(|uri| json!({ "data": self.semantic_tokens(uri) })),
+            "textDocument/definition" => self
+                .position_param(params)
+                .map(|(uri, line, character)| self.definition(uri, line, character)),
+            "textDocument/hover" => self
+                .position_param(params)
+                .map(|(uri, line, character)| self.hover(uri, line, character)),
+            _ => Err((METHOD_NOT_FOUND, format!("unsupported method '{method}'"))),
+        };
+        let reply = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message },
+            }),
+        };
+        vec![reply]
+    }
+
+    fn notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let document = &params["textDocument"];
+        let Some(uri) = document["uri"].as_str() else {
+            return Vec::new();
+        };
+        match method {
+            "textDocument/didOpen" => {
+                let text = document["text"].as_str().unwrap_or("").to_string();
+                self.documents.insert(
+                    uri.to_string(),
+                    OpenDocument {
+                        path: uri_to_path(uri),
+                        text,
+                    },
+                );
+            }
+            "textDocument/didChange" => {
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str());
+                match (self.documents.get_mut(uri), text) {
+                    (Some(open), Some(text)) => open.text = text.to_string(),
+                    _ => return Vec::new(),
+                }
+            }
+            "textDocument/didSave" => {}
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+                return vec![publish(uri, Vec::new())];
+            }
+            _ => return Vec::new(),
+        }
+        // Saving or editing one file can fix or break the buffers that include it.
+        let uris: Vec<String> = self.documents.keys().cloned().collect();
+        uris.iter()
+            .map(|uri| publish(uri, self.diagnostics(uri)))
+            .collect()
+    }
+
+    fn diagnostics(&self, uri: &str) -> Vec<Value> {
+        let Some(open) = self.documents.get(uri) else {
+            return Vec::new();
+        };
+        let workspace = self.workspace(uri);
+        let mut spaces: HashMap<String, SpaceKind> = HashMap::new();
+        for doc in &workspace {
+            for (name, kind) in &doc.spaces {
+                spaces.entry(name.clone()).or_insert_with(|| kind.clone());
+            }
+        }
+        check_document(&open.path, &open.text, &spaces)
+    }
+
+    fn semantic_tokens(&self, uri: &str) -> Vec<u32> {
+        let workspace = self.workspace(uri);
+        let Some((document, _)) = workspace.split_first() else {
+            return Vec::new();
+        };
+        let others: Vec<&DocumentIndex> = workspace.iter().collect();
+        semantic_tokens(document, &others)
+    }
+
+    fn definition(&self, uri: &str, line: u32, character: u32) -> Value {
+        self.lookup(
+            uri,
+            line,
+            character,
+            |symbol| json!({ "uri": path_to_uri(&symbol.path), "range": range(symbol) }),
+        )
+    }
+
+    fn hover(&self, uri: &str, line: u32, character: u32) -> Value {
+        self.lookup(uri, line, character, |symbol| {
+            let mut heading = format!("{} `{}`", symbol.kind.label(), symbol.name);
+            if let Some(space) = &symbol.space {
+                heading.push_str(&format!(" in `${space}`"));
+            }
+            if let Some(parent) = &symbol.parent {
+                heading.push_str(&format!(" (of `{parent}`)"));
+            }
+            let value = match &symbol.description {
+                Some(description) => format!("{heading}\n\n{description}"),
+                None => heading,
+            };
+            json!({
+                "contents": { "kind": "markdown", "value": value },
+                "range": range(symbol),
+            })
+        })
+    }
+
+    /// Resolves the identifier under the cursor and renders the symbol it names, or `null`.
+    fn lookup(
+        &self,
+        uri: &str,
+        line: u32,
+        character: u32,
+        render: impl Fn(&Symbol) -> Value,
+    ) -> Value {
+        let workspace = self.workspace(uri);
+        let Some(document) = workspace.first() else {
+            return Value::Null;
+        };
+        let declared = document
+            .symbols
+            .iter()
+            .find(|symbol| symbol.range.contains(line, character));
+        if let Some(symbol) = declared {
+            return render(symbol);
+        }
+        let documents: Vec<&DocumentIndex> = workspace.iter().collect();
+        document
+            .reference_at(line, character)
+            .and_then(|reference| resolve(&documents, &reference))
+            .map(render)
+            .unwrap_or(Value::Null)
+    }
+
+    /// Indexes the document at `uri` first, followed by everything it transitively includes
+    /// and then the remaining open buffers, so nearer declarations win during resolution.
+    fn workspace(&self, uri: &str) -> Vec<DocumentIndex> {
+        let Some(open) = self.documents.get(uri) else {
+            return Vec::new();
+        };
+        let buffers: HashMap<&Path, &str> = self
+            .documents
+            .values()
+            .map(|doc| (doc.path.as_path(), doc.text.as_str()))
+            .collect();
+        let mut pending = vec![open.path.clone()];
+        pending.extend(
+            self.documents
+                .values()
+                .filter(|doc| doc.path != open.path)
+                .map(|doc| doc.path.clone()),
+        );
+        pending.reverse();
+
+        let mut seen = BTreeSet::new();
+        let mut spaces: HashMap<String, SpaceKind> = HashMap::new();
+        let mut indexed = Vec::new();
+        while let Some(path) = pending.pop() {
+            if !seen.insert(path.clone()) {
+                continue;
+            }
+            let text = match buffers.get(path.as_path()) {
+                Some(text) => text.to_string(),
+                None => match fs::read_to_string(&path) {
+                    Ok(text) => text,
+                    Err(_) => continue,
+                },
+            };
+            let index = DocumentIndex::build(&path, &text, &spaces);
+            for (name, kind) in &index.spaces {
+                spaces.entry(name.clone()).or_insert_with(|| kind.clone());
+            }
+            pending.extend(index.includes.iter().rev().cloned());
+            indexed.push(index);
+        }
+        indexed
+    }
+
+    fn document_param<'a>(&self, params: &'a Value) -> Result<&'a str, (i64, String)> {
+        params["textDocument"]["uri"]
+            .as_str()
+            .ok_or_else(|| (INVALID_PARAMS, "missing textDocument.uri".to_string()))
+    }
+
+    fn position_param<'a>(&self, params: &'a Value) -> Result<(&'a str, u32, u32), (i64, String)> {
+        let uri = self.document_param(params)?;
+        let position = &params["position"];
+        match (position["line"].as_u64(), position["character"].as_u64()) {
+            (Some(line), Some(character)) => Ok((uri, line as u32, character as u32)),
+            _ => Err((INVALID_PARAMS, "missing position".to_string())),
+        }
+    }
+}
+
+fn capabilities() -> Value {
+    json!({
+        "capabilities": {
+            "textDocumentSync": { "openClose": true, "change": SYNC_FULL, "save": true },
+            "semanticTokensProvider": { "legend": legend(), "full": true },
+            "definitionProvider": true,
+            "hoverProvider": true,
+        },
+        "serverInfo": { "name": "nanemu-lsp", "version": env!("CARGO_PKG_VERSION") },
+    })
+}
+
+fn publish(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+fn range(symbol: &Symbol) -> Value {
+    json!({
+        "start": { "line": symbol.range.line, "character": symbol.range.start },
+        "end": { "line": symbol.range.line, "character": symbol.range.end },
+    })
+}
+
+/// Converts a `file://` URI to a path, undoing percent-encoding. Other schemes are kept
+/// verbatim so the buffer still gets a stable identity.
+pub fn uri_to_path(uri: &str) -> PathBuf {
+    let Some(rest) = uri.strip_prefix("file://") else {
+        return PathBuf::from(uri);
+    };
+    let bytes = rest.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let escaped = (bytes[idx] == b'%')
+            .then(|| rest.get(idx + 1..idx + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            None => {
+                decoded.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+    PathBuf::from(String::from_utf8_lossy(&decoded).into_owned())
+}
+
+pub fn path_to_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsp::transport::{read_message, write_message};
+    use std::io::Cursor;
+
+    fn open(server: &mut Server, uri: &str, text: &str) -> Vec<Value> {
+        server.handle(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": { "textDocument": { "uri": uri, "languageId": "isa", "version": 1, "text": text } },
+        }))
+    }
+
+    fn request(server: &mut Server, method: &str, params: Value) -> Value {
+        let mut replies = server.handle(&json!({
+            "jsonrpc": "2.0", "id": 1, "method": method, "params": params,
+        }));
+        assert_eq!(replies.len(), 1, "requests get exactly one response");
+        replies.remove(0)
+    }
+
+    fn at(uri: &str, line: u32, character: u32) -> Value {
+        json!({ "textDocument": { "uri": uri }, "position": { "line": line, "character": character } })
+    }
+
+    #[test]
+    fn publishes_diagnostics_on_open_and_change() {
+        let mut server = Server::new();
+        let uri = "file:///virtual/core.isa";
+        let published = open(
+            &mut server,
+            uri,
+            ":space reg addr=32 word=32 type=register\n:bus b addr=32",
+        );
+        assert_eq!(published[0]["method"], "textDocument/publishDiagnostics");
+        assert_eq!(published[0]["params"]["uri"], uri);
+        assert_eq!(
+            published[0]["params"]["diagnostics"]
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let fixed = server.handle(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": { "uri": uri, "version": 2 },
+                "contentChanges": [{ "text": ":space reg addr=32 word=32 type=register" }],
+            },
+        }));
+        assert_eq!(
+            fixed[0]["params"]["diagnostics"],
+            json!([]),
+            "fixing the buffer clears its diagnostics"
+        );
+    }
+
+    #[test]
+    fn resolves_definitions_and_hovers_across_open_buffers() {
+        let mut server = Server::new();
+        let base = "file:///virtual/base.isa";
+        let ext = "file:///virtual/ext.isaext";
+        open(
+            &mut server,
+            base,
+            ":space reg addr=32 word=32 type=register\n:reg PC size=32 descr=\"Program counter\"",
+        );
+        let published = open(&mut server, ext, ":reg LR redirect=PC");
+        let ext_diagnostics = published
+            .iter()
+            .find(|message| message["params"]["uri"] == ext)
+            .expect("extension diagnostics");
+        assert_eq!(
+            ext_diagnostics["params"]["diagnostics"],
+            json!([]),
+            "spaces from other open buffers are known to extensions"
+        );
+
+        let definition = request(&mut server, "textDocument/definition", at(ext, 0, 17));
+        assert_eq!(definition["result"]["uri"], base, "{definition}");
+        assert_eq!(definition["result"]["range"]["start"]["line"], 1);
+        assert_eq!(definition["result"]["range"]["start"]["character"], 5);
+
+        let hover = request(&mut server, "textDocument/hover", at(ext, 0, 17));
+        let text = hover["result"]["contents"]["value"].as_str().unwrap();
+        assert!(
+            text.contains("Program counter"),
+            "hover shows descr: {text}"
+        );
+
+        let nothing = request(&mut server, "textDocument/hover", at(ext, 0, 16));
+        assert_eq!(nothing["result"], Value::Null, "operators have no hover");
+    }
+
+    #[test]
+    fn serves_stdio_session_until_exit() {
+        let mut input = Vec::new();
+        for message in [
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}),
+            json!({"jsonrpc": "2.0", "id": 2, "method": "workspace/symbol", "params": {}}),
+            json!({"jsonrpc": "2.0", "id": 3, "method": "shutdown"}),
+            json!({"jsonrpc": "2.0", "method": "exit"}),
+        ] {
+            write_message(&mut input, &message).expect("frame");
+        }
+        let mut output = Vec::new();
+        let clean = Server::new()
+            .run(&mut Cursor::new(input), &mut output)
+            .expect("session");
+        assert!(clean, "shutdown preceded exit");
+
+        let mut replies = Cursor::new(output);
+        let initialize = read_message(&mut replies).unwrap().unwrap();
+        assert_eq!(
+            initialize["result"]["capabilities"]["semanticTokensProvider"]["legend"],
+            legend()
+        );
+        let unsupported = read_message(&mut replies).unwrap().unwrap();
+        assert_eq!(unsupported["error"]["code"], METHOD_NOT_FOUND);
+        let shutdown = read_message(&mut replies).unwrap().unwrap();
+        assert_eq!(shutdown["id"], 3);
+        assert_eq!(read_message(&mut replies).unwrap(), None);
+    }
+
+    #[test]
+    fn answers_malformed_bodies_with_a_parse_error() {
+        let mut input = b"Content-Length: 8\r\n\r\n{\"id\": 1".to_vec();
+        for message in [
+            json!({"jsonrpc": "2.0", "id": 2, "method": "shutdown"}),
+            json!({"jsonrpc": "2.0", "method": "exit"}),
+        ] {
+            write_message(&mut input, &message).expect("frame");
+        }
+        let mut output = Vec::new();
+        let clean = Server::new()
+            .run(&mut Cursor::new(input), &mut output)
+            .expect("a malformed body does not end the session");
+        assert!(clean, "the session continues to shutdown and exit");
+
+        let mut replies = Cursor::new(output);
+        let parse_error = read_message(&mut replies).unwrap().unwrap();
+        assert_eq!(parse_error["error"]["code"], PARSE_ERROR);
+        assert_eq!(parse_error["id"], Value::Null);
+        let shutdown = read_message(&mut replies).unwrap().unwrap();
+        assert_eq!(shutdown["id"], 2);
+    }
+
+    #[test]
+    fn converts_file_uris() {
+        let path = uri_to_path("file:///home/user/my%20isa/core.coredef");
+        assert_eq!(path, PathBuf::from("/home/user/my isa/core.coredef"));
+        assert_eq!(
+            path_to_uri(&path),
+            "file:///home/user/my%20isa/core.coredef",
+            "paths round-trip"
+        );
+    }
+}
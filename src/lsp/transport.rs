@@ -0,0 +1,102 @@
+//! Base-protocol framing for the language server: `Content-Length` headed JSON-RPC messages
+//! exchanged over any buffered reader/writer pair (stdio in production, byte buffers in tests).
+
+use std::io::{self, BufRead, Write};
+
+use serde_json::Value;
+
+/// Reads the next framed message. Returns `Ok(None)` once the client closes the stream.
+pub fn read_message<R: BufRead>(reader: &mut R) -> io::Result<Option<Value>> {
+    let Some(body) = read_frame(reader)? else {
+        return Ok(None);
+    };
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|err| invalid_data(format!("message body is not valid JSON: {err}")))
+}
+
+/// Reads the body of the next framed message without parsing it, so a server can answer a
+/// malformed body and keep reading. Returns `Ok(None)` once the client closes the stream.
+pub fn read_frame<R: BufRead>(reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(invalid_data(format!("malformed header line '{line}'")));
+        };
+        if name.trim().eq_ignore_ascii_case("content-length") {
+            let length = value
+                .trim()
+                .parse::<usize>()
+                .map_err(|err| invalid_data(format!("invalid Content-Length '{value}': {err}")))?;
+            content_length = Some(length);
+        }
+    }
+    let mut body = vec![0u8; content_length.unwrap_or(0)];
+    reader.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+/// Serializes `message` and writes it with the `Content-Length` header the protocol requires.
+pub fn write_message<W: Write>(writer: &mut W, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    writer.flush()
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    #[test]
+    fn round_trips_framed_messages() {
+        let mut buffer = Vec::new();
+        let first = json!({"jsonrpc": "2.0", "id": 1, "method": "initialize"});
+        let second = json!({"jsonrpc": "2.0", "method": "exit"});
+        write_message(&mut buffer, &first).expect("write first");
+        write_message(&mut buffer, &second).expect("write second");
+
+        let mut reader = Cursor::new(buffer);
+        assert_eq!(read_message(&mut reader).expect("read"), Some(first));
+        assert_eq!(read_message(&mut reader).expect("read"), Some(second));
+        assert_eq!(
+            read_message(&mut reader).expect("eof"),
+            None,
+            "a closed stream ends the message loop"
+        );
+    }
+
+    #[test]
+    fn ignores_extra_headers_and_rejects_bad_lengths() {
+        let body = r#"{"id":7}"#;
+        let framed = format!(
+            "Content-Type: application/vscode-jsonrpc; charset=utf-8\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        let message = read_message(&mut Cursor::new(framed)).expect("read");
+        assert_eq!(
+            message,
+            Some(serde_json::json!({"id": 7})),
+            "content-type header is optional"
+        );
+
+        let err = read_message(&mut Cursor::new("Content-Length: nope\r\n\r\n{}")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -8,13 +8,14 @@ use crate::soc::core::state::{CoreState, StateError};
 use crate::soc::device::Endianness;
 use crate::soc::isa::error::IsaError;
 use crate::soc::isa::machine::{
-    DecodedInstruction, HostServices, MachineDescription, SoftwareHost,
+    DecodeError, DecodedInstruction, HostServices, MachineDescription, SoftwareHost, Syntax,
 };
-use crate::soc::isa::semantics::ParameterBindings;
 use crate::soc::isa::semantics::program::RegisterRef;
 use crate::soc::isa::semantics::runtime::SemanticRuntime;
 use crate::soc::isa::semantics::trace::{ExecutionTracer, TraceEvent};
 use crate::soc::isa::semantics::value::SemanticValue;
+use crate::soc::isa::semantics::{MemoryFault, ParameterBindings};
+use crate::soc::system::bus::DeviceBus;
 
 /// Convenience wrapper that mirrors the ergonomics of emulators like Unicorn by
 /// owning a machine description, core snapshot, and semantics runtime in one
@@ -40,6 +41,14 @@ pub enum HarnessError {
     Isa(IsaError),
     Core(CoreSpecBuildError),
     State(StateError),
+    /// Program exception: execution reached an encoding its `valid=` predicate rejects.
+    Program(DecodeError),
+    /// Data storage exception: the instruction at `address` issued a load or store the data
+    /// bus could not complete.
+    Memory {
+        address: u64,
+        fault: MemoryFault,
+    },
 }
 
 impl std::fmt::Display for HarnessError {
@@ -48,6 +57,10 @@ impl std::fmt::Display for HarnessError {
             HarnessError::Isa(err) => write!(f, "ISA error: {err}"),
             HarnessError::Core(err) => write!(f, "core spec error: {err}"),
             HarnessError::State(err) => write!(f, "core state error: {err}"),
+            HarnessError::Program(err) => write!(f, "program exception: {err}"),
+            HarnessError::Memory { address, fault } => {
+                write!(f, "data storage exception at 0x{address:016X}: {fault}")
+            }
         }
     }
 }
@@ -58,6 +71,8 @@ impl std::error::Error for HarnessError {
             HarnessError::Isa(err) => Some(err),
             HarnessError::Core(err) => Some(err),
             HarnessError::State(err) => Some(err),
+            HarnessError::Program(err) => Some(err),
+            HarnessError::Memory { fault, .. } => Some(fault),
         }
     }
 }
@@ -156,6 +171,14 @@ impl<H: HostServices> ExecutionHarness<H> {
         &mut self.state
     }
 
+    /// Builds the named `:bus` and attaches it as the core's data bus, returning it so callers
+    /// can load memory before executing.
+    pub fn attach_bus(&mut self, name: &str) -> Result<Arc<DeviceBus>, HarnessError> {
+        let bus = Arc::new(self.machine.build_bus(name)?);
+        self.state.attach_data_bus(bus.clone());
+        Ok(bus)
+    }
+
     pub fn read_register_value(
         &mut self,
         space: &str,
@@ -192,7 +215,7 @@ impl<H: HostServices> ExecutionHarness<H> {
             span: None,
         };
         let resolved = registers.resolve(&reference, index)?;
-        resolved.write(&mut self.state, value)?;
+        resolved.write(&mut self.state, value as u128)?;
         Ok(())
     }
 
@@ -201,15 +224,20 @@ impl<H: HostServices> ExecutionHarness<H> {
         base_address: u64,
         rom: &[u8],
     ) -> Result<Vec<InstructionExecution>, HarnessError> {
-        let decoded = self.machine.decode_instructions(rom, base_address);
-        let disassembly = self.machine.disassemble_from(rom, base_address);
-        let mut executions = Vec::with_capacity(decoded.len());
-        for (entry, listing) in decoded.into_iter().zip(disassembly.into_iter()) {
+        let mut executions = Vec::new();
+        for decoded in self.machine.decode_bytes(rom, base_address) {
+            let entry = match decoded {
+                Ok(entry) => entry,
+                Err(err @ DecodeError::Illegal { .. }) => return Err(HarnessError::Program(err)),
+                Err(DecodeError::Undefined { .. }) => continue,
+                Err(_) => break,
+            };
+            let listing = self.machine.disassemble_decoded(&entry, Syntax::Canonical);
             let mnemonic = entry.instruction().name.clone();
             let detail = listing
                 .display
                 .clone()
-                .unwrap_or_else(|| listing.operands.join(", "));
+                .unwrap_or_else(|| self.machine.format_operands(&listing.operands).join(", "));
             self.runtime.emit_trace(TraceEvent::Fetch {
                 address: listing.address,
                 opcode: listing.opcode,
@@ -219,13 +247,21 @@ impl<H: HostServices> ExecutionHarness<H> {
             let return_value = if let Some(block) = entry.instruction().semantics.as_ref() {
                 let program = block.ensure_program()?;
                 let params = self.bind_parameters(&entry)?;
-                self.runtime.execute_program(
-                    &self.machine,
-                    &mut self.state,
-                    &mut self.host,
-                    &params,
-                    program,
-                )?
+                self.runtime
+                    .execute_program(
+                        &self.machine,
+                        &mut self.state,
+                        &mut self.host,
+                        &params,
+                        program,
+                    )
+                    .map_err(|err| match err {
+                        IsaError::Memory(fault) => HarnessError::Memory {
+                            address: entry.address(),
+                            fault,
+                        },
+                        other => other.into(),
+                    })?
             } else {
                 None
             };
@@ -95,8 +95,11 @@ impl std::error::Error for IsaSpecError {
 mod tests {
     use super::*;
     use crate::soc::isa::ast::SpaceKind;
+    use crate::soc::isa::diagnostic::{SourcePosition, SourceSpan};
     use crate::soc::isa::machine::{RegisterInfo, SpaceInfo};
+    use crate::soc::isa::semantics::SemanticEffects;
     use std::collections::BTreeMap;
+    use std::path::PathBuf;
 
     fn sample_machine() -> MachineDescription {
         let mut machine = MachineDescription::new();
@@ -121,9 +124,14 @@ mod tests {
             operands: Vec::new(),
             display: None,
             operator: None,
+            extend: None,
+            alias: None,
+            valid: None,
             mask: None,
             encoding: None,
             semantics: Some(SemanticBlock::empty()),
+            effects: SemanticEffects::default(),
+            span: SourceSpan::point(PathBuf::from("sample.isa"), SourcePosition::new(1, 1)),
         });
         machine
     }
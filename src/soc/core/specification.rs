@@ -3,7 +3,7 @@ use std::convert::TryFrom;
 
 use crate::soc::device::Endianness;
 use crate::soc::isa::ast::SpaceKind;
-use crate::soc::isa::machine::{MachineDescription, RegisterInfo};
+use crate::soc::isa::machine::{MachineDescription, RegisterInfo, resolve_alias_target};
 
 const DEFAULT_REGISTER_BITS: u32 = 64;
 
@@ -57,6 +57,8 @@ pub struct RegisterSpec {
     pub name: String,
     pub bit_offset: u32,
     pub bit_len: u32,
+    /// Power-on value applied by `CoreState::reset`; `None` resets to zero.
+    pub reset: Option<u128>,
 }
 
 impl RegisterSpec {
@@ -72,6 +74,7 @@ pub struct CoreSpecBuilder {
     cursor: u32,
     registers: Vec<RegisterSpec>,
     seen: HashSet<String>,
+    resets: Vec<(String, u128)>,
     errors: Vec<CoreSpecError>,
 }
 
@@ -83,6 +86,7 @@ impl CoreSpecBuilder {
             cursor: 0,
             registers: Vec::new(),
             seen: HashSet::new(),
+            resets: Vec::new(),
             errors: Vec::new(),
         }
     }
@@ -116,7 +120,28 @@ impl CoreSpecBuilder {
         self
     }
 
+    /// Records the power-on value for a register added before or after this call.
+    pub fn reset(mut self, name: impl Into<String>, value: u128) -> Self {
+        self.resets.push((name.into(), value));
+        self
+    }
+
     pub fn build(mut self) -> Result<CoreSpec, CoreSpecBuildError> {
+        for (name, value) in std::mem::take(&mut self.resets) {
+            let Some(spec) = self.registers.iter_mut().find(|spec| spec.name == name) else {
+                self.errors.push(CoreSpecError::UnknownRegister(name));
+                continue;
+            };
+            if spec.bit_len < 128 && value >> spec.bit_len != 0 {
+                self.errors.push(CoreSpecError::ResetOverflow {
+                    name,
+                    value,
+                    width: spec.bit_len,
+                });
+                continue;
+            }
+            spec.reset = Some(value);
+        }
         if !self.errors.is_empty() {
             return Err(CoreSpecBuildError {
                 errors: self.errors,
@@ -151,6 +176,7 @@ impl CoreSpecBuilder {
             name,
             bit_offset,
             bit_len,
+            reset: None,
         });
     }
 }
@@ -165,6 +191,8 @@ impl CoreSpec {
             .or_else(|| register_space_endianness(machine))
             .unwrap_or(Endianness::Little);
         let mut builder = CoreSpec::builder(name, resolved_endian);
+        let mut elements: HashMap<String, (String, u32)> = HashMap::new();
+        let mut resets: HashMap<String, u128> = HashMap::new();
         for (space_name, space) in &machine.spaces {
             if space.kind != SpaceKind::Register {
                 continue;
@@ -175,6 +203,7 @@ impl CoreSpec {
                     continue;
                 }
                 let width = register_bit_width(info, default_bits);
+                let reset = register_reset(space_name, info, width)?;
                 if let Some(range) = &info.range {
                     for (ordinal, index) in (range.start..=range.end).enumerate() {
                         let label = info.format(index as u64);
@@ -186,17 +215,96 @@ impl CoreSpec {
                             info.offset,
                             ordinal as u32,
                         );
+                        let qualified = format!("{space_name}::{label}");
+                        if let Some(value) = reset {
+                            resets.insert(qualified.clone(), value);
+                        }
+                        elements.insert(qualified.clone(), (qualified, width));
                     }
                 } else {
                     let label = info.format(0);
                     builder = append_register(builder, space_name, &label, width, info.offset, 0);
+                    let qualified = format!("{space_name}::{label}");
+                    if let Some(value) = reset {
+                        resets.insert(qualified.clone(), value);
+                    }
+                    // Redirects name the register rather than its display label.
+                    elements.insert(format!("{space_name}::{}", info.name), (qualified, width));
                 }
             }
         }
+        apply_alias_resets(machine, &elements, &mut resets)?;
+        let mut resets: Vec<_> = resets.into_iter().collect();
+        resets.sort();
+        for (name, value) in resets {
+            builder = builder.reset(name, value);
+        }
         builder.build()
     }
 }
 
+fn register_reset(
+    space: &str,
+    info: &RegisterInfo,
+    width: u32,
+) -> Result<Option<u128>, CoreSpecBuildError> {
+    if !info.has_reset() {
+        return Ok(None);
+    }
+    info.reset_value(width)
+        .map(Some)
+        .map_err(|err| CoreSpecBuildError {
+            errors: vec![CoreSpecError::InvalidReset {
+                name: format!("{space}::{}", info.name),
+                reason: err.to_string(),
+            }],
+        })
+}
+
+/// Layers subfield `reset=` values declared on `redirect=` aliases over the reset of the
+/// register they alias, so e.g. `:reg SP redirect=GPR1 subfields={ ... reset=... }` shapes
+/// GPR1's power-on value. Chains of aliases are followed to the register they end on, and
+/// an alias of a subfield places its resets within that subfield's bits.
+fn apply_alias_resets(
+    machine: &MachineDescription,
+    elements: &HashMap<String, (String, u32)>,
+    resets: &mut HashMap<String, u128>,
+) -> Result<(), CoreSpecBuildError> {
+    for (space_name, space) in &machine.spaces {
+        if space.kind != SpaceKind::Register {
+            continue;
+        }
+        for info in space.registers.values() {
+            let Some(reference) = &info.redirect else {
+                continue;
+            };
+            if !info.subfields.iter().any(|sub| sub.reset.is_some()) {
+                continue;
+            }
+            let alias = format!("{space_name}::{}", info.name);
+            let invalid = |reason: String| CoreSpecBuildError {
+                errors: vec![CoreSpecError::InvalidReset {
+                    name: alias.clone(),
+                    reason,
+                }],
+            };
+            let target = resolve_alias_target(&machine.spaces, space_name, &info.name, reference)
+                .map_err(|err| invalid(err.to_string()))?;
+            let (element, width) = elements
+                .get(&format!("{}::{}", target.space, target.element))
+                .ok_or_else(|| invalid("redirect target is not a register element".into()))?;
+            let current = resets.get(element).copied().unwrap_or(0);
+            let value = match target.subfield {
+                Some(sub) => info.apply_subfield_resets_within(*width, sub, current),
+                None => info.apply_subfield_resets(*width, current),
+            }
+            .map_err(|err| invalid(err.to_string()))?;
+            resets.insert(element.clone(), value);
+        }
+    }
+    Ok(())
+}
+
 fn append_register(
     builder: CoreSpecBuilder,
     space: &str,
@@ -241,7 +349,20 @@ fn register_bit_width(info: &RegisterInfo, fallback_bits: u32) -> u32 {
 #[derive(Debug, Clone)]
 pub enum CoreSpecError {
     DuplicateRegister(String),
-    InvalidWidth { name: String, width: u32 },
+    InvalidWidth {
+        name: String,
+        width: u32,
+    },
+    UnknownRegister(String),
+    ResetOverflow {
+        name: String,
+        value: u128,
+        width: u32,
+    },
+    InvalidReset {
+        name: String,
+        reason: String,
+    },
 }
 
 #[derive(Debug)]
@@ -280,6 +401,18 @@ impl std::fmt::Display for CoreSpecError {
                     "register '{name}' must reserve at least 1 bit (got {width})"
                 )
             }
+            CoreSpecError::UnknownRegister(name) => {
+                write!(f, "reset value given for unknown register '{name}'")
+            }
+            CoreSpecError::ResetOverflow { name, value, width } => {
+                write!(
+                    f,
+                    "reset value 0x{value:X} for register '{name}' does not fit in {width} bits"
+                )
+            }
+            CoreSpecError::InvalidReset { name, reason } => {
+                write!(f, "invalid reset for register '{name}': {reason}")
+            }
         }
     }
 }
@@ -348,4 +481,77 @@ mod tests {
         assert_eq!(spec.total_bits(), 64);
         assert_eq!(spec.registers().len(), 2);
     }
+
+    #[test]
+    fn carries_field_subfield_and_alias_resets() {
+        use crate::loader::isa::parse_str;
+        use std::path::PathBuf;
+
+        let doc = parse_str(
+            PathBuf::from("reset.isa"),
+            ":space reg addr=32 word=32 type=register\n:reg GPR[0..1] reset=0x10\n:reg MSR reset=0x1000 subfields={\n    EE @(16) reset=1\n}\n:reg SPR redirect=GPR1 subfields={\n    HI @(0..15) reset=0xABCD\n}\n:reg PC",
+        )
+        .expect("parse");
+        let machine = MachineDescription::from_documents(vec![doc]).expect("machine");
+        let spec = CoreSpec::from_machine("demo", &machine, None).expect("core spec");
+        let reset = |name: &str| spec.register(name).expect(name).reset;
+
+        assert_eq!(
+            reset("reg::GPR0"),
+            Some(0x10),
+            "ranged resets apply per element"
+        );
+        assert_eq!(
+            reset("reg::MSR"),
+            Some(0x1000 | 1 << 15),
+            "subfield resets overlay the field reset (bit 16 counts from the MSB)"
+        );
+        assert_eq!(
+            reset("reg::GPR1"),
+            Some(0xABCD_0010),
+            "alias subfield resets land on the redirected register"
+        );
+        assert_eq!(
+            reset("reg::PC"),
+            None,
+            "registers without reset= stay unset"
+        );
+    }
+
+    #[test]
+    fn alias_resets_follow_chains_and_subfield_targets() {
+        use crate::loader::isa::parse_str;
+        use std::path::PathBuf;
+
+        let doc = parse_str(
+            PathBuf::from("reset.isa"),
+            ":space reg addr=32 word=32 type=register\n:reg SPR[0..31] size=32 subfields={\n    msb @(0..15)\n    lsb @(16..31)\n}\n:reg TBL redirect=SPR8\n:reg ALT redirect=TBL subfields={\n    HI @(0..15) reset=0xABCD\n}\n:reg LO redirect=SPR22::lsb subfields={\n    TOP @(0..3) reset=0xA\n}",
+        )
+        .expect("parse");
+        let machine = MachineDescription::from_documents(vec![doc]).expect("machine");
+        let spec = CoreSpec::from_machine("demo", &machine, None).expect("core spec");
+        let reset = |name: &str| spec.register(name).expect(name).reset;
+
+        assert_eq!(
+            reset("reg::SPR8"),
+            Some(0xABCD_0000),
+            "resets on an alias of an alias land on the final register"
+        );
+        assert_eq!(
+            reset("reg::SPR22"),
+            Some(0xA000),
+            "resets on an alias of a subfield land within that subfield"
+        );
+    }
+
+    #[test]
+    fn builder_rejects_oversized_or_unknown_resets() {
+        let build = CoreSpec::builder("demo", Endianness::Little)
+            .register("r0", 8)
+            .reset("r0", 0x100)
+            .reset("r9", 0)
+            .build();
+        let err = build.expect_err("invalid resets should fail");
+        assert_eq!(err.errors.len(), 2, "both reset errors are reported: {err}");
+    }
 }
@@ -12,6 +12,8 @@ pub struct CoreState {
     memory: Arc<BasicMemory>,
     registers: HashMap<String, RegisterLayout>,
     handle: DataHandle,
+    /// System bus that semantic loads and stores go through.
+    data: Option<DataHandle>,
 }
 
 impl CoreState {
@@ -31,13 +33,16 @@ impl CoreState {
             .iter()
             .map(|spec| (spec.name.clone(), RegisterLayout::from_spec(spec)))
             .collect();
-        Ok(Self {
+        let mut state = Self {
             spec,
             bus,
             memory,
             registers,
             handle,
-        })
+            data: None,
+        };
+        state.reset()?;
+        Ok(state)
     }
 
     pub fn specification(&self) -> &CoreSpec {
@@ -56,6 +61,20 @@ impl CoreState {
         &mut self.handle
     }
 
+    /// Connects the bus `$mem::` accesses in semantics read and write.
+    pub fn attach_data_bus(&mut self, bus: Arc<DeviceBus>) {
+        self.data = Some(DataHandle::new(bus));
+    }
+
+    /// Bus connected with [`Self::attach_data_bus`].
+    pub fn data_bus(&self) -> Option<&Arc<DeviceBus>> {
+        self.data.as_ref().map(|handle| handle.address().bus())
+    }
+
+    pub fn data_bus_handle(&mut self) -> Option<&mut DataHandle> {
+        self.data.as_mut()
+    }
+
     pub fn register_layout(&self, name: &str) -> Option<RegisterLayout> {
         self.registers.get(name).copied()
     }
@@ -110,6 +129,19 @@ impl CoreState {
         self.handle.address_mut().jump(0)?;
         Ok(())
     }
+
+    /// Puts the core into its power-on state: every register is cleared and then loaded
+    /// with the `reset=` value recorded in the specification.
+    pub fn reset(&mut self) -> StateResult<()> {
+        self.zeroize()?;
+        let spec = self.spec.clone();
+        for register in spec.registers() {
+            if let Some(value) = register.reset.filter(|value| *value != 0) {
+                self.write_register(&register.name, value)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -231,4 +263,26 @@ mod tests {
         let second_value = second.read_register("pc").expect("read second");
         assert_eq!(second_value, 0, "independent states keep isolated memory");
     }
+
+    #[test]
+    fn reset_restores_power_on_values() {
+        let descriptor = Arc::new(
+            CoreSpec::builder("demo", Endianness::Little)
+                .register("pc", 32)
+                .register("msr", 32)
+                .reset("pc", 0xFFFF_FFFC)
+                .reset("msr", 0x0000_1000)
+                .build()
+                .expect("descriptor"),
+        );
+        let mut state = CoreState::new(descriptor).expect("core state");
+        assert_eq!(
+            state.read_register("msr").expect("read msr"),
+            0x1000,
+            "new states start from their reset values"
+        );
+        state.write_register("pc", 0x100).expect("write pc");
+        state.reset().expect("reset");
+        assert_eq!(state.read_register("pc").expect("read pc"), 0xFFFF_FFFC);
+    }
 }
@@ -30,6 +30,8 @@ pub enum IsaItem {
     Instruction(InstructionDecl),
     Macro(MacroDecl),
     Include(IncludeDecl),
+    Bus(BusDecl),
+    Attach(AttachDecl),
 }
 
 #[derive(Debug, Clone)]
@@ -90,7 +92,7 @@ pub struct FieldDecl {
     pub range: Option<FieldIndexRange>,
     pub offset: Option<u64>,
     pub size: Option<u32>,
-    pub reset: Option<u64>,
+    pub reset: Option<u128>,
     pub description: Option<String>,
     pub redirect: Option<ContextReference>,
     pub subfields: Vec<SubFieldDecl>,
@@ -126,6 +128,8 @@ pub struct SubFieldDecl {
     pub bit_spec: String,
     pub operations: Vec<SubFieldOp>,
     pub description: Option<String>,
+    /// Power-on value for just these bits; overrides the owning field's `reset=`.
+    pub reset: Option<u128>,
 }
 
 #[derive(Debug, Clone)]
@@ -146,9 +150,36 @@ pub struct InstructionDecl {
     pub semantics: Option<SemanticBlock>,
     pub display: Option<String>,
     pub operator: Option<String>,
+    /// Logic space that decodes the wider word when this entry matches (`extend=`).
+    pub extend: Option<String>,
+    /// Base instruction this entry is a simplified mnemonic of (`alias=` with `when=`).
+    pub alias: Option<AliasSpec>,
+    /// Predicate over the form's subfields that legal encodings satisfy (`valid=`).
+    pub valid: Option<SemanticExpr>,
     pub span: SourceSpan,
 }
 
+/// `alias=<base> when={...}`: the base instruction an alias stands for and the fields it pins.
+#[derive(Debug, Clone)]
+pub struct AliasSpec {
+    pub base: String,
+    pub constraints: Vec<AliasConstraint>,
+}
+
+/// One `FIELD=value` or `FIELD=#OTHER` entry of a `when={...}` block.
+#[derive(Debug, Clone)]
+pub struct AliasConstraint {
+    pub field: String,
+    pub value: AliasValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AliasValue {
+    Number(u64),
+    /// Same value as another field of the form.
+    Field(String),
+}
+
 #[derive(Debug, Clone)]
 pub struct MacroDecl {
     pub name: String,
@@ -179,3 +210,40 @@ pub struct IncludeDecl {
     pub path: PathBuf,
     pub optional: bool,
 }
+
+/// `:attach <context-tag> <filepath>` entry instantiating a core inside a `.sysdef`.
+#[derive(Debug, Clone)]
+pub struct AttachDecl {
+    pub context: String,
+    pub path: PathBuf,
+    pub span: SourceSpan,
+}
+
+/// `:bus` directive mapping bus address windows onto named memory spaces.
+#[derive(Debug, Clone)]
+pub struct BusDecl {
+    pub name: String,
+    pub address_bits: u32,
+    pub ranges: Vec<BusRangeDecl>,
+    pub span: SourceSpan,
+}
+
+/// Single `[start +size] -> space` entry inside a `:bus ranges={...}` block.
+#[derive(Debug, Clone)]
+pub struct BusRangeDecl {
+    pub start: u64,
+    pub size: u64,
+    pub space: String,
+    pub priority: u32,
+    pub space_offset: u64,
+    pub span: SourceSpan,
+}
+
+impl BusRangeDecl {
+    /// Inclusive end address of the bus window, or `None` when it overflows 64 bits.
+    pub fn end(&self) -> Option<u64> {
+        self.size
+            .checked_sub(1)
+            .and_then(|last| self.start.checked_add(last))
+    }
+}
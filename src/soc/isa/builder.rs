@@ -116,6 +116,9 @@ impl IsaBuilder {
             semantics: None,
             display: None,
             operator: None,
+            extend: None,
+            alias: None,
+            valid: None,
             span: self.span.clone(),
         };
         InstructionBuilder {
@@ -208,6 +211,7 @@ pub fn subfield(name: impl Into<String>, bit_spec: impl Into<String>) -> SubFiel
         bit_spec: bit_spec.into(),
         operations: Vec::new(),
         description: None,
+        reset: None,
     }
 }
 
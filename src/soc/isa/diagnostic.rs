@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
-/// Phase of the pipeline that produced a diagnostic.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Phase of the pipeline that produced a diagnostic, in pipeline order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum DiagnosticPhase {
     Lexer,
     Parser,
@@ -58,6 +58,8 @@ pub struct IsaDiagnostic {
     pub code: &'static str,
     pub message: String,
     pub span: Option<SourceSpan>,
+    /// Closest known name when the diagnostic is about a misspelled reference.
+    pub suggestion: Option<String>,
 }
 
 impl IsaDiagnostic {
@@ -74,6 +76,20 @@ impl IsaDiagnostic {
             code,
             message: message.into(),
             span,
+            suggestion: None,
+        }
+    }
+
+    pub fn with_suggestion(mut self, suggestion: Option<impl Into<String>>) -> Self {
+        self.suggestion = suggestion.map(Into::into);
+        self
+    }
+
+    /// Message text with the "did you mean" hint appended when one is available.
+    pub fn message_with_suggestion(&self) -> String {
+        match &self.suggestion {
+            Some(suggestion) => format!("{}; did you mean '{suggestion}'?", self.message),
+            None => self.message.clone(),
         }
     }
 
@@ -94,8 +110,17 @@ impl IsaDiagnostic {
             "{level:?} {code}: {message} @ {location}",
             level = self.level,
             code = self.code,
-            message = self.message,
+            message = self.message_with_suggestion(),
             location = location
         )
     }
 }
+
+/// Earliest pipeline phase among `diagnostics`, used as the phase of the combined error.
+pub fn first_phase(diagnostics: &[IsaDiagnostic]) -> DiagnosticPhase {
+    diagnostics
+        .iter()
+        .map(|diag| diag.phase)
+        .min()
+        .unwrap_or(DiagnosticPhase::Parser)
+}
@@ -1,6 +1,8 @@
 use std::fmt;
 
 use crate::soc::isa::diagnostic::{DiagnosticPhase, IsaDiagnostic};
+use crate::soc::isa::machine::DecodeError;
+use crate::soc::isa::semantics::MemoryFault;
 use crate::soc::system::bus::error::BusError;
 
 /// Represents any failure that can occur while loading, parsing, validating, or executing ISA
@@ -17,6 +19,9 @@ pub enum IsaError {
         phase: DiagnosticPhase,
         diagnostics: Vec<IsaDiagnostic>,
     },
+    Decode(DecodeError),
+    /// A `$mem::` access in semantics faulted.
+    Memory(MemoryFault),
 }
 
 impl From<std::io::Error> for IsaError {
@@ -31,6 +36,18 @@ impl From<BusError> for IsaError {
     }
 }
 
+impl From<DecodeError> for IsaError {
+    fn from(err: DecodeError) -> Self {
+        IsaError::Decode(err)
+    }
+}
+
+impl From<MemoryFault> for IsaError {
+    fn from(err: MemoryFault) -> Self {
+        IsaError::Memory(err)
+    }
+}
+
 impl fmt::Display for IsaError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -39,6 +56,8 @@ impl fmt::Display for IsaError {
             IsaError::Validation(msg) => write!(f, "validation error: {msg}"),
             IsaError::IncludeLoop { chain } => write!(f, "cyclic include detected: {chain:?}"),
             IsaError::Machine(msg) => write!(f, "machine construction error: {msg}"),
+            IsaError::Decode(err) => write!(f, "decode error: {err}"),
+            IsaError::Memory(err) => write!(f, "memory fault: {err}"),
             IsaError::Diagnostics { phase, diagnostics } => {
                 writeln!(f, "{phase:?} produced {} issue(s):", diagnostics.len())?;
                 for diag in diagnostics {
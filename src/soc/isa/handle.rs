@@ -2,10 +2,10 @@
 
 use std::path::Path;
 
-use crate::soc::system::bus::{DataHandle, ext::stream::ByteDataHandleExt};
+use crate::soc::system::bus::{BusError, DataHandle};
 
 use super::error::IsaError;
-use super::machine::{Disassembly, MachineDescription};
+use super::machine::{Disassembly, MachineDescription, Syntax};
 use super::semantics::SemanticBlock;
 use crate::loader::isa::IsaLoader;
 
@@ -20,15 +20,32 @@ impl IsaHandle {
         Ok(Self { machine })
     }
 
-    /// Disassembles len bytes starting at the current address exposed by the `DataHandle`.
+    /// Disassembles the instructions within `len` bytes of the handle's current address. Each
+    /// instruction is fetched from the bus on demand, so the range may span devices and
+    /// redirects, and every entry carries its bus address. An instruction that would run past
+    /// `len` ends the listing; fetch faults surface as [`IsaError::Decode`].
     pub fn disassemble_range(
         &self,
         data: &mut DataHandle,
         len: usize,
     ) -> Result<Vec<Disassembly>, IsaError> {
-        let mut buf = vec![0u8; len];
-        data.read_bytes(&mut buf)?;
-        Ok(self.machine.disassemble(&buf))
+        let start = data
+            .address()
+            .bus_address()
+            .ok_or(BusError::HandleNotPositioned)?;
+        let mut listing = Vec::new();
+        let mut offset = 0usize;
+        while offset < len {
+            let entry =
+                self.machine
+                    .disassemble_at(data, start + offset as u64, Syntax::Canonical)?;
+            if offset + entry.size > len {
+                break;
+            }
+            offset += entry.size;
+            listing.push(entry);
+        }
+        Ok(listing)
     }
 
     /// Emits semantic IR for a previously decoded instruction mnemonic. In the future this will
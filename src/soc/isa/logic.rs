@@ -59,6 +59,10 @@ impl LogicSpaceState {
     pub(crate) fn form(&self, name: &str) -> Option<&LogicForm> {
         self.forms.get(name)
     }
+
+    pub(crate) fn form_names(&self) -> impl Iterator<Item = &str> {
+        self.forms.keys().map(String::as_str)
+    }
 }
 
 #[derive(Debug)]
@@ -3,10 +3,13 @@
 
 use super::MachineDescription;
 use super::instruction::Instruction;
+use super::register::RegisterBinding;
 use super::space::{FieldEncoding, OperandKind, encode_constant, ensure_byte_aligned};
 use crate::soc::device::endianness::Endianness;
 use crate::soc::isa::ast::MaskSelector;
 use crate::soc::isa::error::IsaError;
+use crate::soc::prog::types::BitFieldSpec;
+use crate::soc::prog::types::bitfield::BitFieldError;
 
 impl MachineDescription {
     /// Encodes a machine instruction using explicit operand/value pairs.
@@ -16,14 +19,31 @@ impl MachineDescription {
         operands: &[(&str, i64)],
     ) -> Result<Vec<u8>, IsaError> {
         let instr = self.find_instruction(mnemonic)?;
+        if instr.alias.is_some() {
+            let mut resolved = operands
+                .iter()
+                .map(|(name, value)| (name.to_string(), *value))
+                .collect();
+            let base = self.expand_alias(instr, &mut resolved)?;
+            let pairs: Vec<(&str, i64)> = resolved
+                .iter()
+                .map(|(name, value)| (name.as_str(), *value))
+                .collect();
+            return self.encode_with_operands(base, &pairs);
+        }
         self.encode_with_operands(instr, operands)
     }
 
-    /// Assembles a single textual instruction such as `add. r1, r2, r3`.
+    /// Assembles a single textual instruction such as `add. r1, r2, r3`. Aliases such as
+    /// `mr r3, r4` assemble to their base instruction. Operands are read as the disassembler
+    /// prints them (see [`Self::operand_texts`]); `addr` operands take the displacement.
     pub fn assemble(&self, asm: &str) -> Result<Vec<u8>, IsaError> {
-        let (mnemonic, raw_operands) = parse_assembly(asm)?;
-        let instr = self.find_instruction(&mnemonic)?;
-        let resolved = self.resolve_operand_values(instr, &raw_operands)?;
+        let (mnemonic, text) = parse_assembly(asm)?;
+        let mut instr = self.find_instruction(mnemonic)?;
+        let mut resolved = self.resolve_operand_values(instr, text)?;
+        if instr.alias.is_some() {
+            instr = self.expand_alias(instr, &mut resolved)?;
+        }
         let pairs: Vec<(&str, i64)> = resolved
             .iter()
             .map(|(name, value)| (name.as_str(), *value))
@@ -31,14 +51,14 @@ impl MachineDescription {
         self.encode_with_operands(instr, &pairs)
     }
 
-    fn find_instruction(&self, mnemonic: &str) -> Result<&Instruction, IsaError> {
+    pub(super) fn find_instruction(&self, mnemonic: &str) -> Result<&Instruction, IsaError> {
         self.instructions
             .iter()
             .find(|candidate| candidate.name == mnemonic)
             .ok_or_else(|| IsaError::Machine(format!("unknown instruction '{mnemonic}'")))
     }
 
-    fn encode_with_operands(
+    pub(super) fn encode_with_operands(
         &self,
         instr: &Instruction,
         operands: &[(&str, i64)],
@@ -83,9 +103,8 @@ impl MachineDescription {
                 let field = form.subfield(name).ok_or_else(|| {
                     IsaError::Machine(format!("unknown operand '{name}' for '{}'", instr.name))
                 })?;
-                bits = field
-                    .spec
-                    .write_bits(bits, (*value as i64) as u64)
+                bits = field_bits(&field.spec, *value)
+                    .and_then(|value| field.spec.write_bits(bits, value))
                     .map_err(|err| {
                         IsaError::Machine(format!(
                             "failed to encode operand '{name}' on '{}': {err}",
@@ -100,6 +119,18 @@ impl MachineDescription {
             )));
         }
 
+        let index = self
+            .instructions
+            .iter()
+            .position(|candidate| std::ptr::eq(candidate, instr));
+        if index.is_some_and(|index| !self.is_legal(index, bits)) {
+            return Err(IsaError::Machine(format!(
+                "operands of '{}' form an illegal encoding (0x{bits:0width$X})",
+                instr.name,
+                width = word_bytes * 2
+            )));
+        }
+
         let mut buffer = vec![0u8; word_bytes];
         write_word(bits, &mut buffer, space.endianness);
         Ok(buffer)
@@ -108,13 +139,105 @@ impl MachineDescription {
     fn resolve_operand_values(
         &self,
         instr: &Instruction,
-        raw_operands: &[String],
+        text: &str,
     ) -> Result<Vec<(String, i64)>, IsaError> {
+        self.operand_texts(instr, text)?
+            .fields
+            .into_iter()
+            .map(|(field, raw)| Ok((field.name.clone(), parse_operand_value(self, field, raw)?)))
+            .collect()
+    }
+
+    /// Splits the operand `text` of `instr` into the fields it sets. Accepted, in order: the
+    /// `NAME=value` list of [`Syntax::Raw`](super::Syntax::Raw), the instruction's display
+    /// template (so `lwz r3, 8(r1)` matches `#RT, #D(#RA)`), and a comma-separated list in
+    /// operand order.
+    pub(super) fn operand_texts<'t>(
+        &self,
+        instr: &Instruction,
+        text: &'t str,
+    ) -> Result<OperandTexts<'_, 't>, IsaError> {
+        let items = split_list(text)?;
+        let form = instr
+            .form
+            .as_ref()
+            .and_then(|form| self.spaces.get(&instr.space)?.forms.get(form));
+        if let Some(form) = form {
+            let raw: Option<Vec<_>> = items
+                .iter()
+                .map(|item| {
+                    let (name, value) = item.split_once('=')?;
+                    Some((form.subfield(name.trim())?, value.trim()))
+                })
+                .collect();
+            if let Some(fields) = raw.filter(|raw| !raw.is_empty()) {
+                return Ok(OperandTexts { fields, raw: true });
+            }
+        }
+
+        let template = self.assembly_template(instr);
+        if let (Some(form), Some((template, operator))) = (form, template) {
+            let pieces = template_pieces(template, operator);
+            let mut matched = Vec::new();
+            if match_pieces(&pieces, text, &mut matched) {
+                let fields = matched
+                    .into_iter()
+                    .map(|(name, value)| {
+                        let field = form.subfield(name).ok_or_else(|| {
+                            IsaError::Machine(format!(
+                                "display template of '{}' references unknown field '{name}'",
+                                instr.name
+                            ))
+                        })?;
+                        Ok((field, value))
+                    })
+                    .collect::<Result<_, IsaError>>()?;
+                return Ok(OperandTexts { fields, raw: false });
+            }
+        }
+
+        match self.operand_fields(instr, items.len()) {
+            Ok(fields) => Ok(OperandTexts {
+                fields: fields.into_iter().zip(items).collect(),
+                raw: false,
+            }),
+            Err(_) if template.is_some() => Err(IsaError::Machine(format!(
+                "operands '{}' of '{}' do not match '{}'",
+                text.trim(),
+                instr.name,
+                template.map_or("", |(template, _)| template)
+            ))),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Display template and operator the disassembler renders `instr` with.
+    fn assembly_template(&self, instr: &Instruction) -> Option<(&str, Option<&str>)> {
+        let index = self
+            .instructions
+            .iter()
+            .position(|candidate| std::ptr::eq(candidate, instr))?;
+        let pattern = if instr.alias.is_some() {
+            self.alias_pattern(index)?
+        } else {
+            self.patterns
+                .iter()
+                .find(|pattern| pattern.instruction_idx == index)?
+        };
+        Some((pattern.display.as_deref()?, pattern.operator.as_deref()))
+    }
+
+    /// Fields the written operands of `instr` fill, in order; `count` is how many were written.
+    pub(super) fn operand_fields(
+        &self,
+        instr: &Instruction,
+        count: usize,
+    ) -> Result<Vec<&FieldEncoding>, IsaError> {
         let space = self.spaces.get(&instr.space).ok_or_else(|| {
             IsaError::Machine(format!("instruction space '{}' missing", instr.space))
         })?;
         let Some(form_name) = &instr.form else {
-            if raw_operands.is_empty() {
+            if count == 0 {
                 return Ok(Vec::new());
             }
             return Err(IsaError::Machine(format!(
@@ -129,68 +252,253 @@ impl MachineDescription {
             ))
         })?;
 
-        let operand_names = if !instr.operands.is_empty() {
+        let pinned: Vec<&str> = instr
+            .alias
+            .iter()
+            .flat_map(|alias| &alias.constraints)
+            .map(|constraint| constraint.field.as_str())
+            .collect();
+        let operand_names: Vec<String> = if !instr.operands.is_empty() {
             instr.operands.clone()
         } else {
-            form.operand_order.clone()
+            form.operand_order
+                .iter()
+                .filter(|name| !pinned.contains(&name.as_str()))
+                .cloned()
+                .collect()
         };
 
-        if operand_names.len() != raw_operands.len() {
+        if operand_names.len() != count {
             return Err(IsaError::Machine(format!(
                 "instruction '{}' expects {} operand(s) but got {}",
                 instr.name,
                 operand_names.len(),
-                raw_operands.len()
+                count
             )));
         }
 
-        let mut resolved = Vec::with_capacity(raw_operands.len());
-        for (name, raw) in operand_names.iter().zip(raw_operands.iter()) {
-            let field = form.subfield(name).ok_or_else(|| {
-                IsaError::Machine(format!(
-                    "instruction '{}' references unknown operand '{}'",
-                    instr.name, name
-                ))
-            })?;
-            let value = parse_operand_value(self, field, raw)?;
-            resolved.push((name.clone(), value));
+        operand_names
+            .iter()
+            .map(|name| {
+                form.subfield(name).ok_or_else(|| {
+                    IsaError::Machine(format!(
+                        "instruction '{}' references unknown operand '{}'",
+                        instr.name, name
+                    ))
+                })
+            })
+            .collect()
+    }
+}
+
+/// Operand texts of one instruction, paired with the fields they set.
+pub(super) struct OperandTexts<'m, 't> {
+    pub(super) fields: Vec<(&'m FieldEncoding, &'t str)>,
+    /// Written as `NAME=value` field contents, so `addr` fields hold the displacement rather
+    /// than the target.
+    pub(super) raw: bool,
+}
+
+/// Field bits for `value`. Negative values are written in two's complement when they fit the
+/// field as a signed number; a sign-extended field also takes its raw bits (`0xFFFC` for a
+/// 16-bit `-4`), which is how the disassembler prints it.
+fn field_bits(spec: &BitFieldSpec, value: i64) -> Result<u64, BitFieldError> {
+    let data = u32::from(spec.data_width());
+    let value = if spec.is_signed() && (1..64).contains(&data) && (value >> (data - 1)) == 1 {
+        value - (1i64 << data)
+    } else {
+        value
+    };
+    let width = spec.total_width();
+    if value >= 0 || width >= 64 {
+        return Ok(value as u64);
+    }
+    if width == 0 || value < -(1i64 << (width - 1)) {
+        return Err(BitFieldError::ValueTooWide {
+            bits: width,
+            total: width,
+        });
+    }
+    Ok(value as u64 & ((1u64 << width) - 1))
+}
+
+fn parse_assembly(input: &str) -> Result<(&str, &str), IsaError> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(IsaError::Machine("assembly line missing mnemonic".into()));
+    }
+    Ok(input
+        .split_once(char::is_whitespace)
+        .map_or((input, ""), |(mnemonic, operands)| {
+            (mnemonic, operands.trim())
+        }))
+}
+
+/// Comma-separated items, ignoring commas inside parentheses and string literals.
+pub(super) fn split_list(text: &str) -> Result<Vec<&str>, IsaError> {
+    if text.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = 0;
+    for (idx, ch) in text.char_indices() {
+        if in_string {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match ch {
+            '"' => in_string = true,
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                items.push(text[start..idx].trim());
+                start = idx + 1;
+            }
+            _ => {}
         }
+    }
+    items.push(text[start..].trim());
+    if items.iter().any(|item| item.is_empty()) {
+        return Err(IsaError::Machine(format!(
+            "empty item in '{}'",
+            text.trim()
+        )));
+    }
+    Ok(items)
+}
+
+/// Piece of a display template: literal text or a `#FIELD` placeholder.
+#[derive(Debug, PartialEq, Eq)]
+enum Piece<'t> {
+    Text(&'t str),
+    Field(&'t str),
+}
 
-        Ok(resolved)
+/// Splits `template` the way the display renderer walks it; `#op` becomes the operator text.
+fn template_pieces<'t>(template: &'t str, operator: Option<&'t str>) -> Vec<Piece<'t>> {
+    let mut pieces = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('#') {
+        if start > 0 {
+            pieces.push(Piece::Text(&rest[..start]));
+        }
+        let after = &rest[start + 1..];
+        if let Some(escaped) = after.strip_prefix('#') {
+            pieces.push(Piece::Text("#"));
+            rest = escaped;
+            continue;
+        }
+        let len = after
+            .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_'))
+            .unwrap_or(after.len());
+        let name = &after[..len];
+        match (name, operator) {
+            ("", _) => pieces.push(Piece::Text("#")),
+            (name, Some(operator)) if name.eq_ignore_ascii_case("op") => {
+                pieces.push(Piece::Text(operator))
+            }
+            (name, _) => pieces.push(Piece::Field(name)),
+        }
+        rest = &after[len..];
+    }
+    if !rest.is_empty() {
+        pieces.push(Piece::Text(rest));
     }
+    pieces
 }
 
-fn parse_assembly(input: &str) -> Result<(String, Vec<String>), IsaError> {
-    let mut parts = input
-        .splitn(2, char::is_whitespace)
-        .filter(|part| !part.is_empty());
-    let mnemonic = parts
-        .next()
-        .ok_or_else(|| IsaError::Machine("assembly line missing mnemonic".into()))?;
-    let operands = parts
-        .next()
-        .unwrap_or("")
-        .split(',')
-        .map(str::trim)
-        .filter(|part| !part.is_empty())
-        .map(str::to_string)
-        .collect();
-    Ok((mnemonic.to_string(), operands))
+/// Matches `input` against `pieces`, collecting the text of each field. Whitespace is free
+/// around literal text, and a field takes the shortest balanced text that lets the rest match.
+fn match_pieces<'t, 'i>(
+    pieces: &[Piece<'t>],
+    input: &'i str,
+    matched: &mut Vec<(&'t str, &'i str)>,
+) -> bool {
+    let Some((piece, rest)) = pieces.split_first() else {
+        return input.trim().is_empty();
+    };
+    match piece {
+        Piece::Text(text) => {
+            strip_text(input, text).is_some_and(|after| match_pieces(rest, after, matched))
+        }
+        Piece::Field(name) => {
+            let ends = input
+                .char_indices()
+                .skip(1)
+                .map(|(idx, _)| idx)
+                .chain(std::iter::once(input.len()));
+            for end in ends {
+                let value = input[..end].trim();
+                if value.is_empty() || !balanced(value) {
+                    continue;
+                }
+                matched.push((name, value));
+                if match_pieces(rest, &input[end..], matched) {
+                    return true;
+                }
+                matched.pop();
+            }
+            false
+        }
+    }
 }
 
-fn parse_operand_value(
+/// `input` past the literal `text`, comparing non-space characters without regard to case.
+/// Text made only of whitespace needs at least one space.
+fn strip_text<'i>(input: &'i str, text: &str) -> Option<&'i str> {
+    if !text.is_empty() && text.trim().is_empty() {
+        let trimmed = input.trim_start();
+        return (trimmed.len() < input.len()).then_some(trimmed);
+    }
+    let mut input = input;
+    for expected in text.chars().filter(|ch| !ch.is_whitespace()) {
+        input = input.trim_start();
+        let actual = input.chars().next()?;
+        if !actual.eq_ignore_ascii_case(&expected) {
+            return None;
+        }
+        input = &input[actual.len_utf8()..];
+    }
+    Some(input)
+}
+
+fn balanced(text: &str) -> bool {
+    let mut depth = 0usize;
+    for ch in text.chars() {
+        match ch {
+            '(' => depth += 1,
+            ')' if depth == 0 => return false,
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    depth == 0
+}
+
+pub(super) fn parse_operand_value(
     machine: &MachineDescription,
     field: &FieldEncoding,
     raw: &str,
 ) -> Result<i64, IsaError> {
     if let Some(binding) = &field.register {
-        if let Some((_, element)) = machine.register_schema().find_by_label(&binding.space, raw) {
-            return Ok(i64::from(element.index));
-        }
-        return Err(IsaError::Machine(format!(
-            "unknown register '{raw}' for operand '{}'",
-            field.name
-        )));
+        // Bare numbers select the element directly, as in `lwz 3, 8(1)` or raw syntax.
+        return register_index(machine, binding, raw)
+            .map(Ok)
+            .unwrap_or_else(|| parse_numeric(raw))
+            .map_err(|_| {
+                IsaError::Machine(format!(
+                    "unknown register '{raw}' for operand '{}' ({}::{})",
+                    field.name, binding.space, binding.field
+                ))
+            });
     }
 
     if field.kind == OperandKind::Register {
@@ -202,7 +510,41 @@ fn parse_operand_value(
     parse_numeric(raw)
 }
 
-fn parse_numeric(raw: &str) -> Result<i64, IsaError> {
+/// Index of `raw` within the register file `binding` selects, matched without regard to case
+/// against the names the disassembler prints and the schema's element labels. Names from other
+/// files are rejected, so `r1` does not satisfy a `CR` operand.
+fn register_index(
+    machine: &MachineDescription,
+    binding: &RegisterBinding,
+    raw: &str,
+) -> Option<i64> {
+    let raw = raw.trim();
+    if let Some(info) = machine
+        .spaces
+        .get(&binding.space)
+        .and_then(|space| space.registers.get(&binding.field))
+    {
+        let indices = info
+            .range
+            .as_ref()
+            .map_or(0..=0, |range| range.start..=range.end);
+        if let Some(index) = indices
+            .into_iter()
+            .find(|index| info.format(u64::from(*index)).eq_ignore_ascii_case(raw))
+        {
+            return Some(i64::from(index));
+        }
+    }
+    machine
+        .register_schema()
+        .lookup(&binding.space, &binding.field)?
+        .elements
+        .iter()
+        .find(|element| element.label.eq_ignore_ascii_case(raw))
+        .map(|element| i64::from(element.index))
+}
+
+pub(super) fn parse_numeric(raw: &str) -> Result<i64, IsaError> {
     let trimmed = raw.trim();
     let sign = if trimmed.starts_with('-') { -1 } else { 1 };
     let number = trimmed.trim_start_matches(|ch| ch == '-' || ch == '+');
@@ -225,8 +567,14 @@ fn parse_numeric(raw: &str) -> Result<i64, IsaError> {
     } else {
         (10, token.as_str())
     };
+    // Addresses above i64::MAX (sign-extended targets) keep their two's-complement bits.
     i64::from_str_radix(digits, base)
-        .map(|value| value * sign)
+        .or_else(|err| {
+            u64::from_str_radix(digits, base)
+                .map(|value| value as i64)
+                .map_err(|_| err)
+        })
+        .map(|value| value.wrapping_mul(sign))
         .map_err(|err| IsaError::Machine(format!("unable to parse numeric literal '{raw}': {err}")))
 }
 
@@ -234,7 +582,7 @@ fn resolve_form_field(
     instr: &Instruction,
     space: &super::space::SpaceInfo,
     name: &str,
-) -> Result<crate::soc::prog::types::BitFieldSpec, IsaError> {
+) -> Result<BitFieldSpec, IsaError> {
     let form_name = instr.form.as_ref().ok_or_else(|| {
         IsaError::Machine(format!(
             "instruction '{}' uses mask field '{}' without a form",
@@ -257,7 +605,7 @@ fn resolve_form_field(
         })
 }
 
-fn write_word(bits: u64, buffer: &mut [u8], endianness: Endianness) {
+pub(super) fn write_word(bits: u64, buffer: &mut [u8], endianness: Endianness) {
     match endianness {
         Endianness::Little => {
             for (idx, byte) in buffer.iter_mut().enumerate() {
@@ -273,3 +621,40 @@ fn write_word(bits: u64, buffer: &mut [u8], endianness: Endianness) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_match_memory_operands_and_nested_expressions() {
+        let pieces = template_pieces("#RT, #D(#RA)", None);
+        assert_eq!(
+            pieces,
+            vec![
+                Piece::Field("RT"),
+                Piece::Text(", "),
+                Piece::Field("D"),
+                Piece::Text("("),
+                Piece::Field("RA"),
+                Piece::Text(")"),
+            ]
+        );
+        let mut matched = Vec::new();
+        assert!(match_pieces(&pieces, "r3,lo(msg) ( r1 )", &mut matched));
+        assert_eq!(matched, vec![("RT", "r3"), ("D", "lo(msg)"), ("RA", "r1")]);
+
+        matched.clear();
+        assert!(!match_pieces(&pieces, "r3, r1, 8", &mut matched));
+        assert_eq!(
+            template_pieces("#RT #op ##", Some("+")),
+            vec![
+                Piece::Field("RT"),
+                Piece::Text(" "),
+                Piece::Text("+"),
+                Piece::Text(" "),
+                Piece::Text("#"),
+            ]
+        );
+    }
+}
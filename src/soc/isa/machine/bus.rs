@@ -0,0 +1,293 @@
+//! Bus metadata captured from `:bus` directives plus the logic that materializes it as a
+//! populated [`DeviceBus`].
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::soc::device::BasicMemory;
+use crate::soc::isa::ast::{BusDecl, BusRangeDecl, SpaceKind};
+use crate::soc::isa::diagnostic::SourceSpan;
+use crate::soc::isa::error::IsaError;
+use crate::soc::system::bus::DeviceBus;
+
+use super::space::SpaceInfo;
+
+/// Bucket granularity used for loader-built buses (64 KiB windows).
+const BUS_BUCKET_BITS: u8 = 16;
+
+#[derive(Debug, Clone)]
+pub struct BusInfo {
+    pub name: String,
+    pub address_bits: u32,
+    pub ranges: Vec<BusRangeInfo>,
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Clone)]
+pub struct BusRangeInfo {
+    pub start: u64,
+    pub size: u64,
+    pub space: String,
+    pub priority: u32,
+    pub space_offset: u64,
+}
+
+impl BusInfo {
+    pub fn from_decl(decl: BusDecl) -> Self {
+        Self {
+            name: decl.name,
+            address_bits: decl.address_bits,
+            ranges: decl
+                .ranges
+                .into_iter()
+                .map(BusRangeInfo::from_decl)
+                .collect(),
+            span: decl.span,
+        }
+    }
+
+    /// Builds a bus where every mapped `rw`/`ro` space is backed by a [`BasicMemory`].
+    ///
+    /// Each space's lowest-priority window with `space_off=0` registers the memory device;
+    /// every other window into that space (mirrors and `prio=` overlays) is mapped onto the
+    /// same storage. Declared `prio=` values are ranked onto distinct bus priorities so
+    /// stacked overlays resolve to the highest one. `memio` windows are left unmapped for
+    /// the caller to attach devices.
+    pub fn build_device_bus(
+        &self,
+        spaces: &BTreeMap<String, SpaceInfo>,
+    ) -> Result<DeviceBus, IsaError> {
+        let bus = DeviceBus::new(BUS_BUCKET_BITS);
+        let mut ordered: Vec<&BusRangeInfo> = Vec::with_capacity(self.ranges.len());
+        for range in &self.ranges {
+            let space = spaces.get(&range.space).ok_or_else(|| {
+                IsaError::Machine(format!(
+                    "bus '{}' maps unknown space '{}'",
+                    self.name, range.space
+                ))
+            })?;
+            match space.kind {
+                SpaceKind::ReadWrite | SpaceKind::ReadOnly => ordered.push(range),
+                SpaceKind::MemoryMappedIo => {}
+                _ => {
+                    return Err(IsaError::Machine(format!(
+                        "bus '{}' cannot map non-memory space '{}'",
+                        self.name, range.space
+                    )));
+                }
+            }
+        }
+        ordered.sort_by_key(|range| range.priority);
+
+        let mut levels: Vec<u32> = ordered.iter().map(|range| range.priority).collect();
+        levels.dedup();
+        let bus_priority = |range: &BusRangeInfo| -> Result<u8, IsaError> {
+            let rank = levels.partition_point(|level| *level < range.priority);
+            u8::try_from(rank).map_err(|_| {
+                IsaError::Machine(format!(
+                    "bus '{}' declares too many distinct window priorities",
+                    self.name
+                ))
+            })
+        };
+
+        let mut primaries: BTreeMap<&str, &BusRangeInfo> = BTreeMap::new();
+        for range in &ordered {
+            if range.space_offset == 0 && !primaries.contains_key(range.space.as_str()) {
+                primaries.insert(range.space.as_str(), range);
+            }
+        }
+        for (name, primary) in &primaries {
+            let size = usize::try_from(primary.size).map_err(|_| {
+                IsaError::Machine(format!(
+                    "bus '{}' window for space '{name}' is too large to back with memory",
+                    self.name
+                ))
+            })?;
+            let endianness = spaces[*name].endianness;
+            let memory = Arc::new(BasicMemory::new(*name, size, endianness));
+            bus.register_device_with_priority(memory, primary.start, bus_priority(primary)?)?;
+        }
+
+        for range in ordered {
+            let primary = primaries.get(range.space.as_str()).ok_or_else(|| {
+                IsaError::Machine(format!(
+                    "bus '{}' space '{}' needs a window with space_off=0 to anchor its memory",
+                    self.name, range.space
+                ))
+            })?;
+            if std::ptr::eq(range, *primary) {
+                continue;
+            }
+            let within = range
+                .space_offset
+                .checked_add(range.size)
+                .map(|end| end <= primary.size)
+                .unwrap_or(false);
+            if !within {
+                return Err(IsaError::Machine(format!(
+                    "bus '{}' window 0x{:X} +0x{:X} reaches past the 0x{:X}-byte backing of space '{}'",
+                    self.name, range.start, range.size, primary.size, range.space
+                )));
+            }
+            bus.map_device_window(
+                &range.space,
+                range.start,
+                range.size,
+                range.space_offset,
+                bus_priority(range)?,
+            )?;
+        }
+        Ok(bus)
+    }
+}
+
+impl BusRangeInfo {
+    fn from_decl(decl: BusRangeDecl) -> Self {
+        Self {
+            start: decl.start,
+            size: decl.size,
+            space: decl.space,
+            priority: decl.priority,
+            space_offset: decl.space_offset,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::loader::isa::parse_str;
+    use crate::soc::device::Endianness;
+    use crate::soc::isa::error::IsaError;
+    use crate::soc::isa::machine::MachineDescription;
+
+    fn machine(source: &str) -> MachineDescription {
+        let doc = parse_str(PathBuf::from("bus.isa"), source).expect("parse");
+        MachineDescription::from_documents(vec![doc]).expect("machine")
+    }
+
+    #[test]
+    fn builds_bus_with_memories_and_priority_overlay() {
+        let machine = machine(
+            ":space flash addr=32 word=32 type=ro\n:space ram addr=32 word=32 type=rw\n:space etpu addr=16 word=24 type=memio\n:bus sysbus addr=32 ranges={\n    [0x0 +64kB] -> flash\n    [0x40000000 +0x8000] -> ram\n    [0x40000400 +1kB] -> flash space_off=0x1000 prio=1\n    [0xC3F80000 +0x100] -> etpu\n}",
+        );
+        let bus = machine.build_bus("sysbus").expect("bus");
+
+        let flash = bus.resolve(0x1234).expect("flash mapped");
+        assert_eq!(flash.device.name(), "flash", "flash backs its base window");
+        let ram = bus.resolve(0x4000_0000).expect("ram mapped");
+        assert_eq!(ram.device.name(), "ram", "ram backs its base window");
+
+        let overlay = bus.resolve(0x4000_0410).expect("overlay mapped");
+        assert_eq!(
+            overlay.device.name(),
+            "flash",
+            "higher priority window punches through ram"
+        );
+        assert_eq!(
+            overlay.device_offset + (0x4000_0410 - overlay.bus_start),
+            0x1010,
+            "overlay honours space_off inside the target space"
+        );
+        let after = bus.resolve(0x4000_0800).expect("ram after overlay");
+        assert_eq!(
+            after.device.name(),
+            "ram",
+            "overlay only covers its own window"
+        );
+
+        assert!(
+            bus.resolve(0xC3F8_0000).is_err(),
+            "memio windows stay unmapped for caller-provided devices"
+        );
+    }
+
+    #[test]
+    fn overlay_shares_storage_with_base_window() {
+        let machine = machine(
+            ":space ram addr=32 word=32 type=rw\n:bus sysbus addr=32 ranges={\n    [0x1000 +0x100] -> ram\n    [0x8000 +0x10] -> ram space_off=0x20\n}",
+        );
+        let bus = machine.build_bus("sysbus").expect("bus");
+        let base = bus.resolve(0x1020).expect("base");
+        base.device
+            .write(0x20, &[0xAB])
+            .expect("write through base");
+        let mirror = bus.resolve(0x8000).expect("mirror");
+        let mut byte = [0u8];
+        mirror
+            .device
+            .read(mirror.device_offset, &mut byte)
+            .expect("read through mirror");
+        assert_eq!(byte[0], 0xAB, "mirror windows alias the same memory");
+    }
+
+    #[test]
+    fn stacked_overlays_resolve_to_the_highest_priority() {
+        let machine = machine(
+            ":space ram addr=32 word=32 type=rw\n:bus sysbus addr=32 ranges={\n    [0x0 +0x1000] -> ram\n    [0x8000 +0x100] -> ram space_off=0x10 prio=1\n    [0x8000 +0x10] -> ram space_off=0x20 prio=2\n}",
+        );
+        let bus = machine.build_bus("sysbus").expect("stacked overlays build");
+        let top = bus.resolve(0x8004).expect("top overlay");
+        assert_eq!(top.device_offset + (0x8004 - top.bus_start), 0x24);
+        let below = bus.resolve(0x8020).expect("lower overlay");
+        assert_eq!(
+            below.device_offset + (0x8020 - below.bus_start),
+            0x30,
+            "the prio=1 window shows through past the prio=2 one"
+        );
+    }
+
+    #[test]
+    fn primary_window_may_overlay_another_space() {
+        let machine = machine(
+            ":space flash addr=32 word=32 type=ro\n:space ram addr=32 word=32 type=rw\n:bus sysbus addr=32 ranges={\n    [0x0 +0x1000] -> flash\n    [0x800 +0x100] -> ram prio=1\n}",
+        );
+        let bus = machine
+            .build_bus("sysbus")
+            .expect("overlaying primary builds");
+        assert_eq!(bus.resolve(0x810).expect("ram").device.name(), "ram");
+        assert_eq!(bus.resolve(0x7FF).expect("flash").device.name(), "flash");
+        assert_eq!(bus.resolve(0x900).expect("flash").device.name(), "flash");
+    }
+
+    #[test]
+    fn rejects_window_past_backing_memory() {
+        let machine = machine(
+            ":space ram addr=32 word=32 type=rw\n:bus sysbus addr=32 ranges={\n    [0x0 +0x100] -> ram\n    [0x1000 +0x100] -> ram space_off=0x80 prio=1\n}",
+        );
+        let Err(err) = machine.build_bus("sysbus") else {
+            panic!("window past the backing memory should fail");
+        };
+        assert!(
+            matches!(err, IsaError::Machine(ref msg) if msg.contains("reaches past")),
+            "unexpected error: {err:?}"
+        );
+    }
+
+    #[test]
+    fn unknown_bus_is_reported() {
+        let machine = machine(":space ram addr=32 word=32 type=rw");
+        let Err(err) = machine.build_bus("missing") else {
+            panic!("unknown bus should fail");
+        };
+        assert!(matches!(err, IsaError::Machine(ref msg) if msg.contains("unknown bus")));
+    }
+
+    #[test]
+    fn windows_are_backed_in_the_byte_order_of_their_space() {
+        let machine = machine(
+            ":space flash addr=32 word=32 type=ro endian=little\n:space ram addr=32 word=32 type=rw endian=big\n:bus sysbus addr=32 ranges={\n    [0x0 +0x100] -> flash\n    [0x1000 +0x100] -> ram\n    [0x1080 +0x10] -> flash prio=1\n}",
+        );
+        let bus = machine.build_bus("sysbus").expect("bus");
+        let order = |address| bus.resolve(address).expect("mapped").device.endianness();
+        assert_eq!(order(0x10), Endianness::Little);
+        assert_eq!(order(0x1000), Endianness::Big);
+        assert_eq!(
+            order(0x1084),
+            Endianness::Little,
+            "higher priority window wins"
+        );
+    }
+}
@@ -0,0 +1,366 @@
+//! Recursive-descent code discovery. The traversal decodes only what control flow reaches from
+//! the entry points, splits it into basic blocks, and groups the blocks into per-function
+//! graphs that export as Graphviz DOT.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use crate::soc::prog::symbols::{SymbolKind, SymbolTable};
+
+use super::MachineDescription;
+use super::disassembly::Disassembly;
+use super::fetch::{DecodeError, InstructionFetch};
+use super::flow::{ControlFlow, FlowKind};
+use super::syntax::Syntax;
+
+/// Why an address is known to hold code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Symbol,
+    Reset,
+    Interrupt,
+}
+
+/// Address the traversal starts from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryPoint {
+    pub address: u64,
+    pub name: Option<String>,
+    pub kind: EntryKind,
+}
+
+impl EntryPoint {
+    pub fn symbol(name: impl Into<String>, address: u64) -> Self {
+        Self {
+            address,
+            name: Some(name.into()),
+            kind: EntryKind::Symbol,
+        }
+    }
+
+    pub fn reset(address: u64) -> Self {
+        Self {
+            address,
+            name: Some("reset".into()),
+            kind: EntryKind::Reset,
+        }
+    }
+
+    pub fn interrupt(name: impl Into<String>, address: u64) -> Self {
+        Self {
+            address,
+            name: Some(name.into()),
+            kind: EntryKind::Interrupt,
+        }
+    }
+
+    /// Function symbols of `symbols` that carry a runtime address.
+    pub fn from_symbols(symbols: &SymbolTable) -> Vec<Self> {
+        symbols
+            .query()
+            .iter()
+            .filter(|(_, record)| record.kind == SymbolKind::Function)
+            .filter_map(|(_, record)| {
+                Some(Self::symbol(
+                    symbols.resolve_label(record.label),
+                    record.runtime_addr?,
+                ))
+            })
+            .collect()
+    }
+}
+
+/// How control reaches a successor block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// Unconditional branch.
+    Jump,
+    /// Conditional branch taken.
+    Taken,
+    /// Next instruction: straight-line code, a branch not taken, or the return from a call.
+    Fallthrough,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    pub target: u64,
+    pub kind: EdgeKind,
+}
+
+/// Straight-line run of instructions entered only at `start`.
+#[derive(Debug, Clone)]
+pub struct BasicBlock {
+    pub start: u64,
+    pub instructions: Vec<Disassembly>,
+    /// Effect of the last instruction.
+    pub flow: ControlFlow,
+    pub successors: Vec<Edge>,
+}
+
+impl BasicBlock {
+    /// First address past the block.
+    pub fn end(&self) -> u64 {
+        self.instructions
+            .last()
+            .map_or(self.start, |last| last.address + last.size as u64)
+    }
+}
+
+/// Blocks reachable from `entry` without following calls.
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub entry: u64,
+    pub name: Option<String>,
+    /// Block start addresses, ascending.
+    pub blocks: Vec<u64>,
+    /// Resolved call targets, ascending.
+    pub calls: Vec<u64>,
+}
+
+impl Function {
+    /// `name`, or `sub_1000` for functions only known as call targets.
+    pub fn label(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("sub_{:x}", self.entry))
+    }
+}
+
+/// Code discovered by [`MachineDescription::discover`].
+#[derive(Debug)]
+pub struct ControlFlowGraph {
+    pub blocks: BTreeMap<u64, BasicBlock>,
+    pub functions: BTreeMap<u64, Function>,
+    /// Reached addresses that did not decode; traversal stops on those paths.
+    pub faults: Vec<DecodeError>,
+}
+
+impl MachineDescription {
+    /// Decodes the code reachable from `entries`, following the branch targets each
+    /// instruction's semantics resolve (see [`Self::control_flow`]). Bytes no path reaches
+    /// are never decoded, so data embedded in code stays out of the graph. Call targets start
+    /// new functions. Block instructions are rendered in `syntax`.
+    pub fn discover<F: InstructionFetch + ?Sized>(
+        &self,
+        fetch: &mut F,
+        entries: &[EntryPoint],
+        syntax: Syntax,
+    ) -> ControlFlowGraph {
+        let mut decoded: BTreeMap<u64, (Disassembly, ControlFlow)> = BTreeMap::new();
+        let mut leaders = BTreeSet::new();
+        let mut names: BTreeMap<u64, Option<String>> = BTreeMap::new();
+        let mut faults = Vec::new();
+        let mut pending = Vec::new();
+
+        for entry in entries {
+            let name = names.entry(entry.address).or_default();
+            if name.is_none() {
+                name.clone_from(&entry.name);
+            }
+            leaders.insert(entry.address);
+            pending.push(entry.address);
+        }
+
+        while let Some(mut address) = pending.pop() {
+            while !decoded.contains_key(&address) {
+                let (entry, flow) = match self.disassemble_one_at(fetch, address, syntax) {
+                    Ok((entry, Ok(pattern))) => {
+                        let flow = self.pattern_flow(pattern, entry.opcode, address, entry.size);
+                        (entry, flow)
+                    }
+                    Ok((_, Err(err))) | Err(err) => {
+                        faults.push(err);
+                        break;
+                    }
+                };
+                let next = address + entry.size as u64;
+                for target in &flow.targets {
+                    if flow.kind == FlowKind::Call {
+                        names.entry(*target).or_default();
+                    }
+                    leaders.insert(*target);
+                    pending.push(*target);
+                }
+                let sequential = flow.kind == FlowKind::Sequential;
+                let falls_through = flow.falls_through();
+                decoded.insert(address, (entry, flow));
+                if !falls_through {
+                    break;
+                }
+                if !sequential {
+                    leaders.insert(next);
+                }
+                address = next;
+            }
+        }
+
+        let mut blocks = BTreeMap::new();
+        for &start in &leaders {
+            let mut instructions = Vec::new();
+            let mut flow = ControlFlow::sequential();
+            let mut address = start;
+            while let Some((entry, effect)) = decoded.get(&address) {
+                instructions.push(entry.clone());
+                flow = effect.clone();
+                address += entry.size as u64;
+                if effect.kind != FlowKind::Sequential || leaders.contains(&address) {
+                    break;
+                }
+            }
+            if instructions.is_empty() {
+                continue;
+            }
+            let successors = successors(&flow, address)
+                .into_iter()
+                .filter(|edge| decoded.contains_key(&edge.target))
+                .collect();
+            blocks.insert(
+                start,
+                BasicBlock {
+                    start,
+                    instructions,
+                    flow,
+                    successors,
+                },
+            );
+        }
+
+        names.retain(|entry, _| blocks.contains_key(entry));
+        let entries: BTreeSet<u64> = names.keys().copied().collect();
+        let functions = names
+            .into_iter()
+            .map(|(entry, name)| (entry, collect_function(&blocks, &entries, entry, name)))
+            .collect();
+
+        ControlFlowGraph {
+            blocks,
+            functions,
+            faults,
+        }
+    }
+}
+
+fn successors(flow: &ControlFlow, next: u64) -> Vec<Edge> {
+    let mut edges = Vec::new();
+    if flow.kind != FlowKind::Call {
+        let kind = if flow.conditional {
+            EdgeKind::Taken
+        } else {
+            EdgeKind::Jump
+        };
+        edges.extend(flow.targets.iter().map(|&target| Edge { target, kind }));
+    }
+    if flow.falls_through() {
+        edges.push(Edge {
+            target: next,
+            kind: EdgeKind::Fallthrough,
+        });
+    }
+    edges
+}
+
+/// Blocks reachable from `entry`; a jump to another function's entry is a tail call and is
+/// not followed.
+fn collect_function(
+    blocks: &BTreeMap<u64, BasicBlock>,
+    entries: &BTreeSet<u64>,
+    entry: u64,
+    name: Option<String>,
+) -> Function {
+    let mut seen = BTreeSet::new();
+    let mut calls = BTreeSet::new();
+    let mut stack = vec![entry];
+    while let Some(start) = stack.pop() {
+        let Some(block) = blocks.get(&start) else {
+            continue;
+        };
+        if !seen.insert(start) {
+            continue;
+        }
+        if block.flow.kind == FlowKind::Call {
+            calls.extend(block.flow.targets.iter().copied());
+        }
+        stack.extend(
+            block
+                .successors
+                .iter()
+                .map(|edge| edge.target)
+                .filter(|target| !entries.contains(target)),
+        );
+    }
+    Function {
+        entry,
+        name,
+        blocks: seen.into_iter().collect(),
+        calls: calls.into_iter().collect(),
+    }
+}
+
+impl ControlFlowGraph {
+    /// Graphviz DOT for the function entered at `entry`: one node per block, edges labelled by
+    /// how they are taken. Tail calls point at a plain node named after the callee.
+    pub fn to_dot(&self, machine: &MachineDescription, entry: u64) -> Option<String> {
+        let function = self.functions.get(&entry)?;
+        let mut external = BTreeSet::new();
+        let mut out = String::new();
+        let _ = writeln!(out, "digraph \"{}\" {{", escape(&function.label()));
+        let _ = writeln!(out, "    node [shape=box, fontname=\"monospace\"];");
+        for block in function
+            .blocks
+            .iter()
+            .filter_map(|start| self.blocks.get(start))
+        {
+            let mut label = String::new();
+            for entry in &block.instructions {
+                let operands = entry
+                    .display
+                    .clone()
+                    .unwrap_or_else(|| machine.format_operands(&entry.operands).join(", "));
+                let text = format!("{:x}: {} {operands}", entry.address, entry.mnemonic);
+                let _ = write!(label, "{}\\l", escape(text.trim_end()));
+            }
+            let _ = writeln!(out, "    \"{:x}\" [label=\"{label}\"];", block.start);
+            for edge in &block.successors {
+                if function.blocks.binary_search(&edge.target).is_err() {
+                    external.insert(edge.target);
+                }
+                let attrs = match edge.kind {
+                    EdgeKind::Jump => String::new(),
+                    EdgeKind::Taken if block.flow.condition.is_empty() => {
+                        " [label=\"taken\"]".into()
+                    }
+                    EdgeKind::Taken => format!(
+                        " [label=\"taken: {}\"]",
+                        escape(&block.flow.condition.join(", "))
+                    ),
+                    EdgeKind::Fallthrough if block.flow.conditional => {
+                        " [label=\"not taken\", style=dashed]".into()
+                    }
+                    EdgeKind::Fallthrough => " [style=dashed]".into(),
+                };
+                let _ = writeln!(
+                    out,
+                    "    \"{:x}\" -> \"{:x}\"{attrs};",
+                    block.start, edge.target
+                );
+            }
+        }
+        for target in external {
+            let name = self
+                .functions
+                .get(&target)
+                .map_or_else(|| format!("{target:x}"), Function::label);
+            let _ = writeln!(
+                out,
+                "    \"{target:x}\" [label=\"{}\", shape=plaintext];",
+                escape(&name)
+            );
+        }
+        out.push_str("}\n");
+        Some(out)
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,244 @@
+//! Per-space decode tree replacing the linear pattern scan. Inner nodes switch on a contiguous
+//! run of bits that the remaining patterns fix; leaves hold the few candidates left, ordered so
+//! the first match is the one the highest-specificity rule picks (ties go to the pattern
+//! declared last).
+
+use super::instruction::InstructionPattern;
+
+/// Leaves at or below this size are scanned instead of split further.
+const LEAF_SIZE: usize = 2;
+/// Widest bit run a single node switches on.
+const MAX_SWITCH_BITS: u32 = 8;
+
+#[derive(Debug, Clone, Copy)]
+enum DecodeNode {
+    Switch {
+        shift: u32,
+        mask: u64,
+        first_child: u32,
+    },
+    Leaf {
+        start: u32,
+        len: u32,
+    },
+}
+
+/// Leaf entry carrying its pattern's mask and value so matching stays within the tree.
+#[derive(Debug, Clone, Copy)]
+struct Candidate {
+    mask: u64,
+    value: u64,
+    pattern: u32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct DecodeTree {
+    nodes: Vec<DecodeNode>,
+    candidates: Vec<Candidate>,
+}
+
+impl DecodeTree {
+    /// Builds the tree over every pattern of `space`; indices refer to `patterns`.
+    pub(super) fn build(patterns: &[InstructionPattern], space: &str, word_bits: u32) -> Self {
+        let members: Vec<u32> = patterns
+            .iter()
+            .enumerate()
+            .filter(|(_, pattern)| pattern.space == space)
+            .map(|(idx, _)| idx as u32)
+            .collect();
+        let mut tree = Self::default();
+        if members.is_empty() {
+            return tree;
+        }
+        tree.nodes.push(DecodeNode::Leaf { start: 0, len: 0 });
+        tree.fill(0, patterns, members, 0, word_bits);
+        tree
+    }
+
+    /// Index of the best pattern matching `bits`.
+    pub(super) fn lookup(&self, bits: u64) -> Option<usize> {
+        let mut node = *self.nodes.first()?;
+        loop {
+            match node {
+                DecodeNode::Switch {
+                    shift,
+                    mask,
+                    first_child,
+                } => {
+                    let child = first_child as usize + ((bits >> shift) & mask) as usize;
+                    node = self.nodes[child];
+                }
+                DecodeNode::Leaf { start, len } => {
+                    let range = start as usize..(start + len) as usize;
+                    return self.candidates[range]
+                        .iter()
+                        .find(|candidate| bits & candidate.mask == candidate.value)
+                        .map(|candidate| candidate.pattern as usize);
+                }
+            }
+        }
+    }
+
+    fn fill(
+        &mut self,
+        slot: usize,
+        patterns: &[InstructionPattern],
+        mut members: Vec<u32>,
+        decided: u64,
+        word_bits: u32,
+    ) {
+        let Some((shift, width)) = split_run(patterns, &members, decided, word_bits) else {
+            members.sort_by(|a, b| {
+                let (pa, pb) = (&patterns[*a as usize], &patterns[*b as usize]);
+                pb.specificity.cmp(&pa.specificity).then(b.cmp(a))
+            });
+            self.nodes[slot] = DecodeNode::Leaf {
+                start: self.candidates.len() as u32,
+                len: members.len() as u32,
+            };
+            self.candidates.extend(members.into_iter().map(|idx| {
+                let pattern = &patterns[idx as usize];
+                Candidate {
+                    mask: pattern.mask,
+                    value: pattern.value,
+                    pattern: idx,
+                }
+            }));
+            return;
+        };
+
+        let mask = (1u64 << width) - 1;
+        let run = mask << shift;
+        let first_child = self.nodes.len();
+        self.nodes
+            .extend((0..=mask).map(|_| DecodeNode::Leaf { start: 0, len: 0 }));
+        self.nodes[slot] = DecodeNode::Switch {
+            shift,
+            mask,
+            first_child: first_child as u32,
+        };
+        for key in 0..=mask {
+            let value = key << shift;
+            let child: Vec<u32> = members
+                .iter()
+                .copied()
+                .filter(|idx| {
+                    let pattern = &patterns[*idx as usize];
+                    (pattern.value ^ value) & pattern.mask & run == 0
+                })
+                .collect();
+            self.fill(
+                first_child + key as usize,
+                patterns,
+                child,
+                decided | run,
+                word_bits,
+            );
+        }
+    }
+}
+
+/// Picks the next bits to switch on: the highest run of undecided bits fixed by the largest
+/// number of `members`. Returns `None` when the members should become a leaf.
+fn split_run(
+    patterns: &[InstructionPattern],
+    members: &[u32],
+    decided: u64,
+    word_bits: u32,
+) -> Option<(u32, u32)> {
+    if members.len() <= LEAF_SIZE {
+        return None;
+    }
+    let mut counts = [0usize; 64];
+    for idx in members {
+        let mut open = patterns[*idx as usize].mask & !decided;
+        while open != 0 {
+            counts[open.trailing_zeros() as usize] += 1;
+            open &= open - 1;
+        }
+    }
+    let best = *counts[..word_bits as usize].iter().max()?;
+    if best == 0 {
+        return None;
+    }
+    let top = (0..word_bits)
+        .rev()
+        .find(|bit| counts[*bit as usize] == best)?;
+    let mut low = top;
+    while low > 0 && top - low + 1 < MAX_SWITCH_BITS && counts[(low - 1) as usize] == best {
+        low -= 1;
+    }
+    Some((low, top - low + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::isa::IsaLoader;
+    use std::path::PathBuf;
+
+    fn linear(patterns: &[InstructionPattern], space: &str, bits: u64) -> Option<usize> {
+        patterns
+            .iter()
+            .enumerate()
+            .filter(|(_, pattern)| pattern.space == space && bits & pattern.mask == pattern.value)
+            .max_by_key(|(_, pattern)| pattern.specificity)
+            .map(|(idx, _)| idx)
+    }
+
+    #[test]
+    fn matches_linear_scan_on_powerpc_patterns() {
+        let coredef = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("defs/powerpc/e200.coredef");
+        let machine = IsaLoader::new().load_machine(coredef).expect("load e200");
+        let patterns = machine.patterns();
+        for (space, word_bits) in [("insn", 32), ("vle", 16)] {
+            let tree = DecodeTree::build(patterns, space, word_bits);
+            let mut words: Vec<u64> = patterns
+                .iter()
+                .filter(|pattern| pattern.space == space)
+                .flat_map(|pattern| [pattern.value, pattern.value | !pattern.mask])
+                .map(|word| word & ((1u64 << word_bits) - 1))
+                .collect();
+            let mut seed = 0x2545_F491_4F6C_DD1Du64;
+            for _ in 0..20_000 {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                words.push(seed & ((1u64 << word_bits) - 1));
+            }
+            for word in words {
+                assert_eq!(
+                    tree.lookup(word),
+                    linear(patterns, space, word),
+                    "{space} word 0x{word:X}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn ties_go_to_the_last_declared_pattern() {
+        let pattern = |name: usize, mask: u64, value: u64| InstructionPattern {
+            instruction_idx: name,
+            space: "s".into(),
+            form: None,
+            mask,
+            value,
+            operand_names: Vec::new(),
+            display: None,
+            operator: None,
+            specificity: mask.count_ones(),
+        };
+        let patterns = vec![
+            pattern(0, 0xF0, 0x10),
+            pattern(1, 0xF0, 0x10),
+            pattern(2, 0xFF, 0x12),
+            pattern(3, 0x0F, 0x02),
+        ];
+        let tree = DecodeTree::build(&patterns, "s", 8);
+        assert_eq!(tree.lookup(0x12), Some(2));
+        assert_eq!(tree.lookup(0x13), Some(1));
+        assert_eq!(tree.lookup(0x32), Some(3));
+        assert_eq!(tree.lookup(0x33), None);
+    }
+}
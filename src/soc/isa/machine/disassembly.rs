@@ -4,22 +4,29 @@
 use crate::soc::device::endianness::Endianness;
 use crate::soc::isa::ast::{MaskSelector, SpaceKind};
 use crate::soc::isa::error::IsaError;
-use crate::soc::isa::semantics::{BinaryOperator, SemanticExpr};
+use crate::soc::isa::semantics::{BinaryOperator, SemanticEffects, SemanticExpr};
 use crate::soc::prog::types::BitFieldSpec;
 
 use super::MachineDescription;
+use super::decode_tree::DecodeTree;
+use super::fetch::{DecodeError, FetchWindow, InstructionFetch, WordSource};
 use super::format;
 use super::instruction::{Instruction, InstructionPattern};
+use super::operand::{Operand, OperandValue};
+use super::snapshot::{Snapshot, SnapshotReader, SnapshotWriter};
 use super::space::{
-    SpaceInfo, encode_constant, ensure_byte_aligned, mask_for_bits, parse_bit_spec,
+    FormInfo, SpaceInfo, encode_constant, ensure_byte_aligned, mask_for_bits, parse_bit_spec,
 };
+use super::syntax::{self, Syntax};
 
 #[derive(Debug, Clone)]
 pub struct Disassembly {
     pub address: u64,
+    /// Bytes consumed, including any words fetched through `extend=`.
+    pub size: usize,
     pub opcode: u64,
     pub mnemonic: String,
-    pub operands: Vec<String>,
+    pub operands: Vec<Operand>,
     pub display: Option<String>,
 }
 
@@ -28,9 +35,11 @@ pub struct Disassembly {
 /// can inspect the raw bits instead of stringified operands.
 pub struct DecodedInstruction<'a> {
     address: u64,
+    size: usize,
     bits: u64,
     instruction: &'a Instruction,
     pattern: &'a InstructionPattern,
+    form: Option<&'a FormInfo>,
 }
 
 impl<'a> DecodedInstruction<'a> {
@@ -38,6 +47,11 @@ impl<'a> DecodedInstruction<'a> {
         self.address
     }
 
+    /// Encoded length in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
     pub fn bits(&self) -> u64 {
         self.bits
     }
@@ -57,91 +71,181 @@ impl<'a> DecodedInstruction<'a> {
     pub fn form_name(&self) -> Option<&'a str> {
         self.pattern.form.as_deref()
     }
+
+    pub(super) fn pattern(&self) -> &'a InstructionPattern {
+        self.pattern
+    }
+
+    /// Raw value of operand subfield `name`.
+    pub fn operand_value(&self, name: &str) -> Option<u64> {
+        let field = self.form?.subfield(name)?;
+        Some(field.spec.read_bits(self.bits).0)
+    }
+
+    /// Registers the instruction's semantics read and write, with operand-selected elements
+    /// still symbolic.
+    pub fn effects(&self) -> &'a SemanticEffects {
+        &self.instruction.effects
+    }
+
+    /// [`Self::effects`] with operand-selected elements resolved against this encoding.
+    pub fn bound_effects(&self) -> SemanticEffects {
+        self.instruction
+            .effects
+            .bind(|name| self.operand_value(name))
+    }
 }
 
 impl MachineDescription {
-    pub fn disassemble_from(&self, bytes: &[u8], base_address: u64) -> Vec<Disassembly> {
-        if self.decode_spaces.is_empty() {
-            return Vec::new();
-        }
+    /// Disassembles `bytes` loaded at `base_address`, rendering instructions in `syntax`, up
+    /// to the first word no space applies to.
+    pub fn disassemble_from(
+        &self,
+        bytes: &[u8],
+        base_address: u64,
+        syntax: Syntax,
+    ) -> Vec<Disassembly> {
         let mut cursor = 0usize;
         let mut address = base_address;
         let mut listing = Vec::new();
 
-        while cursor < bytes.len() {
-            let remaining = &bytes[cursor..];
-            let Some(space) = self.select_space(remaining) else {
-                break;
-            };
-            if remaining.len() < space.word_bytes {
-                break;
-            }
-            let chunk = &remaining[..space.word_bytes];
-            let bits = decode_word(chunk, space.endianness) & space.mask;
-            let entry = if let Some(pattern) = self.best_match(&space.name, bits) {
-                let instr = &self.instructions[pattern.instruction_idx];
-                let operands = self.decode_operands(pattern, bits);
-                let display = format::render_display(self, pattern, bits, &operands);
-                Disassembly {
-                    address,
-                    opcode: bits,
-                    mnemonic: instr.name.clone(),
-                    operands,
-                    display,
-                }
-            } else {
-                Disassembly {
-                    address,
-                    opcode: bits,
-                    mnemonic: "unknown".into(),
-                    operands: vec![format!("0x{bits:0width$X}", width = space.word_bytes * 2)],
-                    display: None,
-                }
-            };
-            listing.push(entry);
-            cursor += space.word_bytes;
-            address += space.word_bytes as u64;
+        while let Ok(fetched) = self.fetch_slice(&bytes[cursor..], address) {
+            let size = fetched.space.word_bytes;
+            listing.push(self.listing_entry(address, fetched, syntax));
+            cursor += size;
+            address += size as u64;
         }
 
         listing
     }
 
+    /// Disassembles the single instruction at bus `address`, fetching only the bytes it spans.
+    /// Unmatched words come back as `unknown` entries like in [`Self::disassemble_from`].
+    pub fn disassemble_at<F: InstructionFetch + ?Sized>(
+        &self,
+        fetch: &mut F,
+        address: u64,
+        syntax: Syntax,
+    ) -> Result<Disassembly, DecodeError> {
+        self.disassemble_one_at(fetch, address, syntax)
+            .map(|(entry, _)| entry)
+    }
+
+    /// Like [`Self::disassemble_at`], along with the pattern the word matched or why it does
+    /// not decode to an instruction.
+    pub(super) fn disassemble_one_at<F: InstructionFetch + ?Sized>(
+        &self,
+        fetch: &mut F,
+        address: u64,
+        syntax: Syntax,
+    ) -> Result<(Disassembly, Result<&InstructionPattern, DecodeError>), DecodeError> {
+        let fetched = self.fetch(&mut FetchWindow::new(fetch, address), address)?;
+        let pattern = self.matched_pattern(&fetched, address);
+        Ok((self.listing_entry(address, fetched, syntax), pattern))
+    }
+
+    /// Listing entry of an instruction [`Self::decode_bytes`] or [`Self::decode_at`] already
+    /// decoded, rendered in `syntax` without fetching its bytes again.
+    pub fn disassemble_decoded(
+        &self,
+        decoded: &DecodedInstruction<'_>,
+        syntax: Syntax,
+    ) -> Disassembly {
+        self.render_entry(
+            decoded.address,
+            decoded.size,
+            decoded.bits,
+            decoded.pattern,
+            syntax,
+        )
+    }
+
+    /// Decodes the single instruction at bus `address`, fetching only the bytes it spans.
+    pub fn decode_at<F: InstructionFetch + ?Sized>(
+        &self,
+        fetch: &mut F,
+        address: u64,
+    ) -> Result<DecodedInstruction<'_>, DecodeError> {
+        self.decode_window(&mut FetchWindow::new(fetch, address), address)
+    }
+
+    /// Decodes the instruction `window` starts with; unmatched words are errors.
+    pub(super) fn decode_window<F: InstructionFetch + ?Sized>(
+        &self,
+        window: &mut FetchWindow<'_, F>,
+        address: u64,
+    ) -> Result<DecodedInstruction<'_>, DecodeError> {
+        let fetched = self.fetch(window, address)?;
+        let pattern = self.matched_pattern(&fetched, address)?;
+        Ok(DecodedInstruction {
+            address,
+            size: fetched.space.word_bytes,
+            bits: fetched.bits,
+            instruction: &self.instructions[pattern.instruction_idx],
+            pattern,
+            form: self.pattern_form(pattern),
+        })
+    }
+
+    /// Decodes `bytes` loaded at `base_address` up to the first word no space applies to.
+    /// Unmatched and illegal words are skipped silently; [`Self::decode_bytes`] reports them.
     pub fn decode_instructions(
         &self,
         bytes: &[u8],
         base_address: u64,
     ) -> Vec<DecodedInstruction<'_>> {
-        if self.decode_spaces.is_empty() {
-            return Vec::new();
-        }
         let mut cursor = 0usize;
         let mut address = base_address;
         let mut entries = Vec::new();
-        while cursor < bytes.len() {
-            let remaining = &bytes[cursor..];
-            let Some(space) = self.select_space(remaining) else {
-                break;
-            };
-            if remaining.len() < space.word_bytes {
-                break;
-            }
-            let chunk = &remaining[..space.word_bytes];
-            let bits = decode_word(chunk, space.endianness) & space.mask;
-            if let Some(pattern) = self.best_match(&space.name, bits) {
+        while let Ok(fetched) = self.fetch_slice(&bytes[cursor..], address) {
+            if let Ok(pattern) = self.matched_pattern(&fetched, address) {
                 let instr = &self.instructions[pattern.instruction_idx];
                 entries.push(DecodedInstruction {
                     address,
-                    bits,
+                    size: fetched.space.word_bytes,
+                    bits: fetched.bits,
                     instruction: instr,
                     pattern,
+                    form: self.pattern_form(pattern),
                 });
             }
-            cursor += space.word_bytes;
-            address += space.word_bytes as u64;
+            cursor += fetched.space.word_bytes;
+            address += fetched.space.word_bytes as u64;
         }
         entries
     }
 
+    /// Decodes the instruction at the start of `bytes` along with the pattern it matched.
+    pub(super) fn disassemble_one(
+        &self,
+        bytes: &[u8],
+        address: u64,
+        syntax: Syntax,
+    ) -> Option<(Disassembly, Option<&InstructionPattern>)> {
+        let fetched = self.fetch_slice(bytes, address).ok()?;
+        let pattern = self.matched_pattern(&fetched, address).ok();
+        Some((self.listing_entry(address, fetched, syntax), pattern))
+    }
+
+    /// Renders operands the way the listing prints them when there is no display template.
+    pub fn format_operands(&self, operands: &[Operand]) -> Vec<String> {
+        operands
+            .iter()
+            .map(|operand| format::format_value(self, &operand.value))
+            .collect()
+    }
+
+    pub(super) fn pattern_form(&self, pattern: &InstructionPattern) -> Option<&FormInfo> {
+        let form = pattern.form.as_ref()?;
+        self.spaces.get(&pattern.space)?.forms.get(form)
+    }
+
+    /// Best pattern of the logic space `space` for an already fetched and masked word.
+    pub fn match_pattern(&self, space: &str, bits: u64) -> Option<&InstructionPattern> {
+        let space = self.decode_spaces.iter().find(|info| info.name == space)?;
+        self.best_match(space, bits)
+    }
+
     pub fn build_patterns(&mut self) -> Result<(), IsaError> {
         let mut patterns = Vec::new();
         for (idx, instr) in self.instructions.iter().enumerate() {
@@ -166,7 +270,12 @@ impl MachineDescription {
             let word_bytes = ensure_byte_aligned(word_bits, &info.name)?;
             let mask = mask_for_bits(word_bits);
             let enable = if let Some(expr) = &info.enable {
-                Some(EnablePredicate::new(expr.clone(), word_bits, &info.name)?)
+                Some(EncodingPredicate::new(
+                    expr.clone(),
+                    word_bits,
+                    None,
+                    &format!("enbl expression for space '{}'", info.name),
+                )?)
             } else {
                 None
             };
@@ -177,6 +286,8 @@ impl MachineDescription {
                 mask,
                 endianness: info.endianness,
                 enable,
+                root: true,
+                tree: DecodeTree::default(),
             });
         }
 
@@ -191,56 +302,294 @@ impl MachineDescription {
                 .then_with(|| a.name.cmp(&b.name))
         });
         self.decode_spaces = spaces;
+        for instr in &self.instructions {
+            let Some(target) = &instr.extend else {
+                continue;
+            };
+            let width = |name: &str| {
+                self.decode_spaces
+                    .iter()
+                    .find(|space| space.name == name)
+                    .map(|space| space.word_bits)
+            };
+            match (width(&instr.space), width(target)) {
+                (Some(from), Some(to)) if to > from && to % from == 0 => {}
+                _ => {
+                    return Err(IsaError::Machine(format!(
+                        "instruction '{}' extends '{}' into '{target}', which is not a wider logic space",
+                        instr.name, instr.space
+                    )));
+                }
+            }
+        }
+        self.compile_decode_spaces();
         Ok(())
     }
+
+    /// Compiles the decode tree of every decode space from the current patterns and marks the
+    /// spaces only reachable through `extend=` so they are not selected for a fresh fetch.
+    pub(super) fn compile_decode_spaces(&mut self) {
+        for space in &mut self.decode_spaces {
+            space.tree = DecodeTree::build(&self.patterns, &space.name, space.word_bits);
+            space.root = !self
+                .instructions
+                .iter()
+                .any(|instr| instr.extend.as_deref() == Some(space.name.as_str()));
+        }
+    }
+}
+
+fn truncated<S: WordSource>(source: &S, address: u64, needed: usize) -> DecodeError {
+    DecodeError::Truncated {
+        address,
+        needed,
+        available: source.remaining().unwrap_or(0),
+    }
+}
+
+/// One fetched instruction: the space whose word decoded it, that word, and the match.
+struct Fetched<'a> {
+    space: &'a LogicDecodeSpace,
+    bits: u64,
+    pattern: Option<&'a InstructionPattern>,
+    /// Whether the word satisfies the `valid=` predicate of the match.
+    legal: bool,
 }
 
 impl MachineDescription {
-    fn select_space(&self, bytes: &[u8]) -> Option<&LogicDecodeSpace> {
-        self.decode_spaces.iter().find(|space| {
-            if bytes.len() < space.word_bytes {
-                return false;
+    /// Decodes the instruction at the start of `source`, found at `address`, following
+    /// `extend=` matches into wider spaces.
+    fn fetch<S: WordSource>(
+        &self,
+        source: &mut S,
+        address: u64,
+    ) -> Result<Fetched<'_>, DecodeError> {
+        let mut space = self.select_space(source, address)?;
+        loop {
+            let Some(chunk) = source.prefix(space.word_bytes)? else {
+                return Err(truncated(source, address, space.word_bytes));
+            };
+            let bits = decode_word(chunk, space.endianness) & space.mask;
+            let pattern = self.best_match(space, bits);
+            let target = pattern
+                .and_then(|pattern| self.instructions[pattern.instruction_idx].extend.as_deref());
+            match target {
+                // Targets are strictly wider (checked when the spaces are built), so this ends.
+                Some(target) => {
+                    let Some(next) = self.decode_spaces.iter().find(|info| info.name == target)
+                    else {
+                        return Err(DecodeError::NoSpace {
+                            address,
+                            size: space.word_bytes,
+                            bits,
+                        });
+                    };
+                    space = next;
+                }
+                None => {
+                    return Ok(Fetched {
+                        space,
+                        bits,
+                        pattern,
+                        legal: pattern
+                            .is_none_or(|pattern| self.is_legal(pattern.instruction_idx, bits)),
+                    });
+                }
             }
-            let chunk = &bytes[..space.word_bytes];
+        }
+    }
+
+    /// The instruction `fetched` decoded to, or why there is none.
+    fn matched_pattern<'a>(
+        &'a self,
+        fetched: &Fetched<'a>,
+        address: u64,
+    ) -> Result<&'a InstructionPattern, DecodeError> {
+        let size = fetched.space.word_bytes;
+        let bits = fetched.bits;
+        match fetched.pattern {
+            Some(pattern) if fetched.legal => Ok(pattern),
+            Some(pattern) => Err(DecodeError::Illegal {
+                address,
+                size,
+                bits,
+                instruction: self.instructions[pattern.instruction_idx].name.clone(),
+            }),
+            None => Err(DecodeError::Undefined {
+                address,
+                size,
+                bits,
+            }),
+        }
+    }
+
+    /// Compiles the `valid=` predicate of every instruction against its form.
+    pub(super) fn build_validity(&mut self) -> Result<(), IsaError> {
+        let mut validity = vec![None; self.instructions.len()];
+        for pattern in &self.patterns {
+            let instr = &self.instructions[pattern.instruction_idx];
+            let Some(expr) = &instr.valid else {
+                continue;
+            };
+            let word_bits = match self.spaces.get(&pattern.space) {
+                Some(space) => space.word_bits()?,
+                None => continue,
+            };
+            validity[pattern.instruction_idx] = Some(EncodingPredicate::new(
+                expr.clone(),
+                word_bits,
+                self.pattern_form(pattern),
+                &format!("valid expression for instruction '{}'", instr.name),
+            )?);
+        }
+        if let Some((_, instr)) = self
+            .instructions
+            .iter()
+            .enumerate()
+            .find(|(idx, instr)| instr.valid.is_some() && validity[*idx].is_none())
+        {
+            return Err(IsaError::Machine(format!(
+                "instruction '{}' declares valid= but has no decode pattern",
+                instr.name
+            )));
+        }
+        self.validity = validity;
+        Ok(())
+    }
+
+    /// Whether `bits` satisfies the `valid=` predicate of instruction `instruction_idx`.
+    pub(super) fn is_legal(&self, instruction_idx: usize, bits: u64) -> bool {
+        self.validity
+            .get(instruction_idx)
+            .and_then(Option::as_ref)
+            .is_none_or(|predicate| predicate.evaluate(bits))
+    }
+
+    /// Smallest instruction size a fresh fetch can decode, in bytes.
+    pub(super) fn min_alignment(&self) -> usize {
+        self.decode_spaces
+            .iter()
+            .filter(|space| space.root)
+            .map(|space| space.word_bytes)
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Byte order of the first root space, used for data laid out next to code.
+    pub(super) fn code_endianness(&self) -> Endianness {
+        self.decode_spaces
+            .iter()
+            .find(|space| space.root)
+            .map_or(Endianness::Big, |space| space.endianness)
+    }
+
+    fn fetch_slice(&self, bytes: &[u8], address: u64) -> Result<Fetched<'_>, DecodeError> {
+        self.fetch(&mut &bytes[..], address)
+    }
+
+    /// Narrowest root space enabled for the word at the start of `source`. A source too short
+    /// for some space whose narrower siblings are all disabled reports truncation rather than
+    /// a missing space.
+    fn select_space<S: WordSource>(
+        &self,
+        source: &mut S,
+        address: u64,
+    ) -> Result<&LogicDecodeSpace, DecodeError> {
+        let mut narrowest = None;
+        let mut short = None;
+        for space in self.decode_spaces.iter().filter(|space| space.root) {
+            let Some(chunk) = source.prefix(space.word_bytes)? else {
+                short.get_or_insert(space.word_bytes);
+                continue;
+            };
             let bits = decode_word(chunk, space.endianness) & space.mask;
-            match &space.enable {
+            let enabled = match &space.enable {
                 Some(predicate) => predicate.evaluate(bits),
                 None => true,
+            };
+            if enabled {
+                return Ok(space);
             }
+            narrowest.get_or_insert((space.word_bytes, bits));
+        }
+        if let Some(needed) = short {
+            return Err(truncated(source, address, needed));
+        }
+        let (size, bits) = narrowest.unwrap_or_default();
+        Err(DecodeError::NoSpace {
+            address,
+            size,
+            bits,
         })
     }
 
-    fn best_match(&self, space: &str, bits: u64) -> Option<&InstructionPattern> {
-        self.patterns
-            .iter()
-            .filter(|pattern| pattern.space == space && bits & pattern.mask == pattern.value)
-            .max_by_key(|pattern| pattern.specificity)
+    fn listing_entry(&self, address: u64, fetched: Fetched<'_>, syntax: Syntax) -> Disassembly {
+        let Fetched {
+            space,
+            bits,
+            pattern,
+            legal,
+        } = fetched;
+        if let Some(pattern) = pattern
+            && legal
+        {
+            self.render_entry(address, space.word_bytes, bits, pattern, syntax)
+        } else {
+            Disassembly {
+                address,
+                size: space.word_bytes,
+                opcode: bits,
+                mnemonic: if pattern.is_some() {
+                    "illegal"
+                } else {
+                    "unknown"
+                }
+                .into(),
+                operands: vec![Operand {
+                    name: String::new(),
+                    value: OperandValue::Unsigned {
+                        value: bits,
+                        width: space.word_bits as u16,
+                    },
+                }],
+                display: None,
+            }
+        }
     }
 
-    fn decode_operands(&self, pattern: &InstructionPattern, bits: u64) -> Vec<String> {
-        let form_name = match &pattern.form {
-            Some(name) => name.clone(),
-            None => return Vec::new(),
-        };
-        let Some(space) = self.spaces.get(&pattern.space) else {
-            return Vec::new();
-        };
-        let Some(form) = space.forms.get(&form_name) else {
-            return Vec::new();
+    /// Listing entry of the `size`-byte word `bits` that matched `pattern`.
+    fn render_entry(
+        &self,
+        address: u64,
+        size: usize,
+        bits: u64,
+        pattern: &InstructionPattern,
+        syntax: Syntax,
+    ) -> Disassembly {
+        let rendered = self.rendered_pattern(pattern, bits, syntax);
+        let instr = &self.instructions[rendered.instruction_idx];
+        let (operands, display) = if syntax == Syntax::Raw {
+            let operands = self.raw_operands(pattern, bits);
+            let display = Some(syntax::raw_display(&operands));
+            (operands, display)
+        } else {
+            (
+                self.decode_operands(rendered, bits, address),
+                format::render_display(self, rendered, bits, address),
+            )
         };
+        Disassembly {
+            address,
+            size,
+            opcode: bits,
+            mnemonic: instr.name.clone(),
+            operands,
+            display,
+        }
+    }
 
-        pattern
-            .operand_names
-            .iter()
-            .map(|name| {
-                form.subfield(name)
-                    .map(|field| {
-                        let (value, _) = field.spec.read_bits(bits);
-                        format::format_operand(self, field, value)
-                    })
-                    .unwrap_or_else(|| format!("?{name}"))
-            })
-            .collect()
+    fn best_match(&self, space: &LogicDecodeSpace, bits: u64) -> Option<&InstructionPattern> {
+        space.tree.lookup(bits).map(|idx| &self.patterns[idx])
     }
 
     fn build_pattern(
@@ -358,7 +707,7 @@ impl MachineDescription {
     }
 }
 
-fn decode_word(bytes: &[u8], endianness: Endianness) -> u64 {
+pub(super) fn decode_word(bytes: &[u8], endianness: Endianness) -> u64 {
     match endianness {
         Endianness::Little => bytes
             .iter()
@@ -377,23 +726,91 @@ pub(super) struct LogicDecodeSpace {
     word_bytes: usize,
     mask: u64,
     endianness: Endianness,
-    enable: Option<EnablePredicate>,
+    enable: Option<EncodingPredicate>,
+    /// Derived from the instructions and patterns, so these are rebuilt rather than snapshotted.
+    root: bool,
+    tree: DecodeTree,
+}
+
+impl Snapshot for LogicDecodeSpace {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.name.encode(out);
+        out.u32(self.word_bits);
+        self.word_bytes.encode(out);
+        out.u64(self.mask);
+        self.endianness.encode(out);
+        self.enable.encode(out);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        Ok(Self {
+            name: String::decode(input)?,
+            word_bits: input.u32()?,
+            word_bytes: usize::decode(input)?,
+            mask: input.u64()?,
+            endianness: Endianness::decode(input)?,
+            enable: Option::decode(input)?,
+            root: true,
+            tree: DecodeTree::default(),
+        })
+    }
 }
 
+/// Enable windows at most this wide are precomputed into a lookup table.
+const MAX_ENABLE_TABLE_BITS: u32 = 16;
+
+/// Compiled `enbl=` or `valid=` expression over the bits of a word.
 #[derive(Debug, Clone)]
-struct EnablePredicate {
+pub(super) struct EncodingPredicate {
     expr: EnableExpr,
+    table: Option<EnableTable>,
 }
 
-impl EnablePredicate {
-    fn new(expr: SemanticExpr, word_bits: u32, space: &str) -> Result<Self, IsaError> {
-        Ok(Self {
-            expr: EnableExpr::compile(expr, word_bits, space)?,
-        })
+/// Predicate results for every value of the bit window the expression reads.
+#[derive(Debug, Clone)]
+struct EnableTable {
+    shift: u32,
+    mask: u64,
+    results: Vec<bool>,
+}
+
+impl EncodingPredicate {
+    /// Compiles `expr` over a `word_bits` word. Identifiers other than `true`/`false` name
+    /// subfields of `form`, with or without a leading `#`; `context` names the expression in
+    /// errors.
+    fn new(
+        expr: SemanticExpr,
+        word_bits: u32,
+        form: Option<&FormInfo>,
+        context: &str,
+    ) -> Result<Self, IsaError> {
+        Ok(Self::from_expr(EnableExpr::compile(
+            expr, word_bits, form, context,
+        )?))
+    }
+
+    fn from_expr(expr: EnableExpr) -> Self {
+        let table = expr.bit_window().and_then(|(low, high)| {
+            let width = high - low;
+            (width <= MAX_ENABLE_TABLE_BITS).then(|| {
+                let mask = mask_for_bits(width);
+                EnableTable {
+                    shift: low,
+                    mask,
+                    results: (0..=mask)
+                        .map(|window| expr.evaluate(window << low).as_bool())
+                        .collect(),
+                }
+            })
+        });
+        Self { expr, table }
     }
 
     fn evaluate(&self, bits: u64) -> bool {
-        self.expr.evaluate(bits).as_bool()
+        match &self.table {
+            Some(table) => table.results[((bits >> table.shift) & table.mask) as usize],
+            None => self.expr.evaluate(bits).as_bool(),
+        }
     }
 }
 
@@ -410,21 +827,29 @@ enum EnableExpr {
 }
 
 impl EnableExpr {
-    fn compile(expr: SemanticExpr, word_bits: u32, space: &str) -> Result<Self, IsaError> {
+    fn compile(
+        expr: SemanticExpr,
+        word_bits: u32,
+        form: Option<&FormInfo>,
+        context: &str,
+    ) -> Result<Self, IsaError> {
         match expr {
             SemanticExpr::Literal(value) => Ok(Self::Literal(value)),
             SemanticExpr::Identifier(name) => match name.to_ascii_lowercase().as_str() {
                 "true" => Ok(Self::Bool(true)),
                 "false" => Ok(Self::Bool(false)),
-                other => Err(IsaError::Machine(format!(
-                    "identifier '{other}' is not supported in enbl expression for space '{space}'",
-                ))),
+                other => form
+                    .and_then(|form| form.subfield(name.strip_prefix('#').unwrap_or(&name)))
+                    .map(|field| Self::BitField(field.spec.clone()))
+                    .ok_or_else(|| {
+                        IsaError::Machine(format!(
+                            "identifier '{other}' is not supported in {context}",
+                        ))
+                    }),
             },
             SemanticExpr::BitExpr(spec) => {
                 let parsed = parse_bit_spec(word_bits, &spec).map_err(|err| {
-                    IsaError::Machine(format!(
-                        "invalid bit selector '{spec}' in enbl expression for space '{space}': {err}",
-                    ))
+                    IsaError::Machine(format!("invalid bit selector '{spec}' in {context}: {err}",))
                 })?;
                 Ok(Self::BitField(parsed))
             }
@@ -437,11 +862,11 @@ impl EnableExpr {
                         | BinaryOperator::LogicalOr
                 ) {
                     return Err(IsaError::Machine(format!(
-                        "operator '{op:?}' is not supported in enbl expression for space '{space}'",
+                        "operator '{op:?}' is not supported in {context}",
                     )));
                 }
-                let left = Self::compile(*lhs, word_bits, space)?;
-                let right = Self::compile(*rhs, word_bits, space)?;
+                let left = Self::compile(*lhs, word_bits, form, context)?;
+                let right = Self::compile(*rhs, word_bits, form, context)?;
                 Ok(Self::Binary {
                     op,
                     lhs: Box::new(left),
@@ -451,6 +876,24 @@ impl EnableExpr {
         }
     }
 
+    /// Lowest and one-past-highest container bit the expression reads; `(0, 0)` when it reads
+    /// none.
+    fn bit_window(&self) -> Option<(u32, u32)> {
+        match self {
+            EnableExpr::Literal(_) | EnableExpr::Bool(_) => Some((0, 0)),
+            EnableExpr::BitField(spec) => spec
+                .bit_span()
+                .map(|(low, high)| (u32::from(low), u32::from(high))),
+            EnableExpr::Binary { lhs, rhs, .. } => {
+                let (left, right) = (lhs.bit_window()?, rhs.bit_window()?);
+                Some(match (left, right) {
+                    ((0, 0), other) | (other, (0, 0)) => other,
+                    ((a, b), (c, d)) => (a.min(c), b.max(d)),
+                })
+            }
+        }
+    }
+
     fn evaluate(&self, bits: u64) -> EnableValue {
         match self {
             EnableExpr::Literal(value) => EnableValue::Number(*value),
@@ -492,6 +935,55 @@ impl EnableExpr {
     }
 }
 
+impl Snapshot for EncodingPredicate {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.expr.encode(out);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        EnableExpr::decode(input).map(Self::from_expr)
+    }
+}
+
+impl Snapshot for EnableExpr {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        match self {
+            EnableExpr::Literal(value) => {
+                out.u8(0);
+                out.u64(*value);
+            }
+            EnableExpr::Bool(value) => {
+                out.u8(1);
+                value.encode(out);
+            }
+            EnableExpr::BitField(spec) => {
+                out.u8(2);
+                spec.encode(out);
+            }
+            EnableExpr::Binary { op, lhs, rhs } => {
+                out.u8(3);
+                op.encode(out);
+                lhs.encode(out);
+                rhs.encode(out);
+            }
+        }
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        match input.u8()? {
+            0 => input.u64().map(EnableExpr::Literal),
+            1 => bool::decode(input).map(EnableExpr::Bool),
+            2 => BitFieldSpec::decode(input).map(EnableExpr::BitField),
+            3 => Ok(EnableExpr::Binary {
+                op: BinaryOperator::decode(input)?,
+                lhs: Box::decode(input)?,
+                rhs: Box::decode(input)?,
+            }),
+            tag => input.invalid_tag("enable expression", tag),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 enum EnableValue {
     Number(u64),
@@ -0,0 +1,824 @@
+//! Stable JSON export of a [`MachineDescription`] for external tooling, plus the matching import.
+//! Forms are exported flattened (inherited subfields included) and bit specs use the ISA
+//! `@(...)` syntax. Decode patterns and register schema entries are derived data: the import
+//! ignores them and rebuilds both from the spaces and instructions.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde_json::{Map, Value, json};
+
+use crate::soc::device::endianness::Endianness;
+use crate::soc::isa::ast::{
+    AliasConstraint, AliasSpec, AliasValue, BusDecl, BusRangeDecl, ContextReference, FieldDecl,
+    FieldIndexRange, FormDecl, InstructionDecl, IsaItem, IsaSpecification, MacroDecl, MaskField,
+    MaskSelector, MaskSpec, ParameterDecl, ParameterValue, SpaceAttribute, SpaceDecl, SpaceKind,
+    SpaceMember, SpaceMemberDecl, SubFieldDecl, SubFieldOp,
+};
+use crate::soc::isa::diagnostic::{SourcePosition, SourceSpan};
+use crate::soc::isa::error::IsaError;
+use crate::soc::isa::semantics::{BinaryOperator, SemanticBlock, SemanticExpr};
+use crate::soc::prog::types::{BitFieldSegment, parse_u128_literal};
+
+use super::MachineDescription;
+use super::register::RegisterInfo;
+use super::space::{FieldEncoding, FormInfo, OperandKind, SpaceInfo, parse_bit_spec};
+
+/// Value of the top-level `format` key.
+pub const EXPORT_FORMAT: &str = "nanemu-isa";
+
+/// Bumped whenever a key is removed or changes meaning; new keys do not bump it.
+pub const EXPORT_VERSION: u64 = 1;
+
+impl MachineDescription {
+    /// Exports the machine as a JSON document with the layout described in the module docs.
+    pub fn to_json(&self) -> Value {
+        let parameters: Map<String, Value> = self
+            .parameters
+            .iter()
+            .map(|(name, value)| (name.clone(), parameter_json(value)))
+            .collect();
+        json!({
+            "format": EXPORT_FORMAT,
+            "version": EXPORT_VERSION,
+            "parameters": parameters,
+            "spaces": self.spaces.values().map(|space| self.space_json(space)).collect::<Vec<_>>(),
+            "instructions": self.instructions_json(),
+            "macros": self.macros.iter().map(|mac| json!({
+                "name": mac.name,
+                "parameters": mac.parameters,
+                "semantics": mac.semantics.source,
+            })).collect::<Vec<_>>(),
+            "buses": self.buses.values().map(|bus| json!({
+                "name": bus.name,
+                "address_bits": bus.address_bits,
+                "ranges": bus.ranges.iter().map(|range| json!({
+                    "start": range.start,
+                    "size": range.size,
+                    "space": range.space,
+                    "priority": range.priority,
+                    "space_offset": range.space_offset,
+                })).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    /// Rebuilds a machine from [`MachineDescription::to_json`] output.
+    pub fn from_json(value: &Value) -> Result<Self, IsaError> {
+        let root = Object::new(value, "document")?;
+        let format = root.str("format")?;
+        let version = root.u64("version")?;
+        if format != EXPORT_FORMAT || version != EXPORT_VERSION {
+            return Err(import_error(format!(
+                "expected {EXPORT_FORMAT} version {EXPORT_VERSION}, found {format} version {version}"
+            )));
+        }
+
+        let mut items = Vec::new();
+        for (name, value) in root.map("parameters")? {
+            let value = match value {
+                Value::String(word) => ParameterValue::Word(word.clone()),
+                other => ParameterValue::Number(other.as_u64().ok_or_else(|| {
+                    import_error(format!("parameter '{name}' is not a word or number"))
+                })?),
+            };
+            items.push(IsaItem::Parameter(ParameterDecl {
+                name: name.clone(),
+                value,
+            }));
+        }
+        let mut word_bits = BTreeMap::new();
+        for space in root.array("spaces")? {
+            let space = Object::new(space, "space")?;
+            if let Some(bits) = space.opt_u32("word_bits")? {
+                word_bits.insert(space.str("name")?.to_string(), bits);
+            }
+            import_space(&space, &mut items)?;
+        }
+        for instr in root.array("instructions")? {
+            let instr = Object::new(instr, "instruction")?;
+            items.push(IsaItem::Instruction(import_instruction(
+                &instr, &word_bits,
+            )?));
+        }
+        for mac in root.array("macros")? {
+            let mac = Object::new(mac, "macro")?;
+            items.push(IsaItem::Macro(MacroDecl {
+                name: mac.str("name")?.to_string(),
+                parameters: mac.strings("parameters")?,
+                semantics: SemanticBlock::from_source(mac.str("semantics")?.to_string()),
+                span: import_span(),
+            }));
+        }
+        for bus in root.array("buses")? {
+            let bus = Object::new(bus, "bus")?;
+            let mut ranges = Vec::new();
+            for range in bus.array("ranges")? {
+                let range = Object::new(range, "bus range")?;
+                ranges.push(BusRangeDecl {
+                    start: range.u64("start")?,
+                    size: range.u64("size")?,
+                    space: range.str("space")?.to_string(),
+                    priority: range.u32("priority")?,
+                    space_offset: range.u64("space_offset")?,
+                    span: import_span(),
+                });
+            }
+            items.push(IsaItem::Bus(BusDecl {
+                name: bus.str("name")?.to_string(),
+                address_bits: bus.u32("address_bits")?,
+                ranges,
+                span: import_span(),
+            }));
+        }
+
+        MachineDescription::from_documents(vec![IsaSpecification::new(
+            PathBuf::from(IMPORT_PATH),
+            items,
+        )])
+    }
+
+    fn space_json(&self, space: &SpaceInfo) -> Value {
+        let word_bits = space.size_bits.unwrap_or(0) as u16;
+        let forms: Vec<Value> = space
+            .forms
+            .iter()
+            .map(|(name, form)| form_json(name, form, word_bits))
+            .collect();
+        let registers: Vec<Value> = space
+            .registers
+            .values()
+            .map(|info| self.register_json(space, info))
+            .collect();
+        json!({
+            "name": space.name,
+            "kind": space_kind_name(&space.kind),
+            "word_bits": space.size_bits,
+            "endianness": endianness_name(space.endianness),
+            "enable": space.enable.as_ref().map(enable_json),
+            "forms": forms,
+            "registers": registers,
+        })
+    }
+
+    fn register_json(&self, space: &SpaceInfo, info: &RegisterInfo) -> Value {
+        let schema = self
+            .register_schema()
+            .lookup(&space.name, &info.name)
+            .map(|metadata| {
+                let elements: Vec<&str> = metadata
+                    .elements
+                    .iter()
+                    .map(|element| element.label.as_str())
+                    .collect();
+                let fields: Vec<&str> = metadata
+                    .fields
+                    .iter()
+                    .map(|field| field.name.as_str())
+                    .collect();
+                json!({
+                    "bit_width": metadata.bit_width,
+                    "count": metadata.count,
+                    "byte_order": endianness_name(metadata.byte_order),
+                    "elements": elements,
+                    "fields": fields,
+                })
+            });
+        let range = info
+            .range
+            .as_ref()
+            .map(|range| json!({ "start": range.start, "end": range.end }));
+        json!({
+            "name": info.name,
+            "range": range,
+            "size_bits": info.size_bits,
+            "offset": info.offset,
+            "reset": info.reset.map(reset_json),
+            "description": info.description,
+            "redirect": info.redirect.as_ref().map(|reference| &reference.segments),
+            "display": info.display(),
+            "subfields": info.subfields.iter().map(|sub| json!({
+                "name": sub.name,
+                "bits": sub.bit_spec,
+                "operations": operations_json(&sub.operations),
+                "description": sub.description,
+                "reset": sub.reset.map(reset_json),
+            })).collect::<Vec<_>>(),
+            "schema": schema,
+        })
+    }
+
+    fn instructions_json(&self) -> Vec<Value> {
+        let mut patterns = vec![None; self.instructions.len()];
+        for pattern in &self.patterns {
+            patterns[pattern.instruction_idx] = Some(pattern);
+        }
+        self.instructions
+            .iter()
+            .zip(patterns)
+            .map(|(instr, pattern)| {
+                let word_bits = self
+                    .spaces
+                    .get(&instr.space)
+                    .and_then(|space| space.size_bits)
+                    .unwrap_or(0) as u16;
+                let mask = instr.mask.as_ref().map(|mask| {
+                    mask.fields
+                        .iter()
+                        .map(|field| match &field.selector {
+                            MaskSelector::Field(name) => {
+                                json!({ "field": name, "value": field.value })
+                            }
+                            MaskSelector::BitExpr(bits) => {
+                                json!({ "bits": bits, "value": field.value })
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                });
+                let encoding = instr
+                    .encoding
+                    .as_ref()
+                    .map(|spec| spec.to_spec_string(word_bits));
+                let alias = instr.alias.as_ref().map(|alias| {
+                    let when: Vec<Value> = alias
+                        .constraints
+                        .iter()
+                        .map(|constraint| match &constraint.value {
+                            AliasValue::Number(value) => {
+                                json!({ "field": constraint.field, "value": value })
+                            }
+                            AliasValue::Field(other) => {
+                                json!({ "field": constraint.field, "same": other })
+                            }
+                        })
+                        .collect();
+                    json!({ "base": alias.base, "when": when })
+                });
+                json!({
+                    "space": instr.space,
+                    "name": instr.name,
+                    "form": instr.form,
+                    "description": instr.description,
+                    "operands": instr.operands,
+                    "display": instr.display,
+                    "operator": instr.operator,
+                    "extend": instr.extend,
+                    "alias": alias,
+                    "valid": instr.valid.as_ref().map(enable_json),
+                    "mask": mask,
+                    "encoding": encoding,
+                    "pattern": pattern.map(|pattern| json!({
+                        "mask": pattern.mask,
+                        "value": pattern.value,
+                        "specificity": pattern.specificity,
+                        "operands": pattern.operand_names,
+                        "display": pattern.display,
+                    })),
+                    "semantics": instr.semantics.as_ref().map(|block| &block.source),
+                })
+            })
+            .collect()
+    }
+}
+
+const IMPORT_PATH: &str = "<json>";
+
+fn import_span() -> SourceSpan {
+    SourceSpan::point(PathBuf::from(IMPORT_PATH), SourcePosition::new(1, 1))
+}
+
+fn import_error(message: impl std::fmt::Display) -> IsaError {
+    IsaError::Machine(format!("invalid ISA export: {message}"))
+}
+
+fn parameter_json(value: &ParameterValue) -> Value {
+    match value {
+        ParameterValue::Word(word) => json!(word),
+        ParameterValue::Number(number) => json!(number),
+    }
+}
+
+fn form_json(name: &str, form: &FormInfo, word_bits: u16) -> Value {
+    json!({
+        "name": name,
+        "description": form.description,
+        "display": form.display,
+        "operands": form.operand_order,
+        "fields": form.field_iter().map(|field| field_json(field, word_bits)).collect::<Vec<_>>(),
+    })
+}
+
+fn field_json(field: &FieldEncoding, word_bits: u16) -> Value {
+    let mask = field
+        .spec
+        .segments
+        .iter()
+        .fold(0u64, |acc, segment| match segment {
+            BitFieldSegment::Slice(slice) => acc | slice.mask,
+            BitFieldSegment::Literal { .. } => acc,
+        });
+    json!({
+        "name": field.name,
+        "description": field.description,
+        "bits": field.spec.to_spec_string(word_bits),
+        "mask": mask,
+        "signed": field.spec.is_signed(),
+        "operations": operations_json(&field.operations),
+        "register": field.register.as_ref().map(|binding| json!({
+            "space": binding.space,
+            "field": binding.field,
+        })),
+        "operand_kind": match field.kind {
+            OperandKind::Register => "register",
+            OperandKind::Immediate => "immediate",
+            OperandKind::Other => "other",
+        },
+    })
+}
+
+/// JSON numbers stop at 64 bits, so wider resets are written as hex strings.
+fn reset_json(reset: u128) -> Value {
+    match u64::try_from(reset) {
+        Ok(value) => json!(value),
+        Err(_) => json!(format!("0x{reset:X}")),
+    }
+}
+
+fn operations_json(ops: &[SubFieldOp]) -> Vec<Value> {
+    ops.iter()
+        .map(|op| json!({ "kind": op.kind, "subtype": op.subtype }))
+        .collect()
+}
+
+fn enable_json(expr: &SemanticExpr) -> Value {
+    match expr {
+        SemanticExpr::Literal(value) => json!({ "literal": value }),
+        SemanticExpr::Identifier(name) => json!({ "identifier": name }),
+        SemanticExpr::BitExpr(bits) => json!({ "bits": bits }),
+        SemanticExpr::BinaryOp { op, lhs, rhs } => json!({
+            "op": binary_operator_name(op),
+            "lhs": enable_json(lhs),
+            "rhs": enable_json(rhs),
+        }),
+    }
+}
+
+const BINARY_OPERATORS: [(BinaryOperator, &str); 11] = [
+    (BinaryOperator::Add, "+"),
+    (BinaryOperator::Sub, "-"),
+    (BinaryOperator::And, "&"),
+    (BinaryOperator::Or, "|"),
+    (BinaryOperator::Xor, "^"),
+    (BinaryOperator::Shl, "<<"),
+    (BinaryOperator::Shr, ">>"),
+    (BinaryOperator::Eq, "=="),
+    (BinaryOperator::Ne, "!="),
+    (BinaryOperator::LogicalAnd, "&&"),
+    (BinaryOperator::LogicalOr, "||"),
+];
+
+fn binary_operator_name(op: &BinaryOperator) -> &'static str {
+    BINARY_OPERATORS
+        .iter()
+        .find(|(candidate, _)| std::mem::discriminant(candidate) == std::mem::discriminant(op))
+        .map(|(_, name)| *name)
+        .expect("every operator has a name")
+}
+
+fn space_kind_name(kind: &SpaceKind) -> &'static str {
+    match kind {
+        SpaceKind::ReadWrite => "rw",
+        SpaceKind::ReadOnly => "ro",
+        SpaceKind::MemoryMappedIo => "memio",
+        SpaceKind::Register => "register",
+        SpaceKind::Logic => "logic",
+    }
+}
+
+fn endianness_name(endianness: Endianness) -> &'static str {
+    match endianness {
+        Endianness::Big => "big",
+        Endianness::Little => "little",
+    }
+}
+
+fn import_space(space: &Object<'_>, items: &mut Vec<IsaItem>) -> Result<(), IsaError> {
+    let name = space.str("name")?.to_string();
+    let kind = match space.str("kind")? {
+        "rw" => SpaceKind::ReadWrite,
+        "ro" => SpaceKind::ReadOnly,
+        "memio" => SpaceKind::MemoryMappedIo,
+        "register" => SpaceKind::Register,
+        "logic" => SpaceKind::Logic,
+        other => return Err(import_error(format!("unknown space kind '{other}'"))),
+    };
+    let mut attributes = Vec::new();
+    if let Some(bits) = space.opt_u32("word_bits")? {
+        attributes.push(SpaceAttribute::WordSize(bits));
+    }
+    attributes.push(SpaceAttribute::Endianness(import_endianness(
+        space.str("endianness")?,
+    )?));
+    let enable = match space.get("enable") {
+        None | Some(Value::Null) => None,
+        Some(expr) => Some(import_enable(expr)?),
+    };
+    items.push(IsaItem::Space(SpaceDecl {
+        name: name.clone(),
+        kind,
+        attributes,
+        span: import_span(),
+        enable,
+    }));
+
+    for form in space.array("forms")? {
+        let form = Object::new(form, "form")?;
+        let mut subfields = Vec::new();
+        for field in form.array("fields")? {
+            let field = Object::new(field, "form field")?;
+            subfields.push(SubFieldDecl {
+                name: field.str("name")?.to_string(),
+                bit_spec: field.str("bits")?.to_string(),
+                operations: import_operations(&field)?,
+                description: field.opt_string("description")?,
+                reset: None,
+            });
+        }
+        items.push(member(
+            &name,
+            SpaceMember::Form(FormDecl {
+                space: name.clone(),
+                name: form.str("name")?.to_string(),
+                parent: None,
+                description: form.opt_string("description")?,
+                display: form.opt_string("display")?,
+                subfields,
+                span: import_span(),
+            }),
+        ));
+    }
+
+    for register in space.array("registers")? {
+        let register = Object::new(register, "register")?;
+        let range = match register.get("range") {
+            None | Some(Value::Null) => None,
+            Some(range) => {
+                let range = Object::new(range, "register range")?;
+                Some(FieldIndexRange {
+                    start: range.u32("start")?,
+                    end: range.u32("end")?,
+                })
+            }
+        };
+        let redirect = match register.get("redirect") {
+            None | Some(Value::Null) => None,
+            Some(_) => Some(ContextReference {
+                segments: register.strings("redirect")?,
+            }),
+        };
+        let mut subfields = Vec::new();
+        for sub in register.array("subfields")? {
+            let sub = Object::new(sub, "register subfield")?;
+            subfields.push(SubFieldDecl {
+                name: sub.str("name")?.to_string(),
+                bit_spec: sub.str("bits")?.to_string(),
+                operations: import_operations(&sub)?,
+                description: sub.opt_string("description")?,
+                reset: sub.opt_u128("reset")?,
+            });
+        }
+        items.push(member(
+            &name,
+            SpaceMember::Field(FieldDecl {
+                space: name.clone(),
+                name: register.str("name")?.to_string(),
+                range,
+                offset: register.opt_u64("offset")?,
+                size: register.opt_u32("size_bits")?,
+                reset: register.opt_u128("reset")?,
+                description: register.opt_string("description")?,
+                redirect,
+                subfields,
+                span: import_span(),
+                display: register.opt_string("display")?,
+            }),
+        ));
+    }
+    Ok(())
+}
+
+fn member(space: &str, member: SpaceMember) -> IsaItem {
+    IsaItem::SpaceMember(SpaceMemberDecl {
+        space: space.to_string(),
+        member,
+    })
+}
+
+fn import_instruction(
+    instr: &Object<'_>,
+    word_bits: &BTreeMap<String, u32>,
+) -> Result<InstructionDecl, IsaError> {
+    let space = instr.str("space")?;
+    let mask = match instr.get("mask") {
+        None | Some(Value::Null) => None,
+        Some(_) => {
+            let mut fields = Vec::new();
+            for field in instr.array("mask")? {
+                let field = Object::new(field, "mask field")?;
+                let selector = match field.opt_string("field")? {
+                    Some(name) => MaskSelector::Field(name),
+                    None => MaskSelector::BitExpr(field.str("bits")?.to_string()),
+                };
+                fields.push(MaskField {
+                    selector,
+                    value: field.u64("value")?,
+                });
+            }
+            Some(MaskSpec { fields })
+        }
+    };
+    let alias = match instr.get("alias") {
+        None | Some(Value::Null) => None,
+        Some(value) => {
+            let alias = Object::new(value, "alias")?;
+            let mut constraints = Vec::new();
+            for constraint in alias.array("when")? {
+                let constraint = Object::new(constraint, "alias constraint")?;
+                let value = match constraint.opt_string("same")? {
+                    Some(other) => AliasValue::Field(other),
+                    None => AliasValue::Number(constraint.u64("value")?),
+                };
+                constraints.push(AliasConstraint {
+                    field: constraint.str("field")?.to_string(),
+                    value,
+                });
+            }
+            Some(AliasSpec {
+                base: alias.str("base")?.to_string(),
+                constraints,
+            })
+        }
+    };
+    let valid = match instr.get("valid") {
+        None | Some(Value::Null) => None,
+        Some(expr) => Some(import_enable(expr)?),
+    };
+    let encoding = match instr.opt_string("encoding")? {
+        Some(bits) => {
+            let container = word_bits.get(space).copied().unwrap_or(64);
+            Some(
+                parse_bit_spec(container, &bits)
+                    .map_err(|err| import_error(format!("invalid encoding '{bits}': {err}")))?,
+            )
+        }
+        None => None,
+    };
+    Ok(InstructionDecl {
+        space: space.to_string(),
+        form: instr.opt_string("form")?,
+        name: instr.str("name")?.to_string(),
+        description: instr.opt_string("description")?,
+        operands: instr.strings("operands")?,
+        mask,
+        encoding,
+        semantics: instr
+            .opt_string("semantics")?
+            .map(SemanticBlock::from_source),
+        display: instr.opt_string("display")?,
+        operator: instr.opt_string("operator")?,
+        extend: instr.opt_string("extend")?,
+        alias,
+        valid,
+        span: import_span(),
+    })
+}
+
+fn import_operations(owner: &Object<'_>) -> Result<Vec<SubFieldOp>, IsaError> {
+    let mut ops = Vec::new();
+    for op in owner.array("operations")? {
+        let op = Object::new(op, "operation")?;
+        ops.push(SubFieldOp {
+            kind: op.str("kind")?.to_string(),
+            subtype: op.opt_string("subtype")?,
+        });
+    }
+    Ok(ops)
+}
+
+fn import_enable(value: &Value) -> Result<SemanticExpr, IsaError> {
+    let expr = Object::new(value, "enable expression")?;
+    if let Some(op) = expr.opt_string("op")? {
+        let op = BINARY_OPERATORS
+            .iter()
+            .find(|(_, name)| *name == op)
+            .map(|(operator, _)| operator.clone())
+            .ok_or_else(|| import_error(format!("unknown operator '{op}'")))?;
+        let lhs = expr
+            .get("lhs")
+            .ok_or_else(|| import_error("missing 'lhs'"))?;
+        let rhs = expr
+            .get("rhs")
+            .ok_or_else(|| import_error("missing 'rhs'"))?;
+        return Ok(SemanticExpr::BinaryOp {
+            op,
+            lhs: Box::new(import_enable(lhs)?),
+            rhs: Box::new(import_enable(rhs)?),
+        });
+    }
+    if let Some(bits) = expr.opt_string("bits")? {
+        return Ok(SemanticExpr::BitExpr(bits));
+    }
+    if let Some(name) = expr.opt_string("identifier")? {
+        return Ok(SemanticExpr::Identifier(name));
+    }
+    Ok(SemanticExpr::Literal(expr.u64("literal")?))
+}
+
+fn import_endianness(name: &str) -> Result<Endianness, IsaError> {
+    match name {
+        "big" => Ok(Endianness::Big),
+        "little" => Ok(Endianness::Little),
+        other => Err(import_error(format!("unknown endianness '{other}'"))),
+    }
+}
+
+/// Typed accessors over one JSON object that name the offending key on failure.
+struct Object<'a> {
+    what: &'static str,
+    map: &'a Map<String, Value>,
+}
+
+impl<'a> Object<'a> {
+    fn new(value: &'a Value, what: &'static str) -> Result<Self, IsaError> {
+        value
+            .as_object()
+            .map(|map| Self { what, map })
+            .ok_or_else(|| import_error(format!("{what} is not an object")))
+    }
+
+    fn get(&self, key: &str) -> Option<&'a Value> {
+        self.map.get(key)
+    }
+
+    fn missing(&self, key: &str, expected: &str) -> IsaError {
+        import_error(format!("{} key '{key}' must be {expected}", self.what))
+    }
+
+    fn str(&self, key: &str) -> Result<&'a str, IsaError> {
+        self.get(key)
+            .and_then(Value::as_str)
+            .ok_or_else(|| self.missing(key, "a string"))
+    }
+
+    fn opt_string(&self, key: &str) -> Result<Option<String>, IsaError> {
+        match self.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(text)) => Ok(Some(text.clone())),
+            Some(_) => Err(self.missing(key, "a string or null")),
+        }
+    }
+
+    fn u64(&self, key: &str) -> Result<u64, IsaError> {
+        self.get(key)
+            .and_then(Value::as_u64)
+            .ok_or_else(|| self.missing(key, "an unsigned integer"))
+    }
+
+    fn opt_u64(&self, key: &str) -> Result<Option<u64>, IsaError> {
+        match self.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => value
+                .as_u64()
+                .map(Some)
+                .ok_or_else(|| self.missing(key, "an unsigned integer or null")),
+        }
+    }
+
+    /// Accepts a JSON number, or the hex string [`reset_json`] writes for wider values.
+    fn opt_u128(&self, key: &str) -> Result<Option<u128>, IsaError> {
+        match self.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(text)) => parse_u128_literal(text)
+                .map(Some)
+                .map_err(|_| self.missing(key, "an unsigned integer or null")),
+            Some(value) => value
+                .as_u64()
+                .map(|value| Some(u128::from(value)))
+                .ok_or_else(|| self.missing(key, "an unsigned integer or null")),
+        }
+    }
+
+    fn u32(&self, key: &str) -> Result<u32, IsaError> {
+        u32::try_from(self.u64(key)?).map_err(|_| self.missing(key, "a 32-bit integer"))
+    }
+
+    fn opt_u32(&self, key: &str) -> Result<Option<u32>, IsaError> {
+        self.opt_u64(key)?
+            .map(|value| u32::try_from(value).map_err(|_| self.missing(key, "a 32-bit integer")))
+            .transpose()
+    }
+
+    fn array(&self, key: &str) -> Result<&'a Vec<Value>, IsaError> {
+        self.get(key)
+            .and_then(Value::as_array)
+            .ok_or_else(|| self.missing(key, "an array"))
+    }
+
+    fn map(&self, key: &str) -> Result<&'a Map<String, Value>, IsaError> {
+        self.get(key)
+            .and_then(Value::as_object)
+            .ok_or_else(|| self.missing(key, "an object"))
+    }
+
+    fn strings(&self, key: &str) -> Result<Vec<String>, IsaError> {
+        self.array(key)?
+            .iter()
+            .map(|value| {
+                value
+                    .as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| self.missing(key, "an array of strings"))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::isa::IsaLoader;
+
+    fn e200() -> MachineDescription {
+        let coredef = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("defs/powerpc/e200.coredef");
+        IsaLoader::new().load_machine(coredef).expect("load e200")
+    }
+
+    #[test]
+    fn import_round_trips_the_export() {
+        let machine = e200();
+        let exported = machine.to_json();
+        let imported = MachineDescription::from_json(&exported).expect("import");
+        assert_eq!(imported.to_json(), exported);
+
+        let stream = 0x3800_0000u32.to_be_bytes();
+        assert_eq!(
+            imported.disassemble(&stream)[0].display,
+            machine.disassemble(&stream)[0].display
+        );
+    }
+
+    #[test]
+    fn exports_patterns_fields_and_register_schema() {
+        let exported = e200().to_json();
+        let add = exported["instructions"]
+            .as_array()
+            .expect("instructions")
+            .iter()
+            .find(|instr| instr["name"] == "add")
+            .expect("add exported");
+        assert_eq!(add["form"], "X_Form");
+        assert_eq!(add["mask"][0], json!({ "field": "OPCD", "value": 31 }));
+        let pattern = &add["pattern"];
+        assert_eq!(pattern["value"].as_u64().expect("value") >> 26, 31);
+        assert!(pattern["specificity"].as_u64().expect("specificity") > 0);
+
+        let spaces = exported["spaces"].as_array().expect("spaces");
+        let insn = spaces
+            .iter()
+            .find(|space| space["name"] == "insn")
+            .expect("insn space");
+        let x_form = insn["forms"]
+            .as_array()
+            .expect("forms")
+            .iter()
+            .find(|form| form["name"] == "X_Form")
+            .expect("X_Form");
+        let opcd = &x_form["fields"][0];
+        assert_eq!(opcd["bits"], "@(0..5)");
+        assert_eq!(opcd["mask"], 0xFC00_0000u64);
+
+        let reg = spaces
+            .iter()
+            .find(|space| space["name"] == "reg")
+            .expect("reg space");
+        let gpr = reg["registers"]
+            .as_array()
+            .expect("registers")
+            .iter()
+            .find(|register| register["schema"]["count"] == 32)
+            .expect("a 32-element register file");
+        assert_eq!(gpr["schema"]["elements"].as_array().map(Vec::len), Some(32));
+    }
+
+    #[test]
+    fn rejects_unknown_formats() {
+        let mut exported = e200().to_json();
+        exported["version"] = json!(EXPORT_VERSION + 1);
+        let err = MachineDescription::from_json(&exported).unwrap_err();
+        assert!(err.to_string().contains("version"), "{err}");
+
+        let err = MachineDescription::from_json(&json!({ "format": EXPORT_FORMAT, "version": 1 }))
+            .unwrap_err();
+        assert!(err.to_string().contains("'parameters'"), "{err}");
+    }
+}
@@ -0,0 +1,306 @@
+//! Instruction fetch from absolute addresses. The decoder pulls only the bytes each instruction
+//! needs, so a [`DataHandle`] can feed it across device boundaries and redirects.
+
+use std::fmt;
+
+use crate::soc::system::bus::{BusError, BusResult, DataHandle};
+
+/// Supplies instruction bytes by bus address.
+pub trait InstructionFetch {
+    /// Fills `out` with the bytes starting at `address`.
+    fn fetch(&mut self, address: u64, out: &mut [u8]) -> BusResult<()>;
+}
+
+impl InstructionFetch for DataHandle {
+    /// Re-resolves the address whenever a mapping ends, so one fetch may span several devices.
+    /// The cursor is left after the last byte read.
+    fn fetch(&mut self, address: u64, out: &mut [u8]) -> BusResult<()> {
+        let mut done = 0usize;
+        while done < out.len() {
+            self.address_mut().jump(address + done as u64)?;
+            let chunk = self.address().bytes_to_end().min((out.len() - done) as u64) as usize;
+            self.read(&mut out[done..done + chunk])?;
+            done += chunk;
+        }
+        Ok(())
+    }
+}
+
+impl<F: InstructionFetch + ?Sized> InstructionFetch for &mut F {
+    fn fetch(&mut self, address: u64, out: &mut [u8]) -> BusResult<()> {
+        (**self).fetch(address, out)
+    }
+}
+
+/// Bytes loaded at a fixed base address, for decoding images that are not mapped on a bus.
+#[derive(Debug, Clone, Copy)]
+pub struct ByteImage<'a> {
+    base: u64,
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteImage<'a> {
+    pub fn new(bytes: &'a [u8], base: u64) -> Self {
+        Self { base, bytes }
+    }
+
+    /// First address past the image.
+    pub fn end(&self) -> u64 {
+        self.base + self.bytes.len() as u64
+    }
+}
+
+impl InstructionFetch for ByteImage<'_> {
+    /// Addresses outside the image fault as unmapped.
+    fn fetch(&mut self, address: u64, out: &mut [u8]) -> BusResult<()> {
+        let bytes = address
+            .checked_sub(self.base)
+            .and_then(|offset| usize::try_from(offset).ok())
+            .and_then(|offset| self.bytes.get(offset..offset.checked_add(out.len())?));
+        match bytes {
+            Some(bytes) => {
+                out.copy_from_slice(bytes);
+                Ok(())
+            }
+            None => Err(BusError::NotMapped {
+                address: if address < self.base {
+                    address
+                } else {
+                    self.end()
+                },
+            }),
+        }
+    }
+}
+
+/// Failure to decode the instruction at `address`.
+#[derive(Debug)]
+pub enum DecodeError {
+    /// The bus could not supply the instruction bytes.
+    Fetch { address: u64, source: BusError },
+    /// No root logic space is enabled for the bytes at this address. `bits` is the word of the
+    /// narrowest root space, `size` its length in bytes.
+    NoSpace {
+        address: u64,
+        size: usize,
+        bits: u64,
+    },
+    /// The bytes end before the `needed` an instruction at this address spans.
+    Truncated {
+        address: u64,
+        needed: usize,
+        available: usize,
+    },
+    /// The word decoded in its space but matched no instruction.
+    Undefined {
+        address: u64,
+        size: usize,
+        bits: u64,
+    },
+    /// The word matched `instruction` but fails its `valid=` predicate: a reserved field is
+    /// set or the operands form an illegal (boundedly undefined) combination.
+    Illegal {
+        address: u64,
+        size: usize,
+        bits: u64,
+        instruction: String,
+    },
+}
+
+impl DecodeError {
+    /// Address of the instruction that failed to decode.
+    pub fn address(&self) -> u64 {
+        match self {
+            DecodeError::Fetch { address, .. }
+            | DecodeError::NoSpace { address, .. }
+            | DecodeError::Truncated { address, .. }
+            | DecodeError::Undefined { address, .. }
+            | DecodeError::Illegal { address, .. } => *address,
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Fetch { address, source } => {
+                write!(f, "instruction fetch at 0x{address:016X} failed: {source}")
+            }
+            DecodeError::NoSpace {
+                address,
+                size,
+                bits,
+            } => write!(
+                f,
+                "no logic space is enabled for 0x{bits:0width$X} at 0x{address:016X}",
+                width = size * 2
+            ),
+            DecodeError::Truncated {
+                address,
+                needed,
+                available,
+            } => write!(
+                f,
+                "truncated instruction at 0x{address:016X}: needs {needed} bytes, {available} remain"
+            ),
+            DecodeError::Undefined {
+                address,
+                size,
+                bits,
+            } => write!(
+                f,
+                "undefined instruction 0x{bits:0width$X} at 0x{address:016X}",
+                width = size * 2
+            ),
+            DecodeError::Illegal {
+                address,
+                size,
+                bits,
+                instruction,
+            } => write!(
+                f,
+                "illegal encoding 0x{bits:0width$X} of '{instruction}' at 0x{address:016X}",
+                width = size * 2
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DecodeError::Fetch { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Bytes of the instruction being decoded, addressed from its first byte. Extension only ever
+/// widens the word, so the decoder asks for growing prefixes.
+pub(super) trait WordSource {
+    /// The first `len` bytes, or `None` when the source ends before them.
+    fn prefix(&mut self, len: usize) -> Result<Option<&[u8]>, DecodeError>;
+
+    /// Bytes left before the source ends, if it does.
+    fn remaining(&self) -> Option<usize>;
+}
+
+impl WordSource for &[u8] {
+    fn prefix(&mut self, len: usize) -> Result<Option<&[u8]>, DecodeError> {
+        Ok(self.get(..len))
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+/// Buffers the bytes fetched for one instruction so widening only reads the missing tail.
+pub(super) struct FetchWindow<'f, F: ?Sized> {
+    fetch: &'f mut F,
+    address: u64,
+    bytes: Vec<u8>,
+    end: Option<u64>,
+}
+
+impl<'f, F: InstructionFetch + ?Sized> FetchWindow<'f, F> {
+    pub(super) fn new(fetch: &'f mut F, address: u64) -> Self {
+        Self {
+            fetch,
+            address,
+            bytes: Vec::new(),
+            end: None,
+        }
+    }
+
+    /// Treats `end` as the end of the source, so words crossing it are never fetched.
+    pub(super) fn until(mut self, end: Option<u64>) -> Self {
+        self.end = end;
+        self
+    }
+}
+
+impl<F: InstructionFetch + ?Sized> WordSource for FetchWindow<'_, F> {
+    fn prefix(&mut self, len: usize) -> Result<Option<&[u8]>, DecodeError> {
+        if self.remaining().is_some_and(|remaining| remaining < len) {
+            return Ok(None);
+        }
+        let have = self.bytes.len();
+        if have < len {
+            self.bytes.resize(len, 0);
+            if let Err(source) = self
+                .fetch
+                .fetch(self.address + have as u64, &mut self.bytes[have..])
+            {
+                self.bytes.truncate(have);
+                return Err(DecodeError::Fetch {
+                    address: self.address,
+                    source,
+                });
+            }
+        }
+        Ok(Some(&self.bytes[..len]))
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        self.end
+            .map(|end| usize::try_from(end.saturating_sub(self.address)).unwrap_or(usize::MAX))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::soc::device::{BasicMemory, Device, Endianness};
+    use crate::soc::system::bus::DeviceBus;
+    use std::sync::Arc;
+
+    #[test]
+    fn data_handle_fetch_spans_adjacent_devices() {
+        let bus = Arc::new(DeviceBus::new(12));
+        let low = Arc::new(BasicMemory::new("low", 0x10, Endianness::Big));
+        let high = Arc::new(BasicMemory::new("high", 0x10, Endianness::Big));
+        bus.register_device(low.clone(), 0x100).unwrap();
+        bus.register_device(high.clone(), 0x110).unwrap();
+        low.write(0xE, &[0x11, 0x22]).unwrap();
+        high.write(0, &[0x33, 0x44]).unwrap();
+
+        let mut handle = DataHandle::new(bus);
+        let mut out = [0u8; 4];
+        handle.fetch(0x10E, &mut out).expect("fetch across devices");
+        assert_eq!(out, [0x11, 0x22, 0x33, 0x44]);
+        assert_eq!(handle.address().bus_address(), Some(0x112));
+    }
+
+    #[test]
+    fn fetch_window_reports_the_instruction_address_on_faults() {
+        let bus = Arc::new(DeviceBus::new(12));
+        let ram = Arc::new(BasicMemory::new("ram", 0x10, Endianness::Big));
+        bus.register_device(ram, 0x100).unwrap();
+
+        let mut handle = DataHandle::new(bus);
+        let mut window = FetchWindow::new(&mut handle, 0x10E);
+        assert_eq!(window.prefix(2).unwrap(), Some(&[0u8, 0][..]));
+        let err = window.prefix(4).expect_err("tail is unmapped");
+        let DecodeError::Fetch { address, source } = err else {
+            panic!("expected a fetch error, got {err}");
+        };
+        assert_eq!(address, 0x10E);
+        assert!(matches!(source, BusError::NotMapped { address: 0x110 }));
+    }
+
+    #[test]
+    fn byte_image_window_ends_with_the_image() {
+        let mut image = ByteImage::new(&[0x12, 0x34, 0x56], 0x200);
+        let mut window = FetchWindow::new(&mut image, 0x201).until(Some(0x203));
+        assert_eq!(window.prefix(2).unwrap(), Some(&[0x34u8, 0x56][..]));
+        assert_eq!(window.prefix(4).unwrap(), None);
+        assert_eq!(window.remaining(), Some(2));
+
+        let mut out = [0u8; 2];
+        let err = image
+            .fetch(0x202, &mut out)
+            .expect_err("runs past the image");
+        assert!(matches!(err, BusError::NotMapped { address: 0x203 }));
+    }
+}
@@ -0,0 +1,636 @@
+//! Control-flow effect of a decoded instruction, worked out by evaluating its semantics over
+//! abstract values: reading `PC` yields the instruction address, operands are their decoded
+//! values, and registers the program has not written are unknown.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use crate::soc::isa::ast::ParameterValue;
+use crate::soc::isa::semantics::expression::{shift_left, shift_right};
+use crate::soc::isa::semantics::program::{
+    AssignTarget, ContextCall, ContextKind, Expr, ExprBinaryOp, ExprUnaryOp, SemanticProgram,
+    SemanticStmt,
+};
+use crate::soc::isa::semantics::value::Conversion;
+
+use super::MachineDescription;
+use super::disassembly::DecodedInstruction;
+use super::instruction::InstructionPattern;
+
+/// Candidate values tracked per expression before it is treated as unknown.
+const MAX_CANDIDATES: usize = 16;
+/// Nesting limit for `$macro`/`$insn` calls.
+const MAX_DEPTH: usize = 16;
+/// Iterations of a `for` loop with known bounds that are evaluated one by one; longer loops
+/// run their body once with an unknown loop variable.
+const MAX_UNROLL: u64 = 64;
+
+/// How an instruction leaves straight-line execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowKind {
+    /// `PC` is not redirected.
+    Sequential,
+    /// `PC` is written.
+    Jump,
+    /// `PC` is written and `LR` receives the return address.
+    Call,
+    /// `PC` is loaded from `LR`.
+    Return,
+}
+
+/// Control-flow effect of one instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControlFlow {
+    pub kind: FlowKind,
+    /// Statically resolved destinations, excluding the fall-through address.
+    pub targets: Vec<u64>,
+    /// `PC` may also receive a value only known at run time, such as `CTR`.
+    pub indirect: bool,
+    /// The instruction may continue with the next one instead of branching.
+    pub conditional: bool,
+    /// Registers read by the branch condition, e.g. `CR0` or `CTR`.
+    pub condition: Vec<String>,
+}
+
+impl ControlFlow {
+    pub fn sequential() -> Self {
+        Self {
+            kind: FlowKind::Sequential,
+            targets: Vec::new(),
+            indirect: false,
+            conditional: false,
+            condition: Vec::new(),
+        }
+    }
+
+    /// Whether execution can reach the next instruction, for calls once the callee returns.
+    pub fn falls_through(&self) -> bool {
+        self.conditional || matches!(self.kind, FlowKind::Sequential | FlowKind::Call)
+    }
+}
+
+impl MachineDescription {
+    /// Control-flow effect of `decoded`, derived from what its semantics write to `PC` and
+    /// `LR`. `$host::select` arms, `?:` arms and `if` branches give the candidate values; a
+    /// candidate equal to the next address, or an `if` that leaves `PC` alone on one branch,
+    /// makes the branch conditional. Instructions without semantics are sequential.
+    pub fn control_flow(&self, decoded: &DecodedInstruction<'_>) -> ControlFlow {
+        self.pattern_flow(
+            decoded.pattern(),
+            decoded.bits(),
+            decoded.address(),
+            decoded.size(),
+        )
+    }
+
+    /// [`Self::control_flow`] of the `size`-byte word `bits` at `address` matching `pattern`.
+    pub(super) fn pattern_flow(
+        &self,
+        pattern: &InstructionPattern,
+        bits: u64,
+        address: u64,
+        size: usize,
+    ) -> ControlFlow {
+        let instruction = &self.instructions[pattern.instruction_idx];
+        if !instruction.effects.control_flow {
+            return ControlFlow::sequential();
+        }
+        let Some(program) = instruction
+            .semantics
+            .as_ref()
+            .and_then(|block| block.ensure_program().ok())
+        else {
+            return ControlFlow::sequential();
+        };
+
+        let mut scope = self.parameter_scope();
+        let form = pattern
+            .form
+            .as_ref()
+            .and_then(|form| self.spaces.get(&pattern.space)?.forms.get(form));
+        for field in form.into_iter().flat_map(|form| form.field_iter()) {
+            let value = field.spec.read_signed(bits) as u64;
+            scope.insert(field.name.clone(), Value::constant(value));
+        }
+
+        let mut evaluator = FlowEvaluator {
+            machine: self,
+            address,
+            next: address + size as u64,
+            registers: HashMap::new(),
+            depth: 0,
+        };
+        evaluator.run(program, &mut scope);
+        evaluator.effect()
+    }
+
+    fn parameter_scope(&self) -> HashMap<String, Value> {
+        self.parameters
+            .iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    ParameterValue::Number(number) => Value::constant(*number),
+                    ParameterValue::Word(_) => Value::unknown(Opaque::Register, BTreeSet::new()),
+                };
+                (name.clone(), value)
+            })
+            .collect()
+    }
+}
+
+/// Origin of a value the evaluator cannot resolve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Opaque {
+    Register,
+    Link,
+}
+
+/// Set of values an expression may take.
+#[derive(Debug, Clone, PartialEq)]
+struct Value {
+    known: Vec<u64>,
+    opaque: Option<Opaque>,
+    /// Registers the value was computed from.
+    reads: BTreeSet<String>,
+    /// Registers that choose between the candidates.
+    condition: BTreeSet<String>,
+}
+
+impl Value {
+    fn constant(value: u64) -> Self {
+        Self {
+            known: vec![value],
+            opaque: None,
+            reads: BTreeSet::new(),
+            condition: BTreeSet::new(),
+        }
+    }
+
+    fn unknown(origin: Opaque, reads: BTreeSet<String>) -> Self {
+        Self {
+            known: Vec::new(),
+            opaque: Some(origin),
+            reads,
+            condition: BTreeSet::new(),
+        }
+    }
+
+    fn single(&self) -> Option<u64> {
+        match self.known.as_slice() {
+            [value] if self.opaque.is_none() => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Applies `op` to every pair of candidates.
+    fn combine(&self, other: &Value, op: impl Fn(u64, u64) -> u64) -> Value {
+        let known = self
+            .known
+            .iter()
+            .flat_map(|lhs| other.known.iter().map(|rhs| op(*lhs, *rhs)))
+            .collect();
+        self.merged(other, known, self.opaque.max(other.opaque))
+    }
+
+    /// Either value, as chosen by a condition computed from `condition`.
+    fn union(&self, other: &Value, condition: &Value) -> Value {
+        let known = self.known.iter().chain(&other.known).copied().collect();
+        let mut value = self.merged(other, known, self.opaque.max(other.opaque));
+        value.condition.extend(condition.reads.iter().cloned());
+        value.condition.extend(condition.condition.iter().cloned());
+        value.reads.extend(condition.reads.iter().cloned());
+        value
+    }
+
+    fn merged(&self, other: &Value, mut known: Vec<u64>, mut opaque: Option<Opaque>) -> Value {
+        known.sort_unstable();
+        known.dedup();
+        if known.len() > MAX_CANDIDATES {
+            known.clear();
+            opaque = opaque.max(Some(Opaque::Register));
+        }
+        Value {
+            known,
+            opaque,
+            reads: self.reads.union(&other.reads).cloned().collect(),
+            condition: self.condition.union(&other.condition).cloned().collect(),
+        }
+    }
+
+    fn map(&self, op: impl Fn(u64) -> u64) -> Value {
+        let mut value = self.clone();
+        value.known = self.known.iter().map(|value| op(*value)).collect();
+        value.known.sort_unstable();
+        value.known.dedup();
+        value
+    }
+}
+
+struct FlowEvaluator<'m> {
+    machine: &'m MachineDescription,
+    address: u64,
+    /// Address of the next instruction, which `PC` holds when no branch is taken.
+    next: u64,
+    /// Registers written so far, keyed as `NAME`, `NAME(3)` or `NAME::FIELD`.
+    registers: HashMap<String, Value>,
+    depth: usize,
+}
+
+impl FlowEvaluator<'_> {
+    fn effect(&self) -> ControlFlow {
+        let next = self.next;
+        let Some(pc) = self.registers.get("PC") else {
+            return ControlFlow::sequential();
+        };
+        let links = self.registers.contains_key("LR");
+        let targets: Vec<u64> = pc.known.iter().copied().filter(|t| *t != next).collect();
+        let returns = pc.opaque == Some(Opaque::Link) && !links;
+        let indirect = pc.opaque == Some(Opaque::Register) || (links && pc.opaque.is_some());
+        if targets.is_empty() && !returns && !indirect {
+            return ControlFlow::sequential();
+        }
+        ControlFlow {
+            kind: if links {
+                FlowKind::Call
+            } else if returns {
+                FlowKind::Return
+            } else {
+                FlowKind::Jump
+            },
+            targets,
+            indirect,
+            conditional: pc.known.contains(&next),
+            condition: pc.condition.iter().cloned().collect(),
+        }
+    }
+
+    fn run(&mut self, program: &SemanticProgram, scope: &mut HashMap<String, Value>) -> Value {
+        self.run_block(&program.statements, scope)
+            .unwrap_or_else(|| Value::unknown(Opaque::Register, BTreeSet::new()))
+    }
+
+    /// Evaluates `statements`, returning the value of a `return` that ends them.
+    fn run_block(
+        &mut self,
+        statements: &[SemanticStmt],
+        scope: &mut HashMap<String, Value>,
+    ) -> Option<Value> {
+        for stmt in statements {
+            let returned = match stmt {
+                SemanticStmt::Assign { target, expr } => {
+                    let value = self.eval(expr, scope);
+                    self.assign(target, value, scope);
+                    None
+                }
+                SemanticStmt::Expr(expr) => {
+                    self.eval(expr, scope);
+                    None
+                }
+                SemanticStmt::Return(expr) => Some(self.eval(expr, scope)),
+                SemanticStmt::If {
+                    condition,
+                    then,
+                    otherwise,
+                } => {
+                    let condition = self.eval(condition, scope);
+                    match condition.single() {
+                        Some(0) => self.run_block(otherwise, scope),
+                        Some(_) => self.run_block(then, scope),
+                        None => self.run_branches(&condition, then, otherwise, scope),
+                    }
+                }
+                SemanticStmt::For {
+                    var,
+                    start,
+                    end,
+                    body,
+                } => {
+                    let first = self.eval(start, scope);
+                    let last = self.eval(end, scope);
+                    self.run_loop(var, &first, &last, body, scope)
+                }
+            };
+            if returned.is_some() {
+                return returned;
+            }
+        }
+        None
+    }
+
+    /// Evaluates both arms of an `if` whose condition is unknown and merges what they leave
+    /// behind, so a `PC` written on one arm only becomes a conditional branch.
+    fn run_branches(
+        &mut self,
+        condition: &Value,
+        then: &[SemanticStmt],
+        otherwise: &[SemanticStmt],
+        scope: &mut HashMap<String, Value>,
+    ) -> Option<Value> {
+        let before = self.registers.clone();
+        let mut then_scope = scope.clone();
+        let then_returned = self.run_block(then, &mut then_scope);
+        let then_registers = std::mem::replace(&mut self.registers, before);
+        let else_returned = self.run_block(otherwise, scope);
+
+        let keys: BTreeSet<String> = then_registers
+            .keys()
+            .chain(self.registers.keys())
+            .cloned()
+            .collect();
+        for key in keys {
+            let taken = then_registers
+                .get(&key)
+                .cloned()
+                .unwrap_or_else(|| self.unwritten(&key));
+            let skipped = self
+                .registers
+                .get(&key)
+                .cloned()
+                .unwrap_or_else(|| self.unwritten(&key));
+            if taken != skipped {
+                self.registers.insert(key, taken.union(&skipped, condition));
+            }
+        }
+        for (name, taken) in then_scope {
+            match scope.get(&name) {
+                Some(skipped) if *skipped == taken => {}
+                Some(skipped) => {
+                    let merged = taken.union(skipped, condition);
+                    scope.insert(name, merged);
+                }
+                None => {
+                    scope.insert(name, taken);
+                }
+            }
+        }
+        match (then_returned, else_returned) {
+            (Some(taken), Some(skipped)) => Some(taken.union(&skipped, condition)),
+            (taken, skipped) => taken.or(skipped),
+        }
+    }
+
+    fn run_loop(
+        &mut self,
+        var: &str,
+        first: &Value,
+        last: &Value,
+        body: &[SemanticStmt],
+        scope: &mut HashMap<String, Value>,
+    ) -> Option<Value> {
+        if let (Some(first), Some(last)) = (first.single(), last.single()) {
+            let (first, last) = (first as i64, last as i64);
+            if first.abs_diff(last) < MAX_UNROLL {
+                let step = if last < first { -1 } else { 1 };
+                let mut index = first;
+                loop {
+                    scope.insert(var.to_string(), Value::constant(index as u64));
+                    let returned = self.run_block(body, scope);
+                    if returned.is_some() || index == last {
+                        return returned;
+                    }
+                    index += step;
+                }
+            }
+        }
+        let reads = first.reads.union(&last.reads).cloned().collect();
+        scope.insert(var.to_string(), Value::unknown(Opaque::Register, reads));
+        self.run_block(body, scope)
+    }
+
+    /// Value register `key` holds on a path that does not write it.
+    fn unwritten(&self, key: &str) -> Value {
+        match key {
+            "PC" => Value::constant(self.next),
+            "LR" => Value::unknown(Opaque::Link, BTreeSet::from([key.to_string()])),
+            _ => Value::unknown(Opaque::Register, BTreeSet::from([key.to_string()])),
+        }
+    }
+
+    fn assign(&mut self, target: &AssignTarget, value: Value, scope: &mut HashMap<String, Value>) {
+        match target {
+            AssignTarget::Variable(name) => {
+                scope.insert(name.clone(), value);
+            }
+            AssignTarget::Tuple(names) => {
+                let item = Value::unknown(Opaque::Register, value.reads);
+                for name in names {
+                    scope.insert(name.clone(), item.clone());
+                }
+            }
+            AssignTarget::Register(register) => {
+                let index = register.index.as_ref().map(|expr| self.eval(expr, scope));
+                let key = register_key(&register.name, index.as_ref());
+                let key = match &register.subfield {
+                    Some(field) => format!("{key}::{}", field.to_ascii_uppercase()),
+                    None => key,
+                };
+                self.registers.insert(key, value);
+            }
+        }
+    }
+
+    fn eval(&mut self, expr: &Expr, scope: &mut HashMap<String, Value>) -> Value {
+        match expr {
+            Expr::Number(value) => Value::constant(*value),
+            Expr::Variable { name, .. } | Expr::Parameter { name, .. } => scope
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| Value::unknown(Opaque::Register, BTreeSet::new())),
+            Expr::Call(call) => self.eval_call(call, scope),
+            Expr::Tuple(items) => {
+                let mut reads = BTreeSet::new();
+                for item in items {
+                    reads.extend(self.eval(item, scope).reads);
+                }
+                Value::unknown(Opaque::Register, reads)
+            }
+            Expr::BinaryOp { op, lhs, rhs } => {
+                let lhs = self.eval(lhs, scope);
+                // `||` and `&&` short-circuit, so a decided left side hides the right one.
+                match (op, lhs.single()) {
+                    (ExprBinaryOp::LogicalOr, Some(value)) if value != 0 => return lhs.map(|_| 1),
+                    (ExprBinaryOp::LogicalAnd, Some(0)) => return lhs,
+                    _ => {}
+                }
+                let rhs = self.eval(rhs, scope);
+                lhs.combine(&rhs, binary(*op))
+            }
+            Expr::BitSlice { expr, slice } => {
+                let width = slice.end.saturating_sub(slice.start) + 1;
+                let mask = if width >= 64 {
+                    u64::MAX
+                } else {
+                    (1u64 << width) - 1
+                };
+                let start = slice.start.min(63);
+                self.eval(expr, scope).map(|value| (value >> start) & mask)
+            }
+            Expr::UnaryOp { op, expr } => {
+                let value = self.eval(expr, scope);
+                match op {
+                    ExprUnaryOp::Not => value.map(|value| u64::from(value == 0)),
+                    ExprUnaryOp::BitNot => value.map(|value| !value),
+                    ExprUnaryOp::Neg => value.map(u64::wrapping_neg),
+                }
+            }
+            Expr::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let condition = self.eval(condition, scope);
+                match condition.single() {
+                    Some(0) => self.eval(otherwise, scope),
+                    Some(_) => self.eval(then, scope),
+                    None => {
+                        let then = self.eval(then, scope);
+                        let otherwise = self.eval(otherwise, scope);
+                        then.union(&otherwise, &condition)
+                    }
+                }
+            }
+        }
+    }
+
+    fn eval_call(&mut self, call: &ContextCall, scope: &mut HashMap<String, Value>) -> Value {
+        let args: Vec<Value> = call.args.iter().map(|arg| self.eval(arg, scope)).collect();
+        match call.kind {
+            ContextKind::Register => self.read_register(call, args.first()),
+            ContextKind::Host if call.name == "select" && args.len() == 3 => {
+                match args[0].single() {
+                    Some(0) => args[2].clone(),
+                    Some(_) => args[1].clone(),
+                    None => args[1].union(&args[2], &args[0]),
+                }
+            }
+            ContextKind::Macro => {
+                let program = self
+                    .machine
+                    .macros
+                    .iter()
+                    .find(|info| info.name == call.name)
+                    .and_then(|info| {
+                        let program = info.semantics.ensure_program().ok()?;
+                        Some((info.parameters.clone(), program))
+                    });
+                self.invoke(program, args)
+            }
+            ContextKind::Instruction => {
+                let program = self
+                    .machine
+                    .instructions
+                    .iter()
+                    .find(|instr| instr.name == call.name)
+                    .and_then(|instr| {
+                        let program = instr.semantics.as_ref()?.ensure_program().ok()?;
+                        let operands = if instr.operands.is_empty() {
+                            let form = instr.form.as_ref()?;
+                            let space = self.machine.spaces.get(&instr.space)?;
+                            space.forms.get(form)?.operand_order.clone()
+                        } else {
+                            instr.operands.clone()
+                        };
+                        Some((operands, program))
+                    });
+                self.invoke(program, args)
+            }
+            ContextKind::Cast => match (Conversion::parse(&call.name), args.as_slice()) {
+                (Some(Conversion::Integer { width, signed }), [value]) if width <= 64 => {
+                    let shift = 64 - width;
+                    value.map(|value| {
+                        if signed {
+                            (((value << shift) as i64) >> shift) as u64
+                        } else {
+                            (value << shift) >> shift
+                        }
+                    })
+                }
+                _ => {
+                    let reads = args.into_iter().flat_map(|arg| arg.reads).collect();
+                    Value::unknown(Opaque::Register, reads)
+                }
+            },
+            ContextKind::Host | ContextKind::Memory => {
+                let reads = args.into_iter().flat_map(|arg| arg.reads).collect();
+                Value::unknown(Opaque::Register, reads)
+            }
+        }
+    }
+
+    fn read_register(&self, call: &ContextCall, index: Option<&Value>) -> Value {
+        let key = register_key(&call.name, index);
+        let full = match call.subpath.first() {
+            Some(field) => format!("{key}::{}", field.to_ascii_uppercase()),
+            None => key.clone(),
+        };
+        if let Some(value) = self.registers.get(&full) {
+            return value.clone();
+        }
+        if full == "PC" {
+            return Value::constant(self.address);
+        }
+        let origin = if full == "LR" {
+            Opaque::Link
+        } else {
+            Opaque::Register
+        };
+        Value::unknown(origin, BTreeSet::from([key]))
+    }
+
+    fn invoke(
+        &mut self,
+        program: Option<(Vec<String>, &Arc<SemanticProgram>)>,
+        args: Vec<Value>,
+    ) -> Value {
+        let Some((names, program)) = program.filter(|_| self.depth < MAX_DEPTH) else {
+            let reads = args.into_iter().flat_map(|arg| arg.reads).collect();
+            return Value::unknown(Opaque::Register, reads);
+        };
+        let mut scope = self.machine.parameter_scope();
+        scope.extend(names.into_iter().zip(args));
+        self.depth += 1;
+        let value = self.run(program, &mut scope);
+        self.depth -= 1;
+        value
+    }
+}
+
+/// `NAME` or `NAME(3)`; an index that is not a single known value stays symbolic.
+fn register_key(name: &str, index: Option<&Value>) -> String {
+    let name = name.to_ascii_uppercase();
+    match index.map(Value::single) {
+        None => name,
+        Some(Some(index)) => format!("{name}({index})"),
+        Some(None) => format!("{name}(?)"),
+    }
+}
+
+fn binary(op: ExprBinaryOp) -> fn(u64, u64) -> u64 {
+    match op {
+        ExprBinaryOp::LogicalOr => |l, r| u64::from(l != 0 || r != 0),
+        ExprBinaryOp::LogicalAnd => |l, r| u64::from(l != 0 && r != 0),
+        ExprBinaryOp::BitOr => |l, r| l | r,
+        ExprBinaryOp::BitXor => |l, r| l ^ r,
+        ExprBinaryOp::BitAnd => |l, r| l & r,
+        ExprBinaryOp::Eq => |l, r| u64::from(l == r),
+        ExprBinaryOp::Ne => |l, r| u64::from(l != r),
+        ExprBinaryOp::Lt => |l, r| u64::from((l as i64) < (r as i64)),
+        ExprBinaryOp::Gt => |l, r| u64::from((l as i64) > (r as i64)),
+        ExprBinaryOp::Le => |l, r| u64::from((l as i64) <= (r as i64)),
+        ExprBinaryOp::Ge => |l, r| u64::from((l as i64) >= (r as i64)),
+        ExprBinaryOp::Add => |l, r| l.wrapping_add(r),
+        ExprBinaryOp::Sub => |l, r| l.wrapping_sub(r),
+        ExprBinaryOp::Mul => |l, r| l.wrapping_mul(r),
+        ExprBinaryOp::Div => |l, r| match r {
+            0 => 0,
+            r => (l as i64).wrapping_div(r as i64) as u64,
+        },
+        ExprBinaryOp::Rem => |l, r| match r {
+            0 => 0,
+            r => (l as i64).wrapping_rem(r as i64) as u64,
+        },
+        ExprBinaryOp::Shl => |l, r| shift_left(l, r as i64),
+        ExprBinaryOp::Shr => |l, r| shift_right(l, r as i64),
+        ExprBinaryOp::Sar => |l, r| ((l as i64) >> (r as i64).clamp(0, 63)) as u64,
+    }
+}
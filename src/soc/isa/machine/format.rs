@@ -5,49 +5,82 @@ use std::str::Chars;
 
 use super::MachineDescription;
 use super::instruction::InstructionPattern;
-use super::space::{FieldEncoding, FormInfo, OperandKind};
+use super::operand::{OperandValue, RegisterRef, field_value};
+use super::space::FormInfo;
 
-pub(super) fn format_operand(
+pub(super) fn format_value(machine: &MachineDescription, value: &OperandValue) -> String {
+    format_value_with(machine, value, &plain_target)
+}
+
+/// Like [`format_value`], with resolved targets rendered by `target`.
+pub(super) fn format_value_with(
     machine: &MachineDescription,
-    field: &FieldEncoding,
-    value: u64,
+    value: &OperandValue,
+    target: &dyn Fn(u64) -> String,
 ) -> String {
-    if let Some(binding) = &field.register {
-        if let Some(space) = machine.spaces.get(&binding.space) {
-            if let Some(register) = space.registers.get(&binding.field) {
-                return register.format(value);
-            }
-        }
-        return format!("{}{}", binding.field, value);
-    }
-
-    if field.kind == OperandKind::Immediate {
-        return format_immediate(field, value);
+    match value {
+        OperandValue::Register(register) => format_register(machine, register),
+        OperandValue::Signed { value, width } => format_immediate(*value as u64, *width),
+        OperandValue::Unsigned { value, width } => format_immediate(*value, *width),
+        OperandValue::Target { address } => target(*address),
+        OperandValue::Memory {
+            base,
+            displacement,
+            width,
+        } => format!(
+            "{}({})",
+            format_immediate(*displacement as u64, *width),
+            format_register(machine, base)
+        ),
+        OperandValue::Field(value) => format!("{value}"),
     }
+}
 
-    if field
-        .operations
-        .iter()
-        .any(|op| op.kind.eq_ignore_ascii_case("reg"))
-    {
-        return format!("r{value}");
-    }
+fn plain_target(address: u64) -> String {
+    format!("0x{address:X}")
+}
 
-    format!("{value}")
+fn format_register(machine: &MachineDescription, register: &RegisterRef) -> String {
+    machine
+        .spaces
+        .get(&register.space)
+        .and_then(|space| space.registers.get(&register.file))
+        .map(|info| info.format(register.index))
+        .unwrap_or_else(|| format!("{}{}", register.file, register.index))
 }
 
 pub(super) fn render_display(
     machine: &MachineDescription,
     pattern: &InstructionPattern,
     bits: u64,
-    operands: &[String],
+    address: u64,
+) -> Option<String> {
+    render_display_with(machine, pattern, bits, address, &plain_target)
+}
+
+/// Like [`render_display`], with resolved targets rendered by `target`.
+pub(super) fn render_display_with(
+    machine: &MachineDescription,
+    pattern: &InstructionPattern,
+    bits: u64,
+    address: u64,
+    target: &dyn Fn(u64) -> String,
 ) -> Option<String> {
     let template = pattern.display.as_ref()?;
     let form_name = pattern.form.as_ref()?;
     let space = machine.spaces.get(&pattern.space)?;
     let form = space.forms.get(form_name)?;
 
-    Some(DisplayRenderer::new(template, machine, form, pattern, bits, operands).render())
+    let renderer = DisplayRenderer {
+        machine,
+        form,
+        pattern,
+        bits,
+        address,
+        template,
+        target,
+    };
+    Some(renderer.render())
 }
 
 pub(super) fn default_display_template(
@@ -61,11 +94,8 @@ pub(super) fn default_display_template(
     Some(parts.join(", "))
 }
 
-fn format_immediate(field: &FieldEncoding, value: u64) -> String {
-    let mut bits = u32::from(field.spec.data_width());
-    if bits == 0 {
-        bits = 1;
-    }
+fn format_immediate(value: u64, width: u16) -> String {
+    let bits = u32::from(width.max(1));
     let digits = ((bits as usize) + 3) / 4;
     let truncated = if bits >= 64 {
         value
@@ -81,29 +111,12 @@ struct DisplayRenderer<'a> {
     form: &'a FormInfo,
     pattern: &'a InstructionPattern,
     bits: u64,
-    operands: &'a [String],
+    address: u64,
     template: &'a str,
+    target: &'a dyn Fn(u64) -> String,
 }
 
-impl<'a> DisplayRenderer<'a> {
-    fn new(
-        template: &'a str,
-        machine: &'a MachineDescription,
-        form: &'a FormInfo,
-        pattern: &'a InstructionPattern,
-        bits: u64,
-        operands: &'a [String],
-    ) -> Self {
-        Self {
-            machine,
-            form,
-            pattern,
-            bits,
-            operands,
-            template,
-        }
-    }
-
+impl DisplayRenderer<'_> {
     fn render(&self) -> String {
         let mut result = String::with_capacity(self.template.len());
         let mut chars = self.template.chars().peekable();
@@ -149,20 +162,11 @@ impl<'a> DisplayRenderer<'a> {
         if token.eq_ignore_ascii_case("op") {
             return self.pattern.operator.as_ref().cloned();
         }
-        if let Some(value) = self.operand_value(token) {
-            return Some(value.to_string());
-        }
         let field = self.form.subfield(token)?;
-        let (value, _) = field.spec.read_bits(self.bits);
-        Some(format_operand(self.machine, field, value))
-    }
-
-    fn operand_value(&self, token: &str) -> Option<&str> {
-        self.pattern
-            .operand_names
-            .iter()
-            .zip(self.operands.iter())
-            .find(|(name, _)| name.as_str() == token)
-            .map(|(_, value)| value.as_str())
+        Some(format_value_with(
+            self.machine,
+            &field_value(self.form, field, self.bits, self.address),
+            self.target,
+        ))
     }
 }
@@ -1,8 +1,9 @@
 //! Instruction metadata and derived pattern descriptions used for decoding
 //! machine words back into structured operations.
 
-use crate::soc::isa::ast::InstructionDecl;
-use crate::soc::isa::semantics::SemanticBlock;
+use crate::soc::isa::ast::{AliasSpec, InstructionDecl};
+use crate::soc::isa::diagnostic::SourceSpan;
+use crate::soc::isa::semantics::{SemanticBlock, SemanticEffects, SemanticExpr};
 use crate::soc::prog::types::BitFieldSpec;
 
 use super::format::default_display_template;
@@ -16,9 +17,19 @@ pub struct Instruction {
     pub operands: Vec<String>,
     pub display: Option<String>,
     pub operator: Option<String>,
+    /// Logic space that decodes the wider word when this entry matches.
+    pub extend: Option<String>,
+    /// Base instruction this entry is a simplified mnemonic of; aliases never decode directly.
+    pub alias: Option<AliasSpec>,
+    /// Predicate legal encodings satisfy; matching words that fail it are illegal.
+    pub valid: Option<SemanticExpr>,
     pub mask: Option<InstructionMask>,
     pub encoding: Option<BitFieldSpec>,
     pub semantics: Option<SemanticBlock>,
+    /// Registers the semantics read and write, filled in when the machine is built.
+    pub effects: SemanticEffects,
+    /// Declaration the instruction was built from.
+    pub span: SourceSpan,
 }
 
 impl Instruction {
@@ -31,11 +42,16 @@ impl Instruction {
             operands: decl.operands,
             display: decl.display,
             operator: decl.operator,
+            extend: decl.extend,
+            alias: decl.alias,
+            valid: decl.valid,
             mask: decl.mask.map(|mask| InstructionMask {
                 fields: mask.fields,
             }),
             encoding: decl.encoding,
             semantics: decl.semantics,
+            effects: SemanticEffects::default(),
+            span: decl.span,
         }
     }
 }
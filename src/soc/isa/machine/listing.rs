@@ -0,0 +1,450 @@
+//! Objdump-style listings annotated from a program [`SymbolTable`]: function labels,
+//! `label+offset` targets, inbound cross-references, and typed data for object ranges.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::soc::prog::symbols::{SymbolKind, SymbolRecord, SymbolTable, SymbolWalker, ValueKind};
+
+use super::MachineDescription;
+use super::disassembly::{Disassembly, decode_word};
+use super::format;
+use super::instruction::InstructionPattern;
+use super::operand::OperandValue;
+use super::syntax::Syntax;
+
+impl MachineDescription {
+    /// Lists `bytes` loaded at `base_address` in `syntax`. Ranges covered by
+    /// `SymbolKind::Object` symbols are rendered as data (typed when the symbol carries a type)
+    /// instead of being decoded.
+    pub fn symbolized_listing(
+        &self,
+        bytes: &[u8],
+        base_address: u64,
+        symbols: &SymbolTable,
+        syntax: Syntax,
+    ) -> String {
+        let index = SymbolIndex::new(symbols);
+        let rows = self.sweep(bytes, base_address, &index, syntax);
+
+        let mut xrefs: BTreeMap<u64, Vec<u64>> = BTreeMap::new();
+        for row in &rows {
+            if let Row::Code { entry, .. } = row {
+                for operand in &entry.operands {
+                    if let OperandValue::Target { address } = operand.value {
+                        xrefs.entry(address).or_default().push(entry.address);
+                    }
+                }
+            }
+        }
+
+        let mut out = String::new();
+        for row in &rows {
+            let address = row.address();
+            if let Some(symbol) = index.at(address) {
+                let _ = write!(out, "\n{address:08x} <{}>:\n", symbol.name);
+            }
+            if let Some(sources) = xrefs.get(&address) {
+                let sources: Vec<String> = sources
+                    .iter()
+                    .map(|source| index.describe(*source))
+                    .collect();
+                let _ = writeln!(out, "\t\t\t# called from {}", sources.join(", "));
+            }
+            match row {
+                Row::Code {
+                    entry,
+                    pattern,
+                    bytes,
+                } => {
+                    let text = self.instruction_text(entry, *pattern, &index, syntax);
+                    push_line(&mut out, entry.address, bytes, &text, None);
+                }
+                Row::Data {
+                    address,
+                    bytes,
+                    symbol,
+                } => push_data(&mut out, *address, bytes, *symbol, &index),
+            }
+        }
+        out
+    }
+
+    /// Linear sweep that decodes code and steps over object ranges.
+    fn sweep<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        base_address: u64,
+        index: &SymbolIndex<'_>,
+        syntax: Syntax,
+    ) -> Vec<Row<'a>> {
+        let end = base_address + bytes.len() as u64;
+        let slice = |from: u64, to: u64| {
+            &bytes[(from - base_address) as usize..(to - base_address) as usize]
+        };
+        let mut rows = Vec::new();
+        let mut address = base_address;
+        while address < end {
+            if let Some((symbol, data_end)) = index.data_containing(address) {
+                let stop = data_end.min(end);
+                rows.push(Row::Data {
+                    address,
+                    bytes: slice(address, stop),
+                    symbol: Some(symbol),
+                });
+                address = stop;
+                continue;
+            }
+            let limit = index.next_data(address).map_or(end, |start| start.min(end));
+            let window = slice(address, limit);
+            match self.disassemble_one(window, address, syntax) {
+                Some((entry, pattern)) => {
+                    let size = entry.size;
+                    rows.push(Row::Code {
+                        entry,
+                        pattern,
+                        bytes: &window[..size],
+                    });
+                    address += size as u64;
+                }
+                None => {
+                    rows.push(Row::Data {
+                        address,
+                        bytes: window,
+                        symbol: None,
+                    });
+                    address = limit;
+                }
+            }
+        }
+        rows
+    }
+
+    fn instruction_text(
+        &self,
+        entry: &Disassembly,
+        pattern: Option<&InstructionPattern>,
+        index: &SymbolIndex<'_>,
+        syntax: Syntax,
+    ) -> String {
+        let Some(pattern) = pattern else {
+            return format!(
+                "{:<7} 0x{:0width$x}",
+                directive(entry.size),
+                entry.opcode,
+                width = entry.size * 2
+            );
+        };
+        if syntax == Syntax::Raw {
+            return format!(
+                "{:<7} {}",
+                entry.mnemonic,
+                entry.display.as_deref().unwrap_or_default()
+            );
+        }
+        let pattern = self.rendered_pattern(pattern, entry.opcode, syntax);
+        let target = |address: u64| index.describe(address);
+        let operands =
+            format::render_display_with(self, pattern, entry.opcode, entry.address, &target)
+                .unwrap_or_else(|| {
+                    entry
+                        .operands
+                        .iter()
+                        .map(|operand| format::format_value_with(self, &operand.value, &target))
+                        .collect::<Vec<_>>()
+                        .join(",")
+                });
+        if operands.is_empty() {
+            entry.mnemonic.clone()
+        } else {
+            format!("{:<7} {operands}", entry.mnemonic)
+        }
+    }
+}
+
+enum Row<'a> {
+    Code {
+        entry: Disassembly,
+        pattern: Option<&'a InstructionPattern>,
+        bytes: &'a [u8],
+    },
+    /// Bytes of the object symbol at `symbol` in the index, or bytes that did not decode.
+    Data {
+        address: u64,
+        bytes: &'a [u8],
+        symbol: Option<usize>,
+    },
+}
+
+impl Row<'_> {
+    fn address(&self) -> u64 {
+        match self {
+            Row::Code { entry, .. } => entry.address,
+            Row::Data { address, .. } => *address,
+        }
+    }
+}
+
+struct Symbol<'a> {
+    address: u64,
+    size: Option<u64>,
+    name: &'a str,
+    record: &'a SymbolRecord,
+}
+
+/// Function and object symbols with a runtime address, sorted by address.
+struct SymbolIndex<'a> {
+    table: &'a SymbolTable,
+    symbols: Vec<Symbol<'a>>,
+}
+
+impl<'a> SymbolIndex<'a> {
+    fn new(table: &'a SymbolTable) -> Self {
+        let mut symbols: Vec<Symbol<'a>> = table
+            .query()
+            .iter()
+            .filter(|(_, record)| matches!(record.kind, SymbolKind::Function | SymbolKind::Object))
+            .filter_map(|(_, record)| {
+                Some(Symbol {
+                    address: record.runtime_addr?,
+                    size: symbol_size(table, record),
+                    name: table.resolve_label(record.label),
+                    record,
+                })
+            })
+            .collect();
+        symbols.sort_by_key(|symbol| symbol.address);
+        symbols.dedup_by_key(|symbol| symbol.address);
+        Self { table, symbols }
+    }
+
+    fn at(&self, address: u64) -> Option<&Symbol<'a>> {
+        self.symbols
+            .binary_search_by_key(&address, |symbol| symbol.address)
+            .ok()
+            .map(|idx| &self.symbols[idx])
+    }
+
+    /// Nearest symbol at or below `address` whose extent (when known) covers it.
+    fn containing(&self, address: u64) -> Option<&Symbol<'a>> {
+        let idx = self
+            .symbols
+            .partition_point(|symbol| symbol.address <= address)
+            .checked_sub(1)?;
+        let symbol = &self.symbols[idx];
+        match symbol.size {
+            Some(size) if address >= symbol.address + size => None,
+            _ => Some(symbol),
+        }
+    }
+
+    /// `name` or `name+0x4` for the symbol covering `address`.
+    fn symbolic(&self, address: u64) -> Option<String> {
+        let symbol = self.containing(address)?;
+        Some(if symbol.address == address {
+            symbol.name.to_string()
+        } else {
+            format!("{}+0x{:x}", symbol.name, address - symbol.address)
+        })
+    }
+
+    /// `1010 <name+0x4>`, or the bare address when no symbol covers it.
+    fn describe(&self, address: u64) -> String {
+        match self.symbolic(address) {
+            Some(name) => format!("{address:x} <{name}>"),
+            None => format!("{address:x}"),
+        }
+    }
+
+    /// Index and end address of the object symbol covering `address`.
+    fn data_containing(&self, address: u64) -> Option<(usize, u64)> {
+        self.symbols.iter().enumerate().find_map(|(idx, symbol)| {
+            let end = symbol.address + symbol.size?;
+            (symbol.record.kind == SymbolKind::Object && (symbol.address..end).contains(&address))
+                .then_some((idx, end))
+        })
+    }
+
+    /// Start of the first object symbol after `address`.
+    fn next_data(&self, address: u64) -> Option<u64> {
+        self.symbols
+            .iter()
+            .filter(|symbol| symbol.record.kind == SymbolKind::Object && symbol.size.is_some())
+            .map(|symbol| symbol.address)
+            .find(|start| *start > address)
+    }
+}
+
+/// Declared size, falling back to the extent of the symbol's type.
+fn symbol_size(table: &SymbolTable, record: &SymbolRecord) -> Option<u64> {
+    if let Some(size) = record.size {
+        return Some(u64::from(size));
+    }
+    let type_id = record.type_id?;
+    SymbolWalker::new(table.type_arena(), type_id)
+        .map(|item| item.offset_bits / 8 + u64::from(item.byte_len()))
+        .max()
+}
+
+fn push_data(
+    out: &mut String,
+    address: u64,
+    bytes: &[u8],
+    symbol: Option<usize>,
+    index: &SymbolIndex<'_>,
+) {
+    let mut cursor = 0usize;
+    let typed = symbol
+        .map(|idx| &index.symbols[idx])
+        .and_then(|symbol| Some((symbol, symbol.record.type_id?)));
+    if let Some((symbol, type_id)) = typed {
+        let arena = index.table.type_arena();
+        // The image may start partway into the object.
+        let skip = address - symbol.address;
+        for item in SymbolWalker::new(arena, type_id) {
+            let Some(start) = (item.offset_bits / 8).checked_sub(skip) else {
+                continue;
+            };
+            let (start, len) = (start as usize, item.byte_len() as usize);
+            if start < cursor || start + len > bytes.len() {
+                continue;
+            }
+            push_bytes(out, address + cursor as u64, &bytes[cursor..start]);
+            let raw = &bytes[start..start + len];
+            let path = item.path.to_string(arena);
+            let comment = match path.as_str() {
+                "<root>" => None,
+                indexed if indexed.starts_with('[') => Some(format!("{}{indexed}", symbol.name)),
+                member => Some(format!("{}.{member}", symbol.name)),
+            };
+            let text = typed_value(&item.kind, raw, symbol.record, index);
+            push_line(out, address + start as u64, raw, &text, comment.as_deref());
+            cursor = start + len;
+        }
+    }
+    push_bytes(out, address + cursor as u64, &bytes[cursor..]);
+}
+
+fn typed_value(kind: &ValueKind, raw: &[u8], record: &SymbolRecord, index: &SymbolIndex) -> String {
+    let value = || decode_word(raw, record.byte_order);
+    let name = directive(raw.len());
+    match kind {
+        ValueKind::Signed { .. } => {
+            let shift = 64 - 8 * raw.len().min(8) as u32;
+            format!("{name:<7} {}", ((value() << shift) as i64) >> shift)
+        }
+        ValueKind::Float32 => format!("{:<7} {}", ".float", f32::from_bits(value() as u32)),
+        ValueKind::Float64 => format!("{:<7} {}", ".double", f64::from_bits(value())),
+        ValueKind::Utf8 { .. } => {
+            let text = String::from_utf8_lossy(raw);
+            format!("{:<7} {:?}", ".ascii", text.trim_end_matches('\0'))
+        }
+        ValueKind::Pointer { .. } => {
+            let target = value();
+            let text = format!("{name:<7} 0x{target:0width$x}", width = raw.len() * 2);
+            match index.symbolic(target) {
+                Some(symbol) => format!("{text} <{symbol}>"),
+                None => text,
+            }
+        }
+        ValueKind::Unsigned { .. } | ValueKind::Enum | ValueKind::Fixed => {
+            format!("{name:<7} 0x{:0width$x}", value(), width = raw.len() * 2)
+        }
+    }
+}
+
+/// Untyped bytes, four per `.byte` line.
+fn push_bytes(out: &mut String, address: u64, bytes: &[u8]) {
+    for (chunk_idx, chunk) in bytes.chunks(4).enumerate() {
+        let values: Vec<String> = chunk.iter().map(|byte| format!("0x{byte:02x}")).collect();
+        let text = format!("{:<7} {}", ".byte", values.join(","));
+        push_line(out, address + 4 * chunk_idx as u64, chunk, &text, None);
+    }
+}
+
+fn push_line(out: &mut String, address: u64, bytes: &[u8], text: &str, comment: Option<&str>) {
+    let hex: Vec<String> = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    let _ = write!(out, "{address:8x}:\t{} \t{text}", hex.join(" "));
+    if let Some(comment) = comment {
+        let _ = write!(out, "\t# {comment}");
+    }
+    out.push('\n');
+}
+
+fn directive(size: usize) -> &'static str {
+    match size {
+        2 => ".short",
+        4 => ".long",
+        8 => ".quad",
+        _ => ".byte",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use crate::loader::isa::IsaLoader;
+    use crate::soc::device::Endianness;
+    use crate::soc::prog::types::{DisplayFormat, ScalarEncoding, TypeArena, TypeBuilder};
+
+    use super::*;
+
+    #[test]
+    fn listing_labels_targets_and_renders_objects_as_data() {
+        let coredef = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("defs/powerpc/e200.coredef");
+        let machine = IsaLoader::new().load_machine(coredef).expect("load e200");
+
+        let mut arena = TypeArena::new();
+        let mut builder = TypeBuilder::new(&mut arena);
+        let half = builder.scalar(Some("u16"), 2, ScalarEncoding::Unsigned, DisplayFormat::Hex);
+        let table_ty = builder.sequence_static(half, 2, 2);
+        let mut symbols = SymbolTable::new(Arc::new(arena));
+        symbols
+            .builder()
+            .label("main")
+            .kind(SymbolKind::Function)
+            .runtime_addr(0x1000)
+            .size(6)
+            .finish();
+        symbols
+            .builder()
+            .label("spin")
+            .kind(SymbolKind::Function)
+            .runtime_addr(0x1006)
+            .finish();
+        symbols
+            .builder()
+            .label("limits")
+            .kind(SymbolKind::Object)
+            .type_id(table_ty)
+            .byte_order(Endianness::Big)
+            .runtime_addr(0x1008)
+            .finish();
+
+        // addi r3,r0,5; se_b spin; spin: se_b spin; limits: { 3000, 16 }; two stray bytes
+        let mut image = Vec::new();
+        image.extend_from_slice(&0x3860_0005u32.to_be_bytes());
+        image.extend_from_slice(&0xE801u16.to_be_bytes());
+        image.extend_from_slice(&0xE800u16.to_be_bytes());
+        image.extend_from_slice(&[0x0B, 0xB8, 0x00, 0x10, 0xFF, 0xFF]);
+
+        let listing = machine.symbolized_listing(&image, 0x1000, &symbols, Syntax::Canonical);
+        let expected = "
+00001000 <main>:
+    1000:\t38 60 00 05 \taddi    r3, r0, 0x0005
+    1004:\te8 01 \tse_b    1006 <spin>
+
+00001006 <spin>:
+\t\t\t# called from 1004 <main+0x4>, 1006 <spin>
+    1006:\te8 00 \tse_b    1006 <spin>
+
+00001008 <limits>:
+    1008:\t0b b8 \t.short  0x0bb8\t# limits[0]
+    100a:\t00 10 \t.short  0x0010\t# limits[1]
+    100c:\tff ff \t.byte   0xff,0xff
+";
+        assert_eq!(listing, expected);
+    }
+}
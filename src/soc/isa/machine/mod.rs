@@ -4,37 +4,62 @@
 //! and display formatting.
 
 mod assembly;
+mod bus;
+mod cfg;
+mod decode_tree;
 mod disassembly;
+mod export;
+mod fetch;
+mod flow;
 mod format;
 mod host;
 
 mod instruction;
+mod listing;
 mod macros;
+mod operand;
+mod program;
+mod reference;
 mod register;
+mod snapshot;
 mod space;
+mod stream;
+mod syntax;
 
+pub use bus::{BusInfo, BusRangeInfo};
+pub use cfg::{BasicBlock, ControlFlowGraph, Edge, EdgeKind, EntryKind, EntryPoint, Function};
 pub use disassembly::{DecodedInstruction, Disassembly};
+pub use export::{EXPORT_FORMAT, EXPORT_VERSION};
+pub use fetch::{ByteImage, DecodeError, InstructionFetch};
+pub use flow::{ControlFlow, FlowKind};
 pub use host::{HostArithResult, HostMulResult, HostServices, SoftwareHost};
-pub use instruction::{Instruction, InstructionMask};
+pub use instruction::{Instruction, InstructionMask, InstructionPattern};
 pub use macros::MacroInfo;
+pub use operand::{Operand, OperandValue, RegisterRef};
+pub use program::{Program, ProgramSymbol, Relocation, RelocationKind};
+pub use reference::ReferenceFormat;
 pub use register::{
-    RegisterBinding, RegisterElement, RegisterFieldMetadata, RegisterInfo, RegisterMetadata,
-    RegisterSchema, RegisterTypeHandles,
+    AliasTarget, RegisterBinding, RegisterElement, RegisterFieldMetadata, RegisterInfo,
+    RegisterMetadata, RegisterSchema, RegisterTypeHandles, resolve_alias_target,
 };
+pub use snapshot::SNAPSHOT_VERSION;
 pub use space::{FieldEncoding, FormInfo, OperandKind, SpaceInfo, encode_constant, parse_bit_spec};
+pub use stream::{InstructionStream, Resync};
+pub use syntax::Syntax;
 
 use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use crate::soc::isa::ast::{
-    FieldDecl, FormDecl, IsaItem, IsaSpecification, MacroDecl, ParameterDecl, ParameterValue,
-    SpaceDecl, SpaceKind, SpaceMember,
+    BusDecl, FieldDecl, FormDecl, IsaItem, IsaSpecification, MacroDecl, ParameterDecl,
+    ParameterValue, SpaceDecl, SpaceKind, SpaceMember,
 };
 use crate::soc::isa::error::IsaError;
 use crate::soc::isa::semantics::analyzer::SemanticAnalyzer;
+use crate::soc::system::bus::DeviceBus;
 
-use disassembly::LogicDecodeSpace;
-use instruction::InstructionPattern;
+use disassembly::{EncodingPredicate, LogicDecodeSpace};
+use syntax::AliasPattern;
 
 #[derive(Debug, Clone)]
 pub struct MachineDescription {
@@ -42,9 +67,13 @@ pub struct MachineDescription {
     pub spaces: BTreeMap<String, SpaceInfo>,
     pub macros: Vec<MacroInfo>,
     pub parameters: BTreeMap<String, ParameterValue>,
+    pub buses: BTreeMap<String, BusInfo>,
     patterns: Vec<InstructionPattern>,
     decode_spaces: Vec<LogicDecodeSpace>,
     register_schema: Arc<RegisterSchema>,
+    aliases: Vec<AliasPattern>,
+    /// Compiled `valid=` predicates, indexed like `instructions`.
+    validity: Vec<Option<EncodingPredicate>>,
 }
 
 impl Default for MachineDescription {
@@ -54,9 +83,12 @@ impl Default for MachineDescription {
             spaces: BTreeMap::new(),
             macros: Vec::new(),
             parameters: BTreeMap::new(),
+            buses: BTreeMap::new(),
             patterns: Vec::new(),
             decode_spaces: Vec::new(),
             register_schema: Arc::new(RegisterSchema::empty()),
+            aliases: Vec::new(),
+            validity: Vec::new(),
         }
     }
 }
@@ -70,12 +102,18 @@ impl MachineDescription {
         self.register_schema.as_ref()
     }
 
+    /// Resolved mask/value decode patterns, one per masked logic-space instruction.
+    pub fn patterns(&self) -> &[InstructionPattern] {
+        &self.patterns
+    }
+
     pub fn from_documents(docs: Vec<IsaSpecification>) -> Result<Self, IsaError> {
         let mut spaces = Vec::new();
         let mut forms = Vec::new();
         let mut fields = Vec::new();
         let mut instructions = Vec::new();
         let mut macros = Vec::new();
+        let mut buses = Vec::new();
         let mut parameters: BTreeMap<String, ParameterValue> = BTreeMap::new();
 
         for doc in docs {
@@ -89,6 +127,7 @@ impl MachineDescription {
                     },
                     IsaItem::Instruction(instr) => instructions.push(instr),
                     IsaItem::Macro(mac) => macros.push(mac),
+                    IsaItem::Bus(bus) => buses.push(bus),
                     IsaItem::Parameter(ParameterDecl { name, value }) => {
                         parameters.insert(name, value);
                     }
@@ -113,8 +152,13 @@ impl MachineDescription {
         for mac in macros {
             machine.register_macro(mac);
         }
+        for bus in buses {
+            machine.register_bus(bus);
+        }
         machine.parameters = parameters;
         machine.build_patterns()?;
+        machine.build_validity()?;
+        machine.build_aliases()?;
         machine.build_decode_spaces()?;
         machine.rebuild_register_schema()?;
         machine.compile_semantics()?;
@@ -122,8 +166,18 @@ impl MachineDescription {
         Ok(machine)
     }
 
+    /// Disassembles `bytes` loaded at address 0 in [`Syntax::Canonical`].
     pub fn disassemble(&self, bytes: &[u8]) -> Vec<Disassembly> {
-        self.disassemble_from(bytes, 0)
+        self.disassemble_from(bytes, 0, Syntax::Canonical)
+    }
+
+    /// Materializes the named `:bus` as a [`DeviceBus`] backed by memory for each `rw`/`ro` space.
+    pub fn build_bus(&self, name: &str) -> Result<DeviceBus, IsaError> {
+        let bus = self
+            .buses
+            .get(name)
+            .ok_or_else(|| IsaError::Machine(format!("unknown bus '{name}'")))?;
+        bus.build_device_bus(&self.spaces)
     }
 
     pub fn finalize_machine(
@@ -166,13 +220,18 @@ impl MachineDescription {
         self.macros.push(MacroInfo::from_decl(mac));
     }
 
+    fn register_bus(&mut self, bus: BusDecl) {
+        let info = BusInfo::from_decl(bus);
+        self.buses.insert(info.name.clone(), info);
+    }
+
     fn rebuild_register_schema(&mut self) -> Result<(), IsaError> {
         let schema = RegisterSchema::build(&mut self.spaces)?;
         self.register_schema = Arc::new(schema);
         Ok(())
     }
 
-    fn compile_semantics(&self) -> Result<(), IsaError> {
+    fn compile_semantics(&mut self) -> Result<(), IsaError> {
         let analyzer = SemanticAnalyzer::new(self);
         for mac in &self.macros {
             let program = mac.semantics.ensure_program()?;
@@ -184,6 +243,20 @@ impl MachineDescription {
                 analyzer.analyze_instruction(instr, program.as_ref())?;
             }
         }
+        self.summarize_semantics()
+    }
+
+    /// Fills in each instruction's [`Instruction::effects`].
+    fn summarize_semantics(&mut self) -> Result<(), IsaError> {
+        let analyzer = SemanticAnalyzer::new(self);
+        let effects = self
+            .instructions
+            .iter()
+            .map(|instr| analyzer.summarize_instruction(instr))
+            .collect::<Result<Vec<_>, _>>()?;
+        for (instr, effects) in self.instructions.iter_mut().zip(effects) {
+            instr.effects = effects;
+        }
         Ok(())
     }
 }
@@ -223,6 +296,7 @@ mod tests {
                     bit_spec: "@(0..3)".into(),
                     operations: vec![subfield_op("func", None::<&str>)],
                     description: None,
+                    reset: None,
                 },
                 SubFieldDecl {
                     name: "DST".into(),
@@ -232,6 +306,7 @@ mod tests {
                         subfield_op("reg", Some("GPR")),
                     ],
                     description: None,
+                    reset: None,
                 },
             ],
         );
@@ -243,12 +318,15 @@ mod tests {
         let doc = builder.build();
         let machine = MachineDescription::from_documents(vec![doc]).expect("machine");
         let bytes = [0xA5u8];
-        let listing = machine.disassemble_from(&bytes, 0x1000);
+        let listing = machine.disassemble_from(&bytes, 0x1000, Syntax::Canonical);
         assert_eq!(listing.len(), 1);
         let entry = &listing[0];
         assert_eq!(entry.address, 0x1000);
         assert_eq!(entry.mnemonic, "mov");
-        assert_eq!(entry.operands, vec!["GPR5".to_string()]);
+        assert_eq!(
+            machine.format_operands(&entry.operands),
+            vec!["GPR5".to_string()]
+        );
         assert_eq!(entry.opcode, 0xA5);
     }
 
@@ -274,6 +352,7 @@ mod tests {
                     bit_spec: "@(0..1)".into(),
                     operations: vec![subfield_op("func", None::<&str>)],
                     description: None,
+                    reset: None,
                 },
                 SubFieldDecl {
                     name: "RT".into(),
@@ -283,6 +362,7 @@ mod tests {
                         subfield_op("reg", Some("GPR")),
                     ],
                     description: None,
+                    reset: None,
                 },
                 SubFieldDecl {
                     name: "RA".into(),
@@ -292,6 +372,7 @@ mod tests {
                         subfield_op("reg", Some("GPR")),
                     ],
                     description: None,
+                    reset: None,
                 },
                 SubFieldDecl {
                     name: "RB".into(),
@@ -301,6 +382,7 @@ mod tests {
                         subfield_op("reg", Some("GPR")),
                     ],
                     description: None,
+                    reset: None,
                 },
             ],
         );
@@ -323,14 +405,14 @@ mod tests {
         assert_eq!(listing.len(), 2);
         assert_eq!(listing[0].mnemonic, "add");
         assert_eq!(
-            listing[0].operands,
+            machine.format_operands(&listing[0].operands),
             vec!["GPR1".to_string(), "GPR2".to_string(), "GPR3".to_string()]
         );
         assert_eq!(listing[0].display.as_deref(), Some("GPR1 <- GPR2 + GPR3"));
 
         assert_eq!(listing[1].mnemonic, "swap");
         assert_eq!(
-            listing[1].operands,
+            machine.format_operands(&listing[1].operands),
             vec!["GPR0".to_string(), "GPR3".to_string(), "GPR2".to_string()]
         );
         assert_eq!(listing[1].display.as_deref(), Some("GPR0 <-> GPR3"));
@@ -347,6 +429,7 @@ mod tests {
             bit_spec: "@(0..15)".into(),
             operations: Vec::new(),
             description: None,
+            reset: None,
         }];
         registers.insert("GPR".into(), gpr);
         let space = SpaceInfo {
@@ -415,12 +498,14 @@ mod tests {
                     bit_spec: "@(0..3)".into(),
                     operations: vec![subfield_op("func", None::<&str>)],
                     description: None,
+                    reset: None,
                 },
                 SubFieldDecl {
                     name: "SIMM".into(),
                     bit_spec: "@(4..15)".into(),
                     operations: vec![subfield_op("immediate", None::<&str>)],
                     description: None,
+                    reset: None,
                 },
             ],
         );
@@ -435,7 +520,10 @@ mod tests {
         let listing = machine.disassemble(&bytes);
         assert_eq!(listing.len(), 1);
         assert_eq!(listing[0].mnemonic, "addi");
-        assert_eq!(listing[0].operands, vec!["0x123".to_string()]);
+        assert_eq!(
+            machine.format_operands(&listing[0].operands),
+            vec!["0x123".to_string()]
+        );
     }
 
     #[test]
@@ -459,6 +547,7 @@ mod tests {
                     bit_spec: "@(0..1)".into(),
                     operations: vec![subfield_op("func", None::<&str>)],
                     description: None,
+                    reset: None,
                 },
                 SubFieldDecl {
                     name: "RT".into(),
@@ -468,6 +557,7 @@ mod tests {
                         subfield_op("reg", Some("GPR")),
                     ],
                     description: None,
+                    reset: None,
                 },
                 SubFieldDecl {
                     name: "RA".into(),
@@ -477,6 +567,7 @@ mod tests {
                         subfield_op("reg", Some("GPR")),
                     ],
                     description: None,
+                    reset: None,
                 },
                 SubFieldDecl {
                     name: "RB".into(),
@@ -486,6 +577,7 @@ mod tests {
                         subfield_op("reg", Some("GPR")),
                     ],
                     description: None,
+                    reset: None,
                 },
             ],
         );
@@ -502,12 +594,67 @@ mod tests {
         let entry = &listing[0];
         assert_eq!(entry.mnemonic, "copy");
         assert_eq!(
-            entry.operands,
+            machine.format_operands(&entry.operands),
             vec!["GPR1".to_string(), "GPR2".to_string(), "GPR3".to_string()]
         );
         assert_eq!(entry.display.as_deref(), Some("GPR1, GPR2, GPR3"));
     }
 
+    #[test]
+    fn unbound_register_operands_leave_the_file_empty() {
+        let mut builder = IsaBuilder::new("bare_reg.isa");
+        builder.add_space(
+            "logic",
+            SpaceKind::Logic,
+            vec![
+                SpaceAttribute::WordSize(8),
+                SpaceAttribute::Endianness(Endianness::Big),
+            ],
+        );
+        builder.add_form(
+            "logic",
+            "ONE",
+            None,
+            vec![
+                SubFieldDecl {
+                    name: "OPC".into(),
+                    bit_spec: "@(0..3)".into(),
+                    operations: vec![subfield_op("func", None::<&str>)],
+                    description: None,
+                    reset: None,
+                },
+                SubFieldDecl {
+                    name: "R".into(),
+                    bit_spec: "@(4..7)".into(),
+                    operations: vec![subfield_op("reg", None::<&str>)],
+                    description: None,
+                    reset: None,
+                },
+            ],
+        );
+        builder
+            .instruction("logic", "push")
+            .form("ONE")
+            .mask_field(mask_field_selector("OPC"), 0x5)
+            .finish();
+
+        let machine = MachineDescription::from_documents(vec![builder.build()]).expect("machine");
+        let listing = machine.disassemble(&[0x57u8]);
+        assert_eq!(listing.len(), 1);
+        match &listing[0].operands[0].value {
+            OperandValue::Register(register) => {
+                assert!(register.space.is_empty());
+                assert!(register.file.is_empty());
+                assert_eq!(register.index, 7);
+            }
+            other => panic!("expected a register operand, got {other:?}"),
+        }
+        assert_eq!(
+            machine.format_operands(&listing[0].operands),
+            vec!["7".to_string()]
+        );
+    }
+
     #[test]
     fn collects_macros_from_documents() {
         let macro_decl = MacroDecl {
@@ -0,0 +1,212 @@
+//! Typed operand model for decoded instructions. Values come straight from the form subfields;
+//! turning them into text is left to the formatting pass in `format.rs`.
+
+use super::MachineDescription;
+use super::instruction::{Instruction, InstructionPattern};
+use super::space::{FieldEncoding, FormInfo, OperandKind};
+
+/// One decoded operand and the subfield it was read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Operand {
+    /// Subfield name; for a memory operand, the displacement subfield. Empty for the raw word
+    /// of an undecoded entry.
+    pub name: String,
+    pub value: OperandValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OperandValue {
+    Register(RegisterRef),
+    Signed {
+        value: i64,
+        width: u16,
+    },
+    Unsigned {
+        value: u64,
+        width: u16,
+    },
+    /// `addr` field resolved against the instruction address, or taken as is when the word
+    /// sets the form's `abs` flag.
+    Target {
+        address: u64,
+    },
+    /// Displacement shown as `#D(#RA)` in the display template.
+    Memory {
+        base: RegisterRef,
+        displacement: i64,
+        width: u16,
+    },
+    /// Subfield without an operand classification.
+    Field(u64),
+}
+
+/// Register selected by an operand: index `index` of register file `file` in `space`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisterRef {
+    /// Empty when the subfield is only marked `reg` without naming a register space.
+    pub space: String,
+    pub file: String,
+    pub index: u64,
+}
+
+/// Typed value of `field` of `form` within the instruction word `bits` fetched at `address`.
+pub(super) fn field_value(
+    form: &FormInfo,
+    field: &FieldEncoding,
+    bits: u64,
+    address: u64,
+) -> OperandValue {
+    let (value, _) = field.spec.read_bits(bits);
+    if let Some(binding) = &field.register {
+        return OperandValue::Register(RegisterRef {
+            space: binding.space.clone(),
+            file: binding.field.clone(),
+            index: value,
+        });
+    }
+    if field.is_pc_relative() {
+        let offset = field.spec.read_signed(bits) as u64;
+        let address = if form.is_absolute(bits) {
+            offset
+        } else {
+            address.wrapping_add(offset)
+        };
+        return OperandValue::Target { address };
+    }
+    if field.kind == OperandKind::Immediate {
+        let width = field.spec.data_width().max(1);
+        return if field.spec.is_signed() {
+            OperandValue::Signed {
+                value: field.spec.read_signed(bits),
+                width,
+            }
+        } else {
+            OperandValue::Unsigned { value, width }
+        };
+    }
+    if field
+        .operations
+        .iter()
+        .any(|op| op.kind.eq_ignore_ascii_case("reg"))
+    {
+        // A bare `op=reg` names no register file, so only the index is known.
+        return OperandValue::Register(RegisterRef {
+            space: String::new(),
+            file: String::new(),
+            index: value,
+        });
+    }
+    OperandValue::Field(value)
+}
+
+impl MachineDescription {
+    /// Operands of `pattern` in declaration order. An immediate and register the display
+    /// template writes as `#D(#RA)` are folded into one memory operand.
+    pub(super) fn decode_operands(
+        &self,
+        pattern: &InstructionPattern,
+        bits: u64,
+        address: u64,
+    ) -> Vec<Operand> {
+        let Some(form) = pattern
+            .form
+            .as_ref()
+            .and_then(|form| self.spaces.get(&pattern.space)?.forms.get(form))
+        else {
+            return Vec::new();
+        };
+
+        let mut operands: Vec<Operand> = pattern
+            .operand_names
+            .iter()
+            .filter_map(|name| {
+                let field = form.subfield(name)?;
+                Some(Operand {
+                    name: name.clone(),
+                    value: field_value(form, field, bits, address),
+                })
+            })
+            .collect();
+
+        let Some(template) = pattern.display.as_deref() else {
+            return operands;
+        };
+        for (disp, base) in memory_pairs(template) {
+            let position = |name: &str| operands.iter().position(|operand| operand.name == name);
+            let (Some(disp_idx), Some(base_idx)) = (position(disp), position(base)) else {
+                continue;
+            };
+            let OperandValue::Register(base) = &operands[base_idx].value else {
+                continue;
+            };
+            let (displacement, width) = match operands[disp_idx].value {
+                OperandValue::Signed { value, width } => (value, width),
+                OperandValue::Unsigned { value, width } => (value as i64, width),
+                _ => continue,
+            };
+            operands[disp_idx].value = OperandValue::Memory {
+                base: base.clone(),
+                displacement,
+                width,
+            };
+            operands.remove(base_idx);
+        }
+        operands
+    }
+
+    /// Whether the encoding of `instr` (or of its alias base) sets an `abs` flag, making its
+    /// `addr` operands absolute addresses rather than displacements.
+    pub(super) fn encodes_absolute(&self, instr: &Instruction) -> bool {
+        let name = instr.alias.as_ref().map_or(&instr.name, |spec| &spec.base);
+        let Some((index, _)) = self.find_base(&instr.space, name) else {
+            return false;
+        };
+        self.patterns
+            .iter()
+            .filter(|pattern| pattern.instruction_idx == index)
+            .any(|pattern| {
+                pattern
+                    .form
+                    .as_ref()
+                    .and_then(|form| self.spaces.get(&pattern.space)?.forms.get(form))
+                    .is_some_and(|form| form.is_absolute(pattern.value))
+            })
+    }
+}
+
+/// `(displacement, base)` token pairs written as `#D(#RA)` in a display template.
+fn memory_pairs(template: &str) -> Vec<(&str, &str)> {
+    let ident = |text: &str| {
+        text.find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_'))
+            .unwrap_or(text.len())
+    };
+    let mut pairs = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('#') {
+        let after = &rest[start + 1..];
+        let disp_len = ident(after);
+        let tail = &after[disp_len..];
+        if disp_len > 0
+            && let Some(inner) = tail.strip_prefix("(#")
+        {
+            let base_len = ident(inner);
+            if base_len > 0 && inner[base_len..].starts_with(')') {
+                pairs.push((&after[..disp_len], &inner[..base_len]));
+            }
+        }
+        rest = after;
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_pairs_find_displacement_base_tokens() {
+        assert_eq!(memory_pairs("#RT, #D(#RA)"), vec![("D", "RA")]);
+        assert_eq!(memory_pairs("#RT, #RA, #D"), Vec::<(&str, &str)>::new());
+        assert_eq!(memory_pairs("#D(#RA"), Vec::<(&str, &str)>::new());
+    }
+}
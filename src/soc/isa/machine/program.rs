@@ -0,0 +1,937 @@
+//! Two-pass assembler for multi-line sources. The first pass lays out labels, directives and
+//! instructions (each as wide as the word of its space); the second evaluates operands against
+//! the finished symbol table and encodes every line with the single-instruction encoder.
+//!
+//! ```text
+//! start:  li r3, 0
+//! loop:   addi r3, r3, 1
+//!         bdnz loop           # addr operands take the target; the displacement is computed
+//!         lis r4, msg@ha      # @h / @l / @ha, or hi() / lo() / ha()
+//!         .align 4
+//! msg:    .ascii "hi\n"
+//!         .word start, msg - start
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+use crate::soc::device::endianness::Endianness;
+use crate::soc::isa::error::IsaError;
+use crate::soc::prog::symbols::{SymbolKind, SymbolTable};
+use crate::soc::prog::types::arena::TypeArena;
+
+use super::MachineDescription;
+use super::assembly::{parse_numeric, parse_operand_value, split_list, write_word};
+use super::instruction::Instruction;
+use super::space::{OperandKind, ensure_byte_aligned};
+
+/// How a relocation derives the patched value from `symbol + addend`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelocationKind {
+    /// The address itself.
+    Absolute,
+    /// The address minus that of the instruction (`addr` operands).
+    PcRelative,
+    /// Low 16 bits (`@l`, `lo()`).
+    Low,
+    /// High 16 bits (`@h`, `hi()`).
+    High,
+    /// High 16 bits adjusted for a sign-extended low half (`@ha`, `ha()`).
+    HighAdjusted,
+}
+
+/// Reference to an `.extern` symbol, encoded as zero in the image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relocation {
+    /// Address of the instruction or data item to patch.
+    pub address: u64,
+    /// Size of that item in bytes.
+    pub size: usize,
+    /// Instruction operand receiving the value; `None` for data directives.
+    pub operand: Option<String>,
+    pub symbol: String,
+    pub addend: i64,
+    pub kind: RelocationKind,
+}
+
+/// Label defined by a program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramSymbol {
+    pub address: u64,
+    /// Bytes up to the next label or the end of the image.
+    pub size: u64,
+    /// [`SymbolKind::Function`] for labels on instructions, [`SymbolKind::Object`] otherwise.
+    pub kind: SymbolKind,
+}
+
+/// Output of [`MachineDescription::assemble_program`].
+#[derive(Debug, Clone, Default)]
+pub struct Program {
+    /// Address of the first byte.
+    pub origin: u64,
+    pub bytes: Vec<u8>,
+    pub symbols: BTreeMap<String, ProgramSymbol>,
+    pub relocations: Vec<Relocation>,
+}
+
+impl Program {
+    /// First address past the image.
+    pub fn end(&self) -> u64 {
+        self.origin + self.bytes.len() as u64
+    }
+
+    /// Address of label `name`.
+    pub fn symbol(&self, name: &str) -> Option<u64> {
+        self.symbols.get(name).map(|symbol| symbol.address)
+    }
+
+    /// The labels as a [`SymbolTable`] for listings and code discovery.
+    pub fn symbol_table(&self, types: Arc<TypeArena>) -> SymbolTable {
+        let mut table = SymbolTable::new(types);
+        for (name, symbol) in &self.symbols {
+            table
+                .builder()
+                .label(name)
+                .kind(symbol.kind)
+                .runtime_addr(symbol.address)
+                .size(u32::try_from(symbol.size).unwrap_or(u32::MAX))
+                .finish();
+        }
+        table
+    }
+}
+
+impl MachineDescription {
+    /// Assembles a multi-line `source` placed at `origin`. Errors name the offending line.
+    pub fn assemble_program(&self, source: &str, origin: u64) -> Result<Program, IsaError> {
+        let mut assembler = Assembler::new(self, origin);
+        for (idx, line) in source.lines().enumerate() {
+            assembler
+                .layout_line(line)
+                .map_err(|err| at_line(idx + 1, err))?;
+        }
+        assembler.finish()
+    }
+}
+
+/// Item laid out by the first pass.
+struct Item<'a> {
+    line: usize,
+    address: u64,
+    kind: ItemKind<'a>,
+}
+
+enum ItemKind<'a> {
+    Instruction {
+        instr: &'a Instruction,
+        size: usize,
+        operands: &'a str,
+    },
+    /// `.byte`, `.half` or `.word` values, evaluated in the second pass.
+    Data {
+        width: usize,
+        values: Vec<&'a str>,
+    },
+    Bytes(Vec<u8>),
+}
+
+/// Result of evaluating an operand or data expression.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Constant(i64),
+    /// `symbol + addend` of an `.extern` symbol, left to a relocation.
+    External {
+        symbol: String,
+        addend: i64,
+        kind: RelocationKind,
+    },
+}
+
+struct Assembler<'a> {
+    machine: &'a MachineDescription,
+    origin: u64,
+    location: u64,
+    line: usize,
+    items: Vec<Item<'a>>,
+    labels: BTreeMap<String, ProgramSymbol>,
+    /// Labels waiting for the item that decides their kind.
+    pending: Vec<String>,
+    constants: BTreeMap<String, i64>,
+    externs: BTreeSet<String>,
+}
+
+impl<'a> Assembler<'a> {
+    fn new(machine: &'a MachineDescription, origin: u64) -> Self {
+        Self {
+            machine,
+            origin,
+            location: origin,
+            line: 0,
+            items: Vec::new(),
+            labels: BTreeMap::new(),
+            pending: Vec::new(),
+            constants: BTreeMap::new(),
+            externs: BTreeSet::new(),
+        }
+    }
+
+    /// First pass over one line: defines its labels and sizes its statement.
+    fn layout_line(&mut self, line: &'a str) -> Result<(), IsaError> {
+        self.line += 1;
+        let mut rest = strip_comment(line).trim();
+        while let Some((label, after)) = split_label(rest) {
+            self.define(label)?;
+            self.labels.insert(
+                label.to_string(),
+                ProgramSymbol {
+                    address: self.location,
+                    size: 0,
+                    kind: SymbolKind::Object,
+                },
+            );
+            self.pending.push(label.to_string());
+            rest = after.trim_start();
+        }
+        if rest.is_empty() {
+            return Ok(());
+        }
+        let (head, tail) = rest
+            .split_once(char::is_whitespace)
+            .map_or((rest, ""), |(head, tail)| (head, tail.trim()));
+        if head.starts_with('.') {
+            self.directive(head, tail)
+        } else {
+            self.instruction(head, tail)
+        }
+    }
+
+    fn instruction(&mut self, mnemonic: &str, tail: &'a str) -> Result<(), IsaError> {
+        let instr = self.machine.find_instruction(mnemonic)?;
+        let space = self.machine.spaces.get(&instr.space).ok_or_else(|| {
+            IsaError::Machine(format!("instruction space '{}' missing", instr.space))
+        })?;
+        let size = ensure_byte_aligned(space.word_bits()?, &instr.name)?;
+        self.push(
+            ItemKind::Instruction {
+                instr,
+                size,
+                operands: tail,
+            },
+            size,
+            SymbolKind::Function,
+        );
+        Ok(())
+    }
+
+    fn directive(&mut self, name: &str, tail: &'a str) -> Result<(), IsaError> {
+        match name.to_ascii_lowercase().as_str() {
+            ".org" => {
+                let target = self.constant(tail)? as u64;
+                if target < self.location {
+                    return Err(IsaError::Machine(format!(
+                        ".org 0x{target:X} is behind the current address 0x{:X}",
+                        self.location
+                    )));
+                }
+                self.pad(target - self.location, 0);
+            }
+            ".align" => {
+                let alignment = self.constant(tail)?;
+                if alignment <= 0 || alignment.count_ones() != 1 {
+                    return Err(IsaError::Machine(format!(
+                        ".align expects a power of two, got {alignment}"
+                    )));
+                }
+                let alignment = alignment as u64;
+                self.pad(self.location.next_multiple_of(alignment) - self.location, 0);
+            }
+            ".space" => {
+                let args = split_list(tail)?;
+                let (count, fill) = match args.as_slice() {
+                    [count] => (self.constant(count)?, 0),
+                    [count, fill] => (self.constant(count)?, self.constant(fill)?),
+                    _ => return Err(IsaError::Machine(".space expects a size and fill".into())),
+                };
+                let fill = u8::try_from(fill).map_err(|_| {
+                    IsaError::Machine(format!(".space fill {fill} does not fit a byte"))
+                })?;
+                let count = u64::try_from(count)
+                    .map_err(|_| IsaError::Machine(format!(".space size {count} is negative")))?;
+                self.push(
+                    ItemKind::Bytes(vec![fill; count as usize]),
+                    count as usize,
+                    SymbolKind::Object,
+                );
+            }
+            ".byte" | ".half" | ".word" => {
+                let width = match name.to_ascii_lowercase().as_str() {
+                    ".byte" => 1,
+                    ".half" => 2,
+                    _ => 4,
+                };
+                let values = split_list(tail)?;
+                if values.is_empty() {
+                    return Err(IsaError::Machine(format!("{name} expects a value")));
+                }
+                let size = width * values.len();
+                self.push(ItemKind::Data { width, values }, size, SymbolKind::Object);
+            }
+            ".ascii" => {
+                let mut bytes = Vec::new();
+                for part in split_list(tail)? {
+                    bytes.extend(parse_string(part)?);
+                }
+                let size = bytes.len();
+                self.push(ItemKind::Bytes(bytes), size, SymbolKind::Object);
+            }
+            ".equ" | ".set" => {
+                let (symbol, expr) = tail
+                    .split_once(',')
+                    .map(|(symbol, expr)| (symbol.trim(), expr))
+                    .filter(|(symbol, _)| is_identifier(symbol))
+                    .ok_or_else(|| IsaError::Machine(format!("{name} expects 'name, value'")))?;
+                self.define(symbol)?;
+                let value = self.constant(expr)?;
+                self.constants.insert(symbol.to_string(), value);
+            }
+            ".extern" => {
+                for symbol in split_list(tail)? {
+                    if !is_identifier(symbol) {
+                        return Err(IsaError::Machine(format!("invalid symbol name '{symbol}'")));
+                    }
+                    self.define(symbol)?;
+                    self.externs.insert(symbol.to_string());
+                }
+            }
+            _ => {
+                return Err(IsaError::Machine(format!("unknown directive '{name}'")));
+            }
+        }
+        Ok(())
+    }
+
+    /// Rejects a second definition of `name` as a label, constant or external.
+    fn define(&self, name: &str) -> Result<(), IsaError> {
+        if self.labels.contains_key(name)
+            || self.constants.contains_key(name)
+            || self.externs.contains(name)
+        {
+            return Err(IsaError::Machine(format!(
+                "symbol '{name}' is already defined"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Appends an item, settling the kind of the labels that precede it.
+    fn push(&mut self, kind: ItemKind<'a>, size: usize, symbol_kind: SymbolKind) {
+        for label in self.pending.drain(..) {
+            if let Some(symbol) = self.labels.get_mut(&label) {
+                symbol.kind = symbol_kind;
+            }
+        }
+        self.items.push(Item {
+            line: self.line,
+            address: self.location,
+            kind,
+        });
+        self.location += size as u64;
+    }
+
+    /// Fills up to an `.org` or `.align` boundary without settling pending labels.
+    fn pad(&mut self, count: u64, fill: u8) {
+        if count == 0 {
+            return;
+        }
+        self.items.push(Item {
+            line: self.line,
+            address: self.location,
+            kind: ItemKind::Bytes(vec![fill; count as usize]),
+        });
+        self.location += count;
+    }
+
+    /// Directive argument, which may only use symbols defined above it.
+    fn constant(&self, expr: &str) -> Result<i64, IsaError> {
+        match self.evaluate(expr, self.location)? {
+            Value::Constant(value) => Ok(value),
+            Value::External { symbol, .. } => Err(IsaError::Machine(format!(
+                "external symbol '{symbol}' cannot be used here"
+            ))),
+        }
+    }
+
+    fn evaluate(&self, expr: &str, here: u64) -> Result<Value, IsaError> {
+        let mut parser = ExprParser {
+            assembler: self,
+            text: expr,
+            pos: 0,
+            here,
+        };
+        let value = parser.expr()?;
+        parser.skip_whitespace();
+        if parser.pos < expr.len() {
+            return Err(IsaError::Machine(format!(
+                "unexpected '{}' in expression '{}'",
+                &expr[parser.pos..],
+                expr.trim()
+            )));
+        }
+        Ok(value)
+    }
+
+    fn lookup(&self, name: &str) -> Result<Value, IsaError> {
+        if let Some(symbol) = self.labels.get(name) {
+            return Ok(Value::Constant(symbol.address as i64));
+        }
+        if let Some(value) = self.constants.get(name) {
+            return Ok(Value::Constant(*value));
+        }
+        if self.externs.contains(name) {
+            return Ok(Value::External {
+                symbol: name.to_string(),
+                addend: 0,
+                kind: RelocationKind::Absolute,
+            });
+        }
+        Err(IsaError::Machine(format!("undefined symbol '{name}'")))
+    }
+
+    /// Second pass: encodes every item against the complete symbol table.
+    fn finish(self) -> Result<Program, IsaError> {
+        let endianness = self.machine.code_endianness();
+        let mut bytes = Vec::with_capacity((self.location - self.origin) as usize);
+        let mut relocations = Vec::new();
+        for item in &self.items {
+            let encoded = match &item.kind {
+                ItemKind::Instruction {
+                    instr,
+                    size,
+                    operands,
+                } => self.encode(instr, *size, operands, item.address, &mut relocations),
+                ItemKind::Data { width, values } => {
+                    self.data(*width, values, item.address, endianness, &mut relocations)
+                }
+                ItemKind::Bytes(data) => Ok(data.clone()),
+            };
+            bytes.extend(encoded.map_err(|err| at_line(item.line, err))?);
+        }
+
+        let mut labels = self.labels;
+        let mut starts: Vec<u64> = labels.values().map(|symbol| symbol.address).collect();
+        starts.push(self.location);
+        for symbol in labels.values_mut() {
+            let next = starts
+                .iter()
+                .filter(|start| **start > symbol.address)
+                .min()
+                .copied()
+                .unwrap_or(symbol.address);
+            symbol.size = next - symbol.address;
+        }
+        Ok(Program {
+            origin: self.origin,
+            bytes,
+            symbols: labels,
+            relocations,
+        })
+    }
+
+    fn data(
+        &self,
+        width: usize,
+        values: &[&str],
+        address: u64,
+        endianness: Endianness,
+        relocations: &mut Vec<Relocation>,
+    ) -> Result<Vec<u8>, IsaError> {
+        let mut data = vec![0u8; width * values.len()];
+        for (idx, (chunk, expr)) in data.chunks_mut(width).zip(values).enumerate() {
+            let address = address + (idx * width) as u64;
+            match self.evaluate(expr, address)? {
+                Value::Constant(value) if fits(value, width) => {
+                    write_word(value as u64, chunk, endianness);
+                }
+                Value::Constant(value) => {
+                    return Err(IsaError::Machine(format!(
+                        "value {value} does not fit in {width} byte(s)"
+                    )));
+                }
+                Value::External {
+                    symbol,
+                    addend,
+                    kind,
+                } => relocations.push(Relocation {
+                    address,
+                    size: width,
+                    operand: None,
+                    symbol,
+                    addend,
+                    kind,
+                }),
+            }
+        }
+        Ok(data)
+    }
+
+    fn encode(
+        &self,
+        instr: &Instruction,
+        size: usize,
+        operands: &str,
+        address: u64,
+        relocations: &mut Vec<Relocation>,
+    ) -> Result<Vec<u8>, IsaError> {
+        let operands = self.machine.operand_texts(instr, operands)?;
+        let absolute = self.machine.encodes_absolute(instr);
+        let mut resolved = Vec::with_capacity(operands.fields.len());
+        for (field, raw) in operands.fields {
+            let pc_relative = field.is_pc_relative() && !operands.raw && !absolute;
+            let register = field.register.is_some() || field.kind == OperandKind::Register;
+            let parsed = register
+                .then(|| parse_operand_value(self.machine, field, raw).ok())
+                .flatten();
+            let value = match parsed {
+                Some(value) => value,
+                None => match self.evaluate(raw, address)? {
+                    Value::Constant(target) if pc_relative => target.wrapping_sub(address as i64),
+                    Value::Constant(value) => value,
+                    Value::External {
+                        symbol,
+                        addend,
+                        kind,
+                    } => {
+                        let kind = match kind {
+                            RelocationKind::Absolute if pc_relative => RelocationKind::PcRelative,
+                            kind => kind,
+                        };
+                        relocations.push(Relocation {
+                            address,
+                            size,
+                            operand: Some(field.name.clone()),
+                            symbol,
+                            addend,
+                            kind,
+                        });
+                        0
+                    }
+                },
+            };
+            resolved.push((field.name.clone(), value));
+        }
+        let mut instr = instr;
+        if instr.alias.is_some() {
+            instr = self.machine.expand_alias(instr, &mut resolved)?;
+        }
+        let pairs: Vec<(&str, i64)> = resolved
+            .iter()
+            .map(|(name, value)| (name.as_str(), *value))
+            .collect();
+        self.machine.encode_with_operands(instr, &pairs)
+    }
+}
+
+/// Recursive-descent evaluator with C operator precedence.
+struct ExprParser<'p, 'a> {
+    assembler: &'p Assembler<'a>,
+    text: &'p str,
+    pos: usize,
+    /// Value of `.`.
+    here: u64,
+}
+
+impl<'p> ExprParser<'p, '_> {
+    fn expr(&mut self) -> Result<Value, IsaError> {
+        self.binary(0)
+    }
+
+    /// Operators from loosest to tightest binding.
+    const LEVELS: [&'static [&'static str]; 6] = [
+        &["|"],
+        &["^"],
+        &["&"],
+        &["<<", ">>"],
+        &["+", "-"],
+        &["*", "/", "%"],
+    ];
+
+    fn binary(&mut self, level: usize) -> Result<Value, IsaError> {
+        if level == Self::LEVELS.len() {
+            return self.unary();
+        }
+        let mut lhs = self.binary(level + 1)?;
+        while let Some(op) = self.operator(Self::LEVELS[level]) {
+            let rhs = self.binary(level + 1)?;
+            lhs = combine(op, lhs, rhs)?;
+        }
+        Ok(lhs)
+    }
+
+    fn operator(&mut self, candidates: &[&'static str]) -> Option<&'static str> {
+        self.skip_whitespace();
+        let rest = &self.text[self.pos..];
+        let op = candidates.iter().find(|op| rest.starts_with(**op))?;
+        self.pos += op.len();
+        Some(op)
+    }
+
+    fn unary(&mut self) -> Result<Value, IsaError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('-') => {
+                self.pos += 1;
+                match self.unary()? {
+                    Value::Constant(value) => Ok(Value::Constant(value.wrapping_neg())),
+                    value => Err(external_error(&value)),
+                }
+            }
+            Some('~') => {
+                self.pos += 1;
+                match self.unary()? {
+                    Value::Constant(value) => Ok(Value::Constant(!value)),
+                    value => Err(external_error(&value)),
+                }
+            }
+            Some('+') => {
+                self.pos += 1;
+                self.unary()
+            }
+            _ => self.postfix(),
+        }
+    }
+
+    /// Primary followed by an optional `@h`, `@l` or `@ha`.
+    fn postfix(&mut self) -> Result<Value, IsaError> {
+        let value = self.primary()?;
+        if self.peek() != Some('@') {
+            return Ok(value);
+        }
+        self.pos += 1;
+        let suffix = self.identifier();
+        let kind = match suffix.to_ascii_lowercase().as_str() {
+            "l" => RelocationKind::Low,
+            "h" => RelocationKind::High,
+            "ha" => RelocationKind::HighAdjusted,
+            _ => {
+                return Err(IsaError::Machine(format!(
+                    "unknown operator '@{suffix}'; expected @h, @l or @ha"
+                )));
+            }
+        };
+        half(kind, value)
+    }
+
+    fn primary(&mut self) -> Result<Value, IsaError> {
+        self.skip_whitespace();
+        let Some(ch) = self.peek() else {
+            return Err(IsaError::Machine(format!(
+                "expression '{}' ends early",
+                self.text.trim()
+            )));
+        };
+        if ch == '(' {
+            self.pos += 1;
+            let value = self.expr()?;
+            self.expect(')')?;
+            return Ok(value);
+        }
+        if ch.is_ascii_digit() {
+            let start = self.pos;
+            while self
+                .peek()
+                .is_some_and(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+            {
+                self.pos += 1;
+            }
+            return parse_numeric(&self.text[start..self.pos]).map(Value::Constant);
+        }
+        let name = self.identifier();
+        match name {
+            "" => Err(IsaError::Machine(format!(
+                "unexpected '{ch}' in expression '{}'",
+                self.text.trim()
+            ))),
+            "." => Ok(Value::Constant(self.here as i64)),
+            "hi" | "lo" | "ha" if self.peek_after_whitespace() == Some('(') => {
+                self.skip_whitespace();
+                self.pos += 1;
+                let value = self.expr()?;
+                self.expect(')')?;
+                let kind = match name {
+                    "lo" => RelocationKind::Low,
+                    "hi" => RelocationKind::High,
+                    _ => RelocationKind::HighAdjusted,
+                };
+                half(kind, value)
+            }
+            name => self.assembler.lookup(name),
+        }
+    }
+
+    fn identifier(&mut self) -> &'p str {
+        let start = self.pos;
+        while self.peek().is_some_and(is_identifier_char) {
+            self.pos += 1;
+        }
+        &self.text[start..self.pos]
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), IsaError> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            return Ok(());
+        }
+        Err(IsaError::Machine(format!(
+            "expected '{expected}' in expression '{}'",
+            self.text.trim()
+        )))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn peek_after_whitespace(&self) -> Option<char> {
+        self.text[self.pos..].trim_start().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+}
+
+fn combine(op: &str, lhs: Value, rhs: Value) -> Result<Value, IsaError> {
+    match (lhs, rhs) {
+        (Value::Constant(a), Value::Constant(b)) => {
+            let value = match op {
+                "|" => a | b,
+                "^" => a ^ b,
+                "&" => a & b,
+                "<<" => a.wrapping_shl(b as u32),
+                ">>" => a.wrapping_shr(b as u32),
+                "+" => a.wrapping_add(b),
+                "-" => a.wrapping_sub(b),
+                "*" => a.wrapping_mul(b),
+                "/" | "%" if b == 0 => {
+                    return Err(IsaError::Machine("division by zero in expression".into()));
+                }
+                "/" => a.wrapping_div(b),
+                _ => a.wrapping_rem(b),
+            };
+            Ok(Value::Constant(value))
+        }
+        (
+            Value::External {
+                symbol,
+                addend,
+                kind: RelocationKind::Absolute,
+            },
+            Value::Constant(offset),
+        ) if op == "+" || op == "-" => Ok(Value::External {
+            symbol,
+            addend: if op == "+" {
+                addend.wrapping_add(offset)
+            } else {
+                addend.wrapping_sub(offset)
+            },
+            kind: RelocationKind::Absolute,
+        }),
+        (
+            Value::Constant(offset),
+            Value::External {
+                symbol,
+                addend,
+                kind: RelocationKind::Absolute,
+            },
+        ) if op == "+" => Ok(Value::External {
+            symbol,
+            addend: addend.wrapping_add(offset),
+            kind: RelocationKind::Absolute,
+        }),
+        (value @ Value::External { .. }, _) | (_, value @ Value::External { .. }) => {
+            Err(external_error(&value))
+        }
+    }
+}
+
+/// Applies a 16-bit half operator to a constant, or records it on an external reference.
+fn half(kind: RelocationKind, value: Value) -> Result<Value, IsaError> {
+    match value {
+        Value::Constant(value) => Ok(Value::Constant(match kind {
+            RelocationKind::Low => value & 0xFFFF,
+            RelocationKind::High => (value >> 16) & 0xFFFF,
+            _ => (value.wrapping_add(0x8000) >> 16) & 0xFFFF,
+        })),
+        Value::External {
+            symbol,
+            addend,
+            kind: RelocationKind::Absolute,
+        } => Ok(Value::External {
+            symbol,
+            addend,
+            kind,
+        }),
+        value => Err(external_error(&value)),
+    }
+}
+
+fn external_error(value: &Value) -> IsaError {
+    let symbol = match value {
+        Value::External { symbol, .. } => symbol.as_str(),
+        Value::Constant(_) => "",
+    };
+    IsaError::Machine(format!(
+        "external symbol '{symbol}' only supports symbol +/- constant, optionally under @h/@l/@ha"
+    ))
+}
+
+/// Whether `value` fits `width` bytes as a signed or unsigned number.
+fn fits(value: i64, width: usize) -> bool {
+    let bits = width * 8;
+    bits >= 64 || (value >= -(1i64 << (bits - 1)) && value < (1i64 << bits))
+}
+
+fn at_line(line: usize, err: IsaError) -> IsaError {
+    match err {
+        IsaError::Machine(message) => IsaError::Machine(format!("line {line}: {message}")),
+        other => IsaError::Machine(format!("line {line}: {other}")),
+    }
+}
+
+/// Drops a `#` or `//` comment outside string literals.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (idx, ch) in line.char_indices() {
+        if in_string {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match ch {
+            '"' => in_string = true,
+            '#' => return &line[..idx],
+            '/' if line[idx + 1..].starts_with('/') => return &line[..idx],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// `name:` at the start of `text`, with the rest of the line.
+fn split_label(text: &str) -> Option<(&str, &str)> {
+    let end = text
+        .char_indices()
+        .find(|(_, ch)| !is_identifier_char(*ch))
+        .map_or(text.len(), |(idx, _)| idx);
+    let name = &text[..end];
+    let rest = text[end..].strip_prefix(':')?;
+    is_identifier(name).then_some((name, rest))
+}
+
+fn parse_string(literal: &str) -> Result<Vec<u8>, IsaError> {
+    let inner = literal
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .filter(|_| literal.len() >= 2)
+        .ok_or_else(|| IsaError::Machine(format!("expected a string literal, got '{literal}'")))?;
+    let mut bytes = Vec::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            let mut buffer = [0u8; 4];
+            bytes.extend_from_slice(ch.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+        let byte = match chars.next() {
+            Some('n') => b'\n',
+            Some('r') => b'\r',
+            Some('t') => b'\t',
+            Some('0') => 0,
+            Some('\\') => b'\\',
+            Some('"') => b'"',
+            Some('x') => {
+                let digits: String = chars.by_ref().take(2).collect();
+                u8::from_str_radix(&digits, 16).map_err(|_| {
+                    IsaError::Machine(format!("invalid escape '\\x{digits}' in {literal}"))
+                })?
+            }
+            other => {
+                return Err(IsaError::Machine(format!(
+                    "invalid escape '\\{}' in {literal}",
+                    other.map(String::from).unwrap_or_default()
+                )));
+            }
+        };
+        bytes.push(byte);
+    }
+    Ok(bytes)
+}
+
+fn is_identifier_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || matches!(ch, '_' | '.' | '$')
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.chars().next().is_some_and(|ch| !ch.is_ascii_digit())
+        && name.chars().all(is_identifier_char)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comments_and_lists_respect_strings_and_parentheses() {
+        assert_eq!(strip_comment(r#".ascii "a#b" # note"#), r#".ascii "a#b" "#);
+        assert_eq!(strip_comment("nop // note"), "nop ");
+        assert_eq!(
+            split_list(r#"hi(a, b), "x,y", 3"#).expect("split"),
+            vec!["hi(a, b)", r#""x,y""#, "3"]
+        );
+        assert!(split_list("1,,2").is_err());
+        assert_eq!(split_label("loop: bdnz loop"), Some(("loop", " bdnz loop")));
+        assert_eq!(split_label("li r3, 0"), None);
+    }
+
+    #[test]
+    fn strings_decode_escapes() {
+        assert_eq!(
+            parse_string(r#""a\n\x41\0""#).expect("string"),
+            b"a\nA\0".to_vec()
+        );
+        assert!(parse_string(r#""\q""#).is_err());
+    }
+
+    #[test]
+    fn expressions_follow_precedence_and_half_operators() {
+        let machine = MachineDescription::default();
+        let mut assembler = Assembler::new(&machine, 0x1000);
+        assembler.constants.insert("base".into(), 0x1234_9000);
+        assembler.externs.insert("ext".into());
+        let eval = |expr| assembler.evaluate(expr, 0x1004).expect(expr);
+        assert_eq!(eval("1 + 2 * 3 << 1"), Value::Constant(14));
+        assert_eq!(eval("-(4 | 1) & ~0"), Value::Constant(-5));
+        assert_eq!(eval(". - 4"), Value::Constant(0x1000));
+        assert_eq!(eval("base@ha"), Value::Constant(0x1235));
+        assert_eq!(eval("hi(base)"), Value::Constant(0x1234));
+        assert_eq!(eval("lo(base)"), Value::Constant(0x9000));
+        assert_eq!(
+            eval("ha(ext + 8)"),
+            Value::External {
+                symbol: "ext".into(),
+                addend: 8,
+                kind: RelocationKind::HighAdjusted,
+            }
+        );
+        assert!(assembler.evaluate("ext * 2", 0).is_err());
+        assert!(assembler.evaluate("missing", 0).is_err());
+        assert!(assembler.evaluate("1 / 0", 0).is_err());
+    }
+}
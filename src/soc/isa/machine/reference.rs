@@ -0,0 +1,884 @@
+//! Reference manual generator. Builds a small document model from a [`MachineDescription`]
+//! (parameters, register maps, forms, per-instruction encoding diagrams, operand tables and
+//! semantics) and renders it as Markdown or standalone HTML.
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use crate::soc::isa::ast::{AliasValue, MaskSelector, ParameterValue, SpaceKind};
+use crate::soc::prog::types::BitFieldSegment;
+
+use super::MachineDescription;
+use super::instruction::{Instruction, InstructionPattern};
+use super::register::RegisterInfo;
+use super::space::{FieldEncoding, FormInfo, OperandKind, SpaceInfo};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceFormat {
+    Markdown,
+    Html,
+}
+
+impl MachineDescription {
+    /// Renders a browsable reference for every space, register, form and instruction.
+    pub fn reference_manual(&self, title: &str, format: ReferenceFormat) -> String {
+        let doc = ManualBuilder::new(self).build(title);
+        match format {
+            ReferenceFormat::Markdown => doc.to_markdown(),
+            ReferenceFormat::Html => doc.to_html(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Inline {
+    Text(String),
+    Code(String),
+    Link { text: String, anchor: String },
+}
+
+type Cell = Vec<Inline>;
+
+#[derive(Debug)]
+enum Block {
+    Heading {
+        level: u8,
+        text: String,
+        anchor: String,
+    },
+    Paragraph(Vec<Inline>),
+    List(Vec<Vec<Inline>>),
+    Table {
+        header: Vec<String>,
+        rows: Vec<Vec<Cell>>,
+    },
+    Code(String),
+}
+
+struct Document {
+    title: String,
+    blocks: Vec<Block>,
+}
+
+fn text(value: impl Into<String>) -> Inline {
+    Inline::Text(value.into())
+}
+
+fn code(value: impl Into<String>) -> Inline {
+    Inline::Code(value.into())
+}
+
+fn cell(value: impl Into<String>) -> Cell {
+    let value = value.into();
+    if value.is_empty() {
+        Vec::new()
+    } else {
+        vec![text(value)]
+    }
+}
+
+fn code_cell(value: impl Into<String>) -> Cell {
+    vec![code(value)]
+}
+
+struct ManualBuilder<'a> {
+    machine: &'a MachineDescription,
+    blocks: Vec<Block>,
+    anchors: BTreeSet<String>,
+}
+
+impl<'a> ManualBuilder<'a> {
+    fn new(machine: &'a MachineDescription) -> Self {
+        Self {
+            machine,
+            blocks: Vec::new(),
+            anchors: BTreeSet::new(),
+        }
+    }
+
+    fn build(mut self, title: &str) -> Document {
+        let instruction_anchors: Vec<String> = self
+            .machine
+            .instructions
+            .iter()
+            .map(|instr| self.reserve_anchor(&format!("{}-{}", instr.space, instr.name)))
+            .collect();
+
+        self.contents();
+        self.parameters();
+        self.spaces();
+        for space in self.machine.spaces.values() {
+            if space.kind == SpaceKind::Register {
+                self.register_space(space);
+            }
+        }
+        for space in self.machine.spaces.values() {
+            if space.kind == SpaceKind::Logic {
+                self.logic_space(space, &instruction_anchors);
+            }
+        }
+        self.macros();
+        Document {
+            title: title.to_string(),
+            blocks: self.blocks,
+        }
+    }
+
+    fn reserve_anchor(&mut self, name: &str) -> String {
+        let base = slug(name);
+        let mut anchor = base.clone();
+        let mut suffix = 1;
+        while !self.anchors.insert(anchor.clone()) {
+            suffix += 1;
+            anchor = format!("{base}-{suffix}");
+        }
+        anchor
+    }
+
+    fn heading(&mut self, level: u8, title: impl Into<String>, anchor: &str) {
+        self.blocks.push(Block::Heading {
+            level,
+            text: title.into(),
+            anchor: anchor.to_string(),
+        });
+    }
+
+    fn section(&mut self, level: u8, title: impl Into<String>) {
+        let title = title.into();
+        let anchor = self.reserve_anchor(&title);
+        self.heading(level, title, &anchor);
+    }
+
+    fn contents(&mut self) {
+        let mut entries = vec![section_link("Parameters"), section_link("Spaces")];
+        for space in self.machine.spaces.values() {
+            if space.kind == SpaceKind::Register {
+                entries.push(section_link(&register_title(space)));
+            }
+        }
+        for space in self.machine.spaces.values() {
+            if space.kind == SpaceKind::Logic {
+                entries.push(section_link(&forms_title(space)));
+                entries.push(section_link(&instructions_title(space)));
+            }
+        }
+        if !self.machine.macros.is_empty() {
+            entries.push(section_link("Macros"));
+        }
+        self.blocks.push(Block::List(entries));
+    }
+
+    fn parameters(&mut self) {
+        self.section(2, "Parameters");
+        let rows = self
+            .machine
+            .parameters
+            .iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    ParameterValue::Word(word) => word.clone(),
+                    ParameterValue::Number(number) => number.to_string(),
+                };
+                vec![code_cell(name), cell(value)]
+            })
+            .collect();
+        self.table(&["Name", "Value"], rows);
+    }
+
+    fn spaces(&mut self) {
+        self.section(2, "Spaces");
+        let rows = self
+            .machine
+            .spaces
+            .values()
+            .map(|space| {
+                vec![
+                    code_cell(&space.name),
+                    cell(space_kind_label(&space.kind)),
+                    cell(
+                        space
+                            .size_bits
+                            .map(|bits| bits.to_string())
+                            .unwrap_or_default(),
+                    ),
+                    cell(format!("{:?}", space.endianness).to_ascii_lowercase()),
+                ]
+            })
+            .collect();
+        self.table(&["Space", "Kind", "Word bits", "Endianness"], rows);
+    }
+
+    fn register_space(&mut self, space: &SpaceInfo) {
+        self.section(2, register_title(space));
+        let default_bits = space.size_bits.unwrap_or(64);
+        let (aliases, registers): (Vec<&RegisterInfo>, Vec<&RegisterInfo>) = space
+            .registers
+            .values()
+            .partition(|info| info.redirect.is_some());
+
+        let rows = registers
+            .iter()
+            .map(|info| {
+                let name = match &info.range {
+                    Some(range) => format!("{}[{}..{}]", info.name, range.start, range.end),
+                    None => info.name.clone(),
+                };
+                vec![
+                    code_cell(name),
+                    cell(info.size_bits.unwrap_or(default_bits).to_string()),
+                    cell(
+                        info.offset
+                            .map(|offset| format!("0x{offset:X}"))
+                            .unwrap_or_default(),
+                    ),
+                    cell(
+                        info.reset
+                            .map(|reset| format!("0x{reset:X}"))
+                            .unwrap_or_default(),
+                    ),
+                    cell(info.description.clone().unwrap_or_default()),
+                ]
+            })
+            .collect();
+        self.table(
+            &["Register", "Bits", "Offset", "Reset", "Description"],
+            rows,
+        );
+
+        if !aliases.is_empty() {
+            self.section(3, format!("{} aliases", space.name));
+            let rows = aliases
+                .iter()
+                .map(|info| {
+                    let target = info
+                        .redirect
+                        .as_ref()
+                        .map(|reference| reference.segments.join("::"))
+                        .unwrap_or_default();
+                    vec![
+                        code_cell(&info.name),
+                        code_cell(target),
+                        cell(info.description.clone().unwrap_or_default()),
+                    ]
+                })
+                .collect();
+            self.table(&["Alias", "Redirects to", "Description"], rows);
+        }
+
+        for info in space.registers.values() {
+            if info.subfields.is_empty() {
+                continue;
+            }
+            self.section(3, format!("{}::{}", space.name, info.name));
+            let mut about = Vec::new();
+            if let Some(description) = &info.description {
+                about.push(text(format!("{description}. ")));
+            }
+            if let Some(reference) = &info.redirect {
+                about.push(text("Alias of "));
+                about.push(code(reference.segments.join("::")));
+                about.push(text("."));
+            }
+            if !about.is_empty() {
+                self.blocks.push(Block::Paragraph(about));
+            }
+            let rows = info
+                .subfields
+                .iter()
+                .map(|sub| {
+                    vec![
+                        code_cell(&sub.name),
+                        code_cell(&sub.bit_spec),
+                        cell(
+                            sub.reset
+                                .map(|reset| format!("0x{reset:X}"))
+                                .unwrap_or_default(),
+                        ),
+                        cell(sub.description.clone().unwrap_or_default()),
+                    ]
+                })
+                .collect();
+            self.table(&["Field", "Bits", "Reset", "Description"], rows);
+        }
+    }
+
+    fn logic_space(&mut self, space: &SpaceInfo, instruction_anchors: &[String]) {
+        let word_bits = space.size_bits.unwrap_or(0);
+        self.section(2, forms_title(space));
+        for (name, form) in &space.forms {
+            self.section(3, format!("{}::{}", space.name, name));
+            if let Some(description) = &form.description {
+                self.blocks
+                    .push(Block::Paragraph(vec![text(description.clone())]));
+            }
+            let rows = form
+                .field_iter()
+                .map(|field| field_row(field, word_bits))
+                .collect();
+            self.table(&["Field", "Bits", "Kind", "Description"], rows);
+        }
+
+        self.section(2, instructions_title(space));
+        let members: Vec<(&Instruction, &String)> = self
+            .machine
+            .instructions
+            .iter()
+            .zip(instruction_anchors)
+            .filter(|(instr, _)| instr.space == space.name)
+            .collect();
+        let mut index = Vec::new();
+        for (position, (instr, anchor)) in members.iter().enumerate() {
+            if position > 0 {
+                index.push(text(" · "));
+            }
+            index.push(Inline::Link {
+                text: instr.name.clone(),
+                anchor: (*anchor).clone(),
+            });
+        }
+        self.blocks.push(Block::Paragraph(index));
+
+        for (instr, anchor) in members {
+            self.instruction(space, instr, anchor);
+        }
+    }
+
+    fn instruction(&mut self, space: &SpaceInfo, instr: &Instruction, anchor: &str) {
+        let title = match &instr.description {
+            Some(description) => format!("{} — {description}", instr.name),
+            None => instr.name.clone(),
+        };
+        self.heading(3, title, anchor);
+
+        let form = instr.form.as_ref().and_then(|name| space.forms.get(name));
+        let operands: Vec<String> = if !instr.operands.is_empty() {
+            instr.operands.clone()
+        } else {
+            form.map(|form| form.operand_order.clone())
+                .unwrap_or_default()
+        };
+
+        let mut summary = vec![text("Syntax: "), code(syntax(instr, &operands))];
+        if let Some(form_name) = &instr.form {
+            summary.push(text(" · Form: "));
+            summary.push(Inline::Link {
+                text: form_name.clone(),
+                anchor: slug(&format!("{}::{form_name}", space.name)),
+            });
+        }
+        if let Some(operator) = &instr.operator {
+            summary.push(text(" · Operator: "));
+            summary.push(code(operator.clone()));
+        }
+        if let Some(target) = &instr.extend {
+            summary.push(text(" · Extends to: "));
+            summary.push(Inline::Link {
+                text: target.clone(),
+                anchor: slug(&instructions_title_for(target)),
+            });
+        }
+        if let Some(alias) = &instr.alias {
+            summary.push(text(" · Alias of: "));
+            summary.push(code(alias.base.clone()));
+            if !alias.constraints.is_empty() {
+                let pinned: Vec<String> = alias
+                    .constraints
+                    .iter()
+                    .map(|constraint| match &constraint.value {
+                        AliasValue::Number(value) => format!("{}={value}", constraint.field),
+                        AliasValue::Field(other) => format!("{}=#{other}", constraint.field),
+                    })
+                    .collect();
+                summary.push(text(" when "));
+                summary.push(code(pinned.join(", ")));
+            }
+        }
+        self.blocks.push(Block::Paragraph(summary));
+
+        let word_bits = space.size_bits.unwrap_or(0);
+        let pattern = self.machine.patterns.iter().find(|pattern| {
+            std::ptr::eq(&self.machine.instructions[pattern.instruction_idx], instr)
+        });
+        if let (Some(form), true) = (form, word_bits > 0) {
+            let cells = encoding_cells(form, word_bits, pattern);
+            self.blocks.push(Block::Table {
+                header: cells
+                    .iter()
+                    .map(|cell| {
+                        if cell.start == cell.end {
+                            cell.start.to_string()
+                        } else {
+                            format!("{}..{}", cell.start, cell.end)
+                        }
+                    })
+                    .collect(),
+                rows: vec![
+                    cells.iter().map(|c| self::cell(c.label.clone())).collect(),
+                    cells.iter().map(|c| code_cell(c.bits.clone())).collect(),
+                ],
+            });
+        }
+
+        if let Some(mask) = &instr.mask {
+            let fixed: Vec<String> = mask
+                .fields
+                .iter()
+                .map(|field| match &field.selector {
+                    MaskSelector::Field(name) => format!("{name}={}", field.value),
+                    MaskSelector::BitExpr(bits) => format!("{bits}={}", field.value),
+                })
+                .collect();
+            let mut line = vec![text("Fixed fields: "), code(fixed.join(", "))];
+            if let Some(pattern) = pattern {
+                line.push(text(" · Match: "));
+                line.push(code(format!(
+                    "(word & 0x{:X}) == 0x{:X}",
+                    pattern.mask, pattern.value
+                )));
+            }
+            self.blocks.push(Block::Paragraph(line));
+        }
+
+        if let Some(form) = form {
+            let rows: Vec<Vec<Cell>> = operands
+                .iter()
+                .filter_map(|name| form.subfield(name))
+                .map(|field| field_row(field, word_bits))
+                .collect();
+            if !rows.is_empty() {
+                self.table(&["Operand", "Bits", "Kind", "Description"], rows);
+            }
+        }
+
+        if let Some(semantics) = &instr.semantics {
+            let source = dedent(&semantics.source);
+            if !source.is_empty() {
+                self.blocks.push(Block::Code(source));
+            }
+        }
+    }
+
+    fn macros(&mut self) {
+        if self.machine.macros.is_empty() {
+            return;
+        }
+        self.section(2, "Macros");
+        for mac in &self.machine.macros {
+            self.section(3, format!("{}({})", mac.name, mac.parameters.join(", ")));
+            self.blocks.push(Block::Code(dedent(&mac.semantics.source)));
+        }
+    }
+
+    fn table(&mut self, header: &[&str], rows: Vec<Vec<Cell>>) {
+        if rows.is_empty() {
+            self.blocks.push(Block::Paragraph(vec![text("None.")]));
+            return;
+        }
+        self.blocks.push(Block::Table {
+            header: header.iter().map(|title| title.to_string()).collect(),
+            rows,
+        });
+    }
+}
+
+fn section_link(title: &str) -> Vec<Inline> {
+    vec![Inline::Link {
+        text: title.to_string(),
+        anchor: slug(title),
+    }]
+}
+
+fn register_title(space: &SpaceInfo) -> String {
+    format!("Registers ({})", space.name)
+}
+
+fn forms_title(space: &SpaceInfo) -> String {
+    format!("Forms ({})", space.name)
+}
+
+fn instructions_title(space: &SpaceInfo) -> String {
+    instructions_title_for(&space.name)
+}
+
+fn instructions_title_for(space: &str) -> String {
+    format!("Instructions ({space})")
+}
+
+fn space_kind_label(kind: &SpaceKind) -> &'static str {
+    match kind {
+        SpaceKind::ReadWrite => "rw",
+        SpaceKind::ReadOnly => "ro",
+        SpaceKind::MemoryMappedIo => "memio",
+        SpaceKind::Register => "register",
+        SpaceKind::Logic => "logic",
+    }
+}
+
+fn syntax(instr: &Instruction, operands: &[String]) -> String {
+    if operands.is_empty() {
+        instr.name.clone()
+    } else {
+        format!("{} {}", instr.name, operands.join(", "))
+    }
+}
+
+fn field_row(field: &FieldEncoding, word_bits: u32) -> Vec<Cell> {
+    let kind = match (&field.register, field.kind) {
+        (Some(binding), _) => vec![
+            text("register "),
+            code(format!("{}::{}", binding.space, binding.field)),
+        ],
+        (None, _) if field.is_pc_relative() => cell("PC-relative target"),
+        (None, OperandKind::Immediate) if field.spec.is_signed() => cell("signed immediate"),
+        (None, OperandKind::Immediate) => cell("immediate"),
+        (None, OperandKind::Other) if field.is_function_only() => cell("opcode"),
+        (None, OperandKind::Other) | (None, OperandKind::Register) => cell(
+            field
+                .operations
+                .iter()
+                .map(|op| op.kind.as_str())
+                .collect::<Vec<_>>()
+                .join("|"),
+        ),
+    };
+    vec![
+        code_cell(&field.name),
+        code_cell(field.spec.to_spec_string(word_bits as u16)),
+        kind,
+        cell(field.description.clone().unwrap_or_default()),
+    ]
+}
+
+/// One column of an encoding diagram, using MSB-zero bit numbering.
+#[derive(Debug, PartialEq)]
+struct DiagramCell {
+    start: u32,
+    end: u32,
+    label: String,
+    bits: String,
+}
+
+/// Splits the instruction word at every subfield boundary, labels each run with the subfields
+/// covering it, and shows the bits fixed by the decode pattern (`-` marks free bits).
+fn encoding_cells(
+    form: &FormInfo,
+    word_bits: u32,
+    pattern: Option<&InstructionPattern>,
+) -> Vec<DiagramCell> {
+    let mut ranges: Vec<(&str, u32, u32)> = Vec::new();
+    for field in form.field_iter() {
+        for segment in &field.spec.segments {
+            if let BitFieldSegment::Slice(slice) = segment {
+                let offset = u32::from(slice.offset);
+                let end = word_bits - 1 - offset;
+                let start = end + 1 - u32::from(slice.width);
+                ranges.push((field.name.as_str(), start, end));
+            }
+        }
+    }
+    let mut boundaries: BTreeSet<u32> = [0, word_bits].into_iter().collect();
+    for (_, start, end) in &ranges {
+        boundaries.insert(*start);
+        boundaries.insert(end + 1);
+    }
+    let boundaries: Vec<u32> = boundaries.into_iter().collect();
+
+    let mut cells: Vec<DiagramCell> = Vec::new();
+    for window in boundaries.windows(2) {
+        let (start, end) = (window[0], window[1] - 1);
+        let mut names: Vec<&str> = Vec::new();
+        for (name, from, to) in &ranges {
+            if *from <= start && end <= *to && !names.contains(name) {
+                names.push(name);
+            }
+        }
+        let label = names.join("/");
+        let bits: String = (start..=end)
+            .map(|bit| {
+                let shift = word_bits - 1 - bit;
+                match pattern {
+                    Some(pattern) if (pattern.mask >> shift) & 1 == 1 => {
+                        if (pattern.value >> shift) & 1 == 1 {
+                            '1'
+                        } else {
+                            '0'
+                        }
+                    }
+                    _ => '-',
+                }
+            })
+            .collect();
+        match cells.last_mut() {
+            Some(last) if last.label == label && !label.is_empty() => {
+                last.end = end;
+                last.bits.push_str(&bits);
+            }
+            _ => cells.push(DiagramCell {
+                start,
+                end,
+                label,
+                bits,
+            }),
+        }
+    }
+    cells
+}
+
+fn slug(title: &str) -> String {
+    let mut slug = String::new();
+    for ch in title.chars() {
+        if ch.is_ascii_alphanumeric() {
+            slug.push(ch.to_ascii_lowercase());
+        } else if ch == '.' {
+            slug.push_str("-dot");
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_matches('-').to_string()
+}
+
+/// Strips the indentation shared by every non-blank line of a semantics block.
+fn dedent(source: &str) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or("").trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_matches('\n')
+        .to_string()
+}
+
+impl Document {
+    fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n", self.title);
+        for block in &self.blocks {
+            out.push('\n');
+            match block {
+                Block::Heading {
+                    level,
+                    text,
+                    anchor,
+                } => {
+                    let _ = writeln!(
+                        out,
+                        "<a id=\"{anchor}\"></a>\n{} {}",
+                        "#".repeat(usize::from(*level)),
+                        markdown_text(text)
+                    );
+                }
+                Block::Paragraph(inlines) => {
+                    let _ = writeln!(out, "{}", markdown_inlines(inlines));
+                }
+                Block::List(items) => {
+                    for item in items {
+                        let _ = writeln!(out, "- {}", markdown_inlines(item));
+                    }
+                }
+                Block::Table { header, rows } => {
+                    let header: Vec<String> = header.iter().map(|h| markdown_text(h)).collect();
+                    let _ = writeln!(out, "| {} |", header.join(" | "));
+                    let _ = writeln!(out, "|{}", " --- |".repeat(header.len()));
+                    for row in rows {
+                        let cells: Vec<String> =
+                            row.iter().map(|cell| markdown_inlines(cell)).collect();
+                        let _ = writeln!(out, "| {} |", cells.join(" | "));
+                    }
+                }
+                Block::Code(source) => {
+                    let _ = writeln!(out, "```\n{source}\n```");
+                }
+            }
+        }
+        out
+    }
+
+    fn to_html(&self) -> String {
+        let mut out = String::new();
+        let _ = write!(
+            out,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n{HTML_STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n",
+            title = html_escape(&self.title)
+        );
+        for block in &self.blocks {
+            match block {
+                Block::Heading {
+                    level,
+                    text,
+                    anchor,
+                } => {
+                    let _ = writeln!(
+                        out,
+                        "<h{level} id=\"{anchor}\">{}</h{level}>",
+                        html_escape(text)
+                    );
+                }
+                Block::Paragraph(inlines) => {
+                    let _ = writeln!(out, "<p>{}</p>", html_inlines(inlines));
+                }
+                Block::List(items) => {
+                    out.push_str("<ul>\n");
+                    for item in items {
+                        let _ = writeln!(out, "<li>{}</li>", html_inlines(item));
+                    }
+                    out.push_str("</ul>\n");
+                }
+                Block::Table { header, rows } => {
+                    out.push_str("<table>\n<tr>");
+                    for title in header {
+                        let _ = write!(out, "<th>{}</th>", html_escape(title));
+                    }
+                    out.push_str("</tr>\n");
+                    for row in rows {
+                        out.push_str("<tr>");
+                        for cell in row {
+                            let _ = write!(out, "<td>{}</td>", html_inlines(cell));
+                        }
+                        out.push_str("</tr>\n");
+                    }
+                    out.push_str("</table>\n");
+                }
+                Block::Code(source) => {
+                    let _ = writeln!(out, "<pre><code>{}</code></pre>", html_escape(source));
+                }
+            }
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+const HTML_STYLE: &str = "body { font-family: sans-serif; max-width: 72em; margin: 2em auto; }\n\
+table { border-collapse: collapse; margin: 0.5em 0; }\n\
+th, td { border: 1px solid #999; padding: 0.2em 0.6em; text-align: left; }\n\
+pre { background: #f4f4f4; padding: 0.6em; }\n";
+
+fn markdown_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '\\' | '*' | '_' | '|' | '<' | '[' | ']' | '`' => {
+                out.push('\\');
+                out.push(ch);
+            }
+            '\n' => out.push(' '),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+fn markdown_inlines(inlines: &[Inline]) -> String {
+    inlines
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text(value) => markdown_text(value),
+            Inline::Code(value) => {
+                let value = value.replace('|', "\\|");
+                if value.contains('`') {
+                    format!("`` {value} ``")
+                } else {
+                    format!("`{value}`")
+                }
+            }
+            Inline::Link { text, anchor } => format!("[{}](#{anchor})", markdown_text(text)),
+        })
+        .collect()
+}
+
+fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html_inlines(inlines: &[Inline]) -> String {
+    inlines
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text(value) => html_escape(value),
+            Inline::Code(value) => format!("<code>{}</code>", html_escape(value)),
+            Inline::Link { text, anchor } => {
+                format!("<a href=\"#{anchor}\">{}</a>", html_escape(text))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::isa::IsaLoader;
+    use std::path::PathBuf;
+
+    fn e200() -> MachineDescription {
+        let coredef = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("defs/powerpc/e200.coredef");
+        IsaLoader::new().load_machine(coredef).expect("load e200")
+    }
+
+    #[test]
+    fn diagram_splits_word_at_field_boundaries() {
+        let machine = e200();
+        let add = machine
+            .instructions
+            .iter()
+            .position(|instr| instr.name == "add")
+            .expect("add");
+        let pattern = machine
+            .patterns
+            .iter()
+            .find(|pattern| pattern.instruction_idx == add)
+            .expect("pattern");
+        let form = &machine.spaces["insn"].forms["X_Form"];
+        let cells = encoding_cells(form, 32, Some(pattern));
+        assert_eq!(
+            cells[0],
+            DiagramCell {
+                start: 0,
+                end: 5,
+                label: "OPCD".into(),
+                bits: "011111".into(),
+            }
+        );
+        assert_eq!(cells[1].label, "RT");
+        assert_eq!(cells[1].bits, "-----");
+        assert_eq!(cells.last().map(|cell| cell.end), Some(31));
+        let covered: u32 = cells.iter().map(|cell| cell.end - cell.start + 1).sum();
+        assert_eq!(covered, 32);
+    }
+
+    #[test]
+    fn markdown_manual_covers_registers_forms_and_instructions() {
+        let manual = e200().reference_manual("e200", ReferenceFormat::Markdown);
+        assert!(manual.starts_with("# e200\n"));
+        assert!(manual.contains("## Registers (reg)"));
+        assert!(manual.contains("| `SO` | `@(32)` |  | Summary Overflow |"));
+        assert!(manual.contains("| `XER` | `SPR1` |"));
+        assert!(manual.contains("### add — Add (X-Form)"));
+        assert!(manual.contains("Syntax: `add RT, RA, RB`"));
+        assert!(manual.contains("| `RT` | `@(6..10)` | register `reg::GPR` | Target register |"));
+        assert!(manual.contains("[add](#insn-add)"));
+        assert!(
+            manual.contains("```\n"),
+            "semantics are rendered as code blocks"
+        );
+    }
+
+    #[test]
+    fn html_manual_escapes_and_links() {
+        let manual = e200().reference_manual("e200 <core>", ReferenceFormat::Html);
+        assert!(manual.contains("<title>e200 &lt;core&gt;</title>"));
+        assert!(manual.contains("<h3 id=\"insn-add\">"));
+        assert!(manual.contains("<a href=\"#insn-add\">add</a>"));
+        assert!(manual.contains("<td><code>011111</code></td>"));
+    }
+}
@@ -18,11 +18,13 @@ use crate::soc::prog::types::{
     AggregateKind, BitFieldSpec, DisplayFormat, ScalarEncoding, TypeArena, TypeBuilder, TypeId,
 };
 
-use super::space::SpaceInfo;
+use super::snapshot::{Snapshot, SnapshotReader, SnapshotWriter};
+use super::space::{SpaceInfo, encode_constant, parse_bit_spec};
 use crate::soc::isa::space::resolve_reference_path;
 
 const DEFAULT_REGISTER_BITS: u32 = 64;
 const MAX_REGISTER_BITS: u32 = 64;
+const MAX_REDIRECT_DEPTH: usize = 8;
 
 #[derive(Debug, Clone)]
 pub struct RegisterInfo {
@@ -30,6 +32,7 @@ pub struct RegisterInfo {
     pub range: Option<FieldIndexRange>,
     pub size_bits: Option<u32>,
     pub offset: Option<u64>,
+    pub reset: Option<u128>,
     pub description: Option<String>,
     pub redirect: Option<ContextReference>,
     pub subfields: Vec<SubFieldDecl>,
@@ -44,6 +47,7 @@ impl RegisterInfo {
             range: decl.range,
             size_bits: decl.size,
             offset: decl.offset,
+            reset: decl.reset,
             description: decl.description,
             redirect: decl.redirect,
             subfields: decl.subfields,
@@ -58,6 +62,7 @@ impl RegisterInfo {
             range: None,
             size_bits,
             offset: None,
+            reset: None,
             description: None,
             redirect: None,
             subfields: Vec::new(),
@@ -70,6 +75,11 @@ impl RegisterInfo {
         self.size_bits
     }
 
+    /// Display template from the register's `disp=` attribute.
+    pub fn display(&self) -> Option<&str> {
+        self.display.as_deref()
+    }
+
     pub fn type_handles(&self) -> Option<RegisterTypeHandles> {
         self.type_handles
     }
@@ -78,6 +88,76 @@ impl RegisterInfo {
         self.type_handles = Some(handles);
     }
 
+    /// Returns true when the register or any of its subfields declares a `reset=` value.
+    pub fn has_reset(&self) -> bool {
+        self.reset.is_some() || self.subfields.iter().any(|sub| sub.reset.is_some())
+    }
+
+    /// Power-on value of one register element: the field-level `reset=` with every
+    /// subfield `reset=` written over its bits.
+    pub fn reset_value(&self, bit_width: u32) -> Result<u128, IsaError> {
+        let base = self.reset.unwrap_or(0);
+        if bit_width < 128 && base >> bit_width != 0 {
+            return Err(IsaError::Machine(format!(
+                "reset value 0x{base:X} for register '{}' does not fit in {bit_width} bits",
+                self.name
+            )));
+        }
+        self.apply_subfield_resets(bit_width, base)
+    }
+
+    /// Writes each subfield `reset=` into `value`, leaving bits without a subfield reset untouched.
+    /// Aliases use this to layer their subfield resets over the register they redirect to.
+    pub fn apply_subfield_resets(&self, bit_width: u32, value: u128) -> Result<u128, IsaError> {
+        let mut value = container_bits(bit_width, value, &self.name)?;
+        for sub in &self.subfields {
+            let Some(reset) = sub.reset else {
+                continue;
+            };
+            let spec = parse_bit_spec(bit_width, &sub.bit_spec).map_err(|err| {
+                IsaError::Machine(format!(
+                    "invalid bit spec '{}' on register '{}::{}': {err}",
+                    sub.bit_spec, self.name, sub.name
+                ))
+            })?;
+            let encoded = u64::try_from(reset)
+                .map_err(|_| format!("value exceeds {MAX_REGISTER_BITS} bits"))
+                .and_then(|reset| encode_constant(&spec, reset).map_err(|err| err.to_string()));
+            let (mask, bits) = encoded.map_err(|err| {
+                IsaError::Machine(format!(
+                    "reset value 0x{reset:X} does not fit subfield '{}::{}': {err}",
+                    self.name, sub.name
+                ))
+            })?;
+            value = (value & !mask) | bits;
+        }
+        Ok(u128::from(value))
+    }
+
+    /// Like [`RegisterInfo::apply_subfield_resets`], for an alias of just the `target`
+    /// subfield of a `bit_width`-bit register: the alias's subfields address the bits of
+    /// `target`, which are then written back into `value`.
+    pub fn apply_subfield_resets_within(
+        &self,
+        bit_width: u32,
+        target: &SubFieldDecl,
+        value: u128,
+    ) -> Result<u128, IsaError> {
+        let container = container_bits(bit_width, value, &self.name)?;
+        let spec = parse_bit_spec(bit_width, &target.bit_spec).map_err(|err| {
+            IsaError::Machine(format!(
+                "invalid bit spec '{}' on redirect target '{}': {err}",
+                target.bit_spec, target.name
+            ))
+        })?;
+        let (current, width) = spec.read_bits(container);
+        let updated = self.apply_subfield_resets(u32::from(width), u128::from(current))?;
+        let written = spec
+            .write_bits(container, updated as u64)
+            .map_err(|err| IsaError::Machine(format!("alias '{}' reset: {err}", self.name)))?;
+        Ok(u128::from(written))
+    }
+
     pub fn format(&self, value: u64) -> String {
         if let Some(pattern) = &self.display {
             return format_register_display(pattern, value);
@@ -90,6 +170,36 @@ impl RegisterInfo {
     }
 }
 
+impl Snapshot for RegisterInfo {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.name.encode(out);
+        self.range.encode(out);
+        self.size_bits.encode(out);
+        self.offset.encode(out);
+        self.reset.encode(out);
+        self.description.encode(out);
+        self.redirect.encode(out);
+        self.subfields.encode(out);
+        self.display.encode(out);
+    }
+
+    /// Type handles are left unset; they are assigned when the register schema is rebuilt.
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        Ok(Self {
+            name: String::decode(input)?,
+            range: Option::decode(input)?,
+            size_bits: Option::decode(input)?,
+            offset: Option::decode(input)?,
+            reset: Option::decode(input)?,
+            description: Option::decode(input)?,
+            redirect: Option::decode(input)?,
+            subfields: Vec::decode(input)?,
+            display: Option::decode(input)?,
+            type_handles: None,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct RegisterTypeHandles {
     pub structure: TypeId,
@@ -554,20 +664,8 @@ fn build_alias_fields(
     if alias_info.subfields.is_empty() {
         return Ok(Vec::new());
     }
-    let (target_space, path) = resolve_reference_path(alias_space, reference);
-    let Some(target_name) = path.first() else {
-        return Err(IsaError::Machine(format!(
-            "redirect for '{}::{}' is missing a target register",
-            alias_space, alias_info.name
-        )));
-    };
-    if path.len() > 1 {
-        return Err(IsaError::Machine(format!(
-            "redirect for '{}::{}' cannot reference subfields",
-            alias_space, alias_info.name
-        )));
-    }
-    let bit_width = resolve_register_bit_width(spaces, &target_space, target_name)?;
+    let bit_width =
+        resolve_alias_target(spaces, alias_space, &alias_info.name, reference)?.bit_width()?;
     let container = *scalar_cache.entry(bit_width).or_insert_with(|| {
         builder.scalar(
             None,
@@ -594,34 +692,132 @@ fn build_alias_fields(
     Ok(fields)
 }
 
-fn resolve_register_bit_width(
-    spaces: &BTreeMap<String, SpaceInfo>,
-    space_name: &str,
-    target: &str,
-) -> Result<u32, IsaError> {
-    let space = spaces.get(space_name).ok_or_else(|| {
-        IsaError::Machine(format!(
-            "redirect references undefined space '{}'",
-            space_name
-        ))
-    })?;
-    let default_bits = space.size_bits.unwrap_or(DEFAULT_REGISTER_BITS);
-    if let Some(info) = space.registers.get(target) {
-        return Ok(info.size_bits.unwrap_or(default_bits));
-    }
-    for info in space.registers.values() {
-        if let Some(range) = &info.range {
-            if register_label_matches(info, range, target) {
-                return Ok(info.size_bits.unwrap_or(default_bits));
+/// Register element, and subfield when the chain ends on one, that a `redirect=` alias
+/// names once every intermediate alias has been followed.
+#[derive(Debug, Clone)]
+pub struct AliasTarget<'a> {
+    pub space: String,
+    /// Element label: `SPR8` for an element of a ranged register, else the register name.
+    pub element: String,
+    pub register: &'a RegisterInfo,
+    pub element_bits: u32,
+    pub subfield: Option<&'a SubFieldDecl>,
+}
+
+impl AliasTarget<'_> {
+    /// Width of the bits the alias covers: the subfield's when it targets one.
+    pub fn bit_width(&self) -> Result<u32, IsaError> {
+        let Some(sub) = self.subfield else {
+            return Ok(self.element_bits);
+        };
+        parse_bit_spec(self.element_bits, &sub.bit_spec)
+            .map(|spec| u32::from(spec.total_width()))
+            .map_err(|err| {
+                IsaError::Machine(format!(
+                    "invalid bit spec '{}' on redirect target '{}::{}::{}': {err}",
+                    sub.bit_spec, self.space, self.element, sub.name
+                ))
+            })
+    }
+}
+
+/// Follows the `redirect=` of alias `alias_space::alias_name` through any further aliases.
+pub fn resolve_alias_target<'a>(
+    spaces: &'a BTreeMap<String, SpaceInfo>,
+    alias_space: &str,
+    alias_name: &str,
+    reference: &'a ContextReference,
+) -> Result<AliasTarget<'a>, IsaError> {
+    let mut current_space = alias_space.to_string();
+    let mut reference = reference;
+    for _ in 0..MAX_REDIRECT_DEPTH {
+        let (target_space, path) = resolve_reference_path(&current_space, reference);
+        let Some((target, rest)) = path.split_first() else {
+            return Err(IsaError::Machine(format!(
+                "redirect for '{alias_space}::{alias_name}' is missing a target register"
+            )));
+        };
+        let space = spaces.get(&target_space).ok_or_else(|| {
+            IsaError::Machine(format!(
+                "redirect references undefined space '{target_space}'"
+            ))
+        })?;
+        let register = find_register_element(space, target).ok_or_else(|| {
+            IsaError::Machine(format!(
+                "redirect references undefined register '{target_space}::{target}'"
+            ))
+        })?;
+        if let Some(next) = &register.redirect {
+            if !rest.is_empty() {
+                return Err(IsaError::Machine(format!(
+                    "redirect for '{alias_space}::{alias_name}' names a subfield of alias '{target_space}::{target}'"
+                )));
             }
+            current_space = target_space;
+            reference = next;
+            continue;
         }
+        let subfield = match rest {
+            [] => None,
+            [name] => Some(
+                register
+                    .subfields
+                    .iter()
+                    .find(|sub| &sub.name == name)
+                    .ok_or_else(|| {
+                        IsaError::Machine(format!(
+                            "redirect references undefined subfield '{target_space}::{target}::{name}'"
+                        ))
+                    })?,
+            ),
+            _ => {
+                return Err(IsaError::Machine(format!(
+                    "redirect for '{alias_space}::{alias_name}' exceeds register::subfield depth"
+                )));
+            }
+        };
+        let element_bits = register
+            .size_bits
+            .unwrap_or(space.size_bits.unwrap_or(DEFAULT_REGISTER_BITS));
+        return Ok(AliasTarget {
+            space: target_space,
+            element: target.clone(),
+            register,
+            element_bits,
+            subfield,
+        });
     }
     Err(IsaError::Machine(format!(
-        "redirect references undefined register '{}::{}'",
-        space_name, target
+        "redirect chain for '{alias_space}::{alias_name}' exceeds supported depth"
     )))
 }
 
+/// Register declaring `label`, either by name or as one element of a ranged register.
+fn find_register_element<'a>(space: &'a SpaceInfo, label: &str) -> Option<&'a RegisterInfo> {
+    space.registers.get(label).or_else(|| {
+        space.registers.values().find(|info| {
+            info.range
+                .as_ref()
+                .is_some_and(|range| register_label_matches(info, range, label))
+        })
+    })
+}
+
+/// Checks that `value` fits a register of `bit_width` bits, which subfield bit specs
+/// address as a 64-bit container.
+fn container_bits(bit_width: u32, value: u128, register: &str) -> Result<u64, IsaError> {
+    if bit_width > MAX_REGISTER_BITS {
+        return Err(IsaError::Machine(format!(
+            "register '{register}' width {bit_width} exceeds supported limit of {MAX_REGISTER_BITS} bits"
+        )));
+    }
+    u64::try_from(value).map_err(|_| {
+        IsaError::Machine(format!(
+            "reset value 0x{value:X} for register '{register}' does not fit in {bit_width} bits"
+        ))
+    })
+}
+
 fn register_label_matches(info: &RegisterInfo, range: &FieldIndexRange, label: &str) -> bool {
     for index in range.start..=range.end {
         if info.format(index as u64) == label {
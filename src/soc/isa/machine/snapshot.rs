@@ -0,0 +1,1336 @@
+//! Versioned binary snapshot of a validated [`MachineDescription`]. Spaces, forms, registers,
+//! instructions, decode patterns and decode spaces are stored as-is, together with the parsed
+//! semantic programs of every instruction and macro, so restoring a machine skips lexing,
+//! parsing, validation and semantic compilation. The register schema (type arena and symbol
+//! table) and the decode trees are derived from the restored spaces and patterns.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use smallvec::SmallVec;
+
+use crate::soc::device::endianness::Endianness;
+use crate::soc::isa::ast::{
+    AliasConstraint, AliasSpec, AliasValue, ContextReference, FieldIndexRange, MaskField,
+    MaskSelector, ParameterValue, SpaceKind, SubFieldDecl, SubFieldOp,
+};
+use crate::soc::isa::diagnostic::{SourcePosition, SourceSpan};
+use crate::soc::isa::error::IsaError;
+use crate::soc::isa::semantics::program::{
+    AssignTarget, BitSlice, ContextCall, ContextKind, Expr, ExprBinaryOp, ExprUnaryOp, RegisterRef,
+    SemanticStmt,
+};
+use crate::soc::isa::semantics::{
+    BinaryOperator, SemanticBlock, SemanticEffects, SemanticExpr, SemanticProgram,
+};
+use crate::soc::prog::types::bitfield::BitSlice as BitFieldSlice;
+use crate::soc::prog::types::{BitFieldSegment, BitFieldSpec, PadKind, PadSpec, TypeId};
+
+use super::MachineDescription;
+use super::bus::{BusInfo, BusRangeInfo};
+use super::instruction::{Instruction, InstructionMask, InstructionPattern};
+use super::macros::MacroInfo;
+use super::register::{RegisterBinding, RegisterSchema};
+use super::space::{FieldEncoding, FormInfo, OperandKind, SpaceInfo};
+
+const SNAPSHOT_MAGIC: &[u8; 8] = b"NANEMUMD";
+
+/// Bumped whenever the snapshot layout changes; older snapshots are rejected.
+pub const SNAPSHOT_VERSION: u32 = 10;
+
+impl MachineDescription {
+    /// Serializes the machine into a self-contained binary snapshot.
+    pub fn to_snapshot(&self) -> Vec<u8> {
+        let mut out = SnapshotWriter::default();
+        out.bytes(SNAPSHOT_MAGIC);
+        out.u32(SNAPSHOT_VERSION);
+        self.instructions.encode(&mut out);
+        self.spaces.encode(&mut out);
+        self.macros.encode(&mut out);
+        self.parameters.encode(&mut out);
+        self.buses.encode(&mut out);
+        self.patterns.encode(&mut out);
+        self.decode_spaces.encode(&mut out);
+        out.buf
+    }
+
+    /// Restores a machine written by [`MachineDescription::to_snapshot`].
+    pub fn from_snapshot(bytes: &[u8]) -> Result<Self, IsaError> {
+        let mut input = SnapshotReader::new(bytes);
+        if input.take(SNAPSHOT_MAGIC.len())? != SNAPSHOT_MAGIC {
+            return Err(snapshot_error("missing header"));
+        }
+        let version = input.u32()?;
+        if version != SNAPSHOT_VERSION {
+            return Err(snapshot_error(format!(
+                "version {version} is not supported (expected {SNAPSHOT_VERSION})"
+            )));
+        }
+        let instructions = Vec::decode(&mut input)?;
+        let mut spaces = BTreeMap::decode(&mut input)?;
+        let macros = Vec::decode(&mut input)?;
+        let parameters = BTreeMap::decode(&mut input)?;
+        let buses = BTreeMap::decode(&mut input)?;
+        let patterns = Vec::decode(&mut input)?;
+        let decode_spaces = Vec::decode(&mut input)?;
+        if !input.is_empty() {
+            return Err(snapshot_error("trailing bytes"));
+        }
+        let register_schema = Arc::new(RegisterSchema::build(&mut spaces)?);
+        let mut machine = Self {
+            instructions,
+            spaces,
+            macros,
+            parameters,
+            buses,
+            patterns,
+            decode_spaces,
+            register_schema,
+            aliases: Vec::new(),
+            validity: Vec::new(),
+        };
+        machine.compile_decode_spaces();
+        machine.build_validity()?;
+        machine.build_aliases()?;
+        machine.summarize_semantics()?;
+        Ok(machine)
+    }
+}
+
+fn snapshot_error(message: impl std::fmt::Display) -> IsaError {
+    IsaError::Machine(format!("invalid machine snapshot: {message}"))
+}
+
+#[derive(Default)]
+pub(super) struct SnapshotWriter {
+    buf: Vec<u8>,
+}
+
+impl SnapshotWriter {
+    pub(super) fn u8(&mut self, value: u8) {
+        self.buf.push(value);
+    }
+
+    pub(super) fn u32(&mut self, value: u32) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(super) fn u64(&mut self, value: u64) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(super) fn u128(&mut self, value: u128) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(super) fn len(&mut self, len: usize) {
+        self.u32(u32::try_from(len).expect("snapshot collection exceeds u32::MAX entries"));
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+}
+
+pub(super) struct SnapshotReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> SnapshotReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], IsaError> {
+        if self.data.len() < len {
+            return Err(snapshot_error("unexpected end of data"));
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    pub(super) fn u8(&mut self) -> Result<u8, IsaError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(super) fn u32(&mut self) -> Result<u32, IsaError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().expect("4 bytes")))
+    }
+
+    pub(super) fn u64(&mut self) -> Result<u64, IsaError> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().expect("8 bytes")))
+    }
+
+    pub(super) fn u128(&mut self) -> Result<u128, IsaError> {
+        let bytes = self.take(16)?;
+        Ok(u128::from_le_bytes(bytes.try_into().expect("16 bytes")))
+    }
+
+    /// Reads a collection length, rejecting counts that cannot fit in the remaining data.
+    pub(super) fn len(&mut self) -> Result<usize, IsaError> {
+        let len = self.u32()? as usize;
+        if len > self.data.len() {
+            return Err(snapshot_error("collection length exceeds remaining data"));
+        }
+        Ok(len)
+    }
+
+    pub(super) fn invalid_tag<T>(&self, what: &str, tag: u8) -> Result<T, IsaError> {
+        Err(snapshot_error(format!("unknown {what} tag {tag}")))
+    }
+}
+
+/// Binary encoding shared by every value stored in a machine snapshot.
+pub(super) trait Snapshot: Sized {
+    fn encode(&self, out: &mut SnapshotWriter);
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError>;
+}
+
+impl Snapshot for u8 {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        out.u8(*self);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        input.u8()
+    }
+}
+
+impl Snapshot for u16 {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        out.u32(u32::from(*self));
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        u16::try_from(input.u32()?).map_err(|_| snapshot_error("u16 out of range"))
+    }
+}
+
+impl Snapshot for u32 {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        out.u32(*self);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        input.u32()
+    }
+}
+
+impl Snapshot for u64 {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        out.u64(*self);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        input.u64()
+    }
+}
+
+impl Snapshot for u128 {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        out.u128(*self);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        input.u128()
+    }
+}
+
+impl Snapshot for usize {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        out.u64(*self as u64);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        usize::try_from(input.u64()?).map_err(|_| snapshot_error("usize out of range"))
+    }
+}
+
+impl Snapshot for bool {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        out.u8(u8::from(*self));
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        match input.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            tag => input.invalid_tag("bool", tag),
+        }
+    }
+}
+
+impl Snapshot for String {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        out.len(self.len());
+        out.bytes(self.as_bytes());
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        let len = input.len()?;
+        let bytes = input.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| snapshot_error("string is not UTF-8"))
+    }
+}
+
+impl Snapshot for PathBuf {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.to_string_lossy().into_owned().encode(out);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        String::decode(input).map(PathBuf::from)
+    }
+}
+
+impl<T: Snapshot> Snapshot for Option<T> {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        match self {
+            None => out.u8(0),
+            Some(value) => {
+                out.u8(1);
+                value.encode(out);
+            }
+        }
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        match input.u8()? {
+            0 => Ok(None),
+            1 => T::decode(input).map(Some),
+            tag => input.invalid_tag("option", tag),
+        }
+    }
+}
+
+impl<T: Snapshot> Snapshot for Box<T> {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.as_ref().encode(out);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        T::decode(input).map(Box::new)
+    }
+}
+
+impl<T: Snapshot> Snapshot for Vec<T> {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        out.len(self.len());
+        for item in self {
+            item.encode(out);
+        }
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        let len = input.len()?;
+        let mut items = Vec::with_capacity(len);
+        for _ in 0..len {
+            items.push(T::decode(input)?);
+        }
+        Ok(items)
+    }
+}
+
+impl<T: Snapshot> Snapshot for BTreeMap<String, T> {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        out.len(self.len());
+        for (key, value) in self {
+            key.encode(out);
+            value.encode(out);
+        }
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        let len = input.len()?;
+        let mut map = BTreeMap::new();
+        for _ in 0..len {
+            let key = String::decode(input)?;
+            map.insert(key, T::decode(input)?);
+        }
+        Ok(map)
+    }
+}
+
+impl Snapshot for Endianness {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        out.u8(match self {
+            Endianness::Little => 0,
+            Endianness::Big => 1,
+        });
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        match input.u8()? {
+            0 => Ok(Endianness::Little),
+            1 => Ok(Endianness::Big),
+            tag => input.invalid_tag("endianness", tag),
+        }
+    }
+}
+
+impl Snapshot for SourceSpan {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.path.encode(out);
+        for position in [&self.start, &self.end] {
+            position.line.encode(out);
+            position.column.encode(out);
+        }
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        let path = PathBuf::decode(input)?;
+        let start = SourcePosition::new(usize::decode(input)?, usize::decode(input)?);
+        let end = SourcePosition::new(usize::decode(input)?, usize::decode(input)?);
+        Ok(SourceSpan { path, start, end })
+    }
+}
+
+impl Snapshot for ParameterValue {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        match self {
+            ParameterValue::Word(word) => {
+                out.u8(0);
+                word.encode(out);
+            }
+            ParameterValue::Number(value) => {
+                out.u8(1);
+                out.u64(*value);
+            }
+        }
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        match input.u8()? {
+            0 => String::decode(input).map(ParameterValue::Word),
+            1 => input.u64().map(ParameterValue::Number),
+            tag => input.invalid_tag("parameter", tag),
+        }
+    }
+}
+
+impl Snapshot for SpaceKind {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        out.u8(match self {
+            SpaceKind::ReadWrite => 0,
+            SpaceKind::ReadOnly => 1,
+            SpaceKind::MemoryMappedIo => 2,
+            SpaceKind::Register => 3,
+            SpaceKind::Logic => 4,
+        });
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        match input.u8()? {
+            0 => Ok(SpaceKind::ReadWrite),
+            1 => Ok(SpaceKind::ReadOnly),
+            2 => Ok(SpaceKind::MemoryMappedIo),
+            3 => Ok(SpaceKind::Register),
+            4 => Ok(SpaceKind::Logic),
+            tag => input.invalid_tag("space kind", tag),
+        }
+    }
+}
+
+impl Snapshot for BinaryOperator {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        out.u8(match self {
+            BinaryOperator::Add => 0,
+            BinaryOperator::Sub => 1,
+            BinaryOperator::And => 2,
+            BinaryOperator::Or => 3,
+            BinaryOperator::Xor => 4,
+            BinaryOperator::Shl => 5,
+            BinaryOperator::Shr => 6,
+            BinaryOperator::Eq => 7,
+            BinaryOperator::Ne => 8,
+            BinaryOperator::LogicalAnd => 9,
+            BinaryOperator::LogicalOr => 10,
+        });
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        match input.u8()? {
+            0 => Ok(BinaryOperator::Add),
+            1 => Ok(BinaryOperator::Sub),
+            2 => Ok(BinaryOperator::And),
+            3 => Ok(BinaryOperator::Or),
+            4 => Ok(BinaryOperator::Xor),
+            5 => Ok(BinaryOperator::Shl),
+            6 => Ok(BinaryOperator::Shr),
+            7 => Ok(BinaryOperator::Eq),
+            8 => Ok(BinaryOperator::Ne),
+            9 => Ok(BinaryOperator::LogicalAnd),
+            10 => Ok(BinaryOperator::LogicalOr),
+            tag => input.invalid_tag("binary operator", tag),
+        }
+    }
+}
+
+impl Snapshot for SemanticExpr {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        match self {
+            SemanticExpr::Literal(value) => {
+                out.u8(0);
+                out.u64(*value);
+            }
+            SemanticExpr::Identifier(name) => {
+                out.u8(1);
+                name.encode(out);
+            }
+            SemanticExpr::BitExpr(spec) => {
+                out.u8(2);
+                spec.encode(out);
+            }
+            SemanticExpr::BinaryOp { op, lhs, rhs } => {
+                out.u8(3);
+                op.encode(out);
+                lhs.encode(out);
+                rhs.encode(out);
+            }
+        }
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        match input.u8()? {
+            0 => input.u64().map(SemanticExpr::Literal),
+            1 => String::decode(input).map(SemanticExpr::Identifier),
+            2 => String::decode(input).map(SemanticExpr::BitExpr),
+            3 => Ok(SemanticExpr::BinaryOp {
+                op: BinaryOperator::decode(input)?,
+                lhs: Box::decode(input)?,
+                rhs: Box::decode(input)?,
+            }),
+            tag => input.invalid_tag("semantic expression", tag),
+        }
+    }
+}
+
+impl Snapshot for BitFieldSpec {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.container.index().encode(out);
+        out.len(self.segments.len());
+        for segment in &self.segments {
+            match segment {
+                BitFieldSegment::Slice(slice) => {
+                    out.u8(0);
+                    slice.offset.encode(out);
+                    out.u8(slice.width);
+                    out.u64(slice.mask);
+                }
+                BitFieldSegment::Literal { value, width } => {
+                    out.u8(1);
+                    out.u64(*value);
+                    out.u8(*width);
+                }
+            }
+        }
+        match &self.pad {
+            None => out.u8(0),
+            Some(pad) => {
+                out.u8(match pad.kind {
+                    PadKind::Zero => 1,
+                    PadKind::Sign => 2,
+                });
+                pad.width.encode(out);
+            }
+        }
+        self.signed.encode(out);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        let container = TypeId::from_index(usize::decode(input)?);
+        let count = input.len()?;
+        let mut segments = SmallVec::with_capacity(count);
+        for _ in 0..count {
+            let segment = match input.u8()? {
+                0 => BitFieldSegment::Slice(BitFieldSlice {
+                    offset: u16::decode(input)?,
+                    width: input.u8()?,
+                    mask: input.u64()?,
+                }),
+                1 => BitFieldSegment::Literal {
+                    value: input.u64()?,
+                    width: input.u8()?,
+                },
+                tag => return input.invalid_tag("bitfield segment", tag),
+            };
+            segments.push(segment);
+        }
+        let pad = match input.u8()? {
+            0 => None,
+            1 => Some(PadSpec::new(PadKind::Zero, u16::decode(input)?)),
+            2 => Some(PadSpec::new(PadKind::Sign, u16::decode(input)?)),
+            tag => return input.invalid_tag("bitfield pad", tag),
+        };
+        Ok(BitFieldSpec {
+            container,
+            segments,
+            pad,
+            signed: bool::decode(input)?,
+        })
+    }
+}
+
+impl Snapshot for SpaceInfo {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.name.encode(out);
+        self.kind.encode(out);
+        self.size_bits.encode(out);
+        self.endianness.encode(out);
+        self.forms.encode(out);
+        self.registers.encode(out);
+        self.enable.encode(out);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        Ok(Self {
+            name: String::decode(input)?,
+            kind: SpaceKind::decode(input)?,
+            size_bits: Option::decode(input)?,
+            endianness: Endianness::decode(input)?,
+            forms: BTreeMap::decode(input)?,
+            registers: BTreeMap::decode(input)?,
+            enable: Option::decode(input)?,
+        })
+    }
+}
+
+impl Snapshot for FormInfo {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        let fields: Vec<&FieldEncoding> = self.field_iter().collect();
+        out.len(fields.len());
+        for field in fields {
+            field.encode(out);
+        }
+        self.display.encode(out);
+        self.description.encode(out);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        // Operand order and the name index are rebuilt as the fields are pushed back.
+        let mut form = FormInfo::new(String::new());
+        for field in Vec::<FieldEncoding>::decode(input)? {
+            form.push_field(field);
+        }
+        form.display = Option::decode(input)?;
+        form.description = Option::decode(input)?;
+        Ok(form)
+    }
+}
+
+impl Snapshot for FieldEncoding {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.name.encode(out);
+        self.spec.encode(out);
+        self.operations.encode(out);
+        self.register.encode(out);
+        out.u8(match self.kind {
+            OperandKind::Register => 0,
+            OperandKind::Immediate => 1,
+            OperandKind::Other => 2,
+        });
+        self.description.encode(out);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        Ok(Self {
+            name: String::decode(input)?,
+            spec: BitFieldSpec::decode(input)?,
+            operations: Vec::decode(input)?,
+            register: Option::decode(input)?,
+            kind: match input.u8()? {
+                0 => OperandKind::Register,
+                1 => OperandKind::Immediate,
+                2 => OperandKind::Other,
+                tag => return input.invalid_tag("operand kind", tag),
+            },
+            description: Option::decode(input)?,
+        })
+    }
+}
+
+impl Snapshot for SubFieldOp {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.kind.encode(out);
+        self.subtype.encode(out);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        Ok(Self {
+            kind: String::decode(input)?,
+            subtype: Option::decode(input)?,
+        })
+    }
+}
+
+impl Snapshot for RegisterBinding {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.space.encode(out);
+        self.field.encode(out);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        Ok(Self {
+            space: String::decode(input)?,
+            field: String::decode(input)?,
+        })
+    }
+}
+
+impl Snapshot for FieldIndexRange {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        out.u32(self.start);
+        out.u32(self.end);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        Ok(Self {
+            start: input.u32()?,
+            end: input.u32()?,
+        })
+    }
+}
+
+impl Snapshot for ContextReference {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.segments.encode(out);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        Ok(Self {
+            segments: Vec::decode(input)?,
+        })
+    }
+}
+
+impl Snapshot for SubFieldDecl {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.name.encode(out);
+        self.bit_spec.encode(out);
+        self.operations.encode(out);
+        self.description.encode(out);
+        self.reset.encode(out);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        Ok(Self {
+            name: String::decode(input)?,
+            bit_spec: String::decode(input)?,
+            operations: Vec::decode(input)?,
+            description: Option::decode(input)?,
+            reset: Option::decode(input)?,
+        })
+    }
+}
+
+impl Snapshot for Instruction {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.space.encode(out);
+        self.name.encode(out);
+        self.form.encode(out);
+        self.description.encode(out);
+        self.operands.encode(out);
+        self.display.encode(out);
+        self.operator.encode(out);
+        self.extend.encode(out);
+        self.alias.encode(out);
+        self.valid.encode(out);
+        self.mask.encode(out);
+        self.encoding.encode(out);
+        self.semantics.encode(out);
+        self.span.encode(out);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        Ok(Self {
+            space: String::decode(input)?,
+            name: String::decode(input)?,
+            form: Option::decode(input)?,
+            description: Option::decode(input)?,
+            operands: Vec::decode(input)?,
+            display: Option::decode(input)?,
+            operator: Option::decode(input)?,
+            extend: Option::decode(input)?,
+            alias: Option::decode(input)?,
+            valid: Option::decode(input)?,
+            mask: Option::decode(input)?,
+            encoding: Option::decode(input)?,
+            semantics: Option::decode(input)?,
+            effects: SemanticEffects::default(),
+            span: SourceSpan::decode(input)?,
+        })
+    }
+}
+
+impl Snapshot for InstructionMask {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.fields.encode(out);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        Ok(Self {
+            fields: Vec::decode(input)?,
+        })
+    }
+}
+
+impl Snapshot for MaskField {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        match &self.selector {
+            MaskSelector::Field(name) => {
+                out.u8(0);
+                name.encode(out);
+            }
+            MaskSelector::BitExpr(spec) => {
+                out.u8(1);
+                spec.encode(out);
+            }
+        }
+        out.u64(self.value);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        let selector = match input.u8()? {
+            0 => MaskSelector::Field(String::decode(input)?),
+            1 => MaskSelector::BitExpr(String::decode(input)?),
+            tag => return input.invalid_tag("mask selector", tag),
+        };
+        Ok(Self {
+            selector,
+            value: input.u64()?,
+        })
+    }
+}
+
+impl Snapshot for AliasSpec {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.base.encode(out);
+        self.constraints.encode(out);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        Ok(Self {
+            base: String::decode(input)?,
+            constraints: Vec::decode(input)?,
+        })
+    }
+}
+
+impl Snapshot for AliasConstraint {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.field.encode(out);
+        match &self.value {
+            AliasValue::Number(value) => {
+                out.u8(0);
+                out.u64(*value);
+            }
+            AliasValue::Field(name) => {
+                out.u8(1);
+                name.encode(out);
+            }
+        }
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        let field = String::decode(input)?;
+        let value = match input.u8()? {
+            0 => AliasValue::Number(input.u64()?),
+            1 => AliasValue::Field(String::decode(input)?),
+            tag => return input.invalid_tag("alias value", tag),
+        };
+        Ok(Self { field, value })
+    }
+}
+
+impl Snapshot for SemanticBlock {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.source.encode(out);
+        self.span().cloned().encode(out);
+        match self.program() {
+            None => out.u8(0),
+            Some(program) => {
+                out.u8(1);
+                program.statements.encode(out);
+            }
+        }
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        let source = String::decode(input)?;
+        let span = Option::decode(input)?;
+        let mut block = SemanticBlock::with_span(source, span);
+        match input.u8()? {
+            0 => {}
+            1 => block.set_program(SemanticProgram {
+                statements: Vec::decode(input)?,
+            }),
+            tag => return input.invalid_tag("semantic program", tag),
+        }
+        Ok(block)
+    }
+}
+
+impl Snapshot for SemanticStmt {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        match self {
+            SemanticStmt::Assign { target, expr } => {
+                out.u8(0);
+                target.encode(out);
+                expr.encode(out);
+            }
+            SemanticStmt::Expr(expr) => {
+                out.u8(1);
+                expr.encode(out);
+            }
+            SemanticStmt::Return(expr) => {
+                out.u8(2);
+                expr.encode(out);
+            }
+            SemanticStmt::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                out.u8(3);
+                condition.encode(out);
+                then.encode(out);
+                otherwise.encode(out);
+            }
+            SemanticStmt::For {
+                var,
+                start,
+                end,
+                body,
+            } => {
+                out.u8(4);
+                var.encode(out);
+                start.encode(out);
+                end.encode(out);
+                body.encode(out);
+            }
+        }
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        match input.u8()? {
+            0 => Ok(SemanticStmt::Assign {
+                target: AssignTarget::decode(input)?,
+                expr: Expr::decode(input)?,
+            }),
+            1 => Expr::decode(input).map(SemanticStmt::Expr),
+            2 => Expr::decode(input).map(SemanticStmt::Return),
+            3 => Ok(SemanticStmt::If {
+                condition: Expr::decode(input)?,
+                then: Vec::decode(input)?,
+                otherwise: Vec::decode(input)?,
+            }),
+            4 => Ok(SemanticStmt::For {
+                var: String::decode(input)?,
+                start: Expr::decode(input)?,
+                end: Expr::decode(input)?,
+                body: Vec::decode(input)?,
+            }),
+            tag => input.invalid_tag("semantic statement", tag),
+        }
+    }
+}
+
+impl Snapshot for AssignTarget {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        match self {
+            AssignTarget::Variable(name) => {
+                out.u8(0);
+                name.encode(out);
+            }
+            AssignTarget::Tuple(names) => {
+                out.u8(1);
+                names.encode(out);
+            }
+            AssignTarget::Register(reference) => {
+                out.u8(2);
+                reference.encode(out);
+            }
+        }
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        match input.u8()? {
+            0 => String::decode(input).map(AssignTarget::Variable),
+            1 => Vec::decode(input).map(AssignTarget::Tuple),
+            2 => RegisterRef::decode(input).map(AssignTarget::Register),
+            tag => input.invalid_tag("assignment target", tag),
+        }
+    }
+}
+
+impl Snapshot for RegisterRef {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.space.encode(out);
+        self.name.encode(out);
+        self.subfield.encode(out);
+        self.index.encode(out);
+        self.span.encode(out);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        Ok(Self {
+            space: String::decode(input)?,
+            name: String::decode(input)?,
+            subfield: Option::decode(input)?,
+            index: Option::decode(input)?,
+            span: Option::decode(input)?,
+        })
+    }
+}
+
+impl Snapshot for ContextCall {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        out.u8(match self.kind {
+            ContextKind::Register => 0,
+            ContextKind::Macro => 1,
+            ContextKind::Instruction => 2,
+            ContextKind::Host => 3,
+            ContextKind::Memory => 4,
+            ContextKind::Cast => 5,
+        });
+        self.space.encode(out);
+        self.name.encode(out);
+        self.subpath.encode(out);
+        self.args.encode(out);
+        self.span.encode(out);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        let kind = match input.u8()? {
+            0 => ContextKind::Register,
+            1 => ContextKind::Macro,
+            2 => ContextKind::Instruction,
+            3 => ContextKind::Host,
+            4 => ContextKind::Memory,
+            5 => ContextKind::Cast,
+            tag => return input.invalid_tag("context kind", tag),
+        };
+        Ok(Self {
+            kind,
+            space: String::decode(input)?,
+            name: String::decode(input)?,
+            subpath: Vec::decode(input)?,
+            args: Vec::decode(input)?,
+            span: SourceSpan::decode(input)?,
+        })
+    }
+}
+
+impl Snapshot for ExprBinaryOp {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        out.u8(match self {
+            ExprBinaryOp::LogicalOr => 0,
+            ExprBinaryOp::LogicalAnd => 1,
+            ExprBinaryOp::BitOr => 2,
+            ExprBinaryOp::BitXor => 3,
+            ExprBinaryOp::BitAnd => 4,
+            ExprBinaryOp::Eq => 5,
+            ExprBinaryOp::Ne => 6,
+            ExprBinaryOp::Lt => 7,
+            ExprBinaryOp::Gt => 8,
+            ExprBinaryOp::Add => 9,
+            ExprBinaryOp::Sub => 10,
+            ExprBinaryOp::Le => 11,
+            ExprBinaryOp::Ge => 12,
+            ExprBinaryOp::Mul => 13,
+            ExprBinaryOp::Div => 14,
+            ExprBinaryOp::Rem => 15,
+            ExprBinaryOp::Shl => 16,
+            ExprBinaryOp::Shr => 17,
+            ExprBinaryOp::Sar => 18,
+        });
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        match input.u8()? {
+            0 => Ok(ExprBinaryOp::LogicalOr),
+            1 => Ok(ExprBinaryOp::LogicalAnd),
+            2 => Ok(ExprBinaryOp::BitOr),
+            3 => Ok(ExprBinaryOp::BitXor),
+            4 => Ok(ExprBinaryOp::BitAnd),
+            5 => Ok(ExprBinaryOp::Eq),
+            6 => Ok(ExprBinaryOp::Ne),
+            7 => Ok(ExprBinaryOp::Lt),
+            8 => Ok(ExprBinaryOp::Gt),
+            9 => Ok(ExprBinaryOp::Add),
+            10 => Ok(ExprBinaryOp::Sub),
+            11 => Ok(ExprBinaryOp::Le),
+            12 => Ok(ExprBinaryOp::Ge),
+            13 => Ok(ExprBinaryOp::Mul),
+            14 => Ok(ExprBinaryOp::Div),
+            15 => Ok(ExprBinaryOp::Rem),
+            16 => Ok(ExprBinaryOp::Shl),
+            17 => Ok(ExprBinaryOp::Shr),
+            18 => Ok(ExprBinaryOp::Sar),
+            tag => input.invalid_tag("expression operator", tag),
+        }
+    }
+}
+
+impl Snapshot for Expr {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        match self {
+            Expr::Number(value) => {
+                out.u8(0);
+                out.u64(*value);
+            }
+            Expr::Variable { name, span } => {
+                out.u8(1);
+                name.encode(out);
+                span.encode(out);
+            }
+            Expr::Parameter { name, span } => {
+                out.u8(2);
+                name.encode(out);
+                span.encode(out);
+            }
+            Expr::Call(call) => {
+                out.u8(3);
+                call.encode(out);
+            }
+            Expr::Tuple(items) => {
+                out.u8(4);
+                items.encode(out);
+            }
+            Expr::BinaryOp { op, lhs, rhs } => {
+                out.u8(5);
+                op.encode(out);
+                lhs.encode(out);
+                rhs.encode(out);
+            }
+            Expr::BitSlice { expr, slice } => {
+                out.u8(6);
+                expr.encode(out);
+                out.u32(slice.start);
+                out.u32(slice.end);
+            }
+            Expr::UnaryOp { op, expr } => {
+                out.u8(7);
+                out.u8(match op {
+                    ExprUnaryOp::Not => 0,
+                    ExprUnaryOp::BitNot => 1,
+                    ExprUnaryOp::Neg => 2,
+                });
+                expr.encode(out);
+            }
+            Expr::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => {
+                out.u8(8);
+                condition.encode(out);
+                then.encode(out);
+                otherwise.encode(out);
+            }
+        }
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        match input.u8()? {
+            0 => input.u64().map(Expr::Number),
+            1 => Ok(Expr::Variable {
+                name: String::decode(input)?,
+                span: SourceSpan::decode(input)?,
+            }),
+            2 => Ok(Expr::Parameter {
+                name: String::decode(input)?,
+                span: SourceSpan::decode(input)?,
+            }),
+            3 => ContextCall::decode(input).map(Expr::Call),
+            4 => Vec::decode(input).map(Expr::Tuple),
+            5 => Ok(Expr::BinaryOp {
+                op: ExprBinaryOp::decode(input)?,
+                lhs: Box::decode(input)?,
+                rhs: Box::decode(input)?,
+            }),
+            6 => Ok(Expr::BitSlice {
+                expr: Box::decode(input)?,
+                slice: BitSlice {
+                    start: input.u32()?,
+                    end: input.u32()?,
+                },
+            }),
+            7 => {
+                let op = match input.u8()? {
+                    0 => ExprUnaryOp::Not,
+                    1 => ExprUnaryOp::BitNot,
+                    2 => ExprUnaryOp::Neg,
+                    tag => return input.invalid_tag("unary operator", tag),
+                };
+                Ok(Expr::UnaryOp {
+                    op,
+                    expr: Box::decode(input)?,
+                })
+            }
+            8 => Ok(Expr::Conditional {
+                condition: Box::decode(input)?,
+                then: Box::decode(input)?,
+                otherwise: Box::decode(input)?,
+            }),
+            tag => input.invalid_tag("expression", tag),
+        }
+    }
+}
+
+impl Snapshot for MacroInfo {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.name.encode(out);
+        self.parameters.encode(out);
+        self.semantics.encode(out);
+        self.span.encode(out);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        Ok(Self {
+            name: String::decode(input)?,
+            parameters: Vec::decode(input)?,
+            semantics: SemanticBlock::decode(input)?,
+            span: SourceSpan::decode(input)?,
+        })
+    }
+}
+
+impl Snapshot for BusInfo {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.name.encode(out);
+        out.u32(self.address_bits);
+        self.ranges.encode(out);
+        self.span.encode(out);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        Ok(Self {
+            name: String::decode(input)?,
+            address_bits: input.u32()?,
+            ranges: Vec::decode(input)?,
+            span: SourceSpan::decode(input)?,
+        })
+    }
+}
+
+impl Snapshot for BusRangeInfo {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        out.u64(self.start);
+        out.u64(self.size);
+        self.space.encode(out);
+        out.u32(self.priority);
+        out.u64(self.space_offset);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        Ok(Self {
+            start: input.u64()?,
+            size: input.u64()?,
+            space: String::decode(input)?,
+            priority: input.u32()?,
+            space_offset: input.u64()?,
+        })
+    }
+}
+
+impl Snapshot for InstructionPattern {
+    fn encode(&self, out: &mut SnapshotWriter) {
+        self.instruction_idx.encode(out);
+        self.space.encode(out);
+        self.form.encode(out);
+        out.u64(self.mask);
+        out.u64(self.value);
+        self.operand_names.encode(out);
+        self.display.encode(out);
+        self.operator.encode(out);
+        out.u32(self.specificity);
+    }
+
+    fn decode(input: &mut SnapshotReader<'_>) -> Result<Self, IsaError> {
+        Ok(Self {
+            instruction_idx: usize::decode(input)?,
+            space: String::decode(input)?,
+            form: Option::decode(input)?,
+            mask: input.u64()?,
+            value: input.u64()?,
+            operand_names: Vec::decode(input)?,
+            display: Option::decode(input)?,
+            operator: Option::decode(input)?,
+            specificity: input.u32()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::isa::IsaLoader;
+    use crate::soc::isa::machine::Syntax;
+
+    fn e200() -> MachineDescription {
+        let coredef = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("defs/powerpc/e200.coredef");
+        IsaLoader::new().load_machine(coredef).expect("load e200")
+    }
+
+    #[test]
+    fn round_trip_preserves_decoding_and_semantics() {
+        let machine = e200();
+        let restored = MachineDescription::from_snapshot(&machine.to_snapshot()).expect("restore");
+
+        let mut stream = 0x3800_0000u32.to_be_bytes().to_vec();
+        stream.extend_from_slice(&0xE800u16.to_be_bytes());
+        let expected = machine.disassemble_from(&stream, 0x1000, Syntax::Simplified);
+        let listing = restored.disassemble_from(&stream, 0x1000, Syntax::Simplified);
+        assert_eq!(listing.len(), expected.len());
+        assert_eq!(
+            listing[0].mnemonic, "li",
+            "aliases are rebuilt from the restored entries"
+        );
+        for (got, want) in listing.iter().zip(&expected) {
+            assert_eq!(got.mnemonic, want.mnemonic);
+            assert_eq!(got.operands, want.operands);
+            assert_eq!(got.display, want.display);
+        }
+        let bclr = 0x4E80_2020u32.to_be_bytes();
+        assert_eq!(
+            restored.disassemble(&bclr)[0].mnemonic,
+            "illegal",
+            "valid= predicates are rebuilt from the restored entries"
+        );
+
+        assert_eq!(restored.parameters.len(), machine.parameters.len());
+        assert_eq!(restored.macros.len(), machine.macros.len());
+        assert!(
+            restored
+                .instructions
+                .iter()
+                .filter_map(|instr| instr.semantics.as_ref())
+                .all(|block| block.program().is_some()),
+            "semantic programs are restored without reparsing"
+        );
+        assert_eq!(
+            restored.register_schema().symbol_table().len(),
+            machine.register_schema().symbol_table().len()
+        );
+    }
+
+    #[test]
+    fn rejects_other_versions_and_truncated_data() {
+        let mut bytes = e200().to_snapshot();
+        assert!(MachineDescription::from_snapshot(&bytes[..bytes.len() / 2]).is_err());
+
+        bytes[SNAPSHOT_MAGIC.len()..SNAPSHOT_MAGIC.len() + 4]
+            .copy_from_slice(&(SNAPSHOT_VERSION + 1).to_le_bytes());
+        let err = MachineDescription::from_snapshot(&bytes).unwrap_err();
+        assert!(err.to_string().contains("not supported"), "{err}");
+    }
+}
@@ -86,12 +86,17 @@ impl SpaceInfo {
                 operations: sub.operations,
                 register,
                 kind: operand_kind,
+                description: sub.description,
             });
         }
 
         if let Some(template) = form.display.clone() {
             info.display = Some(template);
         }
+        // Derived forms keep the parent's description unless they declare their own.
+        if let Some(description) = form.description {
+            info.description = Some(description);
+        }
 
         self.forms.insert(form.name, info);
         Ok(())
@@ -112,6 +117,7 @@ pub struct FormInfo {
     field_index: BTreeMap<String, usize>,
     pub operand_order: Vec<String>,
     pub display: Option<String>,
+    pub description: Option<String>,
 }
 
 impl FormInfo {
@@ -121,6 +127,7 @@ impl FormInfo {
             field_index: BTreeMap::new(),
             operand_order: Vec::new(),
             display: None,
+            description: None,
         }
     }
 
@@ -146,6 +153,13 @@ impl FormInfo {
     pub fn field_iter(&self) -> impl Iterator<Item = &FieldEncoding> {
         self.fields.iter()
     }
+
+    /// Whether `bits` sets one of the form's `abs` flags.
+    pub fn is_absolute(&self, bits: u64) -> bool {
+        self.fields
+            .iter()
+            .any(|field| field.is_absolute_flag() && field.spec.read_bits(bits).0 != 0)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -155,6 +169,7 @@ pub struct FieldEncoding {
     pub operations: Vec<SubFieldOp>,
     pub register: Option<RegisterBinding>,
     pub kind: OperandKind,
+    pub description: Option<String>,
 }
 
 impl FieldEncoding {
@@ -162,7 +177,21 @@ impl FieldEncoding {
         !self
             .operations
             .iter()
-            .any(|op| !op.kind.eq_ignore_ascii_case("func"))
+            .any(|op| !op.kind.eq_ignore_ascii_case("func") && !op.kind.eq_ignore_ascii_case("abs"))
+    }
+
+    /// `abs` flags turn the form's `addr` fields into absolute addresses when set.
+    pub fn is_absolute_flag(&self) -> bool {
+        self.operations
+            .iter()
+            .any(|op| op.kind.eq_ignore_ascii_case("abs"))
+    }
+
+    /// `addr` fields hold a displacement from the instruction address.
+    pub fn is_pc_relative(&self) -> bool {
+        self.operations
+            .iter()
+            .any(|op| op.kind.eq_ignore_ascii_case("addr"))
     }
 }
 
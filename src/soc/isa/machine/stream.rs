@@ -0,0 +1,109 @@
+//! Lazy instruction-by-instruction decoding. Each item fetches only the bytes its instruction
+//! spans, so large images decode without materializing a listing, and words that fail to
+//! decode are reported in line instead of ending the walk silently.
+
+use super::MachineDescription;
+use super::disassembly::DecodedInstruction;
+use super::fetch::{ByteImage, DecodeError, FetchWindow, InstructionFetch};
+
+/// Where an [`InstructionStream`] continues after a word fails to decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Resync {
+    /// Skip the minimum instruction alignment (the narrowest root space's word) and retry.
+    #[default]
+    Skip,
+    /// Yield the error and end the stream.
+    Stop,
+}
+
+/// Iterator over the instructions from a start address, yielding decode failures as errors.
+pub struct InstructionStream<'m, F> {
+    machine: &'m MachineDescription,
+    fetch: F,
+    address: u64,
+    end: Option<u64>,
+    resync: Resync,
+    done: bool,
+}
+
+impl<'m, F: InstructionFetch> InstructionStream<'m, F> {
+    /// Selects what happens after a failed word; [`Resync::Skip`] by default.
+    pub fn resync(mut self, policy: Resync) -> Self {
+        self.resync = policy;
+        self
+    }
+
+    /// Ends the stream at `end`. An instruction crossing it is reported as truncated.
+    pub fn until(mut self, end: u64) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    /// Address the next item decodes from.
+    pub fn address(&self) -> u64 {
+        self.address
+    }
+
+    fn advance(&mut self, size: u64) {
+        match self.address.checked_add(size) {
+            Some(next) if size > 0 => self.address = next,
+            _ => self.done = true,
+        }
+    }
+}
+
+impl<'m, F: InstructionFetch> Iterator for InstructionStream<'m, F> {
+    type Item = Result<DecodedInstruction<'m>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.end.is_some_and(|end| self.address >= end) {
+            return None;
+        }
+        let machine = self.machine;
+        let address = self.address;
+        let mut window = FetchWindow::new(&mut self.fetch, address).until(self.end);
+        match machine.decode_window(&mut window, address) {
+            Ok(decoded) => {
+                self.advance(decoded.size() as u64);
+                Some(Ok(decoded))
+            }
+            Err(err) => {
+                match self.resync {
+                    Resync::Skip => self.advance(machine.min_alignment() as u64),
+                    Resync::Stop => self.done = true,
+                }
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl MachineDescription {
+    /// Decodes one instruction after another from `address`. The stream is unbounded unless
+    /// [`InstructionStream::until`] sets an end; bus faults surface as [`DecodeError::Fetch`].
+    pub fn decode_stream<F: InstructionFetch>(
+        &self,
+        fetch: F,
+        address: u64,
+    ) -> InstructionStream<'_, F> {
+        InstructionStream {
+            machine: self,
+            fetch,
+            address,
+            end: None,
+            resync: Resync::default(),
+            done: false,
+        }
+    }
+
+    /// [`Self::decode_stream`] over `bytes` loaded at `base_address`, ending with them.
+    pub fn decode_bytes<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        base_address: u64,
+    ) -> InstructionStream<'a, ByteImage<'a>> {
+        let image = ByteImage::new(bytes, base_address);
+        let end = image.end();
+        self.decode_stream(image, base_address).until(end)
+    }
+}
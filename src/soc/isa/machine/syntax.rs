@@ -0,0 +1,273 @@
+//! Disassembly syntax modes and simplified mnemonics. An `alias=` entry names a base
+//! instruction with some fields pinned to constants or to each other (`mr` is `or` with
+//! `RB=#RT`); the disassembler prefers it under [`Syntax::Simplified`] and the assembler accepts
+//! it in every mode.
+
+use crate::soc::isa::ast::AliasValue;
+use crate::soc::isa::error::IsaError;
+use crate::soc::prog::types::BitFieldSpec;
+
+use super::MachineDescription;
+use super::format::default_display_template;
+use super::instruction::{Instruction, InstructionPattern};
+use super::operand::{Operand, OperandValue};
+use super::space::{encode_constant, mask_for_bits};
+
+/// How the disassembler names instructions and renders their operands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Syntax {
+    /// Base mnemonic followed by every subfield of the form as `NAME=0x<bits>`.
+    Raw,
+    /// Base mnemonic with its declared operands and display template.
+    #[default]
+    Canonical,
+    /// Most specific matching alias, falling back to the base instruction.
+    Simplified,
+}
+
+/// Alias resolved against the decode pattern of its base instruction.
+#[derive(Debug, Clone)]
+pub(super) struct AliasPattern {
+    /// Index of the base instruction.
+    base: usize,
+    /// The base pattern narrowed by the pinned constants, with the alias's operands and display.
+    pattern: InstructionPattern,
+    /// Fields that must hold the same value (`FIELD=#OTHER`).
+    ties: Vec<(BitFieldSpec, BitFieldSpec)>,
+}
+
+impl AliasPattern {
+    fn matches(&self, bits: u64) -> bool {
+        bits & self.pattern.mask == self.pattern.value
+            && self
+                .ties
+                .iter()
+                .all(|(field, other)| field.read_bits(bits).0 == other.read_bits(bits).0)
+    }
+}
+
+impl MachineDescription {
+    /// Resolves every `alias=` entry against its base instruction's decode pattern.
+    pub(super) fn build_aliases(&mut self) -> Result<(), IsaError> {
+        let mut aliases = Vec::new();
+        for (idx, instr) in self.instructions.iter().enumerate() {
+            if instr.alias.is_some() {
+                aliases.push(self.build_alias(idx, instr)?);
+            }
+        }
+        self.aliases = aliases;
+        Ok(())
+    }
+
+    fn build_alias(&self, idx: usize, instr: &Instruction) -> Result<AliasPattern, IsaError> {
+        let Some(spec) = &instr.alias else {
+            unreachable!("only called for aliases");
+        };
+        let error = |message: String| {
+            IsaError::Machine(format!("alias '{}::{}' {message}", instr.space, instr.name))
+        };
+        let (base_idx, base) = self
+            .find_base(&instr.space, &spec.base)
+            .ok_or_else(|| error(format!("refers to unknown instruction '{}'", spec.base)))?;
+        let base_pattern = self
+            .patterns
+            .iter()
+            .find(|pattern| pattern.instruction_idx == base_idx)
+            .ok_or_else(|| error(format!("refers to '{}', which has no mask", spec.base)))?;
+        if instr.form != base.form {
+            return Err(error(format!(
+                "must use the form of '{}' ({})",
+                spec.base,
+                base.form.as_deref().unwrap_or("none")
+            )));
+        }
+        let form = self
+            .pattern_form(base_pattern)
+            .ok_or_else(|| error("has no form to constrain".into()))?;
+        let field = |name: &str| {
+            form.subfield(name)
+                .map(|field| field.spec.clone())
+                .ok_or_else(|| error(format!("references unknown field '{name}'")))
+        };
+
+        let mut mask = base_pattern.mask;
+        let mut value = base_pattern.value;
+        let mut ties = Vec::new();
+        let mut pinned = Vec::new();
+        for constraint in &spec.constraints {
+            let bits = field(&constraint.field)?;
+            match &constraint.value {
+                AliasValue::Number(number) => {
+                    let (field_mask, encoded) = encode_constant(&bits, *number).map_err(|err| {
+                        error(format!(
+                            "pins '{}' to a value that does not fit: {err}",
+                            constraint.field
+                        ))
+                    })?;
+                    if value & mask & field_mask != encoded & mask & field_mask {
+                        return Err(error(format!(
+                            "pins '{}' to a value the mask of '{}' excludes",
+                            constraint.field, spec.base
+                        )));
+                    }
+                    mask |= field_mask;
+                    value = (value & !field_mask) | (encoded & field_mask);
+                }
+                AliasValue::Field(other) => ties.push((bits, field(other)?)),
+            }
+            pinned.push(constraint.field.as_str());
+        }
+
+        let operand_names: Vec<String> = if instr.operands.is_empty() {
+            form.operand_order
+                .iter()
+                .filter(|name| !pinned.contains(&name.as_str()))
+                .cloned()
+                .collect()
+        } else {
+            instr.operands.clone()
+        };
+        for name in &operand_names {
+            field(name)?;
+            if pinned.contains(&name.as_str()) {
+                return Err(error(format!("lists pinned field '{name}' as an operand")));
+            }
+        }
+
+        let tie_bits: u32 = ties
+            .iter()
+            .map(|(field, _)| u32::from(field.data_width()))
+            .sum();
+        let display = instr
+            .display
+            .clone()
+            .or_else(|| default_display_template(instr.form.as_ref(), &operand_names));
+        Ok(AliasPattern {
+            base: base_idx,
+            pattern: InstructionPattern {
+                instruction_idx: idx,
+                space: instr.space.clone(),
+                form: instr.form.clone(),
+                mask,
+                value,
+                operand_names,
+                display,
+                operator: instr.operator.clone().or_else(|| base.operator.clone()),
+                specificity: mask.count_ones() + tie_bits,
+            },
+            ties,
+        })
+    }
+
+    /// Pattern of the alias at instruction index `idx`.
+    pub(super) fn alias_pattern(&self, idx: usize) -> Option<&InstructionPattern> {
+        self.aliases
+            .iter()
+            .find(|alias| alias.pattern.instruction_idx == idx)
+            .map(|alias| &alias.pattern)
+    }
+
+    /// Non-alias instruction `name` of `space`.
+    pub(super) fn find_base(&self, space: &str, name: &str) -> Option<(usize, &Instruction)> {
+        self.instructions
+            .iter()
+            .enumerate()
+            .find(|(_, instr)| instr.space == space && instr.name == name && instr.alias.is_none())
+    }
+
+    /// Most specific alias of the instruction `pattern` decodes that also matches `bits`; the
+    /// first declared wins a tie.
+    pub(super) fn alias_for(
+        &self,
+        pattern: &InstructionPattern,
+        bits: u64,
+    ) -> Option<&InstructionPattern> {
+        let mut best: Option<&InstructionPattern> = None;
+        for alias in &self.aliases {
+            if alias.base != pattern.instruction_idx || !alias.matches(bits) {
+                continue;
+            }
+            if best.is_none_or(|best| alias.pattern.specificity > best.specificity) {
+                best = Some(&alias.pattern);
+            }
+        }
+        best
+    }
+
+    /// Pattern whose mnemonic and operands represent the word `bits` matched by `pattern` in
+    /// `syntax`.
+    pub(super) fn rendered_pattern<'a>(
+        &'a self,
+        pattern: &'a InstructionPattern,
+        bits: u64,
+        syntax: Syntax,
+    ) -> &'a InstructionPattern {
+        match syntax {
+            Syntax::Simplified => self.alias_for(pattern, bits).unwrap_or(pattern),
+            Syntax::Raw | Syntax::Canonical => pattern,
+        }
+    }
+
+    /// Every subfield of the form `pattern` decodes with, in declaration order. Values are the
+    /// encoded bits, so signed fields are not sign-extended past their width.
+    pub(super) fn raw_operands(&self, pattern: &InstructionPattern, bits: u64) -> Vec<Operand> {
+        self.pattern_form(pattern)
+            .into_iter()
+            .flat_map(|form| form.field_iter())
+            .map(|field| {
+                let mask = mask_for_bits(u32::from(field.spec.data_width()));
+                Operand {
+                    name: field.name.clone(),
+                    value: OperandValue::Field(field.spec.read_bits(bits).0 & mask),
+                }
+            })
+            .collect()
+    }
+
+    /// Base instruction `alias` assembles to. The pinned constants are appended to `operands`
+    /// and tied fields copy the operand they follow.
+    pub(super) fn expand_alias(
+        &self,
+        alias: &Instruction,
+        operands: &mut Vec<(String, i64)>,
+    ) -> Result<&Instruction, IsaError> {
+        let Some(spec) = &alias.alias else {
+            unreachable!("only called for aliases");
+        };
+        let (_, base) = self.find_base(&alias.space, &spec.base).ok_or_else(|| {
+            IsaError::Machine(format!(
+                "alias '{}' refers to unknown instruction '{}'",
+                alias.name, spec.base
+            ))
+        })?;
+        for constraint in &spec.constraints {
+            let value = match &constraint.value {
+                AliasValue::Number(number) => *number as i64,
+                AliasValue::Field(other) => operands
+                    .iter()
+                    .find(|(name, _)| name == other)
+                    .map(|(_, value)| *value)
+                    .ok_or_else(|| {
+                        IsaError::Machine(format!(
+                            "alias '{}' ties '{}' to '{other}', which is not an operand",
+                            alias.name, constraint.field
+                        ))
+                    })?,
+            };
+            operands.push((constraint.field.clone(), value));
+        }
+        Ok(base)
+    }
+}
+
+/// `NAME=0x<bits>` list of [`Syntax::Raw`] operands.
+pub(super) fn raw_display(operands: &[Operand]) -> String {
+    operands
+        .iter()
+        .map(|operand| match operand.value {
+            OperandValue::Field(value) => format!("{}=0x{value:X}", operand.name),
+            _ => operand.name.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -14,6 +14,7 @@ pub mod machine;
 mod register;
 pub mod semantics;
 mod space;
+pub(crate) mod suggest;
 pub mod validator;
 
 pub use builder::IsaBuilder;
@@ -8,7 +8,9 @@ use crate::soc::isa::error::IsaError;
 pub mod analyzer;
 pub mod bindings;
 pub mod context;
+pub mod effects;
 pub mod expression;
+pub mod memory;
 pub mod program;
 pub mod register;
 pub mod runtime;
@@ -16,6 +18,8 @@ pub mod trace;
 pub mod value;
 
 pub use bindings::{OperandBinder, ParameterBindings};
+pub use effects::{RegisterIndex, RegisterLocation, SemanticEffects};
+pub use memory::{MemoryAccess, MemoryFault};
 pub use program::SemanticProgram;
 
 /// A semantic block captures the original source plus any parsed operations.
@@ -1,13 +1,20 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use crate::soc::isa::ast::SpaceKind;
+use crate::soc::isa::ast::{ParameterValue, SpaceKind};
 use crate::soc::isa::diagnostic::{DiagnosticLevel, DiagnosticPhase, IsaDiagnostic, SourceSpan};
 use crate::soc::isa::error::IsaError;
 use crate::soc::isa::machine::{Instruction, MachineDescription, MacroInfo};
 
+use super::effects::{RegisterIndex, RegisterLocation, SemanticEffects};
 use super::program::{
-    AssignTarget, ContextCall, ContextKind, Expr, RegisterRef, SemanticProgram, SemanticStmt,
+    AssignTarget, ContextCall, ContextKind, Expr, ExprBinaryOp, RegisterRef, SemanticProgram,
+    SemanticStmt,
 };
+use super::register::resolve_reference_path;
+use super::value::Conversion;
+
+/// Redirect hops followed before giving up on an alias chain.
+const MAX_REDIRECTS: usize = 8;
 
 pub struct SemanticAnalyzer<'machine> {
     machine: &'machine MachineDescription,
@@ -52,6 +59,281 @@ impl<'machine> SemanticAnalyzer<'machine> {
         self.validate_program(program, &mut scope)
     }
 
+    /// Registers `instruction` reads and writes, following `$macro::` and `$insn::` calls
+    /// transitively and resolving `redirect=` aliases and element labels such as `CR0`.
+    pub fn summarize_instruction(
+        &self,
+        instruction: &Instruction,
+    ) -> Result<SemanticEffects, IsaError> {
+        let mut effects = SemanticEffects::default();
+        let Some(block) = &instruction.semantics else {
+            return Ok(effects);
+        };
+        let program = block.ensure_program()?;
+        let params = self
+            .instruction_operands(instruction)?
+            .into_iter()
+            .map(|name| (name.clone(), RegisterIndex::Operand(name)))
+            .collect();
+        let mut stack = vec![instruction.name.clone()];
+        self.summarize_program(program, params, &mut effects, &mut stack);
+        Ok(effects)
+    }
+
+    fn summarize_program(
+        &self,
+        program: &SemanticProgram,
+        params: HashMap<String, RegisterIndex>,
+        effects: &mut SemanticEffects,
+        stack: &mut Vec<String>,
+    ) {
+        let mut scope = EffectScope {
+            params,
+            locals: HashMap::new(),
+        };
+        self.summarize_statements(&program.statements, &mut scope, effects, stack);
+    }
+
+    fn summarize_statements(
+        &self,
+        statements: &[SemanticStmt],
+        scope: &mut EffectScope,
+        effects: &mut SemanticEffects,
+        stack: &mut Vec<String>,
+    ) {
+        for stmt in statements {
+            match stmt {
+                SemanticStmt::Assign { target, expr } => {
+                    self.summarize_expr(expr, scope, effects, stack);
+                    match target {
+                        AssignTarget::Variable(name) => {
+                            let index = self.index_of(expr, scope);
+                            scope.locals.insert(name.clone(), index);
+                        }
+                        AssignTarget::Tuple(names) => {
+                            for name in names {
+                                scope.locals.insert(name.clone(), RegisterIndex::Dynamic);
+                            }
+                        }
+                        AssignTarget::Register(reference) => {
+                            if let Some(index) = &reference.index {
+                                self.summarize_expr(index, scope, effects, stack);
+                            }
+                            let location = self.locate(
+                                &reference.space,
+                                &reference.name,
+                                reference
+                                    .index
+                                    .as_ref()
+                                    .map(|expr| self.index_of(expr, scope)),
+                                reference.subfield.as_deref(),
+                            );
+                            if location.register.eq_ignore_ascii_case("PC") {
+                                effects.control_flow = true;
+                            }
+                            effects.writes.insert(location);
+                        }
+                    }
+                }
+                SemanticStmt::Expr(expr) | SemanticStmt::Return(expr) => {
+                    self.summarize_expr(expr, scope, effects, stack);
+                }
+                // Both arms are summarized: either may run.
+                SemanticStmt::If {
+                    condition,
+                    then,
+                    otherwise,
+                } => {
+                    self.summarize_expr(condition, scope, effects, stack);
+                    self.summarize_statements(then, scope, effects, stack);
+                    self.summarize_statements(otherwise, scope, effects, stack);
+                }
+                SemanticStmt::For {
+                    var,
+                    start,
+                    end,
+                    body,
+                } => {
+                    self.summarize_expr(start, scope, effects, stack);
+                    self.summarize_expr(end, scope, effects, stack);
+                    scope.locals.insert(var.clone(), RegisterIndex::Dynamic);
+                    self.summarize_statements(body, scope, effects, stack);
+                }
+            }
+        }
+    }
+
+    fn summarize_expr(
+        &self,
+        expr: &Expr,
+        scope: &EffectScope,
+        effects: &mut SemanticEffects,
+        stack: &mut Vec<String>,
+    ) {
+        match expr {
+            Expr::Call(call) => {
+                for arg in &call.args {
+                    self.summarize_expr(arg, scope, effects, stack);
+                }
+                self.summarize_call(call, scope, effects, stack);
+            }
+            Expr::Tuple(items) => {
+                for item in items {
+                    self.summarize_expr(item, scope, effects, stack);
+                }
+            }
+            Expr::BinaryOp { lhs, rhs, .. } => {
+                self.summarize_expr(lhs, scope, effects, stack);
+                self.summarize_expr(rhs, scope, effects, stack);
+            }
+            Expr::BitSlice { expr, .. } | Expr::UnaryOp { expr, .. } => {
+                self.summarize_expr(expr, scope, effects, stack)
+            }
+            Expr::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => {
+                self.summarize_expr(condition, scope, effects, stack);
+                self.summarize_expr(then, scope, effects, stack);
+                self.summarize_expr(otherwise, scope, effects, stack);
+            }
+            Expr::Number(_) | Expr::Variable { .. } | Expr::Parameter { .. } => {}
+        }
+    }
+
+    fn summarize_call(
+        &self,
+        call: &ContextCall,
+        scope: &EffectScope,
+        effects: &mut SemanticEffects,
+        stack: &mut Vec<String>,
+    ) {
+        let callee = match call.kind {
+            ContextKind::Register => {
+                let location = self.locate(
+                    &call.space,
+                    &call.name,
+                    call.args.first().map(|expr| self.index_of(expr, scope)),
+                    call.subpath.first().map(String::as_str),
+                );
+                effects.reads.insert(location);
+                return;
+            }
+            ContextKind::Host | ContextKind::Cast => return,
+            ContextKind::Memory => {
+                effects.memory = true;
+                return;
+            }
+            ContextKind::Macro => self
+                .machine
+                .macros
+                .iter()
+                .find(|mac| mac.name == call.name)
+                .map(|mac| (mac.parameters.clone(), &mac.semantics)),
+            ContextKind::Instruction => self
+                .machine
+                .instructions
+                .iter()
+                .find(|instr| instr.name == call.name)
+                .and_then(|instr| {
+                    let operands = self.instruction_operands(instr).ok()?;
+                    Some((operands, instr.semantics.as_ref()?))
+                }),
+        };
+        let Some((names, block)) = callee else {
+            return;
+        };
+        // Recursive calls add nothing the outer expansion has not already recorded.
+        if stack.contains(&call.name) {
+            return;
+        }
+        let Ok(program) = block.ensure_program() else {
+            return;
+        };
+        let params = names
+            .into_iter()
+            .zip(call.args.iter().map(|arg| self.index_of(arg, scope)))
+            .collect();
+        stack.push(call.name.clone());
+        self.summarize_program(program, params, effects, stack);
+        stack.pop();
+    }
+
+    /// Register element an index expression selects, as far as it is known statically.
+    fn index_of(&self, expr: &Expr, scope: &EffectScope) -> RegisterIndex {
+        match expr {
+            Expr::Number(value) => RegisterIndex::Fixed(*value),
+            Expr::Parameter { name, .. } => scope
+                .params
+                .get(name)
+                .cloned()
+                .or_else(|| match self.machine.parameters.get(name) {
+                    Some(ParameterValue::Number(value)) => Some(RegisterIndex::Fixed(*value)),
+                    _ => None,
+                })
+                .unwrap_or(RegisterIndex::Dynamic),
+            Expr::Variable { name, .. } => scope
+                .locals
+                .get(name)
+                .cloned()
+                .unwrap_or(RegisterIndex::Dynamic),
+            _ => RegisterIndex::Dynamic,
+        }
+    }
+
+    /// Canonical location of `space::name`: redirect aliases are followed and element labels
+    /// like `CR0` or `SPR8` become an index into their register file.
+    fn locate(
+        &self,
+        space: &str,
+        name: &str,
+        mut index: Option<RegisterIndex>,
+        subfield: Option<&str>,
+    ) -> RegisterLocation {
+        let mut space = space.to_string();
+        let mut name = name.to_string();
+        let mut subfield = subfield.map(str::to_string);
+        for _ in 0..MAX_REDIRECTS {
+            let Some(reference) = self
+                .machine
+                .spaces
+                .get(&space)
+                .and_then(|info| info.registers.get(&name))
+                .and_then(|register| register.redirect.as_ref())
+            else {
+                break;
+            };
+            let (next_space, path) = resolve_reference_path(&space, reference);
+            let Some(next) = path.first() else {
+                break;
+            };
+            if subfield.is_none() {
+                subfield = path.get(1).cloned();
+            }
+            space = next_space;
+            name = next.clone();
+        }
+        let declared = self
+            .machine
+            .spaces
+            .get(&space)
+            .is_some_and(|info| info.registers.contains_key(&name));
+        if !declared
+            && let Some((metadata, element)) =
+                self.machine.register_schema().find_by_label(&space, &name)
+        {
+            name = metadata.name.clone();
+            index = Some(RegisterIndex::Fixed(u64::from(element.index)));
+        }
+        RegisterLocation {
+            space,
+            register: name,
+            index,
+            subfield,
+        }
+    }
+
     fn validate_program(
         &self,
         program: &SemanticProgram,
@@ -85,6 +367,54 @@ impl<'machine> SemanticAnalyzer<'machine> {
             SemanticStmt::Expr(expr) | SemanticStmt::Return(expr) => {
                 self.validate_expr(expr, scope, diags)?;
             }
+            SemanticStmt::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                self.validate_expr(condition, scope, diags)?;
+                let mut then_scope = scope.clone();
+                for stmt in then {
+                    self.validate_statement(stmt, &mut then_scope, diags)?;
+                }
+                let mut else_scope = scope.clone();
+                for stmt in otherwise {
+                    self.validate_statement(stmt, &mut else_scope, diags)?;
+                }
+                // Only names every arm defines are defined after the `if`.
+                for name in then_scope.locals.intersection(&else_scope.locals) {
+                    scope.define(name.clone());
+                }
+            }
+            SemanticStmt::For {
+                var,
+                start,
+                end,
+                body,
+            } => {
+                for bound in [start, end] {
+                    self.validate_expr(bound, scope, diags)?;
+                    if !scope.is_static(bound) {
+                        self.push_diag(
+                            diags,
+                            "semantics.unbounded-loop",
+                            format!(
+                                "bounds of the loop over '{var}' must be built from literals, parameters and enclosing loop variables"
+                            ),
+                            expr_span(bound),
+                        );
+                    }
+                }
+                // The range is inclusive, so the body always runs and its bindings persist.
+                scope.define(var.clone());
+                let outermost = scope.loop_vars.insert(var.clone());
+                for stmt in body {
+                    self.validate_statement(stmt, scope, diags)?;
+                }
+                if outermost {
+                    scope.loop_vars.remove(var);
+                }
+            }
         }
         Ok(())
     }
@@ -95,16 +425,24 @@ impl<'machine> SemanticAnalyzer<'machine> {
         scope: &mut AnalyzerScope,
         diags: &mut Vec<IsaDiagnostic>,
     ) {
-        match target {
-            AssignTarget::Variable(name) => scope.define(name.clone()),
-            AssignTarget::Tuple(names) => {
-                for name in names {
-                    scope.define(name.clone());
-                }
-            }
+        let names = match target {
+            AssignTarget::Variable(name) => std::slice::from_ref(name),
+            AssignTarget::Tuple(names) => names.as_slice(),
             AssignTarget::Register(reference) => {
                 self.validate_register_reference(reference, diags);
+                return;
             }
+        };
+        for name in names {
+            if scope.loop_vars.contains(name) {
+                self.push_diag(
+                    diags,
+                    "semantics.loop-variable-assigned",
+                    format!("loop variable '{name}' cannot be assigned inside its loop"),
+                    None,
+                );
+            }
+            scope.define(name.clone());
         }
     }
 
@@ -147,13 +485,32 @@ impl<'machine> SemanticAnalyzer<'machine> {
                     self.validate_expr(expr, scope, diags)?;
                 }
             }
-            Expr::BinaryOp { lhs, rhs, .. } => {
+            Expr::BinaryOp { op, lhs, rhs } => {
                 self.validate_expr(lhs, scope, diags)?;
                 self.validate_expr(rhs, scope, diags)?;
+                if matches!(op, ExprBinaryOp::Div | ExprBinaryOp::Rem)
+                    && matches!(**rhs, Expr::Number(0))
+                {
+                    self.push_diag(
+                        diags,
+                        "semantics.division-by-zero",
+                        "divisor is the literal 0",
+                        expr_span(lhs),
+                    );
+                }
             }
-            Expr::BitSlice { expr, .. } => {
+            Expr::BitSlice { expr, .. } | Expr::UnaryOp { expr, .. } => {
                 self.validate_expr(expr, scope, diags)?;
             }
+            Expr::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => {
+                self.validate_expr(condition, scope, diags)?;
+                self.validate_expr(then, scope, diags)?;
+                self.validate_expr(otherwise, scope, diags)?;
+            }
         }
         Ok(())
     }
@@ -177,6 +534,14 @@ impl<'machine> SemanticAnalyzer<'machine> {
                 self.validate_host_call(call, diags);
                 Ok(())
             }
+            ContextKind::Memory => {
+                self.validate_memory_call(call, diags);
+                Ok(())
+            }
+            ContextKind::Cast => {
+                self.validate_cast_call(call, diags);
+                Ok(())
+            }
         }
     }
 
@@ -244,7 +609,7 @@ impl<'machine> SemanticAnalyzer<'machine> {
                     self.push_arity_diag(call, 4, call.args.len(), diags);
                 }
             }
-            "mul" => {
+            "mul" | "select" => {
                 if call.args.len() != 3 {
                     self.push_arity_diag(call, 3, call.args.len(), diags);
                 }
@@ -260,6 +625,42 @@ impl<'machine> SemanticAnalyzer<'machine> {
         }
     }
 
+    fn validate_memory_call(&self, call: &ContextCall, diags: &mut Vec<IsaDiagnostic>) {
+        let expected = match call.name.as_str() {
+            "read" | "read_signed" => 2,
+            "write" => 3,
+            other => {
+                self.push_diag(
+                    diags,
+                    "semantics.unknown-memory-access",
+                    format!("memory access '${}::{other}' is not supported", call.space),
+                    Some(call.span.clone()),
+                );
+                return;
+            }
+        };
+        if call.args.len() != expected {
+            self.push_arity_diag(call, expected, call.args.len(), diags);
+        }
+    }
+
+    fn validate_cast_call(&self, call: &ContextCall, diags: &mut Vec<IsaDiagnostic>) {
+        if Conversion::parse(&call.name).is_none() {
+            self.push_diag(
+                diags,
+                "semantics.unknown-conversion",
+                format!(
+                    "conversion '${}::{}' is not supported; use u<N>, i<N>, f32, f64, bits, \
+                     f32_from_bits or f64_from_bits",
+                    call.space, call.name
+                ),
+                Some(call.span.clone()),
+            );
+        } else if call.args.len() != 1 {
+            self.push_arity_diag(call, 1, call.args.len(), diags);
+        }
+    }
+
     fn validate_register_call(&self, call: &ContextCall, diags: &mut Vec<IsaDiagnostic>) {
         self.validate_register_components(
             &call.space,
@@ -401,9 +802,18 @@ impl<'machine> SemanticAnalyzer<'machine> {
     }
 }
 
+/// Parameter and local bindings that select register elements during summarization.
+struct EffectScope {
+    params: HashMap<String, RegisterIndex>,
+    locals: HashMap<String, RegisterIndex>,
+}
+
+#[derive(Clone)]
 struct AnalyzerScope {
     parameters: HashSet<String>,
     locals: HashSet<String>,
+    /// Variables of the `for` loops enclosing the statement being validated.
+    loop_vars: HashSet<String>,
 }
 
 impl AnalyzerScope {
@@ -414,6 +824,7 @@ impl AnalyzerScope {
         Self {
             parameters: params.into_iter().collect(),
             locals: HashSet::new(),
+            loop_vars: HashSet::new(),
         }
     }
 
@@ -428,6 +839,41 @@ impl AnalyzerScope {
     fn define(&mut self, name: String) {
         self.locals.insert(name);
     }
+
+    /// Whether `expr` is fixed once decoding is done, which bounds a loop over it.
+    fn is_static(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Number(_) | Expr::Parameter { .. } => true,
+            Expr::Variable { name, .. } => self.loop_vars.contains(name),
+            Expr::BinaryOp { lhs, rhs, .. } => self.is_static(lhs) && self.is_static(rhs),
+            Expr::UnaryOp { expr, .. } | Expr::BitSlice { expr, .. } => self.is_static(expr),
+            Expr::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => self.is_static(condition) && self.is_static(then) && self.is_static(otherwise),
+            Expr::Call(_) | Expr::Tuple(_) => false,
+        }
+    }
+}
+
+/// First source location inside `expr`; literals and operators carry none of their own.
+fn expr_span(expr: &Expr) -> Option<SourceSpan> {
+    match expr {
+        Expr::Variable { span, .. } | Expr::Parameter { span, .. } => Some(span.clone()),
+        Expr::Call(call) => Some(call.span.clone()),
+        Expr::Number(_) => None,
+        Expr::Tuple(items) => items.iter().find_map(expr_span),
+        Expr::BinaryOp { lhs, rhs, .. } => expr_span(lhs).or_else(|| expr_span(rhs)),
+        Expr::UnaryOp { expr, .. } | Expr::BitSlice { expr, .. } => expr_span(expr),
+        Expr::Conditional {
+            condition,
+            then,
+            otherwise,
+        } => expr_span(condition)
+            .or_else(|| expr_span(then))
+            .or_else(|| expr_span(otherwise)),
+    }
 }
 
 #[cfg(test)]
@@ -497,6 +943,61 @@ mod tests {
         }
     }
 
+    #[test]
+    fn validates_control_flow() {
+        let mut block = SemanticBlock::empty();
+        block.set_program(
+            SemanticProgram::parse(
+                "limit = 4
+                 for i in 0..limit {
+                     i = i + 1
+                 }
+                 for j in 0..31 {
+                     for k in j..31 { }
+                 }
+                 y = 1 / 0
+                 if (limit == 4) { both = 1 only = 1 } else { both = 2 }
+                 (both, only)",
+            )
+            .expect("parse"),
+        );
+        let spec = specification(block);
+        let err = MachineDescription::from_documents(vec![spec]).expect_err("should fail");
+        let IsaError::Diagnostics { diagnostics, .. } = err else {
+            panic!("expected diagnostics error, got {err:?}");
+        };
+        let codes: Vec<&str> = diagnostics.iter().map(|diag| diag.code).collect();
+        assert_eq!(
+            codes,
+            vec![
+                "semantics.unbounded-loop",
+                "semantics.loop-variable-assigned",
+                "semantics.division-by-zero",
+                "semantics.undefined-variable",
+            ]
+        );
+        assert!(diagnostics[3].message.contains("'only'"));
+    }
+
+    #[test]
+    fn rejects_unknown_conversions() {
+        let mut block = SemanticBlock::empty();
+        block.set_program(
+            SemanticProgram::parse("a = $cast::u12(1) b = $cast::u0(1) c = $cast::f32(1, 2)")
+                .expect("parse"),
+        );
+        let spec = specification(block);
+        let err = MachineDescription::from_documents(vec![spec]).expect_err("should fail");
+        let IsaError::Diagnostics { diagnostics, .. } = err else {
+            panic!("expected diagnostics error, got {err:?}");
+        };
+        let codes: Vec<&str> = diagnostics.iter().map(|diag| diag.code).collect();
+        assert_eq!(
+            codes,
+            vec!["semantics.unknown-conversion", "semantics.call-arity"]
+        );
+    }
+
     fn specification(block: SemanticBlock) -> IsaSpecification {
         let path = PathBuf::from("test.isa");
         let span = SourceSpan::point(path.clone(), SourcePosition::new(1, 1));
@@ -522,6 +1023,9 @@ mod tests {
             semantics: Some(block),
             display: None,
             operator: None,
+            extend: None,
+            alias: None,
+            valid: None,
             span,
         }));
         IsaSpecification::new(path, items)
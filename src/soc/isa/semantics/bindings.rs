@@ -188,6 +188,7 @@ mod tests {
             }],
             register: None,
             kind,
+            description: None,
         }
     }
 
@@ -0,0 +1,166 @@
+//! Def/use summaries of semantic programs: the registers an instruction reads and writes, with
+//! `redirect=` aliases resolved, and whether it touches memory or redirects control flow.
+
+use std::collections::BTreeSet;
+
+/// Element of a register file selected by an access.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RegisterIndex {
+    /// Fixed element, e.g. `SPR(8)` reached through `LR`.
+    Fixed(u64),
+    /// Element selected by an instruction operand, e.g. `GPR(#RA)`.
+    Operand(String),
+    /// Element computed at run time.
+    Dynamic,
+}
+
+/// Register (or one subfield of it) touched by a semantic program.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RegisterLocation {
+    pub space: String,
+    /// Register name after following `redirect=` aliases.
+    pub register: String,
+    pub index: Option<RegisterIndex>,
+    pub subfield: Option<String>,
+}
+
+impl RegisterLocation {
+    /// Whether this location overlaps `other`: same register, compatible element and either
+    /// side covering the whole register or both naming the same subfield.
+    pub fn overlaps(&self, other: &RegisterLocation) -> bool {
+        let index = match (&self.index, &other.index) {
+            (Some(RegisterIndex::Fixed(a)), Some(RegisterIndex::Fixed(b))) => a == b,
+            (Some(RegisterIndex::Operand(a)), Some(RegisterIndex::Operand(b))) => a == b,
+            _ => true,
+        };
+        let subfield = match (&self.subfield, &other.subfield) {
+            (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+            _ => true,
+        };
+        self.space == other.space && self.register == other.register && index && subfield
+    }
+}
+
+impl std::fmt::Display for RegisterLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}::{}", self.space, self.register)?;
+        match &self.index {
+            Some(RegisterIndex::Fixed(index)) => write!(f, "({index})")?,
+            Some(RegisterIndex::Operand(name)) => write!(f, "(#{name})")?,
+            Some(RegisterIndex::Dynamic) => write!(f, "(?)")?,
+            None => {}
+        }
+        if let Some(field) = &self.subfield {
+            write!(f, "::{field}")?;
+        }
+        Ok(())
+    }
+}
+
+/// What an instruction's semantics touch, including everything reached through `$macro::` and
+/// `$insn::` calls.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SemanticEffects {
+    pub reads: BTreeSet<RegisterLocation>,
+    pub writes: BTreeSet<RegisterLocation>,
+    /// Reads or writes memory.
+    pub memory: bool,
+    /// Writes the program counter.
+    pub control_flow: bool,
+}
+
+impl SemanticEffects {
+    pub fn is_empty(&self) -> bool {
+        self.reads.is_empty() && self.writes.is_empty() && !self.memory && !self.control_flow
+    }
+
+    /// Copy with operand-selected elements replaced by the values `operand` yields for them.
+    pub fn bind(&self, operand: impl Fn(&str) -> Option<u64>) -> SemanticEffects {
+        let bind = |locations: &BTreeSet<RegisterLocation>| {
+            locations
+                .iter()
+                .map(|location| {
+                    let mut location = location.clone();
+                    if let Some(RegisterIndex::Operand(name)) = &location.index
+                        && let Some(value) = operand(name)
+                    {
+                        location.index = Some(RegisterIndex::Fixed(value));
+                    }
+                    location
+                })
+                .collect()
+        };
+        SemanticEffects {
+            reads: bind(&self.reads),
+            writes: bind(&self.writes),
+            memory: self.memory,
+            control_flow: self.control_flow,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(
+        register: &str,
+        index: Option<RegisterIndex>,
+        subfield: Option<&str>,
+    ) -> RegisterLocation {
+        RegisterLocation {
+            space: "reg".into(),
+            register: register.into(),
+            index,
+            subfield: subfield.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn overlap_respects_elements_and_subfields() {
+        let whole = location("CR", Some(RegisterIndex::Fixed(0)), None);
+        let lt = location("CR", Some(RegisterIndex::Fixed(0)), Some("LT"));
+        let gt = location("CR", Some(RegisterIndex::Fixed(0)), Some("GT"));
+        let other = location("CR", Some(RegisterIndex::Fixed(1)), Some("LT"));
+        let dynamic = location("CR", Some(RegisterIndex::Dynamic), None);
+        assert!(whole.overlaps(&lt));
+        assert!(!lt.overlaps(&gt));
+        assert!(!lt.overlaps(&other));
+        assert!(dynamic.overlaps(&other));
+        assert_eq!(lt.to_string(), "reg::CR(0)::LT");
+    }
+
+    #[test]
+    fn bind_fixes_operand_elements() {
+        let mut effects = SemanticEffects::default();
+        effects.reads.insert(location(
+            "GPR",
+            Some(RegisterIndex::Operand("RA".into())),
+            None,
+        ));
+        effects.writes.insert(location(
+            "GPR",
+            Some(RegisterIndex::Operand("RT".into())),
+            None,
+        ));
+        let bound = effects.bind(|name| (name == "RA").then_some(4));
+        assert_eq!(
+            bound
+                .reads
+                .iter()
+                .next()
+                .map(ToString::to_string)
+                .as_deref(),
+            Some("reg::GPR(4)")
+        );
+        assert_eq!(
+            bound
+                .writes
+                .iter()
+                .next()
+                .map(ToString::to_string)
+                .as_deref(),
+            Some("reg::GPR(#RT)")
+        );
+    }
+}
@@ -4,10 +4,12 @@
 //! `SemanticProgram`. Higher-level constructs such as register or host calls
 //! are handled by the runtime once execution plumbing lands.
 
+use std::cmp::Ordering;
+
 use crate::soc::isa::error::IsaError;
 use crate::soc::isa::semantics::context::ExecutionContext;
-use crate::soc::isa::semantics::program::{BitSlice, ContextCall, Expr, ExprBinaryOp};
-use crate::soc::isa::semantics::value::SemanticValue;
+use crate::soc::isa::semantics::program::{BitSlice, ContextCall, Expr, ExprBinaryOp, ExprUnaryOp};
+use crate::soc::isa::semantics::value::{BitVector, SemanticValue};
 
 /// Resolves `$context::foo()` style expressions when evaluating semantic IR.
 pub trait ContextCallResolver {
@@ -72,6 +74,18 @@ where
             Expr::Tuple(items) => self.evaluate_tuple(items),
             Expr::BinaryOp { op, lhs, rhs } => self.evaluate_binary(*op, lhs, rhs),
             Expr::BitSlice { expr, slice } => self.evaluate_bit_slice(expr, slice),
+            Expr::UnaryOp { op, expr } => self.evaluate_unary(*op, expr),
+            Expr::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => {
+                if self.eval(condition)?.as_bool()? {
+                    self.eval(then)
+                } else {
+                    self.eval(otherwise)
+                }
+            }
         }
     }
 
@@ -89,11 +103,12 @@ where
             .ok_or_else(|| IsaError::Machine(format!("unknown parameter '#{name}'")))
     }
 
+    /// Literals above `i64::MAX` only fit a `u64`.
     fn literal(value: u64) -> Result<SemanticValue, IsaError> {
-        let signed = i64::try_from(value).map_err(|_| {
-            IsaError::Machine(format!("literal value {value} exceeds 64-bit signed range"))
-        })?;
-        Ok(SemanticValue::int(signed))
+        match i64::try_from(value) {
+            Ok(signed) => Ok(SemanticValue::int(signed)),
+            Err(_) => BitVector::new(u128::from(value), 64, false).map(SemanticValue::Bits),
+        }
     }
 
     fn evaluate_call(&mut self, call: &ContextCall) -> Result<SemanticValue, IsaError> {
@@ -137,50 +152,40 @@ where
                     Ok(SemanticValue::bool(right))
                 }
             }
-            ExprBinaryOp::BitOr => self.int_binary(lhs, rhs, |l, r| l | r),
-            ExprBinaryOp::BitXor => self.int_binary(lhs, rhs, |l, r| l ^ r),
-            ExprBinaryOp::BitAnd => self.int_binary(lhs, rhs, |l, r| l & r),
-            ExprBinaryOp::Add => self.int_binary(lhs, rhs, |l, r| l.wrapping_add(r)),
-            ExprBinaryOp::Sub => self.int_binary(lhs, rhs, |l, r| l.wrapping_sub(r)),
-            ExprBinaryOp::Eq => {
-                let left = self.eval(lhs)?;
-                let right = self.eval(rhs)?;
-                let result = match (&left, &right) {
-                    (SemanticValue::Bool(a), SemanticValue::Bool(b)) => *a == *b,
-                    _ => left.as_int()? == right.as_int()?,
-                };
-                Ok(SemanticValue::bool(result))
+            ExprBinaryOp::Shl | ExprBinaryOp::Shr | ExprBinaryOp::Sar => {
+                let value = self.eval(lhs)?;
+                let amount = self.eval(rhs)?.as_int()?;
+                shift(op, value, amount)
             }
-            ExprBinaryOp::Ne => {
+            _ => {
                 let left = self.eval(lhs)?;
                 let right = self.eval(rhs)?;
-                let result = match (&left, &right) {
-                    (SemanticValue::Bool(a), SemanticValue::Bool(b)) => *a != *b,
-                    _ => left.as_int()? != right.as_int()?,
-                };
-                Ok(SemanticValue::bool(result))
+                apply_binary(op, left, right)
             }
-            ExprBinaryOp::Lt => self.int_compare(lhs, rhs, |l, r| l < r),
-            ExprBinaryOp::Gt => self.int_compare(lhs, rhs, |l, r| l > r),
         }
     }
 
-    fn int_binary<F>(&mut self, lhs: &Expr, rhs: &Expr, op: F) -> Result<SemanticValue, IsaError>
-    where
-        F: FnOnce(i64, i64) -> i64,
-    {
-        let left = self.eval(lhs)?.as_int()?;
-        let right = self.eval(rhs)?.as_int()?;
-        Ok(SemanticValue::int(op(left, right)))
-    }
-
-    fn int_compare<F>(&mut self, lhs: &Expr, rhs: &Expr, cmp: F) -> Result<SemanticValue, IsaError>
-    where
-        F: FnOnce(i64, i64) -> bool,
-    {
-        let left = self.eval(lhs)?.as_int()?;
-        let right = self.eval(rhs)?.as_int()?;
-        Ok(SemanticValue::bool(cmp(left, right)))
+    fn evaluate_unary(&mut self, op: ExprUnaryOp, expr: &Expr) -> Result<SemanticValue, IsaError> {
+        let value = self.eval(expr)?;
+        match (op, value) {
+            (ExprUnaryOp::Not, value) => Ok(SemanticValue::bool(!value.as_bool()?)),
+            (ExprUnaryOp::BitNot, SemanticValue::Bits(bits)) => {
+                Ok(SemanticValue::Bits(bits.bit_not()))
+            }
+            (ExprUnaryOp::Neg, SemanticValue::Bits(bits)) => {
+                Ok(SemanticValue::Bits(bits.wrapping_neg()))
+            }
+            (ExprUnaryOp::Neg, SemanticValue::F32(value)) => Ok(SemanticValue::F32(-value)),
+            (ExprUnaryOp::Neg, SemanticValue::F64(value)) => Ok(SemanticValue::F64(-value)),
+            (ExprUnaryOp::BitNot, value @ (SemanticValue::F32(_) | SemanticValue::F64(_))) => {
+                Err(IsaError::Machine(format!(
+                    "operator '~' is not defined for {}",
+                    value.type_name()
+                )))
+            }
+            (ExprUnaryOp::BitNot, value) => Ok(SemanticValue::int(!value.as_int()?)),
+            (ExprUnaryOp::Neg, value) => Ok(SemanticValue::int(value.as_int()?.wrapping_neg())),
+        }
     }
 
     fn evaluate_bit_slice(
@@ -194,20 +199,236 @@ where
                 slice.end, slice.start
             )));
         }
+        let value = self.eval(expr)?;
+        let width = slice.end - slice.start + 1;
+        if let SemanticValue::Bits(bits) = value {
+            if slice.end >= bits.width() {
+                return Err(IsaError::Machine(format!(
+                    "bit slice @({}..{}) exceeds {} width",
+                    slice.start,
+                    slice.end,
+                    bits.type_name()
+                )));
+            }
+            return BitVector::new(bits.bits() >> slice.start, width, false)
+                .map(SemanticValue::Bits);
+        }
         if slice.end >= 64 {
             return Err(IsaError::Machine(format!(
                 "bit slice @({}..{}) exceeds 64-bit width",
                 slice.start, slice.end
             )));
         }
-        let value = self.eval(expr)?.as_int()? as u64;
-        let width = slice.end - slice.start + 1;
+        let value = value.as_int()? as u64;
         let mask = mask_for_bits(width);
         let sliced = (value >> slice.start) & mask;
         Ok(SemanticValue::int(sliced as i64))
     }
 }
 
+/// Applies an arithmetic, bitwise or comparison operator. Unsized integers and booleans adopt
+/// the type of a sized operand when their value fits it; two sized operands must share a width
+/// and stay signed only if both are. Floats combine only with the same float type.
+pub(crate) fn apply_binary(
+    op: ExprBinaryOp,
+    left: SemanticValue,
+    right: SemanticValue,
+) -> Result<SemanticValue, IsaError> {
+    match Operands::unify(left, right)? {
+        Operands::Int(l, r) => int_binary(op, l, r),
+        Operands::Bits(l, r) => bits_binary(op, l, r),
+        Operands::Float(l, r, single) => float_binary(op, l, r, single),
+    }
+}
+
+/// Both sides of a binary operator converted to a common type.
+enum Operands {
+    Int(i64, i64),
+    Bits(BitVector, BitVector),
+    /// `f32` operands are widened exactly; `single` rounds the result back.
+    Float(f64, f64, bool),
+}
+
+impl Operands {
+    fn unify(left: SemanticValue, right: SemanticValue) -> Result<Self, IsaError> {
+        match (left, right) {
+            (SemanticValue::F32(l), SemanticValue::F32(r)) => {
+                Ok(Operands::Float(f64::from(l), f64::from(r), true))
+            }
+            (SemanticValue::F64(l), SemanticValue::F64(r)) => Ok(Operands::Float(l, r, false)),
+            (l @ (SemanticValue::F32(_) | SemanticValue::F64(_)), r)
+            | (l, r @ (SemanticValue::F32(_) | SemanticValue::F64(_))) => {
+                Err(IsaError::Machine(format!(
+                    "cannot combine {} and {} operands without a $cast:: conversion",
+                    l.type_name(),
+                    r.type_name()
+                )))
+            }
+            (SemanticValue::Bits(l), SemanticValue::Bits(r)) => {
+                if l.width() != r.width() {
+                    return Err(IsaError::Machine(format!(
+                        "cannot combine {} and {} operands without a $cast:: conversion",
+                        l.type_name(),
+                        r.type_name()
+                    )));
+                }
+                let signed = l.is_signed() && r.is_signed();
+                Ok(Operands::Bits(
+                    l.resize(l.width(), signed)?,
+                    r.resize(r.width(), signed)?,
+                ))
+            }
+            (SemanticValue::Bits(l), r) => Ok(Operands::Bits(l, adopt(&r, l)?)),
+            (l, SemanticValue::Bits(r)) => Ok(Operands::Bits(adopt(&l, r)?, r)),
+            (l, r) => Ok(Operands::Int(l.as_int()?, r.as_int()?)),
+        }
+    }
+}
+
+/// `value` converted to the type of `like`. Integers must fit the width either sign- or
+/// zero-extended, so `~3` stays a valid mask while `0x1FF` is rejected next to a `u8`.
+fn adopt(value: &SemanticValue, like: BitVector) -> Result<BitVector, IsaError> {
+    let width = like.width();
+    if let SemanticValue::Int(int) = value
+        && width < 64
+        && (int >> width) != 0
+        && (int >> (width - 1)) != -1
+    {
+        return Err(IsaError::Machine(format!(
+            "int value {int} does not fit {} operand; use a $cast:: conversion",
+            like.type_name()
+        )));
+    }
+    BitVector::new(value.to_bits(width)?, width, like.is_signed())
+}
+
+fn int_binary(op: ExprBinaryOp, l: i64, r: i64) -> Result<SemanticValue, IsaError> {
+    if let Some(result) = comparison(op, Some(l.cmp(&r))) {
+        return Ok(SemanticValue::bool(result));
+    }
+    let value = match op {
+        ExprBinaryOp::BitOr => l | r,
+        ExprBinaryOp::BitXor => l ^ r,
+        ExprBinaryOp::BitAnd => l & r,
+        ExprBinaryOp::Add => l.wrapping_add(r),
+        ExprBinaryOp::Sub => l.wrapping_sub(r),
+        ExprBinaryOp::Mul => l.wrapping_mul(r),
+        ExprBinaryOp::Div | ExprBinaryOp::Rem if r == 0 => return Err(division_by_zero()),
+        ExprBinaryOp::Div => l.wrapping_div(r),
+        ExprBinaryOp::Rem => l.wrapping_rem(r),
+        other => return Err(undefined_operator(other, "int")),
+    };
+    Ok(SemanticValue::int(value))
+}
+
+fn bits_binary(op: ExprBinaryOp, l: BitVector, r: BitVector) -> Result<SemanticValue, IsaError> {
+    if let Some(result) = comparison(op, Some(l.compare(r))) {
+        return Ok(SemanticValue::bool(result));
+    }
+    let value = match op {
+        ExprBinaryOp::BitOr => l.or(r),
+        ExprBinaryOp::BitXor => l.xor(r),
+        ExprBinaryOp::BitAnd => l.and(r),
+        ExprBinaryOp::Add => l.wrapping_add(r),
+        ExprBinaryOp::Sub => l.wrapping_sub(r),
+        ExprBinaryOp::Mul => l.wrapping_mul(r),
+        ExprBinaryOp::Div => l.checked_div(r).ok_or_else(division_by_zero)?,
+        ExprBinaryOp::Rem => l.checked_rem(r).ok_or_else(division_by_zero)?,
+        other => return Err(undefined_operator(other, &l.type_name())),
+    };
+    Ok(SemanticValue::Bits(value))
+}
+
+/// IEEE arithmetic: division by zero yields an infinity or NaN and comparisons with NaN are
+/// false except `!=`.
+fn float_binary(op: ExprBinaryOp, l: f64, r: f64, single: bool) -> Result<SemanticValue, IsaError> {
+    if let Some(result) = comparison(op, l.partial_cmp(&r)) {
+        return Ok(SemanticValue::bool(result));
+    }
+    let value = match op {
+        ExprBinaryOp::Add => l + r,
+        ExprBinaryOp::Sub => l - r,
+        ExprBinaryOp::Mul => l * r,
+        ExprBinaryOp::Div => l / r,
+        ExprBinaryOp::Rem => l % r,
+        other => {
+            return Err(undefined_operator(
+                other,
+                if single { "f32" } else { "f64" },
+            ));
+        }
+    };
+    // f64 carries more than twice the f32 precision, so rounding once more is exact.
+    Ok(if single {
+        SemanticValue::F32(value as f32)
+    } else {
+        SemanticValue::F64(value)
+    })
+}
+
+/// Result of a comparison operator, `None` for every other operator. An unordered pair is
+/// only unequal.
+fn comparison(op: ExprBinaryOp, ordering: Option<Ordering>) -> Option<bool> {
+    let result = match op {
+        ExprBinaryOp::Eq => ordering.is_some_and(Ordering::is_eq),
+        ExprBinaryOp::Ne => ordering.is_none_or(Ordering::is_ne),
+        ExprBinaryOp::Lt => ordering.is_some_and(Ordering::is_lt),
+        ExprBinaryOp::Gt => ordering.is_some_and(Ordering::is_gt),
+        ExprBinaryOp::Le => ordering.is_some_and(Ordering::is_le),
+        ExprBinaryOp::Ge => ordering.is_some_and(Ordering::is_ge),
+        _ => return None,
+    };
+    Some(result)
+}
+
+/// `<<`, `>>` and `>>>`; the shifted value keeps its type.
+fn shift(op: ExprBinaryOp, value: SemanticValue, amount: i64) -> Result<SemanticValue, IsaError> {
+    match value {
+        SemanticValue::Bits(bits) => Ok(SemanticValue::Bits(match op {
+            ExprBinaryOp::Shl => bits.shift_left(amount),
+            ExprBinaryOp::Shr => bits.shift_right(amount),
+            _ => bits.shift_right_arithmetic(amount),
+        })),
+        SemanticValue::F32(_) | SemanticValue::F64(_) => {
+            Err(undefined_operator(op, &value.type_name()))
+        }
+        other => {
+            let value = other.as_int()?;
+            Ok(SemanticValue::int(match op {
+                ExprBinaryOp::Shl => shift_left(value as u64, amount) as i64,
+                ExprBinaryOp::Shr => shift_right(value as u64, amount) as i64,
+                _ => value >> amount.clamp(0, 63),
+            }))
+        }
+    }
+}
+
+fn division_by_zero() -> IsaError {
+    IsaError::Machine("division by zero in semantics".into())
+}
+
+fn undefined_operator(op: ExprBinaryOp, type_name: &str) -> IsaError {
+    IsaError::Machine(format!(
+        "operator {op:?} is not defined for {type_name} values"
+    ))
+}
+
+/// `value << amount`, shifting everything out for amounts outside `0..64`.
+pub(crate) fn shift_left(value: u64, amount: i64) -> u64 {
+    u32::try_from(amount)
+        .ok()
+        .and_then(|amount| value.checked_shl(amount))
+        .unwrap_or(0)
+}
+
+/// Logical `value >> amount`, shifting everything out for amounts outside `0..64`.
+pub(crate) fn shift_right(value: u64, amount: i64) -> u64 {
+    u32::try_from(amount)
+        .ok()
+        .and_then(|amount| value.checked_shr(amount))
+        .unwrap_or(0)
+}
+
 fn mask_for_bits(width: u32) -> u64 {
     if width >= 64 {
         u64::MAX
@@ -303,6 +524,102 @@ mod tests {
         assert_eq!(value.as_int().unwrap(), 0b011);
     }
 
+    #[test]
+    fn applies_arithmetic_shift_and_unary_operators() {
+        let params = HashMap::new();
+        let ctx = ExecutionContext::new(&params);
+        let mut evaluator = ExpressionEvaluator::new(&ctx);
+        let eval = |evaluator: &mut ExpressionEvaluator<'_, '_, NoContextResolver>, source| {
+            let program = crate::soc::isa::semantics::SemanticProgram::parse(source).unwrap();
+            let crate::soc::isa::semantics::program::SemanticStmt::Expr(expr) =
+                &program.statements[0]
+            else {
+                panic!("expected an expression");
+            };
+            evaluator.evaluate(expr)
+        };
+        for (source, expected) in [
+            ("6 * 7", 42),
+            ("-7 / 2", -3),
+            ("-7 % 2", -1),
+            ("1 << 63 >> 63", 1),
+            ("1 << 63 >>> 63", -1),
+            ("1 << 64", 0),
+            ("~0", -1),
+            ("-(3)", -3),
+            ("2 <= 2 ? 10 : 1 / 0", 10),
+        ] {
+            let value = eval(&mut evaluator, source).expect(source);
+            assert_eq!(value.as_int().unwrap(), expected, "{source}");
+        }
+        assert!(
+            !eval(&mut evaluator, "!(3 >= 2)")
+                .unwrap()
+                .as_bool()
+                .unwrap()
+        );
+        let err = eval(&mut evaluator, "5 % 0").expect_err("division by zero");
+        assert!(matches!(err, IsaError::Machine(msg) if msg.contains("division by zero")));
+    }
+
+    #[test]
+    fn sized_and_float_values_keep_their_type() {
+        let unsigned = BitVector::new(0xF0, 8, false).unwrap();
+        let signed = BitVector::new(0xF0, 8, true).unwrap();
+        let mut params = HashMap::new();
+        params.insert("a".into(), SemanticValue::Bits(unsigned));
+        params.insert("b".into(), SemanticValue::Bits(signed));
+        params.insert("x".into(), SemanticValue::F32(1.5));
+        params.insert("y".into(), SemanticValue::F64(2.0));
+        let ctx = ExecutionContext::new(&params);
+        let mut evaluator = ExpressionEvaluator::new(&ctx);
+        let mut eval = |source| {
+            let program = crate::soc::isa::semantics::SemanticProgram::parse(source).unwrap();
+            let crate::soc::isa::semantics::program::SemanticStmt::Expr(expr) =
+                &program.statements[0]
+            else {
+                panic!("expected an expression");
+            };
+            evaluator.evaluate(expr)
+        };
+        let bits =
+            |bits, width, signed| SemanticValue::Bits(BitVector::new(bits, width, signed).unwrap());
+        for (source, expected) in [
+            ("a + 0x20", bits(0x10, 8, false)),
+            ("a + b", bits(0xE0, 8, false)),
+            ("b >>> 4", bits(0xFF, 8, true)),
+            ("b >> 4", bits(0x0F, 8, true)),
+            ("b / 16", bits(0xFF, 8, true)),
+            ("a / 16", bits(0x0F, 8, false)),
+            ("~a", bits(0x0F, 8, false)),
+            ("a@(4..7)", bits(0xF, 4, false)),
+            ("0xFFFFFFFFFFFFFFFF", bits(u128::from(u64::MAX), 64, false)),
+            ("b < 0", SemanticValue::bool(true)),
+            ("a < 0", SemanticValue::bool(false)),
+            ("a & ~3", bits(0xF0, 8, false)),
+            ("a + -1", bits(0xEF, 8, false)),
+            ("x * x", SemanticValue::F32(2.25)),
+            ("-y / (y - y) < y", SemanticValue::bool(true)),
+        ] {
+            assert_eq!(eval(source).expect(source), expected, "{source}");
+        }
+        for source in ["x + y", "x + 1", "x & x", "a@(0..8)", "a % 0"] {
+            assert!(eval(source).is_err(), "{source}");
+        }
+        for source in [
+            "a + a@(4..7)",
+            "a == 0xFFFFFFFFFFFFFFFF",
+            "a + 0x1FF",
+            "b | -129",
+        ] {
+            let err = eval(source).expect_err(source);
+            assert!(
+                matches!(&err, IsaError::Machine(msg) if msg.contains("$cast::")),
+                "{source}: {err}"
+            );
+        }
+    }
+
     #[test]
     fn call_nodes_report_missing_dispatch() {
         let params = HashMap::new();
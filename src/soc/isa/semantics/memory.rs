@@ -0,0 +1,128 @@
+//! `$mem::` loads and stores. Semantics reach memory through the data bus attached to the
+//! [`CoreState`]; accesses it cannot complete become [`MemoryFault`]s so exception models can
+//! raise the architected interrupt instead of aborting execution.
+
+use std::fmt;
+
+use crate::soc::core::state::CoreState;
+use crate::soc::device::Endianness;
+use crate::soc::system::bus::BusError;
+
+/// Direction of a `$mem::` access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryAccess {
+    Read,
+    Write,
+}
+
+impl fmt::Display for MemoryAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemoryAccess::Read => write!(f, "read"),
+            MemoryAccess::Write => write!(f, "write"),
+        }
+    }
+}
+
+/// A `$mem::` access the data bus could not complete.
+#[derive(Debug)]
+pub struct MemoryFault {
+    pub access: MemoryAccess,
+    /// Effective address of the access.
+    pub address: u64,
+    /// Access size in bytes.
+    pub size: usize,
+    /// Why the bus refused the access; `None` when the core has no data bus attached.
+    pub source: Option<BusError>,
+}
+
+impl MemoryFault {
+    /// Whether nothing is mapped at the address, as opposed to a device rejecting the access.
+    pub fn is_unmapped(&self) -> bool {
+        matches!(
+            self.source,
+            Some(BusError::NotMapped { .. } | BusError::OutOfRange { .. })
+        )
+    }
+}
+
+impl fmt::Display for MemoryFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-byte {} at 0x{:016X} failed: ",
+            self.size, self.access, self.address
+        )?;
+        match &self.source {
+            Some(err) => write!(f, "{err}"),
+            None => write!(f, "no data bus is attached"),
+        }
+    }
+}
+
+impl std::error::Error for MemoryFault {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|err| err as &(dyn std::error::Error + 'static))
+    }
+}
+
+/// Byte order of the device the data bus maps at `address`, so a `size`-byte `access` there
+/// can be encoded before it reaches the bus.
+pub(crate) fn byte_order(
+    state: &CoreState,
+    access: MemoryAccess,
+    address: u64,
+    size: usize,
+) -> Result<Endianness, MemoryFault> {
+    let fault = |source| MemoryFault {
+        access,
+        address,
+        size,
+        source,
+    };
+    let Some(bus) = state.data_bus() else {
+        return Err(fault(None));
+    };
+    bus.resolve(address)
+        .map(|range| range.device.endianness())
+        .map_err(|err| fault(Some(err)))
+}
+
+/// Fills `out` from the data bus at `address`.
+pub(crate) fn load(state: &mut CoreState, address: u64, out: &mut [u8]) -> Result<(), MemoryFault> {
+    let size = out.len();
+    let fault = |source| MemoryFault {
+        access: MemoryAccess::Read,
+        address,
+        size,
+        source,
+    };
+    let Some(handle) = state.data_bus_handle() else {
+        return Err(fault(None));
+    };
+    handle
+        .address_mut()
+        .jump(address)
+        .and_then(|()| handle.read(out))
+        .map_err(|err| fault(Some(err)))
+}
+
+/// Writes `bytes` to the data bus at `address`.
+pub(crate) fn store(state: &mut CoreState, address: u64, bytes: &[u8]) -> Result<(), MemoryFault> {
+    let fault = |source| MemoryFault {
+        access: MemoryAccess::Write,
+        address,
+        size: bytes.len(),
+        source,
+    };
+    let Some(handle) = state.data_bus_handle() else {
+        return Err(fault(None));
+    };
+    handle
+        .address_mut()
+        .jump(address)
+        .and_then(|()| handle.write(bytes))
+        .map_err(|err| fault(Some(err)))
+}
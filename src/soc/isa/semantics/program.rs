@@ -1,3 +1,4 @@
+use std::collections::VecDeque;
 use std::path::PathBuf;
 
 use crate::loader::isa::lexer::{Lexer, Token, TokenKind};
@@ -23,9 +24,25 @@ impl SemanticProgram {
 
 #[derive(Debug, Clone)]
 pub enum SemanticStmt {
-    Assign { target: AssignTarget, expr: Expr },
+    Assign {
+        target: AssignTarget,
+        expr: Expr,
+    },
     Expr(Expr),
     Return(Expr),
+    /// `if (cond) { } else { }`; an `else if` chain nests in `otherwise`.
+    If {
+        condition: Expr,
+        then: Vec<SemanticStmt>,
+        otherwise: Vec<SemanticStmt>,
+    },
+    /// `for var in start..end { }` over the inclusive range, counting down when `end < start`.
+    For {
+        var: String,
+        start: Expr,
+        end: Expr,
+        body: Vec<SemanticStmt>,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -60,6 +77,10 @@ pub enum ContextKind {
     Macro,
     Instruction,
     Host,
+    /// `$mem::` loads and stores through the core's data bus.
+    Memory,
+    /// `$cast::` conversions between unsized, sized and floating-point values.
+    Cast,
 }
 
 #[derive(Debug, Clone)]
@@ -84,9 +105,29 @@ pub enum Expr {
         expr: Box<Expr>,
         slice: BitSlice,
     },
+    UnaryOp {
+        op: ExprUnaryOp,
+        expr: Box<Expr>,
+    },
+    /// `cond ? then : otherwise`; only the selected arm is evaluated.
+    Conditional {
+        condition: Box<Expr>,
+        then: Box<Expr>,
+        otherwise: Box<Expr>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExprUnaryOp {
+    /// `!`, logical negation.
+    Not,
+    /// `~`, bitwise complement.
+    BitNot,
+    /// `-`, two's complement negation.
+    Neg,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ExprBinaryOp {
     LogicalOr,
     LogicalAnd,
@@ -97,8 +138,20 @@ pub enum ExprBinaryOp {
     Ne,
     Lt,
     Gt,
+    Le,
+    Ge,
     Add,
     Sub,
+    Mul,
+    /// Signed division, truncating toward zero.
+    Div,
+    /// Signed remainder, taking the sign of the dividend.
+    Rem,
+    Shl,
+    /// Logical right shift.
+    Shr,
+    /// Arithmetic right shift (`>>>`).
+    Sar,
 }
 
 #[derive(Debug, Clone)]
@@ -109,7 +162,7 @@ pub struct BitSlice {
 
 struct Parser<'src> {
     lexer: Lexer<'src>,
-    peeked: Option<Token>,
+    lookahead: VecDeque<Token>,
 }
 
 impl<'src> Parser<'src> {
@@ -121,11 +174,16 @@ impl<'src> Parser<'src> {
         };
         Self {
             lexer: Lexer::with_origin(source, path, line, column),
-            peeked: None,
+            lookahead: VecDeque::new(),
         }
     }
 
     fn parse_program(&mut self) -> Result<SemanticProgram, IsaError> {
+        let statements = self.parse_statements()?;
+        Ok(SemanticProgram { statements })
+    }
+
+    fn parse_statements(&mut self) -> Result<Vec<SemanticStmt>, IsaError> {
         let mut statements = Vec::new();
         while !self.check(TokenKind::EOF)? {
             if self.check(TokenKind::RBrace)? {
@@ -137,10 +195,23 @@ impl<'src> Parser<'src> {
             let stmt = self.parse_statement()?;
             statements.push(stmt);
         }
-        Ok(SemanticProgram { statements })
+        Ok(statements)
+    }
+
+    fn parse_block(&mut self) -> Result<Vec<SemanticStmt>, IsaError> {
+        self.expect(TokenKind::LBrace, "'{' to open block")?;
+        let statements = self.parse_statements()?;
+        self.expect(TokenKind::RBrace, "'}' to close block")?;
+        Ok(statements)
     }
 
     fn parse_statement(&mut self) -> Result<SemanticStmt, IsaError> {
+        if self.check_keyword("if")? {
+            return self.parse_if();
+        }
+        if self.check_keyword("for")? {
+            return self.parse_for();
+        }
         let expr = self.parse_expression()?;
         if self.is_assignment_target(&expr)? {
             self.expect(TokenKind::Equals, "'=' in assignment")?;
@@ -154,8 +225,70 @@ impl<'src> Parser<'src> {
         Ok(SemanticStmt::Expr(expr))
     }
 
+    fn parse_if(&mut self) -> Result<SemanticStmt, IsaError> {
+        self.consume()?;
+        self.expect(TokenKind::LParen, "'(' after 'if'")?;
+        let condition = self.parse_expression()?;
+        self.expect(TokenKind::RParen, "')' to close 'if' condition")?;
+        let then = self.parse_block()?;
+        let otherwise = if !self.check_keyword("else")? {
+            Vec::new()
+        } else {
+            self.consume()?;
+            if self.check_keyword("if")? {
+                vec![self.parse_if()?]
+            } else {
+                self.parse_block()?
+            }
+        };
+        Ok(SemanticStmt::If {
+            condition,
+            then,
+            otherwise,
+        })
+    }
+
+    fn parse_for(&mut self) -> Result<SemanticStmt, IsaError> {
+        self.consume()?;
+        let var = self.expect_identifier("loop variable after 'for'")?;
+        if var.starts_with(['#', '$']) || var.contains('.') {
+            return Err(IsaError::Parser(format!(
+                "loop variable '{var}' must be a plain identifier"
+            )));
+        }
+        if !self.check_keyword("in")? {
+            return Err(IsaError::Parser("expected 'in' after loop variable".into()));
+        }
+        self.consume()?;
+        let start = self.parse_expression()?;
+        self.expect(TokenKind::DoublePeriod, "'..' in loop range")?;
+        let end = self.parse_expression()?;
+        let body = self.parse_block()?;
+        Ok(SemanticStmt::For {
+            var,
+            start,
+            end,
+            body,
+        })
+    }
+
     fn parse_expression(&mut self) -> Result<Expr, IsaError> {
-        self.parse_logical_or()
+        self.parse_conditional()
+    }
+
+    fn parse_conditional(&mut self) -> Result<Expr, IsaError> {
+        let condition = self.parse_logical_or()?;
+        if !self.match_token(TokenKind::Question)? {
+            return Ok(condition);
+        }
+        let then = self.parse_expression()?;
+        self.expect(TokenKind::Colon, "':' in conditional expression")?;
+        let otherwise = self.parse_conditional()?;
+        Ok(Expr::Conditional {
+            condition: Box::new(condition),
+            then: Box::new(then),
+            otherwise: Box::new(otherwise),
+        })
     }
 
     fn parse_logical_or(&mut self) -> Result<Expr, IsaError> {
@@ -250,27 +383,42 @@ impl<'src> Parser<'src> {
     }
 
     fn parse_relational(&mut self) -> Result<Expr, IsaError> {
+        let mut expr = self.parse_shift()?;
+        loop {
+            let (op, tokens) = match self.peek_operator()? {
+                Some(("<", tokens)) => (ExprBinaryOp::Lt, tokens),
+                Some((">", tokens)) => (ExprBinaryOp::Gt, tokens),
+                Some(("<=", tokens)) => (ExprBinaryOp::Le, tokens),
+                Some((">=", tokens)) => (ExprBinaryOp::Ge, tokens),
+                _ => break,
+            };
+            self.skip(tokens)?;
+            let rhs = self.parse_shift()?;
+            expr = Expr::BinaryOp {
+                op,
+                lhs: Box::new(expr),
+                rhs: Box::new(rhs),
+            };
+        }
+        Ok(expr)
+    }
+
+    fn parse_shift(&mut self) -> Result<Expr, IsaError> {
         let mut expr = self.parse_term()?;
         loop {
-            if self.match_token(TokenKind::LessThan)? {
-                let rhs = self.parse_term()?;
-                expr = Expr::BinaryOp {
-                    op: ExprBinaryOp::Lt,
-                    lhs: Box::new(expr),
-                    rhs: Box::new(rhs),
-                };
-                continue;
-            }
-            if self.match_token(TokenKind::GreaterThan)? {
-                let rhs = self.parse_term()?;
-                expr = Expr::BinaryOp {
-                    op: ExprBinaryOp::Gt,
-                    lhs: Box::new(expr),
-                    rhs: Box::new(rhs),
-                };
-                continue;
-            }
-            break;
+            let (op, tokens) = match self.peek_operator()? {
+                Some(("<<", tokens)) => (ExprBinaryOp::Shl, tokens),
+                Some((">>", tokens)) => (ExprBinaryOp::Shr, tokens),
+                Some((">>>", tokens)) => (ExprBinaryOp::Sar, tokens),
+                _ => break,
+            };
+            self.skip(tokens)?;
+            let rhs = self.parse_term()?;
+            expr = Expr::BinaryOp {
+                op,
+                lhs: Box::new(expr),
+                rhs: Box::new(rhs),
+            };
         }
         Ok(expr)
     }
@@ -287,7 +435,9 @@ impl<'src> Parser<'src> {
                 };
                 continue;
             }
-            if self.match_token(TokenKind::Dash)? {
+            // The lexer folds `-` into a directly following number, so `x -1` arrives as `x`
+            // and the literal `-1`.
+            if self.match_token(TokenKind::Dash)? || self.match_negative_number()? {
                 let rhs = self.parse_factor()?;
                 expr = Expr::BinaryOp {
                     op: ExprBinaryOp::Sub,
@@ -302,7 +452,42 @@ impl<'src> Parser<'src> {
     }
 
     fn parse_factor(&mut self) -> Result<Expr, IsaError> {
-        self.parse_postfix()
+        let mut expr = self.parse_unary()?;
+        loop {
+            let op = if self.match_token(TokenKind::Asterisk)? {
+                ExprBinaryOp::Mul
+            } else if self.match_token(TokenKind::Slash)? {
+                ExprBinaryOp::Div
+            } else if self.match_token(TokenKind::Percent)? {
+                ExprBinaryOp::Rem
+            } else {
+                break;
+            };
+            let rhs = self.parse_unary()?;
+            expr = Expr::BinaryOp {
+                op,
+                lhs: Box::new(expr),
+                rhs: Box::new(rhs),
+            };
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, IsaError> {
+        let op = if self.match_token(TokenKind::Bang)? {
+            ExprUnaryOp::Not
+        } else if self.match_token(TokenKind::Tilde)? {
+            ExprUnaryOp::BitNot
+        } else if self.match_token(TokenKind::Dash)? || self.match_negative_number()? {
+            ExprUnaryOp::Neg
+        } else {
+            return self.parse_postfix();
+        };
+        let expr = self.parse_unary()?;
+        Ok(Expr::UnaryOp {
+            op,
+            expr: Box::new(expr),
+        })
     }
 
     fn parse_postfix(&mut self) -> Result<Expr, IsaError> {
@@ -417,6 +602,70 @@ impl<'src> Parser<'src> {
         Ok(false)
     }
 
+    /// Operator at the front of the input and the number of tokens it spans. The lexer emits
+    /// `<`, `>` and `=` one at a time, so directly adjacent ones are glued into `<<`, `>>`,
+    /// `>>>`, `<=` and `>=` here.
+    fn peek_operator(&mut self) -> Result<Option<(&'static str, usize)>, IsaError> {
+        let operator = match self.peek()?.kind {
+            TokenKind::LessThan => {
+                if self.adjacent(1, TokenKind::LessThan)? {
+                    ("<<", 2)
+                } else if self.adjacent(1, TokenKind::Equals)? {
+                    ("<=", 2)
+                } else {
+                    ("<", 1)
+                }
+            }
+            TokenKind::GreaterThan => {
+                if self.adjacent(1, TokenKind::GreaterThan)? {
+                    if self.adjacent(2, TokenKind::GreaterThan)? {
+                        (">>>", 3)
+                    } else {
+                        (">>", 2)
+                    }
+                } else if self.adjacent(1, TokenKind::Equals)? {
+                    (">=", 2)
+                } else {
+                    (">", 1)
+                }
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(operator))
+    }
+
+    /// Whether the token `n` ahead has `kind` and starts where the one before it ends.
+    fn adjacent(&mut self, n: usize, kind: TokenKind) -> Result<bool, IsaError> {
+        let prev = self.peek_nth(n - 1)?;
+        let (line, end) = (prev.line, prev.column + prev.lexeme.len());
+        let token = self.peek_nth(n)?;
+        Ok(token.kind == kind && token.line == line && token.column == end)
+    }
+
+    /// Consumes the sign of a negative number literal, leaving its magnitude as the next token.
+    fn match_negative_number(&mut self) -> Result<bool, IsaError> {
+        let token = self.peek()?;
+        if token.kind != TokenKind::Number || !token.lexeme.starts_with('-') {
+            return Ok(false);
+        }
+        let token = self.lookahead.front_mut().expect("peeked token");
+        token.lexeme.remove(0);
+        token.column += 1;
+        Ok(true)
+    }
+
+    fn check_keyword(&mut self, keyword: &str) -> Result<bool, IsaError> {
+        let token = self.peek()?;
+        Ok(token.kind == TokenKind::Identifier && token.lexeme == keyword)
+    }
+
+    fn skip(&mut self, count: usize) -> Result<(), IsaError> {
+        for _ in 0..count {
+            self.consume()?;
+        }
+        Ok(())
+    }
+
     fn expect(&mut self, kind: TokenKind, context: &str) -> Result<Token, IsaError> {
         let token = self.consume()?;
         if token.kind == kind {
@@ -452,17 +701,53 @@ impl<'src> Parser<'src> {
     }
 
     fn peek(&mut self) -> Result<&Token, IsaError> {
-        if self.peeked.is_none() {
-            self.peeked = Some(self.lexer.next_token()?);
+        self.peek_nth(0)
+    }
+
+    fn peek_nth(&mut self, n: usize) -> Result<&Token, IsaError> {
+        while self.lookahead.len() <= n {
+            let token = self.lexer.next_token()?;
+            self.push_token(token);
         }
-        Ok(self.peeked.as_ref().expect("peeked token"))
+        Ok(&self.lookahead[n])
     }
 
-    fn consume(&mut self) -> Result<Token, IsaError> {
-        if let Some(token) = self.peeked.take() {
-            return Ok(token);
+    /// Identifiers may contain `.`, so a range such as `i..31` lexes as one token; it is split
+    /// back into its bounds around the `..`.
+    fn push_token(&mut self, token: Token) {
+        let Some((head, tail)) = token
+            .lexeme
+            .split_once("..")
+            .filter(|_| token.kind == TokenKind::Identifier)
+        else {
+            self.lookahead.push_back(token);
+            return;
+        };
+        let (head, tail) = (head.to_string(), tail.to_string());
+        let piece = |kind, lexeme: &str, offset: usize| Token {
+            kind,
+            lexeme: lexeme.to_string(),
+            line: token.line,
+            column: token.column + offset,
+        };
+        self.lookahead
+            .push_back(piece(TokenKind::Identifier, &head, 0));
+        self.lookahead
+            .push_back(piece(TokenKind::DoublePeriod, "..", head.len()));
+        if !tail.is_empty() {
+            let kind = if tail.starts_with(|ch: char| ch.is_ascii_digit()) {
+                TokenKind::Number
+            } else {
+                TokenKind::Identifier
+            };
+            let tail = piece(kind, &tail, head.len() + 2);
+            self.push_token(tail);
         }
-        self.lexer.next_token()
+    }
+
+    fn consume(&mut self) -> Result<Token, IsaError> {
+        self.peek()?;
+        Ok(self.lookahead.pop_front().expect("peeked token"))
     }
 }
 
@@ -508,6 +793,8 @@ impl ContextKind {
             "macro" => Ok(ContextKind::Macro),
             "insn" => Ok(ContextKind::Instruction),
             "host" => Ok(ContextKind::Host),
+            "mem" => Ok(ContextKind::Memory),
+            "cast" => Ok(ContextKind::Cast),
             other => Err(IsaError::Parser(format!(
                 "unknown context prefix '${other}'"
             ))),
@@ -545,3 +832,101 @@ fn parse_bit_slice(spec: &str) -> Result<BitSlice, IsaError> {
         end: end_val as u32,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_expr(source: &str) -> Expr {
+        let program = SemanticProgram::parse(source).expect("parse");
+        match program.statements.as_slice() {
+            [SemanticStmt::Expr(expr)] => expr.clone(),
+            other => panic!("expected one expression statement, got {other:?}"),
+        }
+    }
+
+    fn binary_op(expr: &Expr) -> ExprBinaryOp {
+        match expr {
+            Expr::BinaryOp { op, .. } => *op,
+            other => panic!("expected a binary operator, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn glues_adjacent_comparison_and_shift_tokens() {
+        for (source, expected) in [
+            ("a << 2", ExprBinaryOp::Shl),
+            ("a >> 2", ExprBinaryOp::Shr),
+            ("a >>> 2", ExprBinaryOp::Sar),
+            ("a <= 2", ExprBinaryOp::Le),
+            ("a >= 2", ExprBinaryOp::Ge),
+            ("a < 2", ExprBinaryOp::Lt),
+            ("a -1", ExprBinaryOp::Sub),
+            ("a % 2", ExprBinaryOp::Rem),
+        ] {
+            assert_eq!(binary_op(&parse_expr(source)), expected, "{source}");
+        }
+        assert!(SemanticProgram::parse("a > > 2").is_err());
+    }
+
+    #[test]
+    fn binds_operators_by_precedence() {
+        // (((a + (b * c)) << 1) == d) ? -e : ~f
+        let Expr::Conditional {
+            condition,
+            then,
+            otherwise,
+        } = parse_expr("a + b * c << 1 == d ? -e : ~f")
+        else {
+            panic!("expected a conditional");
+        };
+        assert_eq!(binary_op(&condition), ExprBinaryOp::Eq);
+        let Expr::BinaryOp { lhs, .. } = *condition else {
+            unreachable!()
+        };
+        assert_eq!(binary_op(&lhs), ExprBinaryOp::Shl);
+        assert!(matches!(
+            *then,
+            Expr::UnaryOp {
+                op: ExprUnaryOp::Neg,
+                ..
+            }
+        ));
+        assert!(matches!(
+            *otherwise,
+            Expr::UnaryOp {
+                op: ExprUnaryOp::BitNot,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn parses_if_chains_and_loops() {
+        let program = SemanticProgram::parse(
+            "if (a) { x = 1 } else if (b) { x = 2 } else { x = 3 }
+             for i in 0..#N { y = i }
+             for j in i..#N { }",
+        )
+        .expect("parse");
+        let [
+            SemanticStmt::If { otherwise, .. },
+            SemanticStmt::For { var, body, .. },
+            SemanticStmt::For { start, end, .. },
+        ] = program.statements.as_slice()
+        else {
+            panic!("unexpected statements {:?}", program.statements);
+        };
+        assert!(matches!(
+            otherwise.as_slice(),
+            [SemanticStmt::If { otherwise, .. }] if otherwise.len() == 1
+        ));
+        assert_eq!(var, "i");
+        assert_eq!(body.len(), 1);
+        assert!(matches!(start, Expr::Variable { name, .. } if name == "i"));
+        assert!(matches!(end, Expr::Parameter { name, .. } if name == "N"));
+
+        assert!(SemanticProgram::parse("for #i in 0..3 { }").is_err());
+        assert!(SemanticProgram::parse("if a { }").is_err());
+    }
+}
@@ -11,7 +11,7 @@ use crate::soc::prog::types::arena::TypeArena;
 use crate::soc::prog::types::bitfield::BitFieldSpec;
 use crate::soc::prog::types::record::TypeRecord;
 
-use crate::soc::isa::semantics::value::SemanticValue;
+use crate::soc::isa::semantics::value::{BitVector, SemanticValue};
 
 /// Resolves `$reg::` references into concrete metadata and offers read/write helpers.
 pub struct RegisterAccess<'machine> {
@@ -286,18 +286,26 @@ impl<'schema> ResolvedRegister<'schema> {
         }
     }
 
+    /// Reads the register or subfield as a value of its width, signed for signed subfields.
     pub fn read(&self, state: &mut CoreState) -> Result<SemanticValue, IsaError> {
-        let raw = self.read_raw(state)?;
-        if let Some(field) = self.field {
-            let spec = self.field_spec(field)?;
-            let value = spec.read_signed(raw);
-            Ok(SemanticValue::int(value))
-        } else {
-            Ok(SemanticValue::int(raw as i64))
-        }
+        let (bits, signed) = match self.field {
+            Some(field) => {
+                let spec = self.field_spec(field)?;
+                let value = spec.read_signed(self.read_raw(state)?);
+                (value as u128, spec.is_signed())
+            }
+            None => (
+                state
+                    .read_register(&self.resolved_name)
+                    .map_err(core_state_error)?,
+                false,
+            ),
+        };
+        BitVector::new(bits, self.bit_width(), signed).map(SemanticValue::Bits)
     }
 
-    pub fn write(&self, state: &mut CoreState, value: i64) -> Result<(), IsaError> {
+    /// Writes the low [`Self::bit_width`] bits of `value`.
+    pub fn write(&self, state: &mut CoreState, value: u128) -> Result<(), IsaError> {
         if let Some(field) = self.field {
             let spec = self.field_spec(field)?;
             let mut container = self.read_raw(state)?;
@@ -312,11 +320,12 @@ impl<'schema> ResolvedRegister<'schema> {
         } else {
             let masked = mask_to_width(value, self.metadata.bit_width);
             state
-                .write_register(&self.resolved_name, masked as u128)
+                .write_register(&self.resolved_name, masked)
                 .map_err(core_state_error)
         }
     }
 
+    /// Container of a subfield, which must fit 64 bits.
     fn read_raw(&self, state: &mut CoreState) -> Result<u64, IsaError> {
         let value = state
             .read_register(&self.resolved_name)
@@ -372,7 +381,7 @@ impl<'schema> ResolvedRegister<'schema> {
     }
 }
 
-fn resolve_reference_path(
+pub(super) fn resolve_reference_path(
     current_space: &str,
     reference: &ContextReference,
 ) -> (String, Vec<String>) {
@@ -386,18 +395,11 @@ fn resolve_reference_path(
     (current_space.to_string(), reference.segments.clone())
 }
 
-fn mask_to_width(value: i64, width: u32) -> u64 {
-    if width >= 64 {
-        value as u64
-    } else if width == 0 {
-        0
+fn mask_to_width(value: u128, width: u32) -> u128 {
+    if width >= 128 {
+        value
     } else {
-        let mask = if width == 64 {
-            u64::MAX
-        } else {
-            (1u64 << width) - 1
-        };
-        (value as u64) & mask
+        value & ((1u128 << width) - 1)
     }
 }
 
@@ -619,6 +621,7 @@ mod tests {
                     bit_spec: "@(0..1)".into(),
                     operations: Vec::new(),
                     description: None,
+                    reset: None,
                 }],
                 span: span.clone(),
                 display: None,
@@ -643,6 +646,7 @@ mod tests {
                     bit_spec: "@(0..15)".into(),
                     operations: Vec::new(),
                     description: None,
+                    reset: None,
                 }],
                 span: span.clone(),
                 display: None,
@@ -8,17 +8,19 @@ use std::collections::HashMap;
 use std::fmt;
 
 use crate::soc::core::state::CoreState;
+use crate::soc::device::endianness::MAX_ENDIAN_BYTES;
 use crate::soc::isa::error::IsaError;
 use crate::soc::isa::machine::{HostServices, Instruction, MachineDescription};
 use crate::soc::isa::semantics::ParameterBindings;
 use crate::soc::isa::semantics::context::ExecutionContext;
 use crate::soc::isa::semantics::expression::{ContextCallResolver, ExpressionEvaluator};
+use crate::soc::isa::semantics::memory::{self, MemoryAccess};
 use crate::soc::isa::semantics::program::{
     AssignTarget, ContextCall, ContextKind, Expr, RegisterRef, SemanticProgram, SemanticStmt,
 };
 use crate::soc::isa::semantics::register::{RegisterAccess, ResolvedRegister};
 use crate::soc::isa::semantics::trace::{ExecutionTracer, HostOpKind, TraceEvent};
-use crate::soc::isa::semantics::value::SemanticValue;
+use crate::soc::isa::semantics::value::{BitVector, Conversion, SemanticValue};
 
 #[derive(Default)]
 pub struct SemanticRuntime {
@@ -26,6 +28,8 @@ pub struct SemanticRuntime {
 }
 
 const MAX_CALL_DEPTH: usize = 32;
+/// Upper bound on the iterations of one `for` loop, well above any architected bit width.
+const MAX_LOOP_ITERATIONS: u64 = 1024;
 
 impl SemanticRuntime {
     pub fn new() -> Self {
@@ -92,7 +96,19 @@ impl SemanticRuntime {
         program: &SemanticProgram,
     ) -> Result<Option<SemanticValue>, IsaError> {
         let _frame = stack.enter()?;
-        for stmt in &program.statements {
+        self.execute_block(machine, state, host, stack, context, &program.statements)
+    }
+
+    fn execute_block<'ctx>(
+        &self,
+        machine: &MachineDescription,
+        state: &mut CoreState,
+        host: &mut dyn HostServices,
+        stack: &CallStack,
+        context: &mut ExecutionContext<'ctx>,
+        statements: &[SemanticStmt],
+    ) -> Result<Option<SemanticValue>, IsaError> {
+        for stmt in statements {
             if let Some(value) =
                 self.execute_statement(machine, state, host, stack, context, stmt)?
             {
@@ -139,6 +155,48 @@ impl SemanticRuntime {
                 let value = self.evaluate_expression(machine, state, host, stack, context, expr)?;
                 Ok(Some(value))
             }
+            SemanticStmt::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let taken = self
+                    .evaluate_expression(machine, state, host, stack, context, condition)?
+                    .as_bool()?;
+                let branch = if taken { then } else { otherwise };
+                self.execute_block(machine, state, host, stack, context, branch)
+            }
+            SemanticStmt::For {
+                var,
+                start,
+                end,
+                body,
+            } => {
+                let first = self
+                    .evaluate_expression(machine, state, host, stack, context, start)?
+                    .as_int()?;
+                let last = self
+                    .evaluate_expression(machine, state, host, stack, context, end)?
+                    .as_int()?;
+                let iterations = first.abs_diff(last) + 1;
+                if iterations > MAX_LOOP_ITERATIONS {
+                    return Err(IsaError::Machine(format!(
+                        "loop over '{var}' from {first} to {last} exceeds {MAX_LOOP_ITERATIONS} iterations"
+                    )));
+                }
+                let step = if last < first { -1 } else { 1 };
+                let mut index = first;
+                for _ in 0..iterations {
+                    context.set_local(var.clone(), SemanticValue::int(index));
+                    if let Some(value) =
+                        self.execute_block(machine, state, host, stack, context, body)?
+                    {
+                        return Ok(Some(value));
+                    }
+                    index = index.wrapping_add(step);
+                }
+                Ok(None)
+            }
         }
     }
 
@@ -185,12 +243,12 @@ impl SemanticRuntime {
             self.evaluate_register_index(machine, state, host, stack, context, reference)?;
         let registers = self.register_access(machine);
         let resolved = registers.resolve(reference, index)?;
-        let int_value = value.as_int()?;
-        resolved.write(state, int_value)?;
+        let bits = value.to_bits(resolved.bit_width())?;
+        resolved.write(state, bits)?;
         let display = format_resolved_name(&resolved, reference.subfield.as_ref());
         self.emit_trace(TraceEvent::RegisterWrite {
             name: display,
-            value: int_value,
+            value: bits,
             width: resolved.bit_width(),
         });
         Ok(())
@@ -320,7 +378,7 @@ impl<'runtime, 'machine, 'state, 'host, 'stack>
         let display = format_resolved_name(&resolved, call.subpath.first());
         self.runtime.emit_trace(TraceEvent::RegisterRead {
             name: display,
-            value: value.as_int().unwrap_or(0),
+            value: value.to_bits(resolved.bit_width())?,
             width: resolved.bit_width(),
         });
         Ok(value)
@@ -395,6 +453,7 @@ impl<'runtime, 'machine, 'state, 'host, 'stack>
             "add" => self.host_add(args, call),
             "sub" => self.host_sub(args, call),
             "mul" => self.host_mul(args, call),
+            "select" => self.host_select(args, call),
             other => Err(IsaError::Machine(format!(
                 "unknown host helper '${}::{other}'",
                 call.space
@@ -475,6 +534,121 @@ impl<'runtime, 'machine, 'state, 'host, 'stack>
         ]))
     }
 
+    fn host_select(
+        &self,
+        mut args: Vec<SemanticValue>,
+        call: &ContextCall,
+    ) -> Result<SemanticValue, IsaError> {
+        if args.len() != 3 {
+            return Err(self.arity_error(call, 3, args.len()));
+        }
+        let taken = args[0].as_bool()?;
+        Ok(args.swap_remove(if taken { 1 } else { 2 }))
+    }
+
+    fn evaluate_memory_call(
+        &mut self,
+        call: &ContextCall,
+        args: Vec<SemanticValue>,
+    ) -> Result<SemanticValue, IsaError> {
+        if !call.subpath.is_empty() {
+            return Err(IsaError::Machine(format!(
+                "memory call '${}::{}' does not support subpaths",
+                call.space, call.name
+            )));
+        }
+        match call.name.as_str() {
+            "read" => self.memory_read(args, call, false),
+            "read_signed" => self.memory_read(args, call, true),
+            "write" => self.memory_write(args, call),
+            other => Err(IsaError::Machine(format!(
+                "unknown memory access '${}::{other}'",
+                call.space
+            ))),
+        }
+    }
+
+    /// `$mem::read(addr, size)`: loads `size` bytes in the byte order of the device mapped at
+    /// `addr` as an unsigned value of `8 * size` bits; `read_signed` makes it signed.
+    fn memory_read(
+        &mut self,
+        args: Vec<SemanticValue>,
+        call: &ContextCall,
+        signed: bool,
+    ) -> Result<SemanticValue, IsaError> {
+        if args.len() != 2 {
+            return Err(self.arity_error(call, 2, args.len()));
+        }
+        let address = args[0].as_int()? as u64;
+        let size = self.parse_size(&args[1], call)?;
+        let order = memory::byte_order(self.state, MemoryAccess::Read, address, size)?;
+        let mut bytes = [0u8; MAX_ENDIAN_BYTES];
+        memory::load(self.state, address, &mut bytes[..size])?;
+        let raw = order.decode_bytes(&bytes[..size]);
+        self.runtime.emit_trace(TraceEvent::MemoryRead {
+            address,
+            value: raw,
+            size,
+        });
+        BitVector::new(raw, 8 * size as u32, signed).map(SemanticValue::Bits)
+    }
+
+    /// `$mem::write(addr, size, value)`: stores the low `size` bytes of `value`.
+    fn memory_write(
+        &mut self,
+        args: Vec<SemanticValue>,
+        call: &ContextCall,
+    ) -> Result<SemanticValue, IsaError> {
+        if args.len() != 3 {
+            return Err(self.arity_error(call, 3, args.len()));
+        }
+        let address = args[0].as_int()? as u64;
+        let size = self.parse_size(&args[1], call)?;
+        let value = args[2].to_bits(8 * size as u32)?;
+        let bytes = memory::byte_order(self.state, MemoryAccess::Write, address, size)?
+            .encode_bits(value, 8 * size, size);
+        memory::store(self.state, address, &bytes[..size])?;
+        self.runtime.emit_trace(TraceEvent::MemoryWrite {
+            address,
+            value,
+            size,
+        });
+        Ok(SemanticValue::Tuple(Vec::new()))
+    }
+
+    fn evaluate_cast_call(
+        &self,
+        call: &ContextCall,
+        args: Vec<SemanticValue>,
+    ) -> Result<SemanticValue, IsaError> {
+        if !call.subpath.is_empty() {
+            return Err(IsaError::Machine(format!(
+                "conversion '${}::{}' does not support subpaths",
+                call.space, call.name
+            )));
+        }
+        let conversion = Conversion::parse(&call.name).ok_or_else(|| {
+            IsaError::Machine(format!(
+                "unknown conversion '${}::{}'",
+                call.space, call.name
+            ))
+        })?;
+        if args.len() != 1 {
+            return Err(self.arity_error(call, 1, args.len()));
+        }
+        args[0].convert(conversion)
+    }
+
+    fn parse_size(&self, value: &SemanticValue, call: &ContextCall) -> Result<usize, IsaError> {
+        match value.as_int()? {
+            size @ (1 | 2 | 4 | 8 | 16) => Ok(size as usize),
+            size => Err(IsaError::Machine(format!(
+                "call '${}::{}' size {size} must be 1, 2, 4, 8 or 16 bytes",
+                call.space, call.name
+            ))),
+        }
+    }
+
     fn arity_error(&self, call: &ContextCall, expected: usize, actual: usize) -> IsaError {
         IsaError::Machine(format!(
             "call '${}::{}' expects {expected} arguments, got {actual}",
@@ -616,6 +790,8 @@ impl<'runtime, 'machine, 'state, 'host, 'stack> ContextCallResolver
             ContextKind::Host => self.evaluate_host_call(call, args),
             ContextKind::Macro => self.evaluate_macro_call(call, args),
             ContextKind::Instruction => self.evaluate_instruction_call(call, args),
+            ContextKind::Memory => self.evaluate_memory_call(call, args),
+            ContextKind::Cast => self.evaluate_cast_call(call, args),
         }
     }
 }
@@ -855,6 +1031,31 @@ mod tests {
         assert_eq!(value.as_int().unwrap(), 12);
     }
 
+    #[test]
+    fn host_select_picks_an_arm() {
+        let (runtime, machine, mut state) = test_runtime_state();
+        let select = |cond| SemanticProgram {
+            statements: vec![SemanticStmt::Return(Expr::Call(ContextCall {
+                kind: ContextKind::Host,
+                space: "host".into(),
+                name: "select".into(),
+                subpath: Vec::new(),
+                args: vec![Expr::Number(cond), Expr::Number(10), Expr::Number(20)],
+                span: helper_span(),
+            }))],
+        };
+
+        let params = HashMap::new();
+        let mut host = SoftwareHost;
+        for (cond, expected) in [(1, 10), (0, 20)] {
+            let value = runtime
+                .execute_program(&machine, &mut state, &mut host, &params, &select(cond))
+                .expect("execute program")
+                .expect("return value");
+            assert_eq!(value.as_int().unwrap(), expected);
+        }
+    }
+
     #[test]
     fn macro_call_reuses_semantics() {
         let (runtime, machine, mut state) = test_runtime_state();
@@ -984,6 +1185,63 @@ mod tests {
         }
     }
 
+    #[test]
+    fn control_flow_statements_pick_branches_and_iterate() {
+        let (runtime, machine, mut state) = test_runtime_state();
+        let program = SemanticProgram::parse(
+            "
+            count = 32
+            for bit in 0..31 {
+                if (((#value >> bit) & 1) == 1) {
+                    count = 31 - bit
+                }
+            }
+            if (count > 16) {
+                kind = 0
+            } else if (count == 11) {
+                kind = 1
+            } else {
+                kind = 2
+            }
+            total = 0
+            for i in 3..1 {
+                total = total * 10 + i
+            }
+            (count, kind, total, count >= 11 ? -1 : 1)
+            ",
+        )
+        .expect("parse program");
+
+        let mut params = HashMap::new();
+        params.insert("value".into(), SemanticValue::int(0x0012_3456));
+        let mut host = SoftwareHost;
+        let value = runtime
+            .execute_program(&machine, &mut state, &mut host, &params, &program)
+            .expect("execute program")
+            .expect("return value");
+        let items: Vec<i64> = value
+            .try_into_tuple()
+            .expect("tuple")
+            .into_vec()
+            .into_iter()
+            .map(|item| item.as_int().unwrap())
+            .collect();
+        assert_eq!(items, vec![11, 1, 321, -1]);
+    }
+
+    #[test]
+    fn loops_beyond_the_iteration_limit_fail() {
+        let (runtime, machine, mut state) = test_runtime_state();
+        let program = SemanticProgram::parse("for i in 0..#n { }").expect("parse program");
+        let mut params = HashMap::new();
+        params.insert("n".into(), SemanticValue::int(0xFFFF));
+        let mut host = SoftwareHost;
+        let err = runtime
+            .execute_program(&machine, &mut state, &mut host, &params, &program)
+            .expect_err("loop exceeds the cap");
+        assert!(matches!(err, IsaError::Machine(msg) if msg.contains("iterations")));
+    }
+
     fn test_runtime_state() -> (SemanticRuntime, MachineDescription, CoreState) {
         let machine = build_machine();
         let core_spec = build_core_spec();
@@ -1059,6 +1317,7 @@ mod tests {
                     bit_spec: "@(0..1)".into(),
                     operations: Vec::new(),
                     description: None,
+                    reset: None,
                 }],
                 span: span.clone(),
                 display: None,
@@ -1192,6 +1451,9 @@ mod tests {
             semantics: Some(mirror_block),
             display: None,
             operator: None,
+            extend: None,
+            alias: None,
+            valid: None,
             span: span.clone(),
         }));
         items.push(IsaItem::Instruction(InstructionDecl {
@@ -1205,6 +1467,9 @@ mod tests {
             semantics: Some(read_size_block),
             display: None,
             operator: None,
+            extend: None,
+            alias: None,
+            valid: None,
             span: span.clone(),
         }));
         items.push(IsaItem::Instruction(InstructionDecl {
@@ -1218,6 +1483,9 @@ mod tests {
             semantics: Some(call_size_block),
             display: None,
             operator: None,
+            extend: None,
+            alias: None,
+            valid: None,
             span: span,
         }));
 
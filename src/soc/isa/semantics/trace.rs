@@ -13,12 +13,12 @@ pub enum TraceEvent {
     },
     RegisterRead {
         name: String,
-        value: i64,
+        value: u128,
         width: u32,
     },
     RegisterWrite {
         name: String,
-        value: i64,
+        value: u128,
         width: u32,
     },
     HostOp {
@@ -26,6 +26,17 @@ pub enum TraceEvent {
         args: Vec<i64>,
         result: i64,
     },
+    /// `$mem::` load of `size` bytes.
+    MemoryRead {
+        address: u64,
+        value: u128,
+        size: usize,
+    },
+    MemoryWrite {
+        address: u64,
+        value: u128,
+        size: usize,
+    },
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -102,19 +113,34 @@ impl<W: Write> ExecutionTracer for PipelinePrinter<W> {
                     self.writeln(&format!("[IntOp]   {op:?} {:?} -> 0x{result:016X}", args));
                 }
             }
+            TraceEvent::MemoryRead {
+                address,
+                value,
+                size,
+            } => self.writeln(&format!(
+                "[ Load]   0x{address:016X} -> {}",
+                format_value(value, size as u32 * 8)
+            )),
+            TraceEvent::MemoryWrite {
+                address,
+                value,
+                size,
+            } => self.writeln(&format!(
+                "[Store]   0x{address:016X} <- {}",
+                format_value(value, size as u32 * 8)
+            )),
         }
     }
 }
 
-fn format_value(value: i64, bits: u32) -> String {
+fn format_value(value: u128, bits: u32) -> String {
     let width = std::cmp::max(1, ((bits as usize + 3) / 4) as usize);
     let masked = if bits == 0 {
         0
-    } else if bits >= 64 {
-        value as u64
+    } else if bits >= 128 {
+        value
     } else {
-        let mask = (1u64 << bits) - 1;
-        (value as u64) & mask
+        value & ((1u128 << bits) - 1)
     };
     format!("0x{masked:0width$X}")
 }
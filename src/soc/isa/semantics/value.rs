@@ -1,12 +1,22 @@
+use std::cmp::Ordering;
+
 use crate::soc::isa::error::IsaError;
 
+/// Widest bit vector semantics can hold, matching the widest register a core state stores.
+pub const MAX_BIT_WIDTH: u32 = 128;
+
 /// Canonical runtime value flowing through semantic programs.
 #[derive(Debug, Clone, PartialEq)]
 pub enum SemanticValue {
+    /// Unsized integer: literals, decoded operands and host helper results.
     Int(i64),
     Bool(bool),
     Word(String),
     Tuple(Vec<SemanticValue>),
+    /// Sized integer produced by register and memory reads and `$cast::` conversions.
+    Bits(BitVector),
+    F32(f32),
+    F64(f64),
 }
 
 impl SemanticValue {
@@ -26,16 +36,40 @@ impl SemanticValue {
         Self::Tuple(values)
     }
 
+    pub fn bits(value: BitVector) -> Self {
+        Self::Bits(value)
+    }
+
+    /// Name of the value's type as used in diagnostics and `$cast::` conversions.
+    pub fn type_name(&self) -> String {
+        match self {
+            SemanticValue::Int(_) => "int".into(),
+            SemanticValue::Bool(_) => "bool".into(),
+            SemanticValue::Word(_) => "word".into(),
+            SemanticValue::Tuple(_) => "tuple".into(),
+            SemanticValue::Bits(bits) => bits.type_name(),
+            SemanticValue::F32(_) => "f32".into(),
+            SemanticValue::F64(_) => "f64".into(),
+        }
+    }
+
+    /// The value as a 64-bit integer. Sized values wider than 64 bits must fit; unsigned
+    /// 64-bit values keep their bit pattern.
     pub fn as_int(&self) -> Result<i64, IsaError> {
         match self {
             SemanticValue::Int(value) => Ok(*value),
             SemanticValue::Bool(value) => Ok(if *value { 1 } else { 0 }),
+            SemanticValue::Bits(bits) => bits.to_i64(),
             SemanticValue::Word(_) => Err(IsaError::Machine(
                 "word value cannot be coerced to integer".into(),
             )),
             SemanticValue::Tuple(_) => Err(IsaError::Machine(
                 "tuple value cannot be coerced to integer".into(),
             )),
+            SemanticValue::F32(_) | SemanticValue::F64(_) => Err(IsaError::Machine(format!(
+                "{} value cannot be coerced to integer without a $cast:: conversion",
+                self.type_name()
+            ))),
         }
     }
 
@@ -43,15 +77,36 @@ impl SemanticValue {
         match self {
             SemanticValue::Bool(value) => Ok(*value),
             SemanticValue::Int(value) => Ok(*value != 0),
+            SemanticValue::Bits(bits) => Ok(bits.bits() != 0),
             SemanticValue::Word(_) => Err(IsaError::Machine(
                 "word value cannot be coerced to boolean".into(),
             )),
             SemanticValue::Tuple(_) => Err(IsaError::Machine(
                 "tuple value cannot be coerced to boolean".into(),
             )),
+            SemanticValue::F32(_) | SemanticValue::F64(_) => Err(IsaError::Machine(format!(
+                "{} value cannot be coerced to boolean",
+                self.type_name()
+            ))),
         }
     }
 
+    /// The low `width` bits of the value's two's-complement encoding, for storing into a
+    /// register or memory. Floats must be converted with `$cast::bits` first.
+    pub fn to_bits(&self, width: u32) -> Result<u128, IsaError> {
+        let extended = match self {
+            SemanticValue::Bits(bits) => bits.extended(),
+            SemanticValue::F32(_) | SemanticValue::F64(_) => {
+                return Err(IsaError::Machine(format!(
+                    "{} value must be converted with $cast::bits before it is stored",
+                    self.type_name()
+                )));
+            }
+            other => other.as_int()? as i128 as u128,
+        };
+        Ok(extended & width_mask(width))
+    }
+
     pub fn as_word(&self) -> Option<&str> {
         if let SemanticValue::Word(value) = self {
             Some(value.as_str())
@@ -68,6 +123,304 @@ impl SemanticValue {
             )),
         }
     }
+
+    /// Applies a `$cast::` conversion.
+    pub fn convert(&self, conversion: Conversion) -> Result<SemanticValue, IsaError> {
+        match conversion {
+            Conversion::Integer { width, signed } => {
+                let bits = match self {
+                    SemanticValue::F32(value) => float_to_bits(f64::from(*value), width, signed),
+                    SemanticValue::F64(value) => float_to_bits(*value, width, signed),
+                    other => other.to_bits(width)?,
+                };
+                BitVector::new(bits, width, signed).map(SemanticValue::Bits)
+            }
+            Conversion::F32 => match self {
+                SemanticValue::F32(value) => Ok(SemanticValue::F32(*value)),
+                SemanticValue::F64(value) => Ok(SemanticValue::F32(*value as f32)),
+                other => Ok(SemanticValue::F32(other.to_f64()? as f32)),
+            },
+            Conversion::F64 => match self {
+                SemanticValue::F32(value) => Ok(SemanticValue::F64(f64::from(*value))),
+                SemanticValue::F64(value) => Ok(SemanticValue::F64(*value)),
+                other => Ok(SemanticValue::F64(other.to_f64()?)),
+            },
+            Conversion::Bits => match self {
+                SemanticValue::F32(value) => {
+                    BitVector::new(u128::from(value.to_bits()), 32, false).map(SemanticValue::Bits)
+                }
+                SemanticValue::F64(value) => {
+                    BitVector::new(u128::from(value.to_bits()), 64, false).map(SemanticValue::Bits)
+                }
+                SemanticValue::Bits(bits) => {
+                    Ok(SemanticValue::Bits(bits.resize(bits.width(), false)?))
+                }
+                other => Err(IsaError::Machine(format!(
+                    "$cast::bits needs a float or sized value, got {}",
+                    other.type_name()
+                ))),
+            },
+            Conversion::F32FromBits => {
+                Ok(SemanticValue::F32(f32::from_bits(self.to_bits(32)? as u32)))
+            }
+            Conversion::F64FromBits => {
+                Ok(SemanticValue::F64(f64::from_bits(self.to_bits(64)? as u64)))
+            }
+        }
+    }
+
+    fn to_f64(&self) -> Result<f64, IsaError> {
+        match self {
+            SemanticValue::Bits(bits) if bits.is_signed() => Ok(bits.to_i128() as f64),
+            SemanticValue::Bits(bits) => Ok(bits.bits() as f64),
+            other => Ok(other.as_int()? as f64),
+        }
+    }
+}
+
+/// Two's-complement integer of 1 to [`MAX_BIT_WIDTH`] bits. Arithmetic wraps at the width;
+/// signedness decides extension, division, right shifts and comparisons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitVector {
+    bits: u128,
+    width: u32,
+    signed: bool,
+}
+
+impl BitVector {
+    /// Truncates `bits` to `width`.
+    pub fn new(bits: u128, width: u32, signed: bool) -> Result<Self, IsaError> {
+        if width == 0 || width > MAX_BIT_WIDTH {
+            return Err(IsaError::Machine(format!(
+                "bit width {width} must be between 1 and {MAX_BIT_WIDTH}"
+            )));
+        }
+        Ok(Self {
+            bits: bits & width_mask(width),
+            width,
+            signed,
+        })
+    }
+
+    /// Raw bits, zero-extended.
+    pub fn bits(&self) -> u128 {
+        self.bits
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn is_signed(&self) -> bool {
+        self.signed
+    }
+
+    /// `u<width>` or `i<width>`.
+    pub fn type_name(&self) -> String {
+        format!("{}{}", if self.signed { 'i' } else { 'u' }, self.width)
+    }
+
+    /// Numeric value, sign-extended when signed. Unsigned 128-bit values above `i128::MAX`
+    /// wrap.
+    pub fn to_i128(&self) -> i128 {
+        self.extended() as i128
+    }
+
+    /// Bits extended to 128 per the signedness.
+    fn extended(&self) -> u128 {
+        if self.signed && self.width < MAX_BIT_WIDTH {
+            let shift = MAX_BIT_WIDTH - self.width;
+            (((self.bits << shift) as i128) >> shift) as u128
+        } else {
+            self.bits
+        }
+    }
+
+    fn to_i64(self) -> Result<i64, IsaError> {
+        let fits = if self.signed {
+            i64::try_from(self.to_i128()).is_ok()
+        } else {
+            self.bits <= u128::from(u64::MAX)
+        };
+        if fits {
+            Ok(self.extended() as i64)
+        } else {
+            Err(IsaError::Machine(format!(
+                "{} value 0x{:X} does not fit in 64 bits",
+                self.type_name(),
+                self.bits
+            )))
+        }
+    }
+
+    /// Extends per the current signedness, then truncates to `width`.
+    pub fn resize(self, width: u32, signed: bool) -> Result<Self, IsaError> {
+        Self::new(self.extended(), width, signed)
+    }
+
+    /// Same type as `self` holding `bits`.
+    fn with_bits(self, bits: u128) -> Self {
+        Self {
+            bits: bits & width_mask(self.width),
+            ..self
+        }
+    }
+
+    pub fn wrapping_add(self, rhs: Self) -> Self {
+        self.with_bits(self.bits.wrapping_add(rhs.bits))
+    }
+
+    pub fn wrapping_sub(self, rhs: Self) -> Self {
+        self.with_bits(self.bits.wrapping_sub(rhs.bits))
+    }
+
+    pub fn wrapping_mul(self, rhs: Self) -> Self {
+        self.with_bits(self.bits.wrapping_mul(rhs.bits))
+    }
+
+    /// Quotient rounded toward zero; `None` for a zero divisor.
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        if rhs.bits == 0 {
+            None
+        } else if self.signed {
+            Some(self.with_bits(self.to_i128().wrapping_div(rhs.to_i128()) as u128))
+        } else {
+            Some(self.with_bits(self.bits / rhs.bits))
+        }
+    }
+
+    /// Remainder with the sign of the dividend; `None` for a zero divisor.
+    pub fn checked_rem(self, rhs: Self) -> Option<Self> {
+        if rhs.bits == 0 {
+            None
+        } else if self.signed {
+            Some(self.with_bits(self.to_i128().wrapping_rem(rhs.to_i128()) as u128))
+        } else {
+            Some(self.with_bits(self.bits % rhs.bits))
+        }
+    }
+
+    pub fn and(self, rhs: Self) -> Self {
+        self.with_bits(self.bits & rhs.bits)
+    }
+
+    pub fn or(self, rhs: Self) -> Self {
+        self.with_bits(self.bits | rhs.bits)
+    }
+
+    pub fn xor(self, rhs: Self) -> Self {
+        self.with_bits(self.bits ^ rhs.bits)
+    }
+
+    pub fn bit_not(self) -> Self {
+        self.with_bits(!self.bits)
+    }
+
+    pub fn wrapping_neg(self) -> Self {
+        self.with_bits(self.bits.wrapping_neg())
+    }
+
+    /// Shifts everything out for amounts outside `0..width`.
+    pub fn shift_left(self, amount: i64) -> Self {
+        match self.shift_amount(amount) {
+            Some(amount) => self.with_bits(self.bits << amount),
+            None => self.with_bits(0),
+        }
+    }
+
+    /// Logical right shift; everything shifts out for amounts outside `0..width`.
+    pub fn shift_right(self, amount: i64) -> Self {
+        match self.shift_amount(amount) {
+            Some(amount) => self.with_bits(self.bits >> amount),
+            None => self.with_bits(0),
+        }
+    }
+
+    /// Right shift replicating the top bit of the width, whatever the signedness.
+    pub fn shift_right_arithmetic(self, amount: i64) -> Self {
+        let amount = amount.clamp(0, i64::from(self.width) - 1) as u32;
+        let shift = MAX_BIT_WIDTH - self.width;
+        self.with_bits(((((self.bits << shift) as i128) >> shift) >> amount) as u128)
+    }
+
+    fn shift_amount(self, amount: i64) -> Option<u32> {
+        u32::try_from(amount)
+            .ok()
+            .filter(|amount| *amount < self.width)
+    }
+
+    /// Numeric ordering: signed values compare sign-extended.
+    pub fn compare(self, rhs: Self) -> Ordering {
+        if self.signed {
+            self.to_i128().cmp(&rhs.to_i128())
+        } else {
+            self.bits.cmp(&rhs.bits)
+        }
+    }
+}
+
+/// Conversion selected by a `$cast::` call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conversion {
+    /// `u<N>` / `i<N>`: integers are extended per their signedness and truncated, floats
+    /// round toward zero and saturate.
+    Integer { width: u32, signed: bool },
+    /// `f32`: numeric conversion from an integer or the other float.
+    F32,
+    /// `f64`: numeric conversion from an integer or the other float.
+    F64,
+    /// `bits`: a float's IEEE encoding, or a sized value reinterpreted as unsigned.
+    Bits,
+    /// `f32_from_bits`: the float encoded by the low 32 bits.
+    F32FromBits,
+    /// `f64_from_bits`: the float encoded by the low 64 bits.
+    F64FromBits,
+}
+
+impl Conversion {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "f32" => return Some(Conversion::F32),
+            "f64" => return Some(Conversion::F64),
+            "bits" => return Some(Conversion::Bits),
+            "f32_from_bits" => return Some(Conversion::F32FromBits),
+            "f64_from_bits" => return Some(Conversion::F64FromBits),
+            _ => {}
+        }
+        let (signed, digits) = if let Some(digits) = name.strip_prefix('u') {
+            (false, digits)
+        } else if let Some(digits) = name.strip_prefix('i') {
+            (true, digits)
+        } else {
+            return None;
+        };
+        if digits.starts_with('0') {
+            return None;
+        }
+        let width = digits.parse().ok()?;
+        (1..=MAX_BIT_WIDTH)
+            .contains(&width)
+            .then_some(Conversion::Integer { width, signed })
+    }
+}
+
+/// Float to integer of `width` bits, rounding toward zero and saturating; NaN becomes 0.
+fn float_to_bits(value: f64, width: u32, signed: bool) -> u128 {
+    if signed {
+        let max = (width_mask(width) >> 1) as i128;
+        let min = -max - 1;
+        (value as i128).clamp(min, max) as u128
+    } else {
+        (value as u128).min(width_mask(width))
+    }
+}
+
+fn width_mask(width: u32) -> u128 {
+    if width >= MAX_BIT_WIDTH {
+        u128::MAX
+    } else {
+        (1u128 << width) - 1
+    }
 }
 
 /// Helper wrapper for tuple semantics so we can enforce arity checks.
@@ -103,7 +456,7 @@ impl TupleValue {
 
 #[cfg(test)]
 mod tests {
-    use super::SemanticValue;
+    use super::{BitVector, Conversion, SemanticValue};
 
     #[test]
     fn semantic_value_bool_int_conversion() {
@@ -136,4 +489,55 @@ mod tests {
         assert!(tuple_value.ensure_len(2).is_ok());
         assert!(tuple_value.ensure_len(3).is_err());
     }
+
+    #[test]
+    fn bit_vectors_wrap_and_extend_per_signedness() {
+        let byte = BitVector::new(0xF0, 8, true).unwrap();
+        assert_eq!(byte.to_i128(), -16);
+        assert_eq!(byte.wrapping_add(byte).bits(), 0xE0);
+        assert_eq!(byte.resize(16, false).unwrap().bits(), 0xFFF0);
+        assert_eq!(byte.shift_right_arithmetic(2).bits(), 0xFC);
+        assert_eq!(byte.shift_right(2).bits(), 0x3C);
+        assert_eq!(byte.shift_left(8).bits(), 0);
+
+        let wide = BitVector::new(u128::MAX, 128, false).unwrap();
+        assert!(SemanticValue::bits(wide).as_int().is_err());
+        let word = BitVector::new(u128::from(u64::MAX), 64, false).unwrap();
+        assert_eq!(SemanticValue::bits(word).as_int().unwrap(), -1);
+        assert!(BitVector::new(0, 129, false).is_err());
+    }
+
+    #[test]
+    fn conversions_are_explicit() {
+        assert_eq!(
+            Conversion::parse("i16"),
+            Some(Conversion::Integer {
+                width: 16,
+                signed: true
+            })
+        );
+        assert_eq!(Conversion::parse("u0"), None);
+        assert_eq!(Conversion::parse("u129"), None);
+        assert_eq!(Conversion::parse("x8"), None);
+
+        let half = SemanticValue::int(-2)
+            .convert(Conversion::parse("u16").unwrap())
+            .unwrap();
+        assert_eq!(half.to_bits(64).unwrap(), 0xFFFE);
+        let float = half.convert(Conversion::F32).unwrap();
+        assert_eq!(float, SemanticValue::F32(65534.0));
+        assert!(float.as_int().is_err());
+        assert!(float.to_bits(32).is_err());
+
+        let encoded = SemanticValue::F32(1.5).convert(Conversion::Bits).unwrap();
+        assert_eq!(encoded.to_bits(32).unwrap(), 0x3FC0_0000);
+        assert_eq!(
+            encoded.convert(Conversion::F32FromBits).unwrap(),
+            SemanticValue::F32(1.5)
+        );
+        let saturated = SemanticValue::F64(-1e30)
+            .convert(Conversion::parse("i8").unwrap())
+            .unwrap();
+        assert_eq!(saturated.as_int().unwrap(), -128);
+    }
 }
@@ -13,6 +13,10 @@ impl SpaceState {
         self.fields.get(name)
     }
 
+    pub(crate) fn field_names(&self) -> impl Iterator<Item = &str> {
+        self.fields.keys().map(String::as_str)
+    }
+
     pub(crate) fn register_field(
         &mut self,
         field: &FieldDecl,
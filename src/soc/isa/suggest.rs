@@ -0,0 +1,83 @@
+//! "Did you mean" lookups for misspelled names in ISA diagnostics.
+
+/// Returns the candidate closest to `name` by case-insensitive edit distance, provided it is
+/// near enough to be a plausible typo. Ties resolve to the lexicographically smallest name so
+/// suggestions stay stable regardless of the candidates' iteration order.
+pub(crate) fn closest_name<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let needle = name.to_ascii_lowercase();
+    let limit = (needle.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| {
+            let distance = edit_distance(&needle, &candidate.to_ascii_lowercase());
+            (distance, candidate)
+        })
+        .filter(|(distance, _)| *distance <= limit)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// Edit distance over characters where an adjacent transposition (`Form`/`From`) counts as a
+/// single edit, matching how names are usually mistyped.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let width = b.len() + 1;
+    let mut table = vec![0usize; (a.len() + 1) * width];
+    for i in 0..=a.len() {
+        for j in 0..=b.len() {
+            table[i * width + j] = if i == 0 || j == 0 {
+                i + j
+            } else {
+                let cost = usize::from(a[i - 1] != b[j - 1]);
+                let mut best = (table[(i - 1) * width + j] + 1)
+                    .min(table[i * width + j - 1] + 1)
+                    .min(table[(i - 1) * width + j - 1] + cost);
+                if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                    best = best.min(table[(i - 2) * width + j - 2] + 1);
+                }
+                best
+            };
+        }
+    }
+    table[a.len() * width + b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_edit_distance() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("GPR", "GPR"), 0);
+        assert_eq!(
+            edit_distance("Form", "From"),
+            1,
+            "transpositions are one edit"
+        );
+    }
+
+    #[test]
+    fn suggests_only_plausible_typos() {
+        let names = ["X_Form", "D_Form", "XO_Form", "reg"];
+        assert_eq!(closest_name("X_From", names), Some("X_Form"));
+        assert_eq!(closest_name("rge", ["reg", "insn"]), Some("reg"));
+        assert_eq!(
+            closest_name("gpr", ["GPR"]),
+            Some("GPR"),
+            "case-only differences are suggested"
+        );
+        assert_eq!(closest_name("completely", names), None);
+        assert_eq!(
+            closest_name("reg", names),
+            None,
+            "exact matches are not suggestions"
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use super::Validator;
+use crate::soc::isa::ast::{BusDecl, BusRangeDecl, SpaceKind};
+
+impl Validator {
+    /// Validates a `:bus` declaration once every space in the document set is known.
+    pub(super) fn validate_bus(&mut self, bus: &BusDecl) {
+        if !self.seen_buses.insert(bus.name.clone()) {
+            self.push_validation_diagnostic(
+                "validation.bus.duplicate",
+                format!("bus '{}' defined multiple times", bus.name),
+                Some(bus.span.clone()),
+            );
+            return;
+        }
+        if bus.address_bits == 0 || bus.address_bits > 64 {
+            self.push_validation_diagnostic(
+                "validation.bus.addr",
+                format!(
+                    "bus '{}' address width {} must be between 1 and 64 bits",
+                    bus.name, bus.address_bits
+                ),
+                Some(bus.span.clone()),
+            );
+            return;
+        }
+        for range in &bus.ranges {
+            self.validate_bus_range(bus, range);
+        }
+        self.ensure_bus_ranges_disjoint(bus);
+    }
+
+    fn validate_bus_range(&mut self, bus: &BusDecl, range: &BusRangeDecl) {
+        let limit = address_limit(bus.address_bits);
+        match range.end() {
+            Some(end) if end <= limit => {}
+            _ => {
+                self.push_validation_diagnostic(
+                    "validation.bus.range",
+                    format!(
+                        "bus '{}' range 0x{:X} +0x{:X} exceeds the {}-bit bus address space",
+                        bus.name, range.start, range.size, bus.address_bits
+                    ),
+                    Some(range.span.clone()),
+                );
+            }
+        }
+
+        let Some(kind) = self.space_kinds.get(&range.space) else {
+            self.push_validation_diagnostic(
+                "validation.bus.unknown-space",
+                format!("bus '{}' maps undefined space '{}'", bus.name, range.space),
+                Some(range.span.clone()),
+            );
+            return;
+        };
+        if matches!(kind, SpaceKind::Register | SpaceKind::Logic) {
+            self.push_validation_diagnostic(
+                "validation.bus.space-kind",
+                format!(
+                    "bus '{}' cannot map space '{}'; only rw, ro and memio spaces are addressable",
+                    bus.name, range.space
+                ),
+                Some(range.span.clone()),
+            );
+            return;
+        }
+
+        if let Some(bits) = self.space_address_bits.get(&range.space).copied() {
+            let fits = range
+                .space_offset
+                .checked_add(range.size.saturating_sub(1))
+                .map(|last| last <= address_limit(bits))
+                .unwrap_or(false);
+            if !fits {
+                self.push_validation_diagnostic(
+                    "validation.bus.space-offset",
+                    format!(
+                        "bus '{}' window at space offset 0x{:X} +0x{:X} exceeds the {}-bit address space of '{}'",
+                        bus.name, range.space_offset, range.size, bits, range.space
+                    ),
+                    Some(range.span.clone()),
+                );
+            }
+        }
+    }
+
+    /// Overlapping windows are only legal when a higher `prio=` punches a hole in a lower one.
+    fn ensure_bus_ranges_disjoint(&mut self, bus: &BusDecl) {
+        for (idx, range) in bus.ranges.iter().enumerate() {
+            let Some(end) = range.end() else {
+                continue;
+            };
+            let conflict = bus.ranges[..idx].iter().find(|other| {
+                other.priority == range.priority
+                    && other
+                        .end()
+                        .map(|other_end| range.start <= other_end && other.start <= end)
+                        .unwrap_or(false)
+            });
+            if let Some(other) = conflict {
+                self.push_validation_diagnostic(
+                    "validation.bus.overlap",
+                    format!(
+                        "bus '{}' range 0x{:X} +0x{:X} -> {} overlaps 0x{:X} +0x{:X} -> {} at priority {}",
+                        bus.name,
+                        range.start,
+                        range.size,
+                        range.space,
+                        other.start,
+                        other.size,
+                        other.space,
+                        range.priority
+                    ),
+                    Some(range.span.clone()),
+                );
+            }
+        }
+    }
+}
+
+fn address_limit(bits: u32) -> u64 {
+    if bits >= 64 {
+        u64::MAX
+    } else {
+        (1u64 << bits) - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::*;
+
+    const SPACES: &str = ":space flash addr=32 word=32 type=ro\n:space ram addr=16 word=32 type=rw\n:space reg addr=32 word=32 type=register\n";
+
+    #[test]
+    fn accepts_overlay_with_higher_priority() {
+        let src = format!(
+            "{SPACES}:bus sysbus addr=32 ranges={{\n    [0x0 +64kB] -> flash\n    [0x40000000 +0x8000] -> ram\n    [0x40000400 +1kB] -> flash space_off=0x1000 prio=1\n}}"
+        );
+        validate_src(&src).expect("priority overlays should validate");
+    }
+
+    #[test]
+    fn rejects_overlap_at_same_priority() {
+        let src = format!(
+            "{SPACES}:bus sysbus addr=32 ranges={{\n    [0x0 +64kB] -> flash\n    [0x8000 +0x100] -> ram\n}}"
+        );
+        let err = validate_src(&src).unwrap_err();
+        expect_validation_diag(err, "overlaps");
+    }
+
+    #[test]
+    fn rejects_unknown_and_register_spaces() {
+        let src = format!(
+            "{SPACES}:bus sysbus addr=32 ranges={{\n    [0x0 +4] -> missing\n    [0x10 +4] -> reg\n}}"
+        );
+        expect_validation_diag(validate_src(&src).unwrap_err(), "undefined space 'missing'");
+        expect_validation_diag(validate_src(&src).unwrap_err(), "cannot map space 'reg'");
+    }
+
+    #[test]
+    fn rejects_ranges_outside_bus_or_space() {
+        let src = format!(
+            "{SPACES}:bus sysbus addr=16 ranges={{\n    [0xFF00 +1kB] -> flash\n    [0x0 +0x100] -> ram space_off=0xFFF0\n}}"
+        );
+        expect_validation_diag(
+            validate_src(&src).unwrap_err(),
+            "exceeds the 16-bit bus address space",
+        );
+        expect_validation_diag(
+            validate_src(&src).unwrap_err(),
+            "exceeds the 16-bit address space of 'ram'",
+        );
+    }
+}
@@ -0,0 +1,405 @@
+//! Decode-ambiguity analysis (spec §7.2.5). Runs on a finished [`MachineDescription`] because it
+//! needs the resolved mask/value patterns: compares every pair of patterns in a logic space and
+//! walks each primary opcode for encodings no pattern accepts.
+
+use std::collections::BTreeMap;
+
+use crate::soc::isa::ast::SpaceKind;
+use crate::soc::isa::diagnostic::{DiagnosticLevel, DiagnosticPhase, IsaDiagnostic, SourceSpan};
+use crate::soc::isa::machine::{Instruction, InstructionPattern, MachineDescription};
+
+/// Primary opcodes wider than this are not enumerated for gap reporting.
+const MAX_PRIMARY_BITS: u32 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// Both patterns accept some word and neither mask refines the other.
+    Overlap,
+    /// The patterns accept a common word with the same specificity, so declaration order
+    /// decides which one decodes.
+    EqualSpecificity,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeConflict {
+    pub space: String,
+    pub kind: ConflictKind,
+    pub first: String,
+    pub second: String,
+    /// A word accepted by both patterns.
+    pub example: u64,
+    /// Declaration of `second`, the later of the two instructions.
+    pub span: SourceSpan,
+}
+
+/// Unassigned encodings within one run of primary opcodes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeGap {
+    pub space: String,
+    /// Inclusive primary opcode range; both ends are equal for a partially used opcode.
+    pub opcodes: (u64, u64),
+    /// Uncovered encodings of a partially used opcode, MSB first with `-` for free bits.
+    /// Empty when no pattern uses any opcode in the range.
+    pub encodings: Vec<String>,
+    /// Declaration of an instruction in the partially used opcode, or of the nearest
+    /// instruction for an unused range.
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DecodeReport {
+    pub conflicts: Vec<DecodeConflict>,
+    pub gaps: Vec<DecodeGap>,
+}
+
+impl DecodeReport {
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty() && self.gaps.is_empty()
+    }
+
+    /// Warning diagnostics for every conflict and gap.
+    pub fn diagnostics(&self) -> Vec<IsaDiagnostic> {
+        let conflicts = self.conflicts.iter().map(|conflict| {
+            let (code, reason) = match conflict.kind {
+                ConflictKind::Overlap => (
+                    "validation.decode.overlap",
+                    "overlap and neither mask refines the other",
+                ),
+                ConflictKind::EqualSpecificity => (
+                    "validation.decode.equal-specificity",
+                    "overlap with equal specificity",
+                ),
+            };
+            warning(
+                code,
+                format!(
+                    "instructions '{}' and '{}' in space '{}' {reason} (both match 0x{:X})",
+                    conflict.first, conflict.second, conflict.space, conflict.example
+                ),
+                &conflict.span,
+            )
+        });
+        let gaps = self.gaps.iter().map(|gap| {
+            let (start, end) = gap.opcodes;
+            let message = if gap.encodings.is_empty() && start == end {
+                format!(
+                    "space '{}' has no instruction for opcode {start}",
+                    gap.space
+                )
+            } else if gap.encodings.is_empty() {
+                format!(
+                    "space '{}' has no instructions for opcodes {start}..{end}",
+                    gap.space
+                )
+            } else {
+                let shown: Vec<&str> = gap.encodings.iter().take(4).map(String::as_str).collect();
+                let more = gap.encodings.len().saturating_sub(shown.len());
+                let suffix = if more > 0 {
+                    format!(" and {more} more")
+                } else {
+                    String::new()
+                };
+                format!(
+                    "space '{}' opcode {start} leaves encodings undecoded: {}{suffix}",
+                    gap.space,
+                    shown.join(", ")
+                )
+            };
+            warning("validation.decode.gap", message, &gap.span)
+        });
+        conflicts.chain(gaps).collect()
+    }
+}
+
+fn warning(code: &'static str, message: String, span: &SourceSpan) -> IsaDiagnostic {
+    IsaDiagnostic::new(
+        DiagnosticPhase::Validation,
+        DiagnosticLevel::Warning,
+        code,
+        message,
+        Some(span.clone()),
+    )
+}
+
+/// Checks every logic space of `machine` for ambiguous pattern pairs and undecoded encodings.
+pub fn analyze_decode(machine: &MachineDescription) -> DecodeReport {
+    let mut by_space: BTreeMap<&str, Vec<&InstructionPattern>> = BTreeMap::new();
+    for pattern in machine.patterns() {
+        by_space.entry(&pattern.space).or_default().push(pattern);
+    }
+    let mut report = DecodeReport::default();
+    for (space, patterns) in by_space {
+        let Some(info) = machine.spaces.get(space) else {
+            continue;
+        };
+        let Some(word_bits) = info.size_bits.filter(|_| info.kind == SpaceKind::Logic) else {
+            continue;
+        };
+        let instruction =
+            |pattern: &InstructionPattern| &machine.instructions[pattern.instruction_idx];
+        for (idx, first) in patterns.iter().enumerate() {
+            for second in &patterns[idx + 1..] {
+                if let Some(kind) = classify(first, second) {
+                    report.conflicts.push(DecodeConflict {
+                        space: space.to_string(),
+                        kind,
+                        first: instruction(first).name.clone(),
+                        second: instruction(second).name.clone(),
+                        example: first.value | second.value,
+                        span: instruction(second).span.clone(),
+                    });
+                }
+            }
+        }
+        report.gaps.extend(find_gaps(
+            space,
+            word_bits,
+            &patterns,
+            &machine.instructions,
+        ));
+    }
+    report
+}
+
+fn classify(first: &InstructionPattern, second: &InstructionPattern) -> Option<ConflictKind> {
+    let shared = first.mask & second.mask;
+    if (first.value ^ second.value) & shared != 0 {
+        return None;
+    }
+    if first.specificity == second.specificity {
+        return Some(ConflictKind::EqualSpecificity);
+    }
+    let refines = |outer: u64, inner: u64| outer & inner == outer;
+    if refines(first.mask, second.mask) || refines(second.mask, first.mask) {
+        None
+    } else {
+        Some(ConflictKind::Overlap)
+    }
+}
+
+/// The primary opcode is the leading run of bits every pattern in the space fixes.
+fn find_gaps(
+    space: &str,
+    word_bits: u32,
+    patterns: &[&InstructionPattern],
+    instructions: &[Instruction],
+) -> Vec<DecodeGap> {
+    let span_of = |pattern: &InstructionPattern| instructions[pattern.instruction_idx].span.clone();
+    let word_mask = if word_bits >= 64 {
+        u64::MAX
+    } else {
+        (1u64 << word_bits) - 1
+    };
+    let common = patterns
+        .iter()
+        .fold(word_mask, |acc, pattern| acc & pattern.mask);
+    let leading = (common << (64 - word_bits)).leading_ones();
+    let primary_bits = leading.min(MAX_PRIMARY_BITS);
+    let shift = word_bits - primary_bits;
+    let primary_mask = word_mask & u64::MAX.checked_shl(shift).unwrap_or(0);
+
+    let mut gaps = Vec::new();
+    let mut unused: Option<(u64, u64)> = None;
+    let mut previous: Option<SourceSpan> = None;
+    for opcode in 0..(1u64 << primary_bits) {
+        let value = opcode.checked_shl(shift).unwrap_or(0);
+        let members: Vec<&InstructionPattern> = patterns
+            .iter()
+            .copied()
+            .filter(|pattern| pattern.value & primary_mask == value)
+            .collect();
+        if members.is_empty() {
+            unused = match unused {
+                Some((start, _)) => Some((start, opcode)),
+                None => Some((opcode, opcode)),
+            };
+            continue;
+        }
+        let span = span_of(members[0]);
+        if let Some(opcodes) = unused.take() {
+            gaps.push(DecodeGap {
+                space: space.to_string(),
+                opcodes,
+                encodings: Vec::new(),
+                span: previous.clone().unwrap_or_else(|| span.clone()),
+            });
+        }
+        previous = Some(span.clone());
+        let mut holes = Vec::new();
+        uncovered(&members, primary_mask, value, &mut holes);
+        if !holes.is_empty() {
+            holes.sort_by_key(|(_, value)| *value);
+            gaps.push(DecodeGap {
+                space: space.to_string(),
+                opcodes: (opcode, opcode),
+                encodings: holes
+                    .into_iter()
+                    .map(|(mask, value)| cube_string(word_bits, mask, value))
+                    .collect(),
+                span,
+            });
+        }
+    }
+    if let (Some(opcodes), Some(span)) = (unused, previous) {
+        gaps.push(DecodeGap {
+            space: space.to_string(),
+            opcodes,
+            encodings: Vec::new(),
+            span,
+        });
+    }
+    gaps
+}
+
+/// Splits the cube (`mask`, `value`) on bits the remaining patterns fix until each piece is
+/// either accepted by some pattern or by none; the latter are collected into `holes`.
+fn uncovered(patterns: &[&InstructionPattern], mask: u64, value: u64, holes: &mut Vec<(u64, u64)>) {
+    let live: Vec<&InstructionPattern> = patterns
+        .iter()
+        .copied()
+        .filter(|pattern| (pattern.value ^ value) & pattern.mask & mask == 0)
+        .collect();
+    let Some(first) = live.first() else {
+        holes.push((mask, value));
+        return;
+    };
+    if live.iter().any(|pattern| pattern.mask & !mask == 0) {
+        return;
+    }
+    let open = first.mask & !mask;
+    let bit = 1u64 << (63 - open.leading_zeros());
+    uncovered(&live, mask | bit, value, holes);
+    uncovered(&live, mask | bit, value | bit, holes);
+}
+
+fn cube_string(word_bits: u32, mask: u64, value: u64) -> String {
+    (0..word_bits)
+        .rev()
+        .map(|bit| match ((mask >> bit) & 1, (value >> bit) & 1) {
+            (0, _) => '-',
+            (_, 1) => '1',
+            _ => '0',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::isa::parse_str;
+    use std::path::PathBuf;
+
+    fn machine(instructions: &str) -> MachineDescription {
+        let source = format!(
+            ":space insn addr=32 word=8 type=logic endian=big\n:insn BASE subfields={{\n    OP @(0..1) op=func\n    SUB @(2..3) op=func\n    IMM @(4..7) op=immediate\n}}\n{instructions}"
+        );
+        let doc = parse_str(PathBuf::from("test.isa"), &source).expect("parse");
+        MachineDescription::from_documents(vec![doc]).expect("machine")
+    }
+
+    #[test]
+    fn reports_partial_overlaps_and_ties() {
+        let machine = machine(
+            ":insn::BASE a mask={OP=1, SUB=0}\n:insn::BASE b mask={OP=1, @(4..5)=0b11}\n:insn::BASE c mask={OP=2, SUB=1}\n:insn::BASE d mask={OP=2, SUB=1}\n:insn::BASE e mask={OP=3}\n:insn::BASE f mask={OP=3, SUB=2}\n",
+        );
+        let report = analyze_decode(&machine);
+        let pairs: Vec<(ConflictKind, &str, &str)> = report
+            .conflicts
+            .iter()
+            .map(|c| (c.kind, c.first.as_str(), c.second.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                (ConflictKind::EqualSpecificity, "a", "b"),
+                (ConflictKind::EqualSpecificity, "c", "d"),
+            ],
+            "refinement e/f is not a conflict"
+        );
+        assert_eq!(report.conflicts[0].example, 0b0100_1100);
+        let diagnostics = report.diagnostics();
+        let lines: Vec<usize> = diagnostics
+            .iter()
+            .filter(|d| d.code == "validation.decode.equal-specificity")
+            .map(|d| {
+                let span = d.span.as_ref().expect("conflict span");
+                assert_eq!(span.path, PathBuf::from("test.isa"));
+                span.start.line
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            vec![8, 10],
+            "conflicts point at the later declaration"
+        );
+        assert!(
+            diagnostics
+                .iter()
+                .all(|d| d.level == DiagnosticLevel::Warning)
+        );
+        assert!(
+            diagnostics
+                .iter()
+                .any(|d| d.code == "validation.decode.equal-specificity")
+        );
+    }
+
+    #[test]
+    fn reports_overlap_without_refinement() {
+        let machine = machine(
+            ":insn::BASE a mask={OP=1}\n:insn::BASE b mask={OP=1, SUB=3}\n:insn::BASE c mask={@(0..2)=0b011, IMM=0}\n",
+        );
+        let report = analyze_decode(&machine);
+        assert!(
+            report
+                .conflicts
+                .iter()
+                .any(|c| c.kind == ConflictKind::Overlap && c.first == "b" && c.second == "c")
+        );
+        assert!(
+            !report
+                .conflicts
+                .iter()
+                .any(|c| c.first == "a" && c.second == "b")
+        );
+    }
+
+    #[test]
+    fn lists_unused_opcodes_and_partial_holes() {
+        let machine = machine(
+            ":insn::BASE a mask={OP=1, SUB=0}\n:insn::BASE b mask={OP=1, SUB=2}\n:insn::BASE c mask={OP=2}\n",
+        );
+        let report = analyze_decode(&machine);
+        assert!(report.conflicts.is_empty());
+        let gaps: Vec<((u64, u64), Vec<&str>, usize)> = report
+            .gaps
+            .iter()
+            .map(|gap| {
+                (
+                    gap.opcodes,
+                    gap.encodings.iter().map(String::as_str).collect(),
+                    gap.span.start.line,
+                )
+            })
+            .collect();
+        assert_eq!(
+            gaps,
+            vec![
+                ((0, 0), Vec::new(), 7),
+                ((1, 1), vec!["0101----", "0111----"], 7),
+                ((3, 3), Vec::new(), 9),
+            ],
+            "unused opcodes point at the nearest instruction, holes at their opcode"
+        );
+        assert!(report.gaps.iter().all(|gap| gap.space == "insn"));
+        let messages: Vec<String> = report
+            .diagnostics()
+            .into_iter()
+            .map(|d| d.message)
+            .collect();
+        assert!(messages.contains(&"space 'insn' has no instruction for opcode 3".to_string()));
+        assert!(messages.contains(
+            &"space 'insn' opcode 1 leaves encodings undecoded: 0101----, 0111----".to_string()
+        ));
+    }
+}
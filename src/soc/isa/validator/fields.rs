@@ -1,27 +1,38 @@
 use super::Validator;
 use crate::soc::isa::ast::{ContextReference, FieldDecl};
+use crate::soc::isa::machine::{encode_constant, parse_bit_spec};
 use crate::soc::isa::register::FieldRegistrationError;
 use crate::soc::isa::space::resolve_reference_path;
+use crate::soc::isa::suggest::closest_name;
 
 impl Validator {
     pub(super) fn validate_field(&mut self, field: &FieldDecl) {
         if let Some(reference) = &field.redirect {
             self.ensure_redirect_target_defined(field, reference);
         }
+        self.ensure_reset_fits(field);
 
         let Some(state) = self.space_states.get_mut(&field.space) else {
-            self.push_validation_diagnostic(
+            self.push_suggested_diagnostic(
                 "validation.unknown-space-field",
                 format!(
                     "field '{}' declared in unknown space '{}'",
                     field.name, field.space
                 ),
                 Some(field.span.clone()),
+                self.suggest_space(&field.space),
             );
             return;
         };
 
-        match state.register_field(field) {
+        let result = state.register_field(field);
+        let base_suggestion = match &result {
+            Err(FieldRegistrationError::MissingBaseField { name }) => {
+                closest_name(name, state.field_names()).map(str::to_string)
+            }
+            _ => None,
+        };
+        match result {
             Ok(()) => {}
             Err(FieldRegistrationError::DuplicateField { name }) => {
                 self.push_validation_diagnostic(
@@ -31,10 +42,11 @@ impl Validator {
                 );
             }
             Err(FieldRegistrationError::MissingBaseField { name }) => {
-                self.push_validation_diagnostic(
+                self.push_suggested_diagnostic(
                     "validation.field.append-missing",
                     format!("cannot append subfields to undefined field '{}'", name),
                     Some(field.span.clone()),
+                    base_suggestion,
                 );
             }
             Err(FieldRegistrationError::EmptySubfieldAppend { name }) => {
@@ -50,6 +62,49 @@ impl Validator {
         }
     }
 
+    /// Checks `reset=` values against the field width and each subfield's bit span.
+    fn ensure_reset_fits(&mut self, field: &FieldDecl) {
+        let width = field
+            .size
+            .or_else(|| self.space_word_bits.get(&field.space).copied());
+        let Some(width) = width else {
+            return;
+        };
+        if let Some(reset) = field.reset
+            && width < 128
+            && reset >> width != 0
+        {
+            self.push_validation_diagnostic(
+                "validation.field.reset",
+                format!(
+                    "reset value 0x{reset:X} for field '{}' does not fit in {width} bits",
+                    field.name
+                ),
+                Some(field.span.clone()),
+            );
+        }
+        for sub in &field.subfields {
+            let Some(reset) = sub.reset else {
+                continue;
+            };
+            let Ok(spec) = parse_bit_spec(width, &sub.bit_spec) else {
+                continue;
+            };
+            let fits =
+                u64::try_from(reset).is_ok_and(|reset| encode_constant(&spec, reset).is_ok());
+            if !fits {
+                self.push_validation_diagnostic(
+                    "validation.field.reset",
+                    format!(
+                        "reset value 0x{reset:X} does not fit subfield '{}::{}' ({})",
+                        field.name, sub.name, sub.bit_spec
+                    ),
+                    Some(field.span.clone()),
+                );
+            }
+        }
+    }
+
     fn ensure_redirect_target_defined(&mut self, field: &FieldDecl, reference: &ContextReference) {
         let (target_space, mut path) = resolve_reference_path(&field.space, reference);
         if path.is_empty() {
@@ -62,33 +117,46 @@ impl Validator {
         }
         let field_name = path.remove(0);
         let Some(space_state) = self.space_states.get(&target_space) else {
-            self.push_validation_diagnostic(
+            let suggestion =
+                closest_name(&target_space, self.space_states.keys().map(String::as_str))
+                    .map(str::to_string);
+            self.push_suggested_diagnostic(
                 "validation.redirect.unknown-space",
                 format!("redirect references undefined space '{}'", target_space),
                 Some(field.span.clone()),
+                suggestion,
             );
             return;
         };
         let Some(field_info) = space_state.lookup_field(&field_name) else {
-            self.push_validation_diagnostic(
+            let suggestion =
+                closest_name(&field_name, space_state.field_names()).map(str::to_string);
+            self.push_suggested_diagnostic(
                 "validation.redirect.unknown-field",
                 format!(
                     "redirect references undefined field '{}' in space '{}'",
                     field_name, target_space
                 ),
                 Some(field.span.clone()),
+                suggestion,
             );
             return;
         };
         if let Some(subfield_name) = path.first() {
             if !field_info.has_subfield(subfield_name) {
-                self.push_validation_diagnostic(
+                let suggestion = closest_name(
+                    subfield_name,
+                    field_info.subfields.iter().map(String::as_str),
+                )
+                .map(str::to_string);
+                self.push_suggested_diagnostic(
                     "validation.redirect.unknown-subfield",
                     format!(
                         "redirect references undefined subfield '{}' on field '{}'",
                         subfield_name, field_name
                     ),
                     Some(field.span.clone()),
+                    suggestion,
                 );
                 return;
             }
@@ -148,6 +216,26 @@ mod tests {
         expect_validation_diag(err, "undefined subfield 'MSB'");
     }
 
+    #[test]
+    fn redirect_suggests_close_register_names() {
+        let err = validate_src(
+            ":space reg addr=32 word=64 type=register\n:reg GPR[0..3] size=64\n:reg SP redirect=GRP1\n:reg FP redirect=$rge::GPR2",
+        )
+        .unwrap_err();
+        let IsaError::Diagnostics { diagnostics, .. } = err else {
+            panic!("expected diagnostics");
+        };
+        let suggestions: Vec<_> = diagnostics
+            .iter()
+            .map(|diag| diag.suggestion.as_deref())
+            .collect();
+        assert_eq!(
+            suggestions,
+            vec![Some("GPR1"), Some("reg")],
+            "{diagnostics:?}"
+        );
+    }
+
     #[test]
     fn validator_collects_multiple_errors() {
         let err = validate_src(
@@ -201,4 +289,21 @@ mod tests {
         .unwrap_err();
         expect_validation_diag(err, "cannot append subfields to undefined field");
     }
+
+    #[test]
+    fn reset_values_must_fit_their_bits() {
+        let src = ":space reg addr=32 word=32 type=register\n:reg MSR size=16 reset=0x10000\n:reg CR reset=0 subfields={\n    LT @(0..1) reset=4\n}";
+        expect_validation_diag(validate_src(src).unwrap_err(), "does not fit in 16 bits");
+        expect_validation_diag(
+            validate_src(src).unwrap_err(),
+            "does not fit subfield 'CR::LT'",
+        );
+
+        let wide =
+            ":space reg addr=32 word=64 type=register\n:reg ACC reset=0x1_0000_0000_0000_0000";
+        expect_validation_diag(
+            validate_src(wide).unwrap_err(),
+            "reset value 0x10000000000000000 for field 'ACC' does not fit in 64 bits",
+        );
+    }
 }
@@ -1,6 +1,7 @@
 use super::Validator;
 use crate::soc::isa::ast::{FormDecl, SpaceKind};
 use crate::soc::isa::logic::LogicFormError;
+use crate::soc::isa::suggest::closest_name;
 
 impl Validator {
     pub(super) fn validate_form(&mut self, form: &FormDecl) {
@@ -18,13 +19,14 @@ impl Validator {
                 return;
             }
             None => {
-                self.push_validation_diagnostic(
+                self.push_suggested_diagnostic(
                     "validation.logic.form-space",
                     format!(
                         "form '{}' declared in unknown space '{}'",
                         form.name, form.space
                     ),
                     Some(form.span.clone()),
+                    self.suggest_space(&form.space),
                 );
                 return;
             }
@@ -39,7 +41,14 @@ impl Validator {
             return;
         };
 
-        match state.register_form(form) {
+        let result = state.register_form(form);
+        let parent_suggestion = match (&result, self.logic_states.get(&form.space)) {
+            (Err(LogicFormError::MissingParent { parent }), Some(state)) => {
+                closest_name(parent, state.form_names()).map(str::to_string)
+            }
+            _ => None,
+        };
+        match result {
             Ok(()) => {}
             Err(LogicFormError::DuplicateForm { name }) => self.push_validation_diagnostic(
                 "validation.logic.form-duplicate",
@@ -51,13 +60,14 @@ impl Validator {
                 format!("form '{}' must declare at least one subfield", name),
                 Some(form.span.clone()),
             ),
-            Err(LogicFormError::MissingParent { parent }) => self.push_validation_diagnostic(
+            Err(LogicFormError::MissingParent { parent }) => self.push_suggested_diagnostic(
                 "validation.logic.form-parent",
                 format!(
                     "parent form '{}' must be declared before it can be extended",
                     parent
                 ),
                 Some(form.span.clone()),
+                parent_suggestion,
             ),
             Err(LogicFormError::DuplicateSubfield { name }) => self.push_validation_diagnostic(
                 "validation.logic.form-subfield-duplicate",
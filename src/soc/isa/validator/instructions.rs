@@ -1,5 +1,46 @@
 use super::Validator;
-use crate::soc::isa::ast::{InstructionDecl, MaskSelector, SpaceKind};
+use crate::soc::isa::ast::{AliasValue, InstructionDecl, MaskSelector, SpaceKind};
+use crate::soc::isa::diagnostic::SourceSpan;
+use crate::soc::isa::semantics::SemanticExpr;
+use crate::soc::isa::suggest::closest_name;
+
+/// An `extend=` entry, checked once every space has been declared.
+pub(super) struct PendingExtend {
+    space: String,
+    name: String,
+    target: String,
+    span: SourceSpan,
+}
+
+impl PendingExtend {
+    pub(super) fn target(&self) -> &str {
+        &self.target
+    }
+}
+
+/// Field names a `valid=` expression reads; `true`/`false` are literals.
+fn collect_identifiers<'a>(expr: &'a SemanticExpr, names: &mut Vec<&'a str>) {
+    match expr {
+        SemanticExpr::Identifier(name)
+            if !name.eq_ignore_ascii_case("true") && !name.eq_ignore_ascii_case("false") =>
+        {
+            names.push(name);
+        }
+        SemanticExpr::BinaryOp { lhs, rhs, .. } => {
+            collect_identifiers(lhs, names);
+            collect_identifiers(rhs, names);
+        }
+        _ => {}
+    }
+}
+
+/// An `alias=` entry, checked once every instruction has been declared.
+pub(super) struct PendingAlias {
+    space: String,
+    name: String,
+    base: String,
+    span: SourceSpan,
+}
 
 impl Validator {
     pub(super) fn validate_instruction(&mut self, instr: &InstructionDecl) {
@@ -17,13 +58,14 @@ impl Validator {
                 return;
             }
             None => {
-                self.push_validation_diagnostic(
+                self.push_suggested_diagnostic(
                     "validation.logic.instruction-space",
                     format!(
                         "instruction '{}' declared in unknown space '{}'",
                         instr.name, instr.space
                     ),
                     Some(instr.span.clone()),
+                    self.suggest_space(&instr.space),
                 );
                 return;
             }
@@ -51,44 +93,203 @@ impl Validator {
         };
 
         let Some(form_info) = state.form(form_name) else {
-            self.push_validation_diagnostic(
+            let suggestion = closest_name(form_name, state.form_names()).map(str::to_string);
+            self.push_suggested_diagnostic(
                 "validation.logic.instruction-form",
                 format!(
                     "instruction '{}' references undefined form '{}'",
                     instr.name, form_name
                 ),
                 Some(instr.span.clone()),
+                suggestion,
             );
             return;
         };
 
+        let mut unknown_constraints = Vec::new();
+        if let Some(alias) = &instr.alias {
+            for constraint in &alias.constraints {
+                let other = match &constraint.value {
+                    AliasValue::Field(other) => Some(other),
+                    AliasValue::Number(_) => None,
+                };
+                for name in std::iter::once(&constraint.field).chain(other) {
+                    if !form_info.subfields.contains_key(name) {
+                        let suggestion =
+                            closest_name(name, form_info.subfields.keys().map(String::as_str))
+                                .map(str::to_string);
+                        unknown_constraints.push((name.clone(), suggestion));
+                    }
+                }
+            }
+        }
+        let mut unknown_valid = Vec::new();
+        if let Some(expr) = &instr.valid {
+            let mut names = Vec::new();
+            collect_identifiers(expr, &mut names);
+            for name in names {
+                let field = name.strip_prefix('#').unwrap_or(name);
+                if !form_info.subfields.contains_key(field) {
+                    let suggestion =
+                        closest_name(field, form_info.subfields.keys().map(String::as_str))
+                            .map(str::to_string);
+                    unknown_valid.push((field.to_string(), suggestion));
+                }
+            }
+        }
         let mut unknown_fields = Vec::new();
         if let Some(mask) = &instr.mask {
             for field in &mask.fields {
                 if let MaskSelector::Field(name) = &field.selector
                     && !form_info.subfields.contains_key(name)
                 {
-                    unknown_fields.push(name.clone());
+                    let suggestion =
+                        closest_name(name, form_info.subfields.keys().map(String::as_str))
+                            .map(str::to_string);
+                    unknown_fields.push((name.clone(), suggestion));
                 }
             }
         }
-        for name in unknown_fields {
-            self.push_validation_diagnostic(
+        for (name, suggestion) in unknown_fields {
+            self.push_suggested_diagnostic(
                 "validation.logic.mask-field",
                 format!(
                     "mask references unknown field '{}' for instruction '{}'",
                     name, instr.name
                 ),
                 Some(instr.span.clone()),
+                suggestion,
+            );
+        }
+
+        for (name, suggestion) in unknown_constraints {
+            self.push_suggested_diagnostic(
+                "validation.logic.alias-field",
+                format!(
+                    "when block references unknown field '{}' for alias '{}'",
+                    name, instr.name
+                ),
+                Some(instr.span.clone()),
+                suggestion,
+            );
+        }
+        for (name, suggestion) in unknown_valid {
+            self.push_suggested_diagnostic(
+                "validation.logic.valid-field",
+                format!(
+                    "valid expression references unknown field '{}' for instruction '{}'",
+                    name, instr.name
+                ),
+                Some(instr.span.clone()),
+                suggestion,
+            );
+        }
+        match &instr.alias {
+            Some(alias) => self.pending_aliases.push(PendingAlias {
+                space: instr.space.clone(),
+                name: instr.name.clone(),
+                base: alias.base.clone(),
+                span: instr.span.clone(),
+            }),
+            None => {
+                self.instruction_names
+                    .entry(instr.space.clone())
+                    .or_default()
+                    .insert(instr.name.clone());
+            }
+        }
+
+        if let Some(target) = &instr.extend {
+            if instr.semantics.is_some() {
+                self.push_validation_diagnostic(
+                    "validation.logic.extend-semantics",
+                    format!(
+                        "extend entry '{}' cannot declare semantics; the wider instruction supplies them",
+                        instr.name
+                    ),
+                    Some(instr.span.clone()),
+                );
+            }
+            self.pending_extends.push(PendingExtend {
+                space: instr.space.clone(),
+                name: instr.name.clone(),
+                target: target.clone(),
+                span: instr.span.clone(),
+            });
+        }
+    }
+
+    /// Every `alias=` must name an instruction of its own space that is not itself an alias.
+    pub(super) fn ensure_alias_bases(&mut self) {
+        for pending in std::mem::take(&mut self.pending_aliases) {
+            let names = self.instruction_names.get(&pending.space);
+            if names.is_some_and(|names| names.contains(&pending.base)) {
+                continue;
+            }
+            let suggestion = names
+                .and_then(|names| closest_name(&pending.base, names.iter().map(String::as_str)))
+                .map(str::to_string);
+            self.push_suggested_diagnostic(
+                "validation.logic.alias-base",
+                format!(
+                    "alias '{}' refers to '{}', which is not an instruction of space '{}'",
+                    pending.name, pending.base, pending.space
+                ),
+                Some(pending.span),
+                suggestion,
             );
         }
     }
+
+    /// The target of every `extend=` must be a wider logic space whose word is a whole number
+    /// of the source space's words.
+    pub(super) fn ensure_extend_targets(&mut self) {
+        for pending in std::mem::take(&mut self.pending_extends) {
+            if self.space_kinds.get(&pending.target) != Some(&SpaceKind::Logic) {
+                let suggestion = closest_name(
+                    &pending.target,
+                    self.space_kinds
+                        .iter()
+                        .filter(|(_, kind)| **kind == SpaceKind::Logic)
+                        .map(|(name, _)| name.as_str()),
+                )
+                .map(str::to_string);
+                self.push_suggested_diagnostic(
+                    "validation.logic.extend-space",
+                    format!(
+                        "extend entry '{}' targets '{}', which is not a logic space",
+                        pending.name, pending.target
+                    ),
+                    Some(pending.span),
+                    suggestion,
+                );
+                continue;
+            }
+            let (Some(&from), Some(&to)) = (
+                self.space_word_bits.get(&pending.space),
+                self.space_word_bits.get(&pending.target),
+            ) else {
+                continue;
+            };
+            if to <= from || to % from != 0 {
+                self.push_validation_diagnostic(
+                    "validation.logic.extend-width",
+                    format!(
+                        "extend entry '{}' widens {from}-bit space '{}' to {to}-bit space '{}'; the target word must be a larger multiple",
+                        pending.name, pending.space, pending.target
+                    ),
+                    Some(pending.span),
+                );
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::super::test_support::*;
     use crate::soc::isa::ast::{SpaceAttribute, SpaceKind};
+    use crate::soc::isa::error::IsaError;
 
     #[test]
     fn logic_instruction_requires_existing_form() {
@@ -99,6 +300,26 @@ mod tests {
         expect_validation_diag(err, "references undefined form");
     }
 
+    #[test]
+    fn logic_lookups_suggest_close_names() {
+        let err = validate_src(
+            ":space logic addr=32 word=32 type=logic\n:logic X_Form subfields={\n    OPCD @(0..5)\n}\n:logic::X_From add mask={OPCD=31}\n:logic::X_Form sub mask={OPDC=31}",
+        )
+        .unwrap_err();
+        let IsaError::Diagnostics { diagnostics, .. } = err else {
+            panic!("expected diagnostics");
+        };
+        let suggestions: Vec<_> = diagnostics
+            .iter()
+            .map(|diag| diag.suggestion.as_deref())
+            .collect();
+        assert_eq!(
+            suggestions,
+            vec![Some("X_Form"), Some("OPCD")],
+            "forms and subfields get did-you-mean hints: {diagnostics:?}"
+        );
+    }
+
     #[test]
     fn logic_mask_requires_known_field() {
         let err = validate_src(
@@ -145,6 +366,59 @@ mod tests {
         expect_validation_diag(err, "must reference a form");
     }
 
+    #[test]
+    fn extend_requires_wider_logic_target() {
+        let err = validate_src(
+            ":space vle addr=32 word=16 type=logic\n:vle PFX subfields={\n    PRE @(0..3) op=func\n}\n:vle::PFX wide mask={PRE=1} extend=vle23\n:space vle32 addr=32 word=32 type=logic\n",
+        )
+        .unwrap_err();
+        let IsaError::Diagnostics { diagnostics, .. } = err else {
+            panic!("expected diagnostics");
+        };
+        let unknown = diagnostics
+            .iter()
+            .find(|diag| diag.code == "validation.logic.extend-space")
+            .expect("unknown extend target is reported");
+        assert_eq!(unknown.suggestion.as_deref(), Some("vle32"));
+
+        let err = validate_src(
+            ":space vle addr=32 word=16 type=logic\n:vle PFX subfields={\n    PRE @(0..3) op=func\n}\n:vle::PFX wide mask={PRE=1} extend=vle24\n:space vle24 addr=32 word=24 type=logic\n",
+        )
+        .unwrap_err();
+        expect_validation_diag(err, "the target word must be a larger multiple");
+
+        validate_src(
+            ":space vle addr=32 word=16 type=logic\n:vle PFX subfields={\n    PRE @(0..3) op=func\n}\n:vle::PFX wide mask={PRE=1} extend=vle32\n:space vle32 addr=32 word=32 type=logic\n",
+        )
+        .expect("extension into a later 32-bit space validates");
+    }
+
+    #[test]
+    fn alias_requires_known_base_and_fields() {
+        let err = validate_src(
+            ":space logic addr=32 word=32 type=logic\n:logic X subfields={\n    OPCD @(0..5) op=func\n    RT @(6..10) op=target\n    RA @(11..15) op=source\n    RB @(16..20) op=source\n}\n:logic::X or mask={OPCD=31}\n:logic::X mr alias=orr when={RBX=#RT}\n",
+        )
+        .unwrap_err();
+        let IsaError::Diagnostics { diagnostics, .. } = err else {
+            panic!("expected diagnostics");
+        };
+        let base = diagnostics
+            .iter()
+            .find(|diag| diag.code == "validation.logic.alias-base")
+            .expect("unknown base is reported");
+        assert_eq!(base.suggestion.as_deref(), Some("or"));
+        let field = diagnostics
+            .iter()
+            .find(|diag| diag.code == "validation.logic.alias-field")
+            .expect("unknown constrained field is reported");
+        assert_eq!(field.suggestion.as_deref(), Some("RB"));
+
+        validate_src(
+            ":space logic addr=32 word=32 type=logic\n:logic X subfields={\n    OPCD @(0..5) op=func\n    RT @(6..10) op=target\n    RA @(11..15) op=source\n    RB @(16..20) op=source\n}\n:logic::X mr alias=or when={RB=#RT}\n:logic::X or mask={OPCD=31}\n",
+        )
+        .expect("alias declared before its base validates");
+    }
+
     #[test]
     fn logic_instruction_accepts_inherited_fields() {
         validate_src(
@@ -152,4 +426,20 @@ mod tests {
         )
         .expect("logic instruction referencing inherited form fields should validate");
     }
+
+    #[test]
+    fn valid_expression_requires_known_fields() {
+        let err = validate_src(
+            ":space logic addr=32 word=32 type=logic\n:logic X subfields={\n    OPCD @(0..5) op=func\n    RT @(6..10) op=target\n    RA @(11..15) op=source\n}\n:logic::X lwzu mask={OPCD=33} valid={#RA != 0 && #RA != #RTT}\n",
+        )
+        .unwrap_err();
+        let IsaError::Diagnostics { diagnostics, .. } = err else {
+            panic!("expected diagnostics");
+        };
+        let field = diagnostics
+            .iter()
+            .find(|diag| diag.code == "validation.logic.valid-field")
+            .expect("unknown field in valid= is reported");
+        assert_eq!(field.suggestion.as_deref(), Some("RT"));
+    }
 }
@@ -1,5 +1,7 @@
 //! Semantic validation for parsed ISA documents and the merged machine description.
 
+mod buses;
+mod decode;
 mod fields;
 mod forms;
 mod instructions;
@@ -8,6 +10,8 @@ mod spaces;
 #[cfg(test)]
 pub(super) mod test_support;
 
+pub use decode::{ConflictKind, DecodeConflict, DecodeGap, DecodeReport, analyze_decode};
+
 use std::collections::{BTreeMap, BTreeSet};
 
 use super::ast::{IsaItem, IsaSpecification, SpaceKind, SpaceMember, SpaceMemberDecl};
@@ -16,6 +20,7 @@ use super::error::IsaError;
 use super::logic::LogicSpaceState;
 use super::machine::MachineDescription;
 use super::space::SpaceState;
+use super::suggest::closest_name;
 
 #[derive(Default)]
 pub struct Validator {
@@ -24,8 +29,14 @@ pub struct Validator {
     space_states: BTreeMap<String, SpaceState>,
     logic_states: BTreeMap<String, LogicSpaceState>,
     space_kinds: BTreeMap<String, SpaceKind>,
+    space_address_bits: BTreeMap<String, u32>,
+    space_word_bits: BTreeMap<String, u32>,
+    seen_buses: BTreeSet<String>,
     logic_sizes: BTreeMap<String, u32>,
     space_enables: BTreeSet<String>,
+    pending_extends: Vec<instructions::PendingExtend>,
+    instruction_names: BTreeMap<String, BTreeSet<String>>,
+    pending_aliases: Vec<instructions::PendingAlias>,
     diagnostics: Vec<IsaDiagnostic>,
 }
 
@@ -48,7 +59,16 @@ impl Validator {
                 }
             }
         }
+        for doc in docs {
+            for item in &doc.items {
+                if let IsaItem::Bus(bus) = item {
+                    self.validate_bus(bus);
+                }
+            }
+        }
         self.ensure_enable_coverage();
+        self.ensure_extend_targets();
+        self.ensure_alias_bases();
         if self.diagnostics.is_empty() {
             Ok(())
         } else {
@@ -80,22 +100,46 @@ impl Validator {
         message: impl Into<String>,
         span: Option<SourceSpan>,
     ) {
-        self.diagnostics.push(IsaDiagnostic::new(
-            DiagnosticPhase::Validation,
-            DiagnosticLevel::Error,
-            code,
-            message,
-            span,
-        ));
+        self.push_suggested_diagnostic(code, message, span, None);
+    }
+
+    /// Like [`Self::push_validation_diagnostic`] with a "did you mean" hint for a misspelled
+    /// name, typically from [`closest_name`].
+    fn push_suggested_diagnostic(
+        &mut self,
+        code: &'static str,
+        message: impl Into<String>,
+        span: Option<SourceSpan>,
+        suggestion: Option<String>,
+    ) {
+        self.diagnostics.push(
+            IsaDiagnostic::new(
+                DiagnosticPhase::Validation,
+                DiagnosticLevel::Error,
+                code,
+                message,
+                span,
+            )
+            .with_suggestion(suggestion),
+        );
+    }
+
+    fn suggest_space(&self, name: &str) -> Option<String> {
+        closest_name(name, self.space_kinds.keys().map(String::as_str)).map(str::to_string)
     }
 
     fn ensure_enable_coverage(&mut self) {
-        if self.logic_sizes.len() <= 1 {
-            return;
-        }
+        // Spaces reached through `extend=` are never selected directly.
+        let targets: BTreeSet<&str> = self
+            .pending_extends
+            .iter()
+            .map(|pending| pending.target())
+            .collect();
         let mut by_size: BTreeMap<u32, Vec<String>> = BTreeMap::new();
         for (space, bits) in &self.logic_sizes {
-            by_size.entry(*bits).or_default().push(space.clone());
+            if !targets.contains(space.as_str()) {
+                by_size.entry(*bits).or_default().push(space.clone());
+            }
         }
         if by_size.len() <= 1 {
             return;
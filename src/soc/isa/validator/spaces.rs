@@ -15,6 +15,12 @@ impl Validator {
         }
         self.space_kinds
             .insert(space.name.clone(), space.kind.clone());
+        if let Some(bits) = address_bits(space) {
+            self.space_address_bits.insert(space.name.clone(), bits);
+        }
+        if let Some(bits) = logic_word_size(space) {
+            self.space_word_bits.insert(space.name.clone(), bits);
+        }
         if matches!(space.kind, SpaceKind::Logic) {
             if let Some(word) = logic_word_size(space) {
                 self.logic_states
@@ -48,6 +54,13 @@ impl Validator {
     }
 }
 
+fn address_bits(space: &SpaceDecl) -> Option<u32> {
+    space.attributes.iter().find_map(|attr| match attr {
+        SpaceAttribute::AddressBits(bits) => Some(*bits),
+        _ => None,
+    })
+}
+
 fn logic_word_size(space: &SpaceDecl) -> Option<u32> {
     space.attributes.iter().find_map(|attr| match attr {
         SpaceAttribute::WordSize(bits) => Some(*bits),
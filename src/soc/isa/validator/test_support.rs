@@ -82,6 +82,9 @@ pub(super) fn logic_instruction(space: &str, form: Option<&str>, name: &str) ->
             semantics: None,
             display: None,
             operator: None,
+            extend: None,
+            alias: None,
+            valid: None,
             span: manual_span(),
         }),
     })
@@ -93,5 +96,6 @@ pub(super) fn simple_subfield(name: &str) -> SubFieldDecl {
         bit_spec: "@(0..5)".to_string(),
         operations: Vec::new(),
         description: None,
+        reset: None,
     }
 }
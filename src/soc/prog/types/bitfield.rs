@@ -173,6 +173,35 @@ impl BitFieldSpec {
         Ok(result)
     }
 
+    /// Renders the spec in the ISA `@(...)` syntax accepted by [`BitFieldSpec::from_spec_str`],
+    /// numbering bits MSB-first within a `container_bits`-wide container.
+    pub fn to_spec_string(&self, container_bits: u16) -> String {
+        let mut tokens = Vec::with_capacity(self.segments.len() + 1);
+        if let Some(pad) = self.pad {
+            tokens.push(match pad.kind {
+                PadKind::Zero => "?0".to_string(),
+                PadKind::Sign => "?1".to_string(),
+            });
+        }
+        for segment in &self.segments {
+            tokens.push(match segment {
+                BitFieldSegment::Slice(slice) => {
+                    let end = container_bits - 1 - slice.offset;
+                    let start = end + 1 - slice.width as u16;
+                    if start == end {
+                        format!("{start}")
+                    } else {
+                        format!("{start}..{end}")
+                    }
+                }
+                BitFieldSegment::Literal { value, width } => {
+                    format!("0b{value:0width$b}", width = *width as usize)
+                }
+            });
+        }
+        format!("@({})", tokens.join("|"))
+    }
+
     pub fn total_width(&self) -> u16 {
         self.data_width() + self.pad.map(|pad| pad.width).unwrap_or(0)
     }
@@ -695,6 +724,19 @@ mod tests {
         assert_eq!(spec.total_width(), 8, "padding contributes to total width");
     }
 
+    #[test]
+    fn spec_string_round_trips() {
+        for text in [
+            "@(?1|8..15|0b0)",
+            "@(21)",
+            "@(0..5|16..20)",
+            "@(?0|0b101|30..31)",
+        ] {
+            let spec = BitFieldSpec::from_spec_str(dummy_container(0), 32, text).expect("parse");
+            assert_eq!(spec.to_spec_string(32), text);
+        }
+    }
+
     #[test]
     fn parses_spec_with_literals_and_pad() {
         let container = dummy_container(3);
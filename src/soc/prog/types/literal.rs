@@ -1,6 +1,7 @@
 //! Numeric literal parser shared between ISA parser, symbol tooling, and other components.
 
 use std::fmt;
+use std::num::IntErrorKind;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Literal {
@@ -126,6 +127,35 @@ pub fn parse_u64_literal(input: &str) -> Result<u64, LiteralError> {
     Literal::parse(trimmed).map(|literal| literal.value())
 }
 
+/// Parses an unsigned literal of up to 128 bits with the ISA grammar, for values such as
+/// register resets that are not limited to a 64-bit word.
+pub fn parse_u128_literal(input: &str) -> Result<u128, LiteralError> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(LiteralError::Empty);
+    }
+    if trimmed.starts_with('-') {
+        return Err(LiteralError::NegativeNotSupported);
+    }
+    let (digits, radix) = if let Some(rest) = strip_prefix_ignore_case(trimmed, "0b") {
+        (rest, 2)
+    } else if let Some(rest) = strip_prefix_ignore_case(trimmed, "0o") {
+        (rest, 8)
+    } else if let Some(rest) = strip_prefix_ignore_case(trimmed, "0x") {
+        (rest, 16)
+    } else {
+        (trimmed, 10)
+    };
+    let digits = digits.replace('_', "");
+    if digits.is_empty() {
+        return Err(LiteralError::InvalidFormat(trimmed.into()));
+    }
+    u128::from_str_radix(&digits, radix).map_err(|err| match err.kind() {
+        IntErrorKind::PosOverflow => LiteralError::OutOfRange(trimmed.into()),
+        _ => LiteralError::InvalidFormat(trimmed.into()),
+    })
+}
+
 /// Parses an unsigned 32-bit literal with the ISA grammar.
 pub fn parse_u32_literal(input: &str) -> Result<u32, LiteralError> {
     let value = parse_u64_literal(input)?;
@@ -164,6 +194,17 @@ mod tests {
         assert_eq!(literal.kind(), LiteralKind::Binary);
     }
 
+    #[test]
+    fn parses_u128_literals_past_64_bits() {
+        assert_eq!(
+            parse_u128_literal("0x1_0000_0000_0000_0000"),
+            Ok(1u128 << 64)
+        );
+        assert_eq!(parse_u128_literal("0b101"), Ok(5));
+        assert!(parse_u128_literal("-1").is_err());
+        assert!(parse_u128_literal("0xZZ").is_err());
+    }
+
     #[test]
     fn rejects_wide_binary() {
         let wide = "0b".to_string() + &"1".repeat(65);
@@ -24,6 +24,7 @@ pub use dynamic::{DynamicAggregate, DynamicField};
 pub use expr::{EvalContext, ExprProgram, OpCode};
 pub use literal::{
     Literal, LiteralError, LiteralKind, parse_index_suffix, parse_u32_literal, parse_u64_literal,
+    parse_u128_literal,
 };
 pub use pointer::{PointerKind, PointerQualifiers, PointerType};
 pub use record::{LayoutSize, MemberRecord, MemberSpan, OpaqueType, TypeRecord};
@@ -48,12 +48,15 @@ impl DeviceBus {
         address >> self.bucket_bits
     }
 
-    fn insert_segment(&self, entry: &mut Vec<BusRange>, segment: BusRange) -> BusResult<()> {
+    fn find_conflict(
+        entry: &[BusRange],
+        segment: &BusRange,
+        devices: &[Arc<dyn Device>],
+    ) -> BusResult<()> {
         if let Some(conflict) = entry
             .iter()
-            .find(|existing| existing.priority == segment.priority && existing.overlaps(&segment))
+            .find(|existing| existing.priority == segment.priority && existing.overlaps(segment))
         {
-            let devices = self.devices.read().unwrap();
             let details = devices
                 .get(conflict.device_id)
                 .map(|d| format!("conflicts with device '{}'", d.name()))
@@ -63,7 +66,10 @@ impl DeviceBus {
                 details,
             });
         }
+        Ok(())
+    }
 
+    fn insert_segment(entry: &mut Vec<BusRange>, segment: BusRange) {
         let pos = entry.iter().position(|existing| {
             existing.priority < segment.priority
                 || (existing.priority == segment.priority && existing.bus_start > segment.bus_start)
@@ -72,44 +78,33 @@ impl DeviceBus {
             Some(idx) => entry.insert(idx, segment),
             None => entry.push(segment),
         }
-        Ok(())
     }
 
-    fn add_range(
-        &self,
-        bus_start: u64,
-        bus_end: u64,
-        device_id: usize,
-        device_offset: u64,
-        priority: u8,
-        kind: RangeKind,
-    ) -> BusResult<u64> {
-        if bus_end <= bus_start {
+    /// Assigns `segment` a fresh id and inserts it, checking every touched bucket first so
+    /// a conflict never leaves a partially mapped range behind. `devices` is passed in
+    /// because callers may already hold the device lock.
+    fn add_range(&self, devices: &[Arc<dyn Device>], mut segment: BusRange) -> BusResult<u64> {
+        if segment.bus_end <= segment.bus_start {
             return Err(BusError::Overlap {
-                address: bus_start,
+                address: segment.bus_start,
                 details: "range is empty".into(),
             });
         }
 
         let id = self.next_range_id.fetch_add(1, Ordering::Relaxed);
+        segment.id = id;
         let mut touched = Vec::new();
-        let start_idx = self.bucket_index(bus_start);
-        let end_idx = self.bucket_index(bus_end - 1);
+        let start_idx = self.bucket_index(segment.bus_start);
+        let end_idx = self.bucket_index(segment.bus_end - 1);
         let mut buckets = self.buckets.write().unwrap();
 
-        let segment = BusRange {
-            id,
-            bus_start,
-            bus_end,
-            device_offset,
-            device_id,
-            priority,
-            kind,
-        };
-
         for idx in start_idx..=end_idx {
-            let entry = buckets.entry(idx).or_default();
-            self.insert_segment(entry, segment.clone())?;
+            if let Some(entry) = buckets.get(&idx) {
+                Self::find_conflict(entry, &segment, devices)?;
+            }
+        }
+        for idx in start_idx..=end_idx {
+            Self::insert_segment(buckets.entry(idx).or_default(), segment.clone());
             touched.push(idx);
         }
 
@@ -136,6 +131,19 @@ impl DeviceBus {
     }
 
     pub fn register_device(&self, device: Arc<dyn Device>, base_address: u64) -> BusResult<()> {
+        self.register_device_with_priority(device, base_address, DEVICE_PRIORITY)
+    }
+
+    /// Registers `device` at `base_address` with an explicit mapping priority; where ranges
+    /// overlap, the higher priority wins. Priorities from [`REDIRECT_PRIORITY`] upwards are
+    /// reserved for redirects.
+    pub fn register_device_with_priority(
+        &self,
+        device: Arc<dyn Device>,
+        base_address: u64,
+        priority: u8,
+    ) -> BusResult<()> {
+        ensure_window_priority(priority)?;
         let span = device.span();
         if span.start != 0 || span.end <= span.start {
             return Err(BusError::InvalidDeviceSpan {
@@ -162,16 +170,72 @@ impl DeviceBus {
         let mut devices = self.devices.write().unwrap();
         let mut names = self.name_index.write().unwrap();
         let device_id = devices.len();
+        self.add_range(
+            &devices,
+            BusRange {
+                id: 0,
+                bus_start: base_address,
+                bus_end: end,
+                device_offset: 0,
+                device_id,
+                priority,
+                kind: RangeKind::Device,
+            },
+        )?;
         devices.push(device);
         names.insert(name, device_id);
+        Ok(())
+    }
 
+    /// Maps `size` bytes of the registered device `name`, starting at `device_offset`, as a
+    /// further window at `bus_start`. Unlike [`DeviceBus::redirect`] the target is named by
+    /// device rather than bus address, so windows can alias storage that other mappings
+    /// shadow.
+    pub fn map_device_window(
+        &self,
+        name: &str,
+        bus_start: u64,
+        size: u64,
+        device_offset: u64,
+        priority: u8,
+    ) -> BusResult<()> {
+        ensure_window_priority(priority)?;
+        let devices = self.devices.read().unwrap();
+        let device_id = self
+            .name_index
+            .read()
+            .unwrap()
+            .get(name)
+            .copied()
+            .ok_or_else(|| BusError::UnknownDevice {
+                device: name.to_string(),
+            })?;
+        let span = devices[device_id].span();
+        let fits = device_offset
+            .checked_add(size)
+            .map(|end| size > 0 && end <= span.end - span.start)
+            .unwrap_or(false);
+        if !fits {
+            return Err(BusError::OutOfRange {
+                address: bus_start,
+                end: bus_start.saturating_add(span.end - span.start),
+            });
+        }
+        let bus_end = bus_start.checked_add(size).ok_or(BusError::Overlap {
+            address: bus_start,
+            details: "range exceeds address space".into(),
+        })?;
         self.add_range(
-            base_address,
-            end,
-            device_id,
-            0,
-            DEVICE_PRIORITY,
-            RangeKind::Device,
+            &devices,
+            BusRange {
+                id: 0,
+                bus_start,
+                bus_end,
+                device_offset,
+                device_id,
+                priority,
+                kind: RangeKind::Device,
+            },
         )?;
         Ok(())
     }
@@ -214,13 +278,18 @@ impl DeviceBus {
                 reason: "source address overflow",
             })?;
         let device_offset = resolved.device_offset + (target_start - resolved.bus_start);
+        let devices = self.devices.read().unwrap();
         let range_id = self.add_range(
-            source_start,
-            source_end,
-            resolved.device_id,
-            device_offset,
-            REDIRECT_PRIORITY,
-            RangeKind::Redirect,
+            &devices,
+            BusRange {
+                id: 0,
+                bus_start: source_start,
+                bus_end: source_end,
+                device_offset,
+                device_id: resolved.device_id,
+                priority: REDIRECT_PRIORITY,
+                kind: RangeKind::Redirect,
+            },
         )?;
         self.redirect_index
             .write()
@@ -277,6 +346,13 @@ impl DeviceBus {
     }
 }
 
+fn ensure_window_priority(priority: u8) -> BusResult<()> {
+    if priority >= REDIRECT_PRIORITY {
+        return Err(BusError::ReservedPriority { priority });
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -334,6 +410,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn overlapping_device_is_rejected_without_being_registered() {
+        let bus = DeviceBus::new(8);
+        bus.register_device(make_memory("flash", 0x1000), 0)
+            .unwrap();
+        let err = bus
+            .register_device(make_memory("ram", 0x100), 0x800)
+            .expect_err("same-priority overlap");
+        assert!(
+            matches!(err, BusError::Overlap { .. }),
+            "unexpected error: {err}"
+        );
+
+        bus.register_device_with_priority(make_memory("ram", 0x100), 0x800, 1)
+            .expect("higher priority overlay registers under the same name");
+        assert_eq!(bus.resolve(0x810).unwrap().device.name(), "ram");
+        assert_eq!(bus.resolve(0x900).unwrap().device.name(), "flash");
+
+        let err = bus
+            .map_device_window("rom", 0x2000, 0x10, 0, 0)
+            .expect_err("no device named rom");
+        assert!(
+            matches!(&err, BusError::UnknownDevice { device } if device == "rom"),
+            "unexpected error: {err}"
+        );
+    }
+
     #[test]
     fn bytes_to_end_tracks_remaining_range_length() {
         let bus = DeviceBus::new(12);
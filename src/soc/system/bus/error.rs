@@ -28,7 +28,13 @@ pub enum BusError {
     InvalidDeviceSpan {
         device: String,
     },
+    UnknownDevice {
+        device: String,
+    },
     HandleNotPositioned,
+    ReservedPriority {
+        priority: u8,
+    },
 }
 
 impl fmt::Display for BusError {
@@ -59,9 +65,15 @@ impl fmt::Display for BusError {
             BusError::InvalidDeviceSpan { device } => {
                 write!(f, "device '{device}' reported an invalid span")
             }
+            BusError::UnknownDevice { device } => {
+                write!(f, "no device named '{device}' is registered on the bus")
+            }
             BusError::HandleNotPositioned => {
                 write!(f, "address handle has not been positioned with jump()")
             }
+            BusError::ReservedPriority { priority } => {
+                write!(f, "mapping priority {priority} is reserved for redirects")
+            }
         }
     }
 }
@@ -0,0 +1,91 @@
+//! System-level description assembled from a `.sysdef`: the attached cores, each under its
+//! own context tag, plus the shared system spaces and buses they sit on.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::soc::core::specification::CoreSpec;
+use crate::soc::isa::error::IsaError;
+use crate::soc::isa::machine::MachineDescription;
+use crate::soc::system::bus::DeviceBus;
+
+/// One `:attach` instance. Cores attached from the same definition share a machine
+/// description but each carries a core spec named after its context tag.
+#[derive(Debug, Clone)]
+pub struct SystemCore {
+    pub context: String,
+    pub source: PathBuf,
+    pub machine: Arc<MachineDescription>,
+    pub spec: Arc<CoreSpec>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SystemDescription {
+    pub path: PathBuf,
+    pub cores: Vec<SystemCore>,
+    /// Spaces, buses and parameters declared directly in the `.sysdef`.
+    pub shared: MachineDescription,
+}
+
+impl SystemDescription {
+    /// Looks up an attached core by its context tag (e.g. `CORE0`).
+    pub fn core(&self, context: &str) -> Option<&SystemCore> {
+        self.cores.iter().find(|core| core.context == context)
+    }
+
+    pub fn contexts(&self) -> impl Iterator<Item = &str> {
+        self.cores.iter().map(|core| core.context.as_str())
+    }
+
+    /// Materializes a shared system bus declared in the `.sysdef`.
+    pub fn build_bus(&self, name: &str) -> Result<DeviceBus, IsaError> {
+        self.shared.build_bus(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::soc::device::Endianness;
+
+    fn core(context: &str, machine: &Arc<MachineDescription>) -> SystemCore {
+        let spec = CoreSpec::builder(context, Endianness::Big)
+            .register("reg::PC", 32)
+            .build()
+            .expect("core spec");
+        SystemCore {
+            context: context.into(),
+            source: PathBuf::from("core.coredef"),
+            machine: machine.clone(),
+            spec: Arc::new(spec),
+        }
+    }
+
+    #[test]
+    fn looks_up_cores_by_context() {
+        let machine = Arc::new(MachineDescription::new());
+        let system = SystemDescription {
+            path: PathBuf::from("chip.sysdef"),
+            cores: vec![core("CORE0", &machine), core("ETPUA", &machine)],
+            shared: MachineDescription::new(),
+        };
+        assert_eq!(
+            system.contexts().collect::<Vec<_>>(),
+            vec!["CORE0", "ETPUA"],
+            "contexts follow attach order"
+        );
+        assert_eq!(
+            system.core("ETPUA").map(|core| core.spec.name()),
+            Some("ETPUA"),
+            "core lookup uses the context tag"
+        );
+        assert!(
+            system.core("CORE1").is_none(),
+            "unknown contexts are absent"
+        );
+        assert!(
+            system.build_bus("sysbus").is_err(),
+            "buses come from the shared description only"
+        );
+    }
+}
@@ -1 +1,4 @@
 pub mod bus;
+pub mod description;
+
+pub use description::{SystemCore, SystemDescription};
@@ -1,2 +1,3 @@
 mod powerpc;
 mod tracer;
+mod vle;
@@ -1,9 +1,17 @@
 use std::path::PathBuf;
+use std::sync::Arc;
 
-use nanemu::loader::isa::IsaLoader;
-use nanemu::soc::core::ExecutionHarness;
-use nanemu::soc::isa::machine::{MachineDescription, SoftwareHost};
+use nanemu::loader::isa::{IsaLoader, MachineCache};
+use nanemu::soc::core::{ExecutionHarness, HarnessError};
+use nanemu::soc::device::{BasicMemory, Device, Endianness};
+use nanemu::soc::isa::machine::{
+    ByteImage, DecodeError, EdgeKind, EntryPoint, FlowKind, MachineDescription, OperandValue,
+    Relocation, RelocationKind, SoftwareHost, Syntax,
+};
 use nanemu::soc::isa::semantics::trace::PipelinePrinter;
+use nanemu::soc::isa::semantics::{MemoryAccess, MemoryFault, RegisterIndex, RegisterLocation};
+use nanemu::soc::prog::symbols::SymbolKind;
+use nanemu::soc::system::bus::DeviceBus;
 
 #[test]
 fn disassembles_powerpc_vle_stream() {
@@ -21,7 +29,7 @@ fn disassembles_powerpc_vle_stream() {
     stream.extend_from_slice(&addi);
     stream.extend_from_slice(&se_b);
 
-    let listing = machine.disassemble_from(&stream, 0x1000);
+    let listing = machine.disassemble_from(&stream, 0x1000, Syntax::Canonical);
     assert_eq!(listing.len(), 2, "expected 32-bit + 16-bit instructions");
 
     if std::env::var_os("SHOW_DISASM").is_some() {
@@ -48,7 +56,7 @@ fn disassembles_powerpc_vle_stream() {
     assert_eq!(listing[0].mnemonic, "addi");
     assert_eq!(listing[0].address, 0x1000);
     assert_eq!(
-        listing[0].operands,
+        machine.format_operands(&listing[0].operands),
         vec!["r0", "r0", "0x0000"],
         "disp formatting should rename registers"
     );
@@ -56,7 +64,65 @@ fn disassembles_powerpc_vle_stream() {
 
     assert_eq!(listing[1].mnemonic, "se_b");
     assert_eq!(listing[1].address, 0x1004);
-    assert_eq!(listing[1].display.as_deref(), Some("0x000"));
+    assert_eq!(listing[1].display.as_deref(), Some("0x1004"));
+}
+
+#[test]
+fn decodes_and_executes_32_bit_vle_words_through_extend() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("defs/powerpc");
+    let coredef = root.join("e200.coredef");
+
+    // addi r0, r0, 0; e_bl . + 0x10; se_b .
+    let mut stream = Vec::new();
+    stream.extend_from_slice(&0x3800_0000u32.to_be_bytes());
+    stream.extend_from_slice(&0x7800_0011u32.to_be_bytes());
+    stream.extend_from_slice(&0xE800u16.to_be_bytes());
+
+    let mut harness = build_powerpc_harness(&coredef);
+    let machine = harness.machine();
+    let listing = machine.disassemble_from(&stream, 0x1000, Syntax::Canonical);
+    let summary: Vec<(u64, usize, &str)> = listing
+        .iter()
+        .map(|entry| (entry.address, entry.size, entry.mnemonic.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (0x1000, 4, "addi"),
+            (0x1004, 4, "e_bl"),
+            (0x1008, 2, "se_b")
+        ]
+    );
+    assert_eq!(listing[1].display.as_deref(), Some("0x1014"));
+    let decoded = machine
+        .decode_bytes(&stream[4..8], 0x1004)
+        .next()
+        .unwrap()
+        .unwrap();
+    assert_eq!(decoded.space(), "vle32");
+    let rendered = machine.disassemble_decoded(&decoded, Syntax::Canonical);
+    assert_eq!(
+        (rendered.size, rendered.display),
+        (4, listing[1].display.clone()),
+        "a decoded instruction renders like its listing entry"
+    );
+    assert_eq!(machine.assemble("e_bl 0x10").unwrap(), [0x78, 0, 0, 0x11]);
+
+    harness
+        .write_register_value("reg", "PC", None, None, 0x1004)
+        .unwrap();
+    harness
+        .execute_block(0x1004, &stream[4..8])
+        .expect("execute e_bl");
+    let read = |harness: &mut ExecutionHarness<SoftwareHost>, name| {
+        harness
+            .read_register_value("reg", name, None, None)
+            .unwrap()
+            .as_int()
+            .unwrap()
+    };
+    assert_eq!(read(&mut harness, "LR"), 0x1008);
+    assert_eq!(read(&mut harness, "PC"), 0x1014);
 }
 
 #[test]
@@ -80,6 +146,25 @@ fn executes_powerpc_add() {
     assert_eq!(r5 as u32, 0x8000_0000);
 }
 
+#[test]
+fn executes_li_with_a_zero_base() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("defs/powerpc");
+    let mut harness = build_powerpc_harness(&root.join("ppc_eref2.isa"));
+    let state = harness.state_mut();
+    state.write_register("reg::r0", 0x100).unwrap();
+    state.write_register("reg::r3", 0x100).unwrap();
+
+    let rom = assemble_block(harness.machine(), &["li r5, 0x10", "addi r6, r3, 0x10"]);
+    harness.execute_block(0x1000, &rom).expect("execute li");
+    let state = harness.state_mut();
+    assert_eq!(
+        state.read_register("reg::r5").unwrap(),
+        0x10,
+        "RA=0 reads as zero, not r0"
+    );
+    assert_eq!(state.read_register("reg::r6").unwrap(), 0x110);
+}
+
 #[test]
 fn executes_powerpc_add_record_sets_cr0() {
     let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("defs/powerpc");
@@ -213,6 +298,32 @@ fn executes_powerpc_add_with_overflow_and_record() {
     assert_eq!(cr_so, 0, "addo. should leave summary overflow clear");
 }
 
+#[test]
+fn executes_powerpc_add_from_cached_machine() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("defs/powerpc");
+    let coredef = root.join("e200.coredef");
+    let cache_dir = tempfile::tempdir().expect("cache dir");
+    let cache = MachineCache::new(cache_dir.path());
+    IsaLoader::new()
+        .with_cache(cache.clone())
+        .load_machine(&coredef)
+        .expect("populate cache");
+    let machine = cache.load(&coredef).expect("cache hit");
+
+    let mut harness = ExecutionHarness::from_machine("ppc-e200", machine, None, SoftwareHost)
+        .expect("construct harness");
+    seed_base_gprs(&mut harness);
+    let rom = assemble_block(harness.machine(), &["add r5, r3, r4"]);
+    harness
+        .execute_block(0x8000_1000, &rom)
+        .expect("execute add");
+    let r5 = harness
+        .state_mut()
+        .read_register("reg::r5")
+        .expect("read r5");
+    assert_eq!(r5 as u32, 0x8000_0000);
+}
+
 fn enable_trace_if_requested(harness: &mut ExecutionHarness<SoftwareHost>) {
     if std::env::var_os("TRACE_PIPELINE").is_some() {
         harness.enable_tracer(Box::new(PipelinePrinter::stdout()));
@@ -242,13 +353,805 @@ fn seed_overflow_gprs(harness: &mut ExecutionHarness<SoftwareHost>) {
     state.write_register("reg::r4", 1).expect("seed r4");
 }
 
+/// `(start, end, successors)` of a discovered block.
+type BlockSummary = (u64, u64, Vec<(u64, EdgeKind)>);
+
 fn assemble_block(machine: &MachineDescription, lines: &[&str]) -> Vec<u8> {
-    let mut rom = Vec::new();
-    for line in lines {
-        let bytes = machine.assemble(line).unwrap_or_else(|err| {
-            panic!("failed to assemble '{line}': {err}");
-        });
-        rom.extend(bytes);
+    machine
+        .assemble_program(&lines.join("\n"), 0)
+        .unwrap_or_else(|err| panic!("failed to assemble {lines:?}: {err}"))
+        .bytes
+}
+
+#[test]
+fn summarizes_powerpc_semantic_effects() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("defs/powerpc");
+    let mut loader = IsaLoader::new();
+    let machine = loader
+        .load_machine(root.join("e200.coredef"))
+        .expect("load powerpc + vle includes");
+    let effects = |mnemonic: &str| {
+        let instruction = machine
+            .instructions
+            .iter()
+            .find(|instruction| instruction.name == mnemonic)
+            .unwrap_or_else(|| panic!("missing {mnemonic}"));
+        let render = |set: &std::collections::BTreeSet<_>| {
+            set.iter()
+                .map(|location: &RegisterLocation| location.to_string())
+                .collect::<Vec<_>>()
+        };
+        (
+            render(&instruction.effects.reads),
+            render(&instruction.effects.writes),
+            instruction.effects.control_flow,
+        )
+    };
+
+    let (reads, writes, control_flow) = effects("add.");
+    assert_eq!(
+        reads,
+        vec!["reg::GPR(#RA)", "reg::GPR(#RB)", "reg::SPR(1)::SO"]
+    );
+    assert_eq!(
+        writes,
+        vec![
+            "reg::CR(0)::NEG",
+            "reg::CR(0)::POS",
+            "reg::CR(0)::SO",
+            "reg::CR(0)::ZERO",
+            "reg::GPR(#RT)",
+        ]
+    );
+    assert!(!control_flow);
+
+    let (reads, writes, control_flow) = effects("se_bl");
+    assert_eq!(reads, vec!["reg::PC"]);
+    assert_eq!(writes, vec!["reg::PC", "reg::SPR(8)"]);
+    assert!(control_flow);
+
+    let add = 0x7C64_2A14u32.to_be_bytes();
+    let decoded = machine.decode_instructions(&add, 0x1000);
+    let bound = decoded[0].bound_effects();
+    assert!(bound.writes.contains(&RegisterLocation {
+        space: "reg".into(),
+        register: "GPR".into(),
+        index: Some(RegisterIndex::Fixed(3)),
+        subfield: None,
+    }));
+    assert!(
+        bound
+            .reads
+            .iter()
+            .any(|location| location.to_string() == "reg::GPR(5)")
+    );
+}
+
+#[test]
+fn disassembles_powerpc_aliases_in_each_syntax() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("defs/powerpc");
+    let mut loader = IsaLoader::new();
+    let machine = loader
+        .load_machine(root.join("e200.coredef"))
+        .expect("load powerpc + vle includes");
+
+    let code = assemble_block(
+        &machine,
+        &[
+            "mr r3, r4",
+            "li r5, 0x10",
+            "or r3, r4, r5",
+            "blr",
+            "bdnz . + 8",
+            "addi r3, r3, -0x8000",
+        ],
+    );
+    assert_eq!(&code[..4], &[0x7C, 0x83, 0x23, 0x78], "mr is or with RB=RS");
+    assert_eq!(code[..4], machine.assemble("or r3, r4, r4").unwrap()[..]);
+    assert_eq!(
+        code[4..8],
+        machine.assemble("addi r5, r0, 0x10").unwrap()[..]
+    );
+
+    let render = |syntax: Syntax| {
+        machine
+            .disassemble_from(&code, 0x1000, syntax)
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{} {}",
+                    entry.mnemonic,
+                    entry.display.as_deref().unwrap_or_default()
+                )
+                .trim_end()
+                .to_string()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        render(Syntax::Canonical),
+        vec![
+            "or r3, r4, r4",
+            "addi r5, r0, 0x0010",
+            "or r3, r4, r5",
+            "bclr 0x14, 0x00, 0x0",
+            "bc 0x10, 0x00, 0x1018",
+            "addi r3, r3, 0x8000",
+        ]
+    );
+    assert_eq!(
+        render(Syntax::Simplified),
+        vec![
+            "mr r3, r4",
+            "li r5, 0x0010",
+            "or r3, r4, r5",
+            "blr",
+            "bdnz 0x1018",
+            "addi r3, r3, 0x8000",
+        ]
+    );
+    let raw = render(Syntax::Raw);
+    assert_eq!(
+        raw[0],
+        "or OPCD=0x1F, RT=0x4, RA=0x3, RB=0x4, XO=0x1BC, Rc=0x0"
+    );
+    assert_eq!(
+        raw[5], "addi OPCD=0xE, RT=0x3, RA=0x3, D=0x8000",
+        "signed fields dump their encoded bits"
+    );
+    assert_eq!(
+        machine.disassemble(&code)[1].mnemonic,
+        "addi",
+        "rendering in one syntax leaves the machine's default alone"
+    );
+}
+
+#[test]
+fn rejects_encodings_that_fail_their_valid_predicate() {
+    // Book E only: 0x84.. would otherwise decode as a 16-bit VLE word.
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("defs/powerpc");
+    let mut harness = build_powerpc_harness(&root.join("ppc_eref2.isa"));
+    let machine = harness.machine();
+
+    assert_eq!(
+        machine.assemble("lwzu r3, r4, 8").unwrap(),
+        0x8464_0008u32.to_be_bytes()
+    );
+    for line in ["lwzu r3, r3, 8", "lwzu r3, r0, 8"] {
+        let err = machine.assemble(line).expect_err("illegal lwzu form");
+        assert!(
+            err.to_string().contains("illegal encoding"),
+            "{line}: {err}"
+        );
+    }
+
+    // lwzu r3, 8(r3) and bclr with a reserved bit set
+    let mut code = Vec::new();
+    code.extend_from_slice(&0x8463_0008u32.to_be_bytes());
+    code.extend_from_slice(&0x4E80_2020u32.to_be_bytes());
+    code.extend_from_slice(&0x4E80_0020u32.to_be_bytes());
+    let decoded: Vec<_> = machine.decode_bytes(&code, 0x1000).collect();
+    assert!(matches!(
+        &decoded[0],
+        Err(DecodeError::Illegal { address: 0x1000, instruction, .. }) if instruction == "lwzu"
+    ));
+    assert!(matches!(
+        &decoded[1],
+        Err(DecodeError::Illegal { instruction, .. }) if instruction == "bclr"
+    ));
+    assert_eq!(
+        decoded[2]
+            .as_ref()
+            .map(|entry| entry.instruction().name.as_str())
+            .ok(),
+        Some("bclr")
+    );
+    let listing = machine.disassemble_from(&code, 0x1000, Syntax::Canonical);
+    let mnemonics: Vec<&str> = listing
+        .iter()
+        .map(|entry| entry.mnemonic.as_str())
+        .collect();
+    assert_eq!(mnemonics, vec!["illegal", "illegal", "bclr"]);
+
+    let err = harness
+        .execute_block(0x1000, &code)
+        .expect_err("illegal encodings raise a program exception");
+    assert!(matches!(
+        err,
+        HarnessError::Program(DecodeError::Illegal {
+            address: 0x1000,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn assembles_programs_with_labels_directives_and_relocations() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("defs/powerpc");
+    let harness = build_powerpc_harness(&root.join("ppc_eref2.isa"));
+    let machine = harness.machine();
+    let source = r#"
+            .extern ext
+    start:  li r3, 0
+    loop:   addi r3, r3, 1
+            bdnz loop               # backward: -4
+            lis r4, ha(msg)
+            addi r4, r4, msg@l
+            lis r5, ext@ha
+            b done
+            .align 8
+    table:  .word start, table - start
+            .half -2, 0xBEEF
+            .byte 1, 2
+    msg:    .ascii "ok\n"
+            .space 3, 0xFF
+    done:   blr
+            .word ext + 4
+    "#;
+    let program = machine
+        .assemble_program(source, 0x1_8000)
+        .expect("assemble program");
+
+    let words = [
+        0x3860_0000u32,
+        0x3863_0001,
+        0x4200_FFFC,
+        0x3C80_0002,
+        0x3884_802E,
+        0x3CA0_0000,
+        0x4800_001C,
+        0x0000_0000,
+    ];
+    let mut expected: Vec<u8> = words.iter().flat_map(|word| word.to_be_bytes()).collect();
+    expected.extend([0x00, 0x01, 0x80, 0x00, 0x00, 0x00, 0x00, 0x20]);
+    expected.extend([0xFF, 0xFE, 0xBE, 0xEF, 0x01, 0x02]);
+    expected.extend(b"ok\n");
+    expected.extend([0xFF; 3]);
+    expected.extend(0x4E80_0020u32.to_be_bytes());
+    expected.extend([0; 4]);
+    assert_eq!(program.bytes, expected);
+    assert_eq!(program.end(), 0x1_803C);
+
+    assert_eq!(program.symbol("loop"), Some(0x1_8004));
+    assert_eq!(program.symbol("msg"), Some(0x1_802E));
+    assert_eq!(program.symbols["done"].kind, SymbolKind::Function);
+    assert_eq!(program.symbols["table"].kind, SymbolKind::Object);
+    assert_eq!(program.symbols["table"].size, 0xE);
+    assert_eq!(
+        program.relocations,
+        vec![
+            Relocation {
+                address: 0x1_8014,
+                size: 4,
+                operand: Some("D".into()),
+                symbol: "ext".into(),
+                addend: 0,
+                kind: RelocationKind::HighAdjusted,
+            },
+            Relocation {
+                address: 0x1_8038,
+                size: 4,
+                operand: None,
+                symbol: "ext".into(),
+                addend: 4,
+                kind: RelocationKind::Absolute,
+            },
+        ]
+    );
+
+    let listing =
+        machine.disassemble_from(&program.bytes[..0x1C], program.origin, Syntax::Canonical);
+    assert_eq!(listing[2].display.as_deref(), Some("0x10, 0x00, 0x18004"));
+    assert_eq!(listing[6].display.as_deref(), Some("0x18034"));
+
+    let padded = machine
+        .assemble_program(".org 0x10\nvalue: .byte 7", 0)
+        .expect("assemble .org");
+    assert_eq!(padded.bytes.len(), 0x11);
+    assert_eq!(padded.symbol("value"), Some(0x10));
+
+    for (source, message) in [
+        ("start: b nowhere", "line 1: undefined symbol 'nowhere'"),
+        (
+            "a: li r3, 0\na: li r4, 0",
+            "line 2: symbol 'a' is already defined",
+        ),
+        (".byte 256", "line 1: value 256 does not fit in 1 byte(s)"),
+        (
+            ".extern ext\n.word ext * 2",
+            "line 2: external symbol 'ext'",
+        ),
+    ] {
+        let err = machine.assemble_program(source, 0).expect_err(source);
+        assert!(err.to_string().contains(message), "{source}: {err}");
+    }
+}
+
+#[test]
+fn resolves_absolute_branch_targets() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("defs/powerpc");
+    let machine = IsaLoader::new()
+        .load_machine(root.join("ppc_eref2.isa"))
+        .expect("load book e");
+
+    let mut code = Vec::new();
+    code.extend(0x4800_0102u32.to_be_bytes());
+    code.extend(0x4800_0100u32.to_be_bytes());
+    let listing = machine.disassemble_from(&code, 0x1000, Syntax::Canonical);
+    assert_eq!(listing[0].mnemonic, "ba");
+    assert_eq!(
+        listing[0].operands[0].value,
+        OperandValue::Target { address: 0x100 },
+        "AA=1 ignores the instruction address"
+    );
+    assert_eq!(listing[0].display.as_deref(), Some("0x100"));
+    assert_eq!(
+        listing[1].operands[0].value,
+        OperandValue::Target { address: 0x1104 }
+    );
+
+    let program = machine
+        .assemble_program("ba 0x100\nb 0x1104", 0x1000)
+        .expect("assemble branches");
+    assert_eq!(program.bytes, code);
+}
+
+#[test]
+fn reassembles_what_the_disassembler_prints() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("defs/powerpc");
+    let mut loader = IsaLoader::new();
+    let book_e = loader
+        .load_machine(root.join("ppc_eref2.isa"))
+        .expect("load book e");
+
+    assert_eq!(
+        book_e.assemble("lwz r3, 8(r1)").unwrap(),
+        0x8061_0008u32.to_be_bytes()
+    );
+    assert_eq!(
+        book_e.assemble("stw r5, -4 ( r31 )").unwrap(),
+        0x90BF_FFFCu32.to_be_bytes()
+    );
+    assert_eq!(
+        book_e.assemble("lwz RT=3, RA=1, D=8").unwrap(),
+        0x8061_0008u32.to_be_bytes()
+    );
+    let err = book_e.assemble("lwz r3, 8[r1]").expect_err("bad syntax");
+    assert!(
+        err.to_string().contains("do not match '#RT, #D(#RA)'"),
+        "{err}"
+    );
+
+    // BO16 names a CR field: `cr1` is accepted, a GPR name is not.
+    let e200 = IsaLoader::new()
+        .load_machine(root.join("e200.coredef"))
+        .expect("load e200");
+    assert_eq!(e200.assemble("se_bc cr1, 2, 4").unwrap(), [0xE6, 0x02]);
+    let err = e200
+        .assemble("se_bc r1, 2, 4")
+        .expect_err("GPR in a CR slot");
+    assert!(err.to_string().contains("unknown register 'r1'"), "{err}");
+
+    // Book E instructions on their own, since e200 decodes some of their words as VLE.
+    let mut seed = 0x2545_F491_4F6C_DD1Du64;
+    let mut random = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+    let address = 0x4000_0000;
+    for (machine, space) in [(&book_e, "insn"), (&e200, "vle"), (&e200, "vle32")] {
+        for syntax in [Syntax::Canonical, Syntax::Simplified, Syntax::Raw] {
+            // `extend=` entries only select the wider space and have no encoding of their own.
+            let patterns: Vec<_> = machine
+                .patterns()
+                .iter()
+                .filter(|pattern| pattern.space == space)
+                .filter(|pattern| {
+                    machine.instructions[pattern.instruction_idx]
+                        .extend
+                        .is_none()
+                })
+                .cloned()
+                .collect();
+            let mut covered = std::collections::BTreeSet::new();
+            for pattern in &patterns {
+                let instr = &machine.instructions[pattern.instruction_idx];
+                let form = &machine.spaces[space].forms[pattern.form.as_ref().unwrap()];
+                for _ in 0..8 {
+                    let word = random();
+                    let operands: Vec<(&str, i64)> = pattern
+                        .operand_names
+                        .iter()
+                        .map(|name| {
+                            let field = form.subfield(name).unwrap();
+                            (name.as_str(), field.spec.read_bits(word).0 as i64)
+                        })
+                        .collect();
+                    let Ok(bytes) = machine.encode_instruction(&instr.name, &operands) else {
+                        continue; // rejected by a `valid=` predicate
+                    };
+                    let entry = &machine.disassemble_from(&bytes, address, syntax)[0];
+                    let text = format!(
+                        "{} {}",
+                        entry.mnemonic,
+                        entry
+                            .display
+                            .clone()
+                            .unwrap_or_else(|| machine.format_operands(&entry.operands).join(", "))
+                    );
+                    let program = machine
+                        .assemble_program(&text, address)
+                        .unwrap_or_else(|err| panic!("{syntax:?} '{text}': {err}"));
+                    assert_eq!(program.bytes, bytes, "{syntax:?} '{text}'");
+                    covered.insert(pattern.instruction_idx);
+                }
+            }
+            assert_eq!(covered.len(), patterns.len(), "{space} {syntax:?}");
+        }
+    }
+}
+
+#[test]
+fn executes_loads_and_stores_through_the_data_bus() {
+    // Book E only: 0x80.. would otherwise decode as a 16-bit VLE word.
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("defs/powerpc");
+    let mut harness = build_powerpc_harness(&root.join("ppc_eref2.isa"));
+    let rom = assemble_block(
+        harness.machine(),
+        &[
+            "lwz r3, 8(r1)",
+            "lha r4, 12(r1)",
+            "lhz r5, 12(r1)",
+            "lbz r6, 14(r1)",
+            "stw r3, -4(r7)",
+            "sth r5, 2(r1)",
+            "stb r6, 0(r1)",
+            "lwzx r8, r1, r9",
+            "lwzu r10, 2(r1)",
+        ],
+    );
+
+    let err = harness
+        .execute_block(0x1000, &rom[..4])
+        .expect_err("no data bus attached");
+    assert!(matches!(
+        err,
+        HarnessError::Memory {
+            address: 0x1000,
+            fault: MemoryFault { source: None, .. },
+        }
+    ));
+
+    let bus = Arc::new(DeviceBus::new(16));
+    let ram = Arc::new(BasicMemory::new("ram", 0x100, Endianness::Big));
+    bus.register_device(ram.clone(), 0x4000_0000).unwrap();
+    ram.write(8, &[0xDE, 0xAD, 0xBE, 0xEF, 0x80, 0x01, 0x7F, 0x00])
+        .unwrap();
+    harness.state_mut().attach_data_bus(bus);
+    let state = harness.state_mut();
+    state.write_register("reg::r1", 0x4000_0000).unwrap();
+    state.write_register("reg::r7", 0x4000_0014).unwrap();
+    state.write_register("reg::r9", 0x10).unwrap();
+
+    harness
+        .execute_block(0x1000, &rom)
+        .expect("execute loads and stores");
+    let state = harness.state_mut();
+    let gpr = |state: &mut nanemu::soc::core::CoreState, index: u32| {
+        state.read_register(&format!("reg::r{index}")).unwrap()
+    };
+    assert_eq!(gpr(state, 3), 0xDEAD_BEEF, "lwz zero-extends");
+    assert_eq!(gpr(state, 4), 0xFFFF_FFFF_FFFF_8001, "lha sign-extends");
+    assert_eq!(gpr(state, 5), 0x8001, "lhz zero-extends");
+    assert_eq!(gpr(state, 6), 0x7F);
+    assert_eq!(gpr(state, 8), 0xDEAD_BEEF, "lwzx reads what stw wrote");
+    assert_eq!(gpr(state, 10), 0x8001_0000, "lwzu reads what sth wrote");
+    assert_eq!(gpr(state, 1), 0x4000_0002, "lwzu updates its base");
+    let mut stored = [0u8; 4];
+    ram.read(0, &mut stored).unwrap();
+    assert_eq!(stored, [0x7F, 0, 0x80, 0x01]);
+    ram.read(0x10, &mut stored).unwrap();
+    assert_eq!(stored, [0xDE, 0xAD, 0xBE, 0xEF]);
+
+    let machine = harness.machine();
+    for (mnemonic, memory) in [("lwz", true), ("stwx", true), ("addi", false)] {
+        let instruction = machine
+            .instructions
+            .iter()
+            .find(|instruction| instruction.name == mnemonic)
+            .unwrap();
+        assert_eq!(instruction.effects.memory, memory, "{mnemonic}");
+    }
+
+    // lwz r3, 0(0) reads address 0, which nothing maps
+    let err = harness
+        .execute_block(0x2000, &0x8060_0000u32.to_be_bytes())
+        .expect_err("unmapped load");
+    let HarnessError::Memory { address, fault } = err else {
+        panic!("expected a data storage exception, got {err}");
+    };
+    assert_eq!(address, 0x2000);
+    assert_eq!(
+        (fault.access, fault.address, fault.size),
+        (MemoryAccess::Read, 0, 4)
+    );
+    assert!(fault.is_unmapped());
+}
+
+#[test]
+fn loads_and_stores_use_the_byte_order_of_the_mapped_device() {
+    // A big-endian core reaching a little-endian peripheral and big-endian RAM.
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("defs/powerpc");
+    let mut harness = build_powerpc_harness(&root.join("ppc_eref2.isa"));
+    let rom = assemble_block(
+        harness.machine(),
+        &[
+            "lwz r3, 0(r1)",
+            "lhz r4, 4(r1)",
+            "stw r3, 0(r2)",
+            "sth r4, 8(r1)",
+        ],
+    );
+
+    let bus = Arc::new(DeviceBus::new(16));
+    let periph = Arc::new(BasicMemory::new("periph", 0x100, Endianness::Little));
+    let ram = Arc::new(BasicMemory::new("ram", 0x100, Endianness::Big));
+    bus.register_device(periph.clone(), 0xC000_0000).unwrap();
+    bus.register_device(ram.clone(), 0x4000_0000).unwrap();
+    periph
+        .write(0, &[0xEF, 0xBE, 0xAD, 0xDE, 0x34, 0x12])
+        .unwrap();
+    harness.state_mut().attach_data_bus(bus);
+    let state = harness.state_mut();
+    state.write_register("reg::r1", 0xC000_0000).unwrap();
+    state.write_register("reg::r2", 0x4000_0000).unwrap();
+
+    harness
+        .execute_block(0x1000, &rom)
+        .expect("execute loads and stores");
+    let state = harness.state_mut();
+    assert_eq!(state.read_register("reg::r3").unwrap(), 0xDEAD_BEEF);
+    assert_eq!(state.read_register("reg::r4").unwrap(), 0x1234);
+    let mut stored = [0u8; 4];
+    ram.read(0, &mut stored).unwrap();
+    assert_eq!(
+        stored,
+        [0xDE, 0xAD, 0xBE, 0xEF],
+        "RAM keeps the core's order"
+    );
+    periph.read(8, &mut stored[..2]).unwrap();
+    assert_eq!(
+        stored[..2],
+        [0x34, 0x12],
+        "the peripheral stores little-endian"
+    );
+}
+
+#[test]
+fn executes_conditional_branches_selects_and_bit_counts() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("defs/powerpc");
+    let mut harness = build_powerpc_harness(&root.join("ppc_eref2.isa"));
+    let rom = assemble_block(
+        harness.machine(),
+        &[
+            "cntlzw r4, r3",
+            "cntlzw r10, r11",
+            "rlwinm r5, r3, 8, 0, 23",
+            "rlwinm r6, r9, 0, 28, 3",
+            "isel r7, r3, r4, 10",
+            "bc 20, 0, . + 8",
+            "isel r8, r0, r4, 9",
+        ],
+    );
+    let state = harness.state_mut();
+    state.write_register("reg::r3", 0x0012_3456).unwrap();
+    state.write_register("reg::r9", 0xABCD_EF12).unwrap();
+    // The harness steps linearly, so PC is seeded with the address of the bc
+    harness
+        .write_register_value("reg", "PC", None, None, 0x1014)
+        .unwrap();
+    harness.execute_block(0x1000, &rom).expect("execute block");
+    let state = harness.state_mut();
+    let gpr = |state: &mut nanemu::soc::core::CoreState, index: u32| {
+        state.read_register(&format!("reg::r{index}")).unwrap()
+    };
+    assert_eq!(gpr(state, 4), 11);
+    assert_eq!(gpr(state, 10), 32, "cntlzw of zero");
+    assert_eq!(gpr(state, 5), 0x1234_5600);
+    assert_eq!(gpr(state, 6), 0xA000_0002, "MB > ME wraps the mask");
+    assert_eq!(gpr(state, 7), 11, "CR2::EQ clear selects RB");
+    assert_eq!(gpr(state, 8), 11);
+    assert_eq!(
+        harness
+            .read_register_value("reg", "PC", None, None)
+            .unwrap()
+            .as_int()
+            .unwrap(),
+        0x101C,
+        "BO=20 branches unconditionally"
+    );
+
+    // bdnz decrements CTR and branches while it stays nonzero
+    let bdnz = assemble_block(harness.machine(), &["bdnz . + 0x40"]);
+    for (ctr, pc) in [(2, 0x2040), (1, 0x2000)] {
+        harness
+            .write_register_value("reg", "CTR", None, None, ctr)
+            .unwrap();
+        harness
+            .write_register_value("reg", "PC", None, None, 0x2000)
+            .unwrap();
+        harness.execute_block(0x2000, &bdnz).expect("execute bdnz");
+        let read = |harness: &mut ExecutionHarness<SoftwareHost>, name| {
+            harness
+                .read_register_value("reg", name, None, None)
+                .unwrap()
+                .as_int()
+                .unwrap()
+        };
+        assert_eq!(read(&mut harness, "CTR"), ctr - 1);
+        assert_eq!(read(&mut harness, "PC"), pc);
     }
-    rom
+
+    // bl leaves the return address in LR; blr drops its low two bits on the way back
+    let read = |harness: &mut ExecutionHarness<SoftwareHost>, name| {
+        harness
+            .read_register_value("reg", name, None, None)
+            .unwrap()
+            .as_int()
+            .unwrap()
+    };
+    let call = assemble_block(harness.machine(), &["bl . + 0x100"]);
+    harness
+        .write_register_value("reg", "PC", None, None, 0x2000)
+        .unwrap();
+    harness.execute_block(0x2000, &call).expect("execute bl");
+    assert_eq!(read(&mut harness, "LR"), 0x2004);
+    assert_eq!(read(&mut harness, "PC"), 0x2100);
+    let ret = assemble_block(harness.machine(), &["blr"]);
+    harness
+        .write_register_value("reg", "LR", None, None, 0x2007)
+        .unwrap();
+    harness.execute_block(0x2100, &ret).expect("execute blr");
+    assert_eq!(read(&mut harness, "PC"), 0x2004);
+
+    let machine = harness.machine();
+    let decoded = machine
+        .decode_bytes(&bdnz, 0x2000)
+        .next()
+        .unwrap()
+        .expect("decode bdnz");
+    let flow = machine.control_flow(&decoded);
+    assert_eq!(flow.kind, FlowKind::Jump);
+    assert_eq!(flow.targets, vec![0x2040]);
+    assert!(flow.conditional);
+    assert_eq!(flow.condition, vec!["CTR".to_string()]);
+}
+
+#[test]
+fn discovery_follows_book_e_branches_calls_and_returns() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("defs/powerpc");
+    let machine = IsaLoader::new()
+        .load_machine(root.join("ppc_eref2.isa"))
+        .expect("load book e");
+    let source = r#"
+    main:   li r3, 1
+            bl func
+            bc 12, 2, skip
+            addi r3, r3, 1
+    skip:   b done
+            .word 0xFFFFFFFF
+    done:   blr
+    func:   addi r4, r3, 2
+            bctr
+    "#;
+    let program = machine
+        .assemble_program(source, 0x1000)
+        .expect("assemble program");
+
+    let mut image = ByteImage::new(&program.bytes, 0x1000);
+    let graph = machine.discover(
+        &mut image,
+        &[EntryPoint::symbol("main", 0x1000)],
+        Syntax::Canonical,
+    );
+    assert!(graph.faults.is_empty(), "{:?}", graph.faults);
+
+    let summary: Vec<BlockSummary> = graph
+        .blocks
+        .values()
+        .map(|block| {
+            let edges = block
+                .successors
+                .iter()
+                .map(|edge| (edge.target, edge.kind))
+                .collect();
+            (block.start, block.end(), edges)
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (0x1000, 0x1008, vec![(0x1008, EdgeKind::Fallthrough)]),
+            (
+                0x1008,
+                0x100C,
+                vec![(0x1010, EdgeKind::Taken), (0x100C, EdgeKind::Fallthrough)]
+            ),
+            (0x100C, 0x1010, vec![(0x1010, EdgeKind::Fallthrough)]),
+            (0x1010, 0x1014, vec![(0x1018, EdgeKind::Jump)]),
+            (0x1018, 0x101C, vec![]),
+            (0x101C, 0x1024, vec![]),
+        ],
+        "the data word after 'b done' is never decoded"
+    );
+    assert_eq!(
+        graph.blocks[&0x1008].flow.condition,
+        vec!["CR(0)".to_string()]
+    );
+    assert_eq!(graph.blocks[&0x1018].flow.kind, FlowKind::Return);
+    let bctr = &graph.blocks[&0x101C].flow;
+    assert_eq!(bctr.kind, FlowKind::Jump);
+    assert!(bctr.indirect && !bctr.falls_through());
+
+    let main = &graph.functions[&0x1000];
+    assert_eq!(main.blocks, vec![0x1000, 0x1008, 0x100C, 0x1010, 0x1018]);
+    assert_eq!(main.calls, vec![0x101C]);
+}
+
+#[test]
+fn executes_wide_products_vectors_and_floats() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("defs/powerpc");
+    let mut harness = build_powerpc_harness(&root.join("ppc_eref2.isa"));
+    let rom = assemble_block(
+        harness.machine(),
+        &[
+            "mullw r4, r3, r5",
+            "mulhw r6, r3, r5",
+            "mulhwu r7, r3, r5",
+            "mulhdu r8, r3, r3",
+            "evaddw r9, r10, r11",
+            "efsadd r12, r13, r14",
+            "efdadd r15, r16, r17",
+        ],
+    );
+    let state = harness.state_mut();
+    for (index, value) in [
+        (3, 0xFFFF_FFFF_FFFF_FFFE),
+        (5, 3),
+        (10, 0xFFFF_FFFF_0000_0001),
+        (11, 0x0000_0002_FFFF_FFFF),
+        (12, 0x1234_5678_0000_0000),
+        (13, u128::from(1.5f32.to_bits())),
+        (14, u128::from(2.25f32.to_bits())),
+        (16, u128::from(1.5f64.to_bits())),
+        (17, u128::from(0.25f64.to_bits())),
+    ] {
+        state
+            .write_register(&format!("reg::r{index}"), value)
+            .unwrap();
+    }
+    harness.execute_block(0x1000, &rom).expect("execute block");
+    let state = harness.state_mut();
+    let mut gpr = |index: u32| state.read_register(&format!("reg::r{index}")).unwrap();
+    assert_eq!(
+        gpr(4),
+        0xFFFF_FFFF_FFFF_FFFA,
+        "mullw sign-extends the low word"
+    );
+    assert_eq!(gpr(6), 0xFFFF_FFFF_FFFF_FFFF);
+    assert_eq!(gpr(7), 2);
+    assert_eq!(
+        gpr(8),
+        0xFFFF_FFFF_FFFF_FFFC,
+        "high half of a 128-bit product"
+    );
+    assert_eq!(gpr(9), 0x0000_0001_0000_0000, "each word wraps on its own");
+    assert_eq!(
+        gpr(12),
+        0x1234_5678_0000_0000 | u128::from(3.75f32.to_bits())
+    );
+    assert_eq!(gpr(15), u128::from(1.75f64.to_bits()));
 }
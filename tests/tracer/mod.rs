@@ -150,21 +150,36 @@ fn format_trace(events: &[TraceEvent]) -> String {
                     format!("[IntOp]   {op:?} {:?} -> 0x{result:016X}", args)
                 }
             }
+            TraceEvent::MemoryRead {
+                address,
+                value,
+                size,
+            } => format!(
+                "[ Load]   0x{address:016X} -> {}",
+                format_trace_value(*value, *size as u32 * 8)
+            ),
+            TraceEvent::MemoryWrite {
+                address,
+                value,
+                size,
+            } => format!(
+                "[Store]   0x{address:016X} <- {}",
+                format_trace_value(*value, *size as u32 * 8)
+            ),
         };
         let _ = writeln!(out, "{line}");
     }
     out
 }
 
-fn format_trace_value(value: i64, bits: u32) -> String {
+fn format_trace_value(value: u128, bits: u32) -> String {
     let width = std::cmp::max(1, ((bits as usize + 3) / 4) as usize);
     let masked = if bits == 0 {
         0
-    } else if bits >= 64 {
-        value as u64
+    } else if bits >= 128 {
+        value
     } else {
-        let mask = (1u64 << bits) - 1;
-        (value as u64) & mask
+        value & ((1u128 << bits) - 1)
     };
     format!("0x{masked:0width$X}")
 }
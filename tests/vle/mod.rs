@@ -0,0 +1,384 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use nanemu::loader::isa::IsaLoader;
+use nanemu::soc::device::{BasicMemory, Device, Endianness};
+use nanemu::soc::isa::IsaHandle;
+use nanemu::soc::isa::machine::{
+    ControlFlow, DecodeError, EdgeKind, EntryPoint, FlowKind, MachineDescription, OperandValue,
+    RegisterRef, Resync, Syntax,
+};
+use nanemu::soc::system::bus::{BusError, DataHandle, DeviceBus};
+
+fn fixture() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/vle/vle.isa")
+}
+
+fn vle() -> MachineDescription {
+    IsaLoader::new()
+        .load_machine(fixture())
+        .expect("load vle fixture")
+}
+
+/// Splits `mixed_stream` over two adjacent memories so `e_add16i` straddles the boundary.
+fn split_bus() -> Arc<DeviceBus> {
+    let stream = mixed_stream();
+    let bus = Arc::new(DeviceBus::new(12));
+    let flash = Arc::new(BasicMemory::new("flash", 0x4, Endianness::Big));
+    let ram = Arc::new(BasicMemory::new("ram", 0x10, Endianness::Big));
+    bus.register_device(flash.clone(), 0x1000).unwrap();
+    bus.register_device(ram.clone(), 0x1004).unwrap();
+    flash.write(0, &stream[..4]).unwrap();
+    ram.write(0, &stream[4..]).unwrap();
+    bus
+}
+
+/// `(start, end, successors)` of a discovered block.
+type BlockSummary = (u64, u64, Vec<(u64, EdgeKind)>);
+
+/// Bus with `words` loaded as big-endian halfwords from `base`.
+fn image(base: u64, words: &[u16]) -> DataHandle {
+    let bus = Arc::new(DeviceBus::new(16));
+    let ram = Arc::new(BasicMemory::new("ram", 0x100, Endianness::Big));
+    bus.register_device(ram.clone(), base).unwrap();
+    let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_be_bytes()).collect();
+    ram.write(0, &bytes).unwrap();
+    DataHandle::new(bus)
+}
+
+/// se_li r3,5; e_add16i r4,r3,0x10; se_add r4,r3; e_b +8; se_b 0
+fn mixed_stream() -> Vec<u8> {
+    let mut stream = Vec::new();
+    stream.extend_from_slice(&0x4853u16.to_be_bytes());
+    stream.extend_from_slice(&0x1C83_0010u32.to_be_bytes());
+    stream.extend_from_slice(&0x0434u16.to_be_bytes());
+    stream.extend_from_slice(&0x7800_0008u32.to_be_bytes());
+    stream.extend_from_slice(&0xE800u16.to_be_bytes());
+    stream
+}
+
+#[test]
+fn disassembles_mixed_16_and_32_bit_stream() {
+    let machine = vle();
+    let listing = machine.disassemble_from(&mixed_stream(), 0x1000, Syntax::Canonical);
+    let summary: Vec<(u64, usize, &str)> = listing
+        .iter()
+        .map(|entry| (entry.address, entry.size, entry.mnemonic.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (0x1000, 2, "se_li"),
+            (0x1002, 4, "e_add16i"),
+            (0x1006, 2, "se_add"),
+            (0x1008, 4, "e_b"),
+            (0x100C, 2, "se_b"),
+        ]
+    );
+    assert_eq!(
+        listing[1].opcode, 0x1C83_0010,
+        "extended word spans both halfwords"
+    );
+    assert_eq!(listing[1].display.as_deref(), Some("r4, r3, 0x0010"));
+    assert_eq!(listing[2].display.as_deref(), Some("r4, r3"));
+}
+
+#[test]
+fn operands_are_typed_before_rendering() {
+    let machine = vle();
+    let gpr = |index| RegisterRef {
+        space: "reg".into(),
+        file: "GPR".into(),
+        index,
+    };
+    // e_lwz r3,-8(r1); e_b +8; se_b -2
+    let mut stream = Vec::new();
+    stream.extend_from_slice(&0x5061_FFF8u32.to_be_bytes());
+    stream.extend_from_slice(&0x7800_0008u32.to_be_bytes());
+    stream.extend_from_slice(&0xE8FFu16.to_be_bytes());
+    let listing = machine.disassemble_from(&stream, 0x2000, Syntax::Canonical);
+
+    let values: Vec<&OperandValue> = listing[0].operands.iter().map(|op| &op.value).collect();
+    assert_eq!(
+        values,
+        vec![
+            &OperandValue::Register(gpr(3)),
+            &OperandValue::Memory {
+                base: gpr(1),
+                displacement: -8,
+                width: 16,
+            },
+        ]
+    );
+    assert_eq!(listing[0].operands[1].name, "SI");
+    assert_eq!(
+        machine.format_operands(&listing[0].operands),
+        vec!["r3", "0xFFF8(r1)"]
+    );
+    assert_eq!(listing[0].display.as_deref(), Some("r3, 0xFFF8(r1)"));
+
+    assert_eq!(
+        listing[1].operands[0].value,
+        OperandValue::Target { address: 0x200C }
+    );
+    assert_eq!(
+        listing[2].operands[0].value,
+        OperandValue::Target { address: 0x2006 }
+    );
+    assert_eq!(listing[2].display.as_deref(), Some("0x2006"));
+}
+
+#[test]
+fn stops_when_an_extended_instruction_is_truncated() {
+    let machine = vle();
+    let mut stream = mixed_stream();
+    stream.extend_from_slice(&0x1C83u16.to_be_bytes());
+    let listing = machine.disassemble_from(&stream, 0, Syntax::Canonical);
+    assert_eq!(listing.len(), 5, "the dangling e_ halfword is not decoded");
+
+    let decoded = machine.decode_instructions(&stream, 0);
+    let sizes: Vec<usize> = decoded.iter().map(|entry| entry.size()).collect();
+    assert_eq!(sizes, vec![2, 4, 2, 4, 2]);
+    assert_eq!(decoded[1].space(), "vle32");
+}
+
+#[test]
+fn decode_stream_reports_failures_and_resyncs() {
+    let machine = vle();
+    let mut stream = mixed_stream();
+    stream.extend_from_slice(&0xFFFFu16.to_be_bytes());
+    stream.extend_from_slice(&0x4853u16.to_be_bytes());
+    stream.extend_from_slice(&0x1C83u16.to_be_bytes());
+
+    let items: Vec<_> = machine.decode_bytes(&stream, 0x1000).collect();
+    let names: Vec<Option<&str>> = items
+        .iter()
+        .map(|item| {
+            item.as_ref()
+                .ok()
+                .map(|entry| entry.instruction().name.as_str())
+        })
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            Some("se_li"),
+            Some("e_add16i"),
+            Some("se_add"),
+            Some("e_b"),
+            Some("se_b"),
+            None,
+            Some("se_li"),
+            None,
+        ]
+    );
+    assert!(matches!(
+        items[5],
+        Err(DecodeError::Undefined {
+            address: 0x100E,
+            size: 2,
+            bits: 0xFFFF,
+        })
+    ));
+    let Err(truncated) = &items[7] else {
+        panic!("the dangling e_ halfword must not decode");
+    };
+    assert!(matches!(
+        truncated,
+        DecodeError::Truncated {
+            address: 0x1012,
+            needed: 4,
+            available: 2,
+        }
+    ));
+    assert_eq!(
+        truncated.to_string(),
+        "truncated instruction at 0x0000000000001012: needs 4 bytes, 2 remain"
+    );
+
+    let stopped: Vec<_> = machine
+        .decode_bytes(&stream, 0x1000)
+        .resync(Resync::Stop)
+        .collect();
+    assert_eq!(stopped.len(), 6);
+    assert!(stopped[5].is_err());
+
+    let mut data = DataHandle::new(split_bus());
+    let sizes: Vec<usize> = machine
+        .decode_stream(&mut data, 0x1000)
+        .until(0x100E)
+        .map(|item| item.expect("decode from the bus").size())
+        .collect();
+    assert_eq!(sizes, vec![2, 4, 2, 4, 2]);
+}
+
+#[test]
+fn disassembles_from_the_bus_across_device_boundaries() {
+    let handle = IsaHandle::from_files(fixture()).expect("load vle fixture");
+    let mut data = DataHandle::new(split_bus());
+    data.address_mut().jump(0x1000).unwrap();
+    let listing = handle
+        .disassemble_range(&mut data, 0xE)
+        .expect("decode range");
+    let summary: Vec<(u64, &str)> = listing
+        .iter()
+        .map(|entry| (entry.address, entry.mnemonic.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (0x1000, "se_li"),
+            (0x1002, "e_add16i"),
+            (0x1006, "se_add"),
+            (0x1008, "e_b"),
+            (0x100C, "se_b"),
+        ]
+    );
+    assert_eq!(listing[1].opcode, 0x1C83_0010);
+}
+
+#[test]
+fn bus_fetch_follows_redirects_and_reports_faults() {
+    let machine = vle();
+    let bus = split_bus();
+    bus.redirect(0x8000, 4, 0x1000).unwrap();
+    let mut data = DataHandle::new(bus);
+
+    let first = machine.decode_at(&mut data, 0x8000).expect("decode alias");
+    assert_eq!(
+        (first.address(), first.instruction().name.as_str()),
+        (0x8000, "se_li")
+    );
+
+    // The alias ends halfway through e_add16i, and nothing is mapped after it.
+    let err = machine
+        .decode_at(&mut data, 0x8002)
+        .err()
+        .expect("fetch past the redirect fails");
+    assert_eq!(err.address(), 0x8002);
+    assert!(matches!(
+        err,
+        DecodeError::Fetch {
+            source: BusError::NotMapped { address: 0x8004 },
+            ..
+        }
+    ));
+}
+
+#[test]
+fn control_flow_comes_from_branch_semantics() {
+    let machine = vle();
+    // se_li r3,1; se_b +6; se_bl -2; se_bc 1,2,+6; se_blr; se_bctr
+    let mut data = image(0x2000, &[0x4813, 0xE803, 0xE9FF, 0xE603, 0x0004, 0x0006]);
+    let flow = |data: &mut DataHandle, address| {
+        let decoded = machine.decode_at(data, address).expect("decode");
+        machine.control_flow(&decoded)
+    };
+
+    assert_eq!(flow(&mut data, 0x2000), ControlFlow::sequential());
+
+    let jump = flow(&mut data, 0x2002);
+    assert_eq!(
+        (jump.kind, &jump.targets[..]),
+        (FlowKind::Jump, &[0x2008][..])
+    );
+    assert!(!jump.falls_through());
+
+    let call = flow(&mut data, 0x2004);
+    assert_eq!(
+        (call.kind, &call.targets[..]),
+        (FlowKind::Call, &[0x2002][..])
+    );
+    assert!(call.falls_through());
+
+    let branch = flow(&mut data, 0x2006);
+    assert_eq!(branch.kind, FlowKind::Jump);
+    assert_eq!(branch.targets, vec![0x200C]);
+    assert!(branch.conditional);
+    assert_eq!(branch.condition, vec!["CR".to_string()]);
+
+    let ret = flow(&mut data, 0x2008);
+    assert_eq!(ret.kind, FlowKind::Return);
+    assert!(ret.targets.is_empty() && !ret.falls_through());
+
+    let indirect = flow(&mut data, 0x200A);
+    assert_eq!(indirect.kind, FlowKind::Jump);
+    assert!(indirect.indirect && indirect.targets.is_empty());
+}
+
+#[test]
+fn discovery_follows_branches_and_skips_embedded_data() {
+    let machine = vle();
+    let words = [
+        0x4813, // 1000: se_li r3,1
+        0xE907, // 1002: se_bl 1010
+        0xE603, // 1004: se_bc 1,2,100a
+        0x0433, // 1006: se_add r3,r3
+        0xE802, // 1008: se_b 100c
+        0x0434, // 100a: se_add r4,r3
+        0x0004, // 100c: se_blr
+        0xFFFF, // 100e: data
+        0x4824, // 1010: se_li r4,2
+        0x0004, // 1012: se_blr
+    ];
+    // A linear sweep runs into the data word.
+    let bytes: Vec<u8> = words
+        .iter()
+        .flat_map(|word: &u16| word.to_be_bytes())
+        .collect();
+    assert_eq!(
+        machine.disassemble_from(&bytes, 0x1000, Syntax::Canonical)[7].mnemonic,
+        "unknown"
+    );
+
+    let mut data = image(0x1000, &words);
+    let graph = machine.discover(
+        &mut data,
+        &[EntryPoint::symbol("main", 0x1000)],
+        Syntax::Canonical,
+    );
+    assert!(graph.faults.is_empty(), "{:?}", graph.faults);
+
+    let summary: Vec<BlockSummary> = graph
+        .blocks
+        .values()
+        .map(|block| {
+            let edges = block
+                .successors
+                .iter()
+                .map(|edge| (edge.target, edge.kind))
+                .collect();
+            (block.start, block.end(), edges)
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (0x1000, 0x1004, vec![(0x1004, EdgeKind::Fallthrough)]),
+            (
+                0x1004,
+                0x1006,
+                vec![(0x100A, EdgeKind::Taken), (0x1006, EdgeKind::Fallthrough)]
+            ),
+            (0x1006, 0x100A, vec![(0x100C, EdgeKind::Jump)]),
+            (0x100A, 0x100C, vec![(0x100C, EdgeKind::Fallthrough)]),
+            (0x100C, 0x100E, vec![]),
+            (0x1010, 0x1014, vec![]),
+        ]
+    );
+
+    let main = &graph.functions[&0x1000];
+    assert_eq!(main.blocks, vec![0x1000, 0x1004, 0x1006, 0x100A, 0x100C]);
+    assert_eq!(main.calls, vec![0x1010]);
+    assert_eq!(graph.functions[&0x1010].label(), "sub_1010");
+
+    let dot = graph.to_dot(&machine, 0x1010).expect("callee graph");
+    assert_eq!(
+        dot,
+        "digraph \"sub_1010\" {\n    node [shape=box, fontname=\"monospace\"];\n    \"1010\" [label=\"1010: se_li r4, 0x02\\l1012: se_blr\\l\"];\n}\n"
+    );
+    let dot = graph.to_dot(&machine, 0x1000).expect("main graph");
+    assert!(dot.contains("\"1004\" -> \"100a\" [label=\"taken: CR\"];"));
+    assert!(dot.contains("\"1004\" -> \"1006\" [label=\"not taken\", style=dashed];"));
+    assert!(dot.contains("\"1006\" -> \"100c\";"));
+}